@@ -0,0 +1,462 @@
+#![no_std]
+
+use klever_sc::imports::*;
+use klever_sc::derive_imports::*;
+
+// ============================================================================
+// DIGIKO DEX V5 - TYPED PROXY FOR CROSS-CONTRACT CALLERS
+// ============================================================================
+//
+// Other Klever contracts can depend on this crate instead of the full
+// digiko-swap contract and call the DEX with compile-time-checked signatures:
+//
+//   #[proxy]
+//   fn digiko_dex(&self, sc_address: ManagedAddress) -> digiko_swap_proxy::Proxy<Self::Api>;
+//
+//   let out: () = self.digiko_dex(dex_address)
+//       .swap_a_to_b(pair_id, min_output, OptionalValue::<ManagedAddress>::None)
+//       .with_kda_transfer((token_a, 0, amount))
+//       .execute_on_dest_context();
+//
+// Only the endpoints meant for integrators are listed. Keep the signatures in
+// sync with the contract/src modules whenever an endpoint listed here changes.
+// Contracts that only read prices can use the smaller price_feed proxy.
+// ============================================================================
+
+pub mod price_feed;
+
+/// Pricing curve of a pair (same layout as the contract)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone, Copy, PartialEq, Eq)]
+pub enum PoolType {
+    ConstantProduct,
+    Stable,
+    Weighted,
+}
+
+/// Full pair snapshot returned by getPairsInfoPaginated / getManyPairInfo (same layout as the contract)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct PairInfo<M: ManagedTypeApi> {
+    pub pair_id: u64,
+    pub token_a: TokenIdentifier<M>,
+    pub token_b: TokenIdentifier<M>,
+    pub token_a_is_klv: bool,
+    pub token_b_is_klv: bool,
+    pub reserve_a: BigUint<M>,
+    pub reserve_b: BigUint<M>,
+    pub fee_bps: u64,
+    pub is_active: bool,
+    pub creator: ManagedAddress<M>,
+    pub total_shares: BigUint<M>,
+    pub lp_count: usize,
+}
+
+/// One entry of a pair's fee history (same layout as the contract)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct FeeChange {
+    pub timestamp: u64,
+    pub old_fee_bps: u64,
+    pub new_fee_bps: u64,
+    pub activation_block: u64,
+}
+
+/// Governance-registered route (same layout as the contract)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct RegisteredRoute<M: ManagedTypeApi> {
+    pub token_in: TokenIdentifier<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub path: ManagedVec<M, u64>,
+}
+
+/// Signed swap order submitted through swapOnBehalf (same layout as the contract)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct MetaSwapOrder<M: ManagedTypeApi> {
+    pub user: ManagedAddress<M>,
+    pub pair_id: u64,
+    pub token_in: TokenIdentifier<M>,
+    pub amount_in: BigUint<M>,
+    pub min_out: BigUint<M>,
+    pub nonce: u64,
+    pub expiry: u64,
+}
+
+#[klever_sc::proxy]
+pub trait DigikoDexV5 {
+    // ========================================================================
+    // PAIR CREATION
+    // ========================================================================
+
+    #[endpoint(createPair)]
+    #[payable("KLV")]
+    fn create_pair(
+        &self,
+        token_a: TokenIdentifier,
+        token_b: TokenIdentifier,
+        token_a_is_klv: bool,
+        token_b_is_klv: bool,
+        fee_bps: u64,
+        pool_type: OptionalValue<PoolType>,
+        curve_param: OptionalValue<u64>,
+    ) -> u64;
+
+    #[endpoint(createSftPair)]
+    #[payable("KLV")]
+    fn create_sft_pair(
+        &self,
+        token_a: TokenIdentifier,
+        nonce_a: u64,
+        token_b: TokenIdentifier,
+        nonce_b: u64,
+        fee_bps: u64,
+    ) -> u64;
+
+    #[endpoint(createLbpPair)]
+    #[payable("KLV")]
+    #[allow(clippy::too_many_arguments)]
+    fn create_lbp_pair(
+        &self,
+        token_a: TokenIdentifier,
+        token_b: TokenIdentifier,
+        token_a_is_klv: bool,
+        token_b_is_klv: bool,
+        fee_bps: u64,
+        start_weight_a: u64,
+        end_weight_a: u64,
+        start_time: u64,
+        end_time: u64,
+    ) -> u64;
+
+    // ========================================================================
+    // LIQUIDITY
+    // ========================================================================
+
+    #[endpoint(mint)]
+    #[payable("*")]
+    fn mint(&self, pair_id: u64, min_lp_shares: BigUint, lock_days: OptionalValue<u64>) -> BigUint;
+
+    #[endpoint(zapInSingleToken)]
+    #[payable("*")]
+    fn zap_in_single_token(&self, pair_id: u64, min_lp_shares: BigUint) -> BigUint;
+
+    #[endpoint(depositPendingA)]
+    #[payable("*")]
+    fn deposit_pending_a(&self, pair_id: u64);
+
+    #[endpoint(depositPendingAKlv)]
+    #[payable("KLV")]
+    fn deposit_pending_a_klv(&self, pair_id: u64);
+
+    #[endpoint(depositPendingB)]
+    #[payable("*")]
+    fn deposit_pending_b(&self, pair_id: u64);
+
+    #[endpoint(depositPendingBKlv)]
+    #[payable("KLV")]
+    fn deposit_pending_b_klv(&self, pair_id: u64);
+
+    #[endpoint(finalizeLiquidity)]
+    fn finalize_liquidity(&self, pair_id: u64, min_shares: BigUint);
+
+    #[endpoint(withdrawPendingAll)]
+    fn withdraw_pending_all(&self, pair_id: u64);
+
+    #[endpoint(removeLiquidity)]
+    #[payable("*")]
+    fn remove_liquidity(
+        &self,
+        pair_id: u64,
+        shares_to_remove: BigUint,
+        min_amount_a: BigUint,
+        min_amount_b: BigUint,
+    );
+
+    #[endpoint(removeLiquidityBatch)]
+    fn remove_liquidity_batch(&self, removals: MultiValueEncoded<MultiValue4<u64, BigUint, BigUint, BigUint>>);
+
+    #[endpoint(emergencyWithdraw)]
+    fn emergency_withdraw(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint>;
+
+    #[endpoint(removeLiquidityLpToken)]
+    #[payable("*")]
+    fn remove_liquidity_lp_token(&self, pair_id: u64, min_amount_a: BigUint, min_amount_b: BigUint);
+
+    #[endpoint(claimLpFees)]
+    fn claim_lp_fees(&self, pair_id: u64);
+
+    #[endpoint(claimAllLpFees)]
+    fn claim_all_lp_fees(&self, pair_ids: MultiValueEncoded<u64>);
+
+    #[endpoint(distributePendingFees)]
+    fn distribute_pending_fees(&self, pair_id: u64, max_lps: u64) -> u64;
+
+    #[endpoint(migrateLiquidity)]
+    fn migrate_liquidity(&self, from_pair: u64, to_pair: u64, shares: BigUint, min_lp_shares: BigUint) -> BigUint;
+
+    // ========================================================================
+    // SWAPS
+    // ========================================================================
+
+    #[endpoint(swap)]
+    #[payable("*")]
+    fn swap(
+        &self,
+        pair_id: u64,
+        min_output: BigUint,
+        deadline: u64,
+        referrer: OptionalValue<ManagedAddress>,
+    ) -> BigUint;
+
+    #[endpoint(swapFor)]
+    #[payable("*")]
+    fn swap_for(
+        &self,
+        pair_id: u64,
+        min_output: BigUint,
+        deadline: u64,
+        beneficiary: ManagedAddress,
+        callback_endpoint: OptionalValue<ManagedBuffer>,
+    ) -> BigUint;
+
+    #[endpoint(swapOnBehalf)]
+    fn swap_on_behalf(&self, user_signature: ManagedBuffer, order: MetaSwapOrder<Self::Api>) -> BigUint;
+
+    #[endpoint(swapAtoB)]
+    #[payable("*")]
+    fn swap_a_to_b(&self, pair_id: u64, min_output: BigUint, referrer: OptionalValue<ManagedAddress>);
+
+    #[endpoint(swapBtoA)]
+    #[payable("*")]
+    fn swap_b_to_a(&self, pair_id: u64, min_output: BigUint, referrer: OptionalValue<ManagedAddress>);
+
+    #[endpoint(swapKlvToB)]
+    #[payable("KLV")]
+    fn swap_klv_to_b(&self, pair_id: u64, min_output: BigUint, referrer: OptionalValue<ManagedAddress>);
+
+    #[endpoint(swapKlvToA)]
+    #[payable("KLV")]
+    fn swap_klv_to_a(&self, pair_id: u64, min_output: BigUint, referrer: OptionalValue<ManagedAddress>);
+
+    #[endpoint(swapTokensForExactOut)]
+    #[payable("*")]
+    fn swap_tokens_for_exact_out(
+        &self,
+        pair_id: u64,
+        amount_out: BigUint,
+        referrer: OptionalValue<ManagedAddress>,
+    );
+
+    #[endpoint(swapExactInMultiHop)]
+    #[payable("*")]
+    fn swap_exact_in_multi_hop(&self, min_output: BigUint, path: MultiValueEncoded<u64>);
+
+    #[endpoint(swapExactInRoute)]
+    #[payable("*")]
+    fn swap_exact_in_route(&self, min_output: BigUint, route_alias: ManagedBuffer);
+
+    #[endpoint(arb)]
+    fn arb(
+        &self,
+        token_in: TokenIdentifier,
+        amount_in: BigUint,
+        min_profit: BigUint,
+        path: MultiValueEncoded<u64>,
+    ) -> BigUint;
+
+    #[endpoint(claimReferralFees)]
+    fn claim_referral_fees(&self);
+
+    #[endpoint(claimFeeRebates)]
+    fn claim_fee_rebates(&self);
+
+    #[endpoint(flashSwap)]
+    fn flash_swap(
+        &self,
+        pair_id: u64,
+        amount_a: BigUint,
+        amount_b: BigUint,
+        callback_contract: ManagedAddress,
+        callback_args: MultiValueEncoded<ManagedBuffer>,
+    );
+
+    #[endpoint(repayFlashSwap)]
+    #[payable("*")]
+    fn repay_flash_swap(&self, pair_id: u64);
+
+    // ========================================================================
+    // STAKING
+    // ========================================================================
+
+    #[endpoint(stakeDgko)]
+    #[payable("*")]
+    fn stake_dgko(&self);
+
+    #[endpoint(unstakeDgko)]
+    fn unstake_dgko(&self, amount: BigUint) -> u64;
+
+    #[endpoint(withdrawUnstakedDgko)]
+    fn withdraw_unstaked_dgko(&self) -> BigUint;
+
+    // ========================================================================
+    // VIEWS
+    // ========================================================================
+
+    #[view(getReserves)]
+    fn get_reserves(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint>;
+
+    #[view(getPairInfo)]
+    fn get_pair_info(
+        &self,
+        pair_id: u64,
+    ) -> MultiValue9<TokenIdentifier, TokenIdentifier, bool, bool, BigUint, BigUint, u64, bool, bool>;
+
+    #[view(getVerifiedPairs)]
+    fn get_verified_pairs(&self) -> MultiValueEncoded<u64>;
+
+    #[view(getPairTokenNonces)]
+    fn get_pair_token_nonces(&self, pair_id: u64) -> MultiValue2<u64, u64>;
+
+    #[view(getMetaSwapNonce)]
+    fn get_meta_swap_nonce(&self, user: ManagedAddress) -> u64;
+
+    #[view(getMetaSwapMessage)]
+    fn get_meta_swap_message(&self, order: MetaSwapOrder<Self::Api>) -> ManagedBuffer;
+
+    #[view(getTraderFeeBps)]
+    fn get_trader_fee_bps(&self, pair_id: u64, addr: ManagedAddress) -> u64;
+
+    #[view(getTraderRebateInfo)]
+    fn get_trader_rebate_info(&self, addr: ManagedAddress, pair_id: u64) -> MultiValue2<BigUint, u64>;
+
+    #[view(getFeeRebates)]
+    fn get_fee_rebates(&self, addr: ManagedAddress) -> MultiValueEncoded<MultiValue2<TokenIdentifier, BigUint>>;
+
+    #[view(getFeeTiers)]
+    fn get_fee_tiers(&self) -> MultiValueEncoded<u64>;
+
+    #[view(getPairFees)]
+    fn get_pair_fees(&self, pair_id: u64) -> MultiValue3<u64, u64, u64>;
+
+    #[view(getCreatorFeeRules)]
+    fn get_creator_fee_rules(&self, pair_id: u64) -> MultiValue4<u64, u64, u64, u64>;
+
+    #[view(isPairFeeOnInput)]
+    fn is_pair_fee_on_input(&self, pair_id: u64) -> bool;
+
+    #[view(getFeeHistory)]
+    fn get_fee_history(&self, pair_id: u64) -> MultiValueEncoded<FeeChange>;
+
+    #[view(getPairsInfoPaginated)]
+    fn get_pairs_info_paginated(&self, start_id: u64, limit: u64) -> MultiValueEncoded<PairInfo<Self::Api>>;
+
+    #[view(getManyPairInfo)]
+    fn get_many_pair_info(&self, pair_ids: MultiValueEncoded<u64>) -> MultiValueEncoded<PairInfo<Self::Api>>;
+
+    #[view(getAllActivePairs)]
+    fn get_all_active_pairs(&self) -> MultiValueEncoded<PairInfo<Self::Api>>;
+
+    #[view(findPairByTokens)]
+    fn find_pair_by_tokens(&self, token_a: TokenIdentifier, token_b: TokenIdentifier) -> u64;
+
+    #[view(getPairCurve)]
+    fn get_pair_curve(&self, pair_id: u64) -> MultiValue2<PoolType, u64>;
+
+    #[view(getLbpInfo)]
+    fn get_lbp_info(&self, pair_id: u64) -> MultiValue7<u64, u64, u64, u64, u64, BigUint, BigUint>;
+
+    #[view(getPairsForToken)]
+    fn get_pairs_for_token(&self, token: TokenIdentifier) -> MultiValueEncoded<u64>;
+
+    #[view(getBestRoute)]
+    fn get_best_route(
+        &self,
+        token_in: TokenIdentifier,
+        token_out: TokenIdentifier,
+        amount_in: BigUint,
+        max_hops: u64,
+    ) -> MultiValue2<BigUint, ManagedVec<Self::Api, u64>>;
+
+    #[view(getRouteAliases)]
+    fn get_route_aliases(&self) -> MultiValueEncoded<ManagedBuffer>;
+
+    #[view(getRoute)]
+    fn get_route(&self, route_alias: ManagedBuffer) -> RegisteredRoute<Self::Api>;
+
+    #[view(getPreferredRoute)]
+    fn get_preferred_route(&self, token_in: TokenIdentifier, token_out: TokenIdentifier) -> ManagedBuffer;
+
+    #[view(quoteSwap)]
+    fn quote_swap(&self, pair_id: u64, input_amount: BigUint, is_a_to_b: bool) -> MultiValue2<BigUint, BigUint>;
+
+    #[view(quoteSwapReverse)]
+    fn quote_swap_reverse(
+        &self,
+        pair_id: u64,
+        desired_output: BigUint,
+        is_a_to_b: bool,
+    ) -> MultiValue2<BigUint, BigUint>;
+
+    #[view(getSwapQuote)]
+    fn get_swap_quote(
+        &self,
+        pair_id: u64,
+        input_amount: BigUint,
+        is_a_to_b: bool,
+    ) -> MultiValue4<BigUint, BigUint, u64, BigUint>;
+
+    #[view(getDetailedQuote)]
+    fn get_detailed_quote(
+        &self,
+        pair_id: u64,
+        amount_in: BigUint,
+        a_to_b: bool,
+    ) -> MultiValue5<BigUint, BigUint, BigUint, BigUint, BigUint>;
+
+    #[view(getSwapQuoteExactOut)]
+    fn get_swap_quote_exact_out(
+        &self,
+        pair_id: u64,
+        desired_out: BigUint,
+        is_a_to_b: bool,
+    ) -> MultiValue4<BigUint, BigUint, u64, BigUint>;
+
+    #[view(getPairTwap)]
+    fn get_pair_twap(&self, pair_id: u64) -> MultiValue3<BigUint, BigUint, u64>;
+
+    #[view(getPriceNormalized)]
+    fn get_price_normalized(&self, pair_id: u64) -> BigUint;
+
+    #[view(getPairPriceCumulative)]
+    fn get_pair_price_cumulative(&self, pair_id: u64) -> MultiValue2<BigUint, u64>;
+
+    #[view(getLpPosition)]
+    fn get_lp_position(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue4<BigUint, BigUint, BigUint, BigUint>;
+
+    #[view(getUserPositions)]
+    fn get_user_positions(
+        &self,
+        addr: ManagedAddress,
+    ) -> MultiValueEncoded<MultiValue6<u64, BigUint, BigUint, BigUint, BigUint, BigUint>>;
+
+    #[view(getPendingDeposits)]
+    fn get_pending_deposits(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue2<BigUint, BigUint>;
+
+    #[view(getPairPendingTotals)]
+    fn get_pair_pending_totals(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint>;
+
+    #[view(getPairLpToken)]
+    fn get_pair_lp_token(&self, pair_id: u64) -> MultiValue3<TokenIdentifier, BigUint, BigUint>;
+
+    #[view(getPairStats)]
+    fn get_pair_stats(
+        &self,
+        pair_id: u64,
+    ) -> MultiValue8<BigUint, BigUint, BigUint, BigUint, BigUint, BigUint, u64, u64>;
+
+    #[view(getPairApr)]
+    fn get_pair_apr(&self, pair_id: u64, window_days: u64) -> MultiValue3<BigUint, BigUint, BigUint>;
+
+    #[view(getUserTradeStats)]
+    fn get_user_trade_stats(&self, addr: ManagedAddress, pair_id: u64) -> MultiValue4<BigUint, BigUint, u64, u64>;
+
+    #[view(getParameters)]
+    fn get_parameters(&self) -> MultiValue6<u64, u64, u64, u64, u64, u64>;
+}
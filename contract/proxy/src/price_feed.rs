@@ -0,0 +1,31 @@
+// ============================================================================
+// DIGIKO PRICE FEED - MINIMAL PROXY FOR PRICE CONSUMERS
+// ============================================================================
+//
+// The price views only, for contracts that read Digiko prices (lending
+// markets, vaults, other DEXes) and don't need the trading endpoints:
+//
+//   #[proxy]
+//   fn digiko_price_feed(&self, sc_address: ManagedAddress) -> digiko_swap_proxy::price_feed::Proxy<Self::Api>;
+//
+//   let price: BigUint = self.digiko_price_feed(dex_address)
+//       .get_twap_price(pair_id, base_token)
+//       .execute_on_dest_context();
+//
+// Prices are for one whole base token in whole units of the other pair token,
+// scaled to 1e18; 0 means no price. These signatures are stable.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+#[klever_sc::proxy]
+pub trait DigikoPriceFeed {
+    #[view(getSpotPrice)]
+    fn get_spot_price(&self, pair_id: u64, base_token: TokenIdentifier) -> BigUint;
+
+    #[view(getTwapPrice)]
+    fn get_twap_price(&self, pair_id: u64, base_token: TokenIdentifier) -> BigUint;
+
+    #[view(getReservesNormalized)]
+    fn get_reserves_normalized(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint>;
+}
@@ -0,0 +1,3036 @@
+// ============================================================================
+// INTERNAL HELPERS - Shared logic behind the endpoints
+// ============================================================================
+// Pricing curves, fee distribution, share accounting, transfers and access
+// checks. Nothing here is an endpoint.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::constants::*;
+use crate::math;
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait CommonModule: crate::storage::StorageModule + crate::events::EventsModule {
+    // ========================================================================
+    // INTERNAL HELPER FUNCTIONS
+    // ========================================================================
+
+    fn pair_exists(&self, pair_id: u64) -> bool {
+        self.registered_pair_ids().contains(&pair_id)
+    }
+
+    /// Take the reentrancy lock for the calling endpoint
+    /// Swaps, liquidity changes and fee claims send tokens after updating state; the
+    /// lock makes any nested call into one of them (e.g. from a transfer hook or a
+    /// swapFor callback) revert until the outer call returns.
+    fn non_reentrant(&self) -> ReentrancyGuard<Self::Api> {
+        require!(!self.reentrancy_lock().get(), "Reentrant call");
+        self.reentrancy_lock().set(true);
+        ReentrancyGuard::new(self.reentrancy_lock())
+    }
+
+    fn get_minimum_liquidity(&self) -> u64 {
+        if self.param_minimum_liquidity().is_empty() {
+            DEFAULT_MINIMUM_LIQUIDITY
+        } else {
+            self.param_minimum_liquidity().get()
+        }
+    }
+
+    fn get_max_lps_per_pair(&self) -> usize {
+        if self.param_max_lps_per_pair().is_empty() {
+            DEFAULT_LP_CAP
+        } else {
+            self.param_max_lps_per_pair().get()
+        }
+    }
+
+    fn get_distribution_incentive_bps(&self) -> u64 {
+        if self.param_distribution_incentive_bps().is_empty() {
+            DEFAULT_DISTRIBUTION_INCENTIVE_BPS
+        } else {
+            self.param_distribution_incentive_bps().get()
+        }
+    }
+
+    fn get_min_fee_bps(&self) -> u64 {
+        if self.param_min_fee_bps().is_empty() {
+            DEFAULT_MIN_FEE_BPS
+        } else {
+            self.param_min_fee_bps().get()
+        }
+    }
+
+    fn get_max_fee_bps(&self) -> u64 {
+        if self.param_max_fee_bps().is_empty() {
+            DEFAULT_MAX_FEE_BPS
+        } else {
+            self.param_max_fee_bps().get()
+        }
+    }
+
+    /// Max fee for pairs that include KLV: the stricter of the KLV cap and the global max
+    fn get_klv_max_fee_bps(&self) -> u64 {
+        let global_max = self.get_max_fee_bps();
+        if self.param_klv_max_fee_bps().is_empty() {
+            return global_max;
+        }
+        let klv_max = self.param_klv_max_fee_bps().get();
+        if klv_max < global_max {
+            klv_max
+        } else {
+            global_max
+        }
+    }
+
+    fn get_pending_expiry_days(&self) -> u64 {
+        if self.param_pending_expiry_days().is_empty() {
+            DEFAULT_PENDING_EXPIRY_DAYS
+        } else {
+            self.param_pending_expiry_days().get()
+        }
+    }
+
+    fn get_unstake_cooldown(&self) -> u64 {
+        if self.param_unstake_cooldown().is_empty() {
+            DEFAULT_UNSTAKE_COOLDOWN
+        } else {
+            self.param_unstake_cooldown().get()
+        }
+    }
+
+    fn get_protocol_fee_bps(&self) -> u64 {
+        if self.param_protocol_fee_bps().is_empty() {
+            DEFAULT_PROTOCOL_FEE_BPS
+        } else {
+            self.param_protocol_fee_bps().get()
+        }
+    }
+
+    fn require_fee_in_range(&self, fee_bps: u64, is_klv_pair: bool) {
+        require!(
+            fee_bps >= self.get_min_fee_bps() && fee_bps <= self.get_max_fee_bps(),
+            "Fee out of allowed range"
+        );
+        if is_klv_pair {
+            require!(fee_bps <= self.get_klv_max_fee_bps(), "Fee above max for KLV pairs");
+        }
+    }
+
+    fn require_fee_tier(&self, fee_bps: u64) {
+        require!(self.fee_tiers().contains(&fee_bps), "Fee is not an approved tier");
+    }
+
+    /// Approve the default fee tiers on a deployment that has none yet
+    fn seed_default_fee_tiers(&self) {
+        if !self.fee_tiers().is_empty() {
+            return;
+        }
+        for fee_bps in DEFAULT_FEE_TIERS_BPS.iter() {
+            self.fee_tiers().insert(*fee_bps);
+        }
+    }
+
+    /// Current admin: recovered backup admin if set, otherwise the contract owner
+    fn get_admin_address(&self) -> ManagedAddress {
+        if self.admin_address().is_empty() {
+            self.blockchain().get_owner_address()
+        } else {
+            self.admin_address().get()
+        }
+    }
+
+    /// Restrict to admin and record activity for the dead-man switch
+    /// Any admin action also cancels an in-progress recovery.
+    fn require_admin(&self) {
+        let caller = self.blockchain().get_caller();
+        require!(caller == self.get_admin_address(), "Endpoint can only be called by owner");
+        
+        self.last_admin_activity().set(self.blockchain().get_block_timestamp());
+        if !self.admin_recovery_started_at().is_empty() {
+            self.admin_recovery_started_at().clear();
+        }
+    }
+
+    /// Restrict to the admin or an address holding `role`
+    /// Admin calls go through require_admin so they still count as admin activity.
+    fn require_role(&self, role: u64) {
+        let caller = self.blockchain().get_caller();
+        if caller == self.get_admin_address() {
+            self.require_admin();
+            return;
+        }
+        require!(self.address_roles(&caller).get() & role != 0, "Caller is missing the required role");
+    }
+
+    /// Restrict to a pair's creator, the admin or an address holding `role`
+    fn require_pair_creator_or_role(&self, pair_id: u64, role: u64) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let caller = self.blockchain().get_caller();
+        if !self.pair_creator(pair_id).is_empty() && caller == self.pair_creator(pair_id).get() {
+            return;
+        }
+        self.require_role(role);
+    }
+
+    /// Permission needed to propose, execute or cancel a timelocked action
+    fn require_action_permission(&self, action: &AdminAction<Self::Api>) {
+        match action {
+            AdminAction::SetPairFee(..) => self.require_role(ROLE_FEE_MANAGER),
+            AdminAction::SetTreasuryAddress(..) | AdminAction::SetFeeSplit(..) => {
+                self.require_role(ROLE_TREASURY_MANAGER)
+            },
+            _ => self.require_admin(),
+        }
+    }
+
+    fn clear_admin_action(&self, action_id: u64) {
+        self.timelock_pending_actions().swap_remove(&action_id);
+        self.timelock_action(action_id).clear();
+        self.timelock_action_eta(action_id).clear();
+    }
+
+    fn get_timelock_delay(&self) -> u64 {
+        if self.admin_timelock_delay().is_empty() {
+            MIN_ADMIN_TIMELOCK_DELAY
+        } else {
+            self.admin_timelock_delay().get()
+        }
+    }
+
+    /// Every share backed by the reserves, including the locked MINIMUM_LIQUIDITY
+    fn get_total_shares_internal(&self, pair_id: u64) -> BigUint {
+        self.owner_shares(pair_id).get()
+            + self.total_lp_shares(pair_id).get()
+            + self.pair_locked_liquidity(pair_id).get()
+    }
+
+    /// Protocol-owned proceeds go to the treasury when one is configured, else to the admin
+    fn get_treasury_or_admin(&self) -> ManagedAddress {
+        if self.treasury_address().is_empty() {
+            self.get_admin_address()
+        } else {
+            self.treasury_address().get()
+        }
+    }
+
+    /// Reject denylisted tokens, and non-allowlisted ones while the allowlist is on
+    fn require_token_listable(&self, token: &TokenIdentifier) {
+        require!(!self.token_denylist().contains(token), "Token is denylisted");
+        
+        if self.token_allowlist_enabled().get() && *token != self.wklv_token_id() {
+            require!(self.token_allowlist().contains(token), "Token is not allowlisted");
+        }
+    }
+
+    /// Enforce the createPair anti-spam rules and forward the creation fee
+    /// Fee goes to the treasury when one is configured, else to the admin.
+    fn charge_pair_creation(&self, creator: &ManagedAddress) {
+        let payment = self.call_value().klv_value().clone_value();
+        if *creator == self.get_admin_address() {
+            require!(payment == BigUint::zero(), "Admin does not pay the creation fee");
+            return;
+        }
+        
+        let fee = self.pair_creation_fee().get();
+        require!(payment == fee, "Wrong pair creation fee");
+        
+        let now = self.blockchain().get_block_timestamp();
+        let cooldown = self.pair_creation_cooldown().get();
+        if cooldown > 0 && !self.creator_last_pair_created(creator).is_empty() {
+            require!(
+                now >= self.creator_last_pair_created(creator).get() + cooldown,
+                "Pair creation cooldown active"
+            );
+        }
+        self.creator_last_pair_created(creator).set(now);
+        
+        let max_pairs = self.max_pairs_per_creator().get();
+        require!(
+            max_pairs == 0 || self.creator_pair_count(creator).get() < max_pairs,
+            "Max pairs per creator reached"
+        );
+        
+        if fee > 0u64 {
+            self.send().direct_klv(&self.get_treasury_or_admin(), &fee);
+        }
+    }
+
+    fn remove_limit_order(&self, order_id: u64, order: &LimitOrder<Self::Api>) {
+        self.limit_order(order_id).clear();
+        self.pair_limit_orders(order.pair_id).swap_remove(&order_id);
+        self.user_limit_orders(&order.owner).swap_remove(&order_id);
+    }
+
+    /// Remove a DCA order from storage and the indexes (no transfer)
+    fn remove_dca_order(&self, order_id: u64, order: &DcaOrder<Self::Api>) {
+        self.dca_order(order_id).clear();
+        self.dca_order_ids().swap_remove(&order_id);
+        self.pair_dca_orders(order.pair_id).swap_remove(&order_id);
+        self.user_dca_orders(&order.owner).swap_remove(&order_id);
+    }
+
+    /// Remove a streaming swap from storage and the indexes (no transfer)
+    fn remove_stream_order(&self, order_id: u64, order: &StreamOrder<Self::Api>) {
+        self.stream_order(order_id).clear();
+        self.stream_order_ids().swap_remove(&order_id);
+        self.pair_stream_orders(order.pair_id).swap_remove(&order_id);
+        self.user_stream_orders(&order.owner).swap_remove(&order_id);
+    }
+
+    fn get_dca_keeper_fee_bps(&self) -> u64 {
+        if self.param_dca_keeper_fee_bps().is_empty() {
+            DEFAULT_DCA_KEEPER_FEE_BPS
+        } else {
+            self.param_dca_keeper_fee_bps().get()
+        }
+    }
+
+    /// Drop an order and return its escrow to the owner
+    fn refund_limit_order(&self, order_id: u64, order: &LimitOrder<Self::Api>) {
+        self.remove_limit_order(order_id, order);
+        
+        self.send_pair_token(&order.owner, order.pair_id, order.is_a_to_b, &order.sell_amount);
+    }
+
+    /// Next batch of up to `limit` ids of a keeper crank's order set
+    /// Starts where the previous batch stopped and wraps around at the end, so
+    /// orders that can't be executed don't hold back the ones after them.
+    fn next_order_batch(
+        &self,
+        order_ids: UnorderedSetMapper<Self::Api, u64>,
+        cursor: SingleValueMapper<Self::Api, u64>,
+        limit: u64,
+    ) -> ManagedVec<u64> {
+        let count = order_ids.len() as u64;
+        let mut start = cursor.get();
+        if start >= count {
+            start = 0;
+        }
+        let end = if start + limit > count { count } else { start + limit };
+        
+        let mut batch = ManagedVec::new();
+        for index in start..end {
+            // UnorderedSetMapper indexes are 1-based
+            batch.push(order_ids.get_by_index((index + 1) as usize));
+        }
+        cursor.set(if end >= count { 0 } else { end });
+        batch
+    }
+
+    /// Accrue emissions for a farmed pair up to now
+    fn update_farm_pool(&self, pair_id: u64) {
+        let now = self.blockchain().get_block_timestamp();
+        let last = self.farm_last_update(pair_id).get();
+        if now <= last {
+            return;
+        }
+        self.farm_last_update(pair_id).set(now);
+        
+        let total_staked = self.farm_total_staked(pair_id).get();
+        let weight = self.farm_pool_weight(pair_id).get();
+        let total_weight = self.farm_total_weight().get();
+        if total_staked == BigUint::zero() || weight == 0 || total_weight == 0 {
+            return;
+        }
+        
+        let reward = self.farm_reward_per_second().get() * (now - last) * weight / total_weight;
+        self.farm_acc_reward_per_share(pair_id)
+            .update(|acc| *acc += reward * PRECISION / &total_staked);
+    }
+
+    fn update_all_farm_pools(&self) {
+        for pair_id in self.farm_pairs().iter() {
+            self.update_farm_pool(pair_id);
+        }
+    }
+
+    /// Pay out a staker's accrued rewards, limited by the funded reward pool
+    /// Whatever the pool can't cover stays owed in farm_unpaid_reward.
+    fn harvest_internal(&self, pair_id: u64, addr: &ManagedAddress) {
+        self.update_farm_pool(pair_id);
+        
+        let staked = self.farm_staked(pair_id, addr).get();
+        let acc = self.farm_acc_reward_per_share(pair_id).get();
+        let accrued = &staked * &acc / PRECISION - self.farm_reward_debt(pair_id, addr).get();
+        let owed = self.farm_unpaid_reward(pair_id, addr).get() + accrued;
+        self.farm_reward_debt(pair_id, addr).set(&staked * &acc / PRECISION);
+        
+        if owed == BigUint::zero() {
+            return;
+        }
+        
+        let pool = self.farm_reward_pool().get();
+        let paid = if owed <= pool { owed.clone() } else { pool.clone() };
+        self.farm_unpaid_reward(pair_id, addr).set(&owed - &paid);
+        
+        if paid > BigUint::zero() {
+            self.farm_reward_pool().set(&pool - &paid);
+            self.send_wrapped(addr, &self.farm_reward_token().get(), &paid);
+        }
+    }
+
+    /// Record a new stake size and reset the reward debt to the current index
+    fn set_farm_stake(&self, pair_id: u64, addr: &ManagedAddress, staked: &BigUint) {
+        let acc = self.farm_acc_reward_per_share(pair_id).get();
+        if *staked == 0u64 {
+            self.farm_staked(pair_id, addr).clear();
+            self.farm_reward_debt(pair_id, addr).clear();
+        } else {
+            self.farm_staked(pair_id, addr).set(staked);
+            self.farm_reward_debt(pair_id, addr).set(staked * &acc / PRECISION);
+        }
+    }
+
+    fn require_not_paused(&self) {
+        require!(!self.globally_paused().get(), "Trading is paused");
+    }
+
+    fn get_pair_k(&self, pair_id: u64) -> BigUint {
+        self.pair_reserve_a(pair_id).get() * self.pair_reserve_b(pair_id).get()
+    }
+
+    /// Swap invariant of a pair for the given reserves
+    /// x * y for constant-product pools, D for stable pools,
+    /// x^wa * y^wb (weights reduced to smallest integers) for weighted pools
+    fn compute_invariant(&self, pair_id: u64, reserve_a: &BigUint, reserve_b: &BigUint) -> BigUint {
+        match self.pair_pool_type(pair_id).get() {
+            PoolType::ConstantProduct => reserve_a * reserve_b,
+            PoolType::Stable => self.stable_get_d(reserve_a, reserve_b, self.pair_amplification(pair_id).get()),
+            PoolType::Weighted => {
+                let (exp_a, exp_b) = self.get_reduced_weights(pair_id, true);
+                reserve_a.pow(exp_a) * reserve_b.pow(exp_b)
+            },
+        }
+    }
+
+    /// Safety net for swaps: the pool invariant must never go down.
+    /// Any math or rounding bug aborts the transaction instead of draining the pool.
+    /// Stable pools allow 1 unit of slack for the Newton iteration's own rounding.
+    fn verify_k_not_decreased(&self, pair_id: u64, old_k: &BigUint) {
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        let new_k = self.compute_invariant(pair_id, &reserve_a, &reserve_b);
+        let slack = match self.pair_pool_type(pair_id).get() {
+            PoolType::Stable => 1u64,
+            _ => 0u64,
+        };
+        require!(new_k + slack >= *old_k, "Invariant violated: k decreased");
+    }
+
+    /// Safety net for mint/burn: k per share must never go down
+    /// new_k / new_shares^2 >= old_k / old_shares^2, checked without division.
+    fn verify_k_per_share_not_decreased(&self, pair_id: u64, old_k: &BigUint, old_shares: &BigUint) {
+        if *old_shares == 0u64 {
+            return;
+        }
+        let new_shares = self.get_total_shares_internal(pair_id);
+        require!(
+            self.get_pair_k(pair_id) * old_shares * old_shares >= old_k * &new_shares * &new_shares,
+            "Invariant violated: k per share decreased"
+        );
+    }
+
+    /// Get pair creator, defaulting to contract owner for legacy pairs
+    fn get_pair_creator_or_owner(&self, pair_id: u64) -> ManagedAddress {
+        if self.pair_creator(pair_id).is_empty() {
+            self.get_admin_address()
+        } else {
+            self.pair_creator(pair_id).get()
+        }
+    }
+
+    /// Fee currently in force for a pair
+    /// A scheduled fee change becomes effective once its activation block is reached
+    fn get_effective_fee_bps(&self, pair_id: u64) -> u64 {
+        if !self.pair_pending_fee_bps(pair_id).is_empty()
+            && self.blockchain().get_block_nonce() >= self.pair_fee_activation_block(pair_id).get()
+        {
+            return self.pair_pending_fee_bps(pair_id).get();
+        }
+        self.get_pair_fee_bps(pair_id)
+    }
+
+    /// Fee a trader pays on a pair: the effective fee minus their staking tier discount
+    fn get_trader_fee_bps(&self, pair_id: u64, trader: &ManagedAddress) -> u64 {
+        let fee_bps = self.get_effective_fee_bps(pair_id);
+        fee_bps - fee_bps * self.get_staking_discount_bps(trader) / BPS_DENOMINATOR
+    }
+
+    /// Discount (bps of the fee) of the highest staking tier an address reaches
+    fn get_staking_discount_bps(&self, addr: &ManagedAddress) -> u64 {
+        let staked = self.staked_balance(addr).get();
+        if staked == 0u64 {
+            return 0;
+        }
+        let mut discount_bps = 0;
+        for tier in self.staking_tiers().get().iter() {
+            if staked < tier.min_stake {
+                break;
+            }
+            discount_bps = tier.discount_bps;
+        }
+        discount_bps
+    }
+
+    /// Stored fee in basis points
+    /// Falls back to the pre-bps `pair_fee_percent` value for pairs not yet migrated
+    fn get_pair_fee_bps(&self, pair_id: u64) -> u64 {
+        if self.pair_fee_bps(pair_id).is_empty() && !self.pair_fee_percent(pair_id).is_empty() {
+            return self.pair_fee_percent(pair_id).get() * 100;
+        }
+        self.pair_fee_bps(pair_id).get()
+    }
+
+    /// Convert a legacy whole-percent fee to basis points in storage
+    fn migrate_pair_fee_to_bps(&self, pair_id: u64) {
+        if self.pair_fee_percent(pair_id).is_empty() {
+            return;
+        }
+        if self.pair_fee_bps(pair_id).is_empty() {
+            self.pair_fee_bps(pair_id).set(self.pair_fee_percent(pair_id).get() * 100);
+        }
+        self.pair_fee_percent(pair_id).clear();
+    }
+
+    /// Split the legacy single active switch into the swaps and liquidity switches
+    fn migrate_pair_active_flags(&self, pair_id: u64) {
+        if self.pair_is_active(pair_id).is_empty() {
+            return;
+        }
+        let is_active = self.pair_is_active(pair_id).take();
+        self.pair_swaps_enabled(pair_id).set(is_active);
+        self.pair_liquidity_enabled(pair_id).set(is_active);
+    }
+
+    /// Move a scheduled fee into pair_fee_bps once it has activated
+    fn settle_pending_fee(&self, pair_id: u64) {
+        if self.pair_pending_fee_bps(pair_id).is_empty() {
+            return;
+        }
+        if self.blockchain().get_block_nonce() >= self.pair_fee_activation_block(pair_id).get() {
+            self.pair_fee_bps(pair_id).set(self.pair_pending_fee_bps(pair_id).get());
+            self.pair_pending_fee_bps(pair_id).clear();
+            self.pair_fee_activation_block(pair_id).clear();
+        }
+    }
+
+    /// Merkle leaf for an LP's pending fees
+    fn lp_fee_leaf(&self, addr: &ManagedAddress, fees_a: &BigUint, fees_b: &BigUint) -> ManagedByteArray<Self::Api, 32> {
+        let mut data = ManagedBuffer::new();
+        data.append(addr.as_managed_buffer());
+        let _ = fees_a.dep_encode(&mut data);
+        let _ = fees_b.dep_encode(&mut data);
+        self.crypto().sha256(&data)
+    }
+
+    /// Message a user signs for a meta-swap order
+    /// META_SWAP_DOMAIN | contract address | nested-encoded order
+    fn meta_swap_message(&self, order: &MetaSwapOrder<Self::Api>) -> ManagedBuffer {
+        let mut data = ManagedBuffer::new_from_bytes(META_SWAP_DOMAIN);
+        data.append(self.blockchain().get_sc_address().as_managed_buffer());
+        let _ = order.dep_encode(&mut data);
+        data
+    }
+
+    /// Hash two merkle nodes in sorted order (proofs need no left/right flags)
+    fn hash_sorted_pair(
+        &self,
+        a: &ManagedByteArray<Self::Api, 32>,
+        b: &ManagedByteArray<Self::Api, 32>,
+    ) -> ManagedByteArray<Self::Api, 32> {
+        let mut data = ManagedBuffer::new();
+        if a.to_byte_array() <= b.to_byte_array() {
+            data.append(a.as_managed_buffer());
+            data.append(b.as_managed_buffer());
+        } else {
+            data.append(b.as_managed_buffer());
+            data.append(a.as_managed_buffer());
+        }
+        self.crypto().sha256(&data)
+    }
+
+    /// Merkle root over all LPs' pending fees for a pair
+    /// Returns (root, leaf_count); root is all zeroes for a pair with no LPs
+    fn compute_lp_fee_merkle_root(&self, pair_id: u64) -> (ManagedByteArray<Self::Api, 32>, usize) {
+        let mut layer: ManagedVec<Self::Api, ManagedByteArray<Self::Api, 32>> = ManagedVec::new();
+        for addr in self.lp_list(pair_id).iter() {
+            let (fees_a, fees_b) = self.calculate_lp_pending_fees(pair_id, &addr);
+            layer.push(self.lp_fee_leaf(&addr, &fees_a, &fees_b));
+        }
+        
+        let leaf_count = layer.len();
+        if leaf_count == 0 {
+            return (ManagedByteArray::new_from_bytes(&[0u8; 32]), 0);
+        }
+        
+        while layer.len() > 1 {
+            let mut next_layer = ManagedVec::new();
+            let mut i = 0;
+            while i < layer.len() {
+                if i + 1 < layer.len() {
+                    next_layer.push(self.hash_sorted_pair(&layer.get(i), &layer.get(i + 1)));
+                } else {
+                    next_layer.push(layer.get(i).clone_value());
+                }
+                i += 2;
+            }
+            layer = next_layer;
+        }
+        
+        (layer.get(0).clone_value(), leaf_count)
+    }
+
+    /// Add a pair to the (token_a, token_b) -> pair_id index, in both orders,
+    /// and to the per-token pair sets used for route discovery
+    /// The first pair for a combination stays canonical; duplicates are not indexed.
+    fn index_pair_tokens(&self, pair_id: u64) {
+        if self.pair_is_archived(pair_id).get() {
+            return;
+        }
+        
+        let token_a = self.pair_token_a(pair_id).get();
+        let token_b = self.pair_token_b(pair_id).get();
+        
+        self.pairs_by_token(&token_a).insert(pair_id);
+        self.pairs_by_token(&token_b).insert(pair_id);
+        
+        if self.pair_by_tokens(&token_a, &token_b).is_empty() {
+            self.pair_by_tokens(&token_a, &token_b).set(pair_id);
+            self.pair_by_tokens(&token_b, &token_a).set(pair_id);
+        }
+    }
+
+    /// Remove a pair from the token index
+    /// If it was the canonical pair, the next remaining duplicate (if any) takes over.
+    fn unindex_pair_tokens(&self, pair_id: u64) {
+        let token_a = self.pair_token_a(pair_id).get();
+        let token_b = self.pair_token_b(pair_id).get();
+        
+        self.pairs_by_token(&token_a).swap_remove(&pair_id);
+        self.pairs_by_token(&token_b).swap_remove(&pair_id);
+        
+        if self.pair_by_tokens(&token_a, &token_b).get() != pair_id {
+            return;
+        }
+        
+        self.pair_by_tokens(&token_a, &token_b).clear();
+        self.pair_by_tokens(&token_b, &token_a).clear();
+        
+        for other_id in self.registered_pair_ids().iter() {
+            if other_id == pair_id || self.pair_is_archived(other_id).get() {
+                continue;
+            }
+            let other_a = self.pair_token_a(other_id).get();
+            let other_b = self.pair_token_b(other_id).get();
+            if (other_a == token_a && other_b == token_b) || (other_a == token_b && other_b == token_a) {
+                self.pair_by_tokens(&token_a, &token_b).set(other_id);
+                self.pair_by_tokens(&token_b, &token_a).set(other_id);
+                return;
+            }
+        }
+    }
+
+    /// Build the full PairInfo snapshot for views
+    fn build_pair_info(&self, pair_id: u64) -> PairInfo<Self::Api> {
+        PairInfo {
+            pair_id,
+            token_a: self.pair_token_a(pair_id).get(),
+            token_b: self.pair_token_b(pair_id).get(),
+            token_a_is_klv: self.pair_token_a_is_klv(pair_id).get(),
+            token_b_is_klv: self.pair_token_b_is_klv(pair_id).get(),
+            reserve_a: self.pair_reserve_a(pair_id).get(),
+            reserve_b: self.pair_reserve_b(pair_id).get(),
+            fee_bps: self.get_effective_fee_bps(pair_id),
+            is_active: self.pair_swaps_enabled(pair_id).get(),
+            creator: self.get_pair_creator_or_owner(pair_id),
+            total_shares: self.get_total_shares_internal(pair_id),
+            lp_count: self.lp_list(pair_id).len(),
+            is_verified: self.verified_pair_ids().contains(&pair_id),
+        }
+    }
+
+    /// Helper: Read a single incoming payment (KLV or one KDA transfer)
+    /// Returns (token, amount), with native KLV wrapped as WKLV; the payment is owed from here on.
+    fn get_single_payment(&self) -> (TokenIdentifier, BigUint) {
+        let klv_amount = self.call_value().klv_value().clone_value();
+        if klv_amount > BigUint::zero() {
+            self.add_token_obligation(&self.wklv_token_id(), &klv_amount);
+            return (self.wklv_token_id(), klv_amount);
+        }
+        
+        let (token, amount) = self.call_value().single_fungible_kda();
+        self.add_token_obligation(&token, &amount);
+        (token, amount)
+    }
+
+    /// Helper: Get the single payment with its nonce (KLV or one KDA, fungible or not)
+    fn get_single_payment_with_nonce(&self) -> (TokenIdentifier, u64, BigUint) {
+        let klv_amount = self.call_value().klv_value().clone_value();
+        if klv_amount > BigUint::zero() {
+            self.add_token_obligation(&self.wklv_token_id(), &klv_amount);
+            return (self.wklv_token_id(), 0, klv_amount);
+        }
+        
+        let payment = self.call_value().single_kda();
+        self.add_token_obligation(&payment.token_identifier, &payment.amount);
+        (payment.token_identifier, payment.token_nonce, payment.amount)
+    }
+
+    /// A payment taken into the contract's books (see `token_obligations`)
+    fn add_token_obligation(&self, token: &TokenIdentifier, amount: &BigUint) {
+        self.token_obligations(token).update(|o| *o += amount);
+    }
+
+    /// A transfer out of the contract's books
+    /// Saturates, so balances held from before the counter was seeded can still leave.
+    /// Skipped while pairs of the token are still being seeded: a transfer out of an
+    /// unseeded pair is already missing from the share the crank will add, so the
+    /// counter may only overstate what is owed until then.
+    fn remove_token_obligation(&self, token: &TokenIdentifier, amount: &BigUint) {
+        if self.token_unseeded_pairs(token).get() > 0 {
+            return;
+        }
+        self.token_obligations(token).update(|o| {
+            if *o > *amount {
+                *o -= amount;
+            } else {
+                *o = BigUint::zero();
+            }
+        });
+    }
+
+    /// Helper: Take the payment sent into a pair and work out the side it pays in
+    /// Returns (is_token_a, amount); the token nonce must match the pair's nonce too.
+    fn take_pair_payment(&self, pair_id: u64) -> (bool, BigUint) {
+        let (token, nonce, amount) = self.get_single_payment_with_nonce();
+        (self.resolve_pair_side(pair_id, &token, nonce), amount)
+    }
+
+    /// Helper: Like resolve_swap_direction, but also checks the token nonce
+    fn resolve_pair_side(&self, pair_id: u64, token: &TokenIdentifier, nonce: u64) -> bool {
+        let is_token_a = self.resolve_swap_direction(pair_id, token);
+        require!(nonce == self.pair_token_nonce(pair_id, is_token_a), "Wrong token nonce");
+        is_token_a
+    }
+
+    /// Helper: Work out swap direction from the token being sent into a pair
+    /// Returns true for A -> B, false for B -> A. Fails if the token is not in the pair.
+    fn resolve_swap_direction(&self, pair_id: u64, token: &TokenIdentifier) -> bool {
+        if *token == self.pair_token(pair_id, true) {
+            return true;
+        }
+        if *token == self.pair_token(pair_id, false) {
+            return false;
+        }
+        
+        sc_panic!("Token not in pair");
+    }
+
+    /// Decimals of a token from the registry; KLV is always KLV_DECIMALS
+    fn get_token_decimals(&self, token: &TokenIdentifier) -> Option<u32> {
+        if *token == self.wklv_token_id() {
+            return Some(KLV_DECIMALS);
+        }
+        if self.token_decimals(token).is_empty() {
+            return None;
+        }
+        Some(self.token_decimals(token).get())
+    }
+
+    fn pair_decimals(&self, pair_id: u64, is_token_a: bool) -> SingleValueMapper<u32> {
+        if is_token_a {
+            self.pair_decimals_a(pair_id)
+        } else {
+            self.pair_decimals_b(pair_id)
+        }
+    }
+
+    /// Decimals of one side of a pair, falling back to the registry for pairs
+    /// created before their tokens were registered
+    fn get_pair_decimals(&self, pair_id: u64, is_token_a: bool) -> Option<u32> {
+        let mapper = self.pair_decimals(pair_id, is_token_a);
+        if !mapper.is_empty() {
+            return Some(mapper.get());
+        }
+        self.get_token_decimals(&self.pair_token(pair_id, is_token_a))
+    }
+
+    /// Turn a raw price of A in B (amount_b / amount_a, in token units) into the
+    /// price of one whole A in whole B, scaled to 1e18
+    /// None when either token's decimals are unknown or amount_a is 0.
+    fn normalize_price_a_in_b(&self, pair_id: u64, amount_b: &BigUint, amount_a: &BigUint) -> Option<BigUint> {
+        let decimals_a = self.get_pair_decimals(pair_id, true)?;
+        let decimals_b = self.get_pair_decimals(pair_id, false)?;
+        if *amount_a == 0u64 {
+            return None;
+        }
+        let ten = BigUint::from(10u64);
+        Some(amount_b * &ten.pow(decimals_a + NORMALIZED_PRICE_DECIMALS) / (amount_a * &ten.pow(decimals_b)))
+    }
+
+    /// Express a normalized price of A in B in `base_token` (A: as is, B: inverted)
+    /// An inverted price of 0 stays 0. Fails if the token is not in the pair.
+    fn price_in_base_token(&self, pair_id: u64, base_token: &TokenIdentifier, price_a_in_b: BigUint) -> BigUint {
+        if self.resolve_swap_direction(pair_id, base_token) || price_a_in_b == 0u64 {
+            return price_a_in_b;
+        }
+        BigUint::from(10u64).pow(2 * NORMALIZED_PRICE_DECIMALS) / price_a_in_b
+    }
+
+    /// KLV-equivalent value of a token amount, if it can be priced
+    /// KLV counts as itself; other tokens use their reference price, else the spot
+    /// price of their canonical constant-product KLV pair.
+    fn token_klv_value(&self, token: &TokenIdentifier, amount: &BigUint) -> Option<BigUint> {
+        let wklv = self.wklv_token_id();
+        if *token == wklv {
+            return Some(amount.clone());
+        }
+        if !self.token_reference_price(token).is_empty() {
+            return Some(amount * &self.token_reference_price(token).get() / PRECISION);
+        }
+        
+        let klv_pair = self.pair_by_tokens(token, &wklv).get();
+        if klv_pair == 0 || self.pair_pool_type(klv_pair).get() != PoolType::ConstantProduct {
+            return None;
+        }
+        let reserve_a = self.pair_reserve_a(klv_pair).get();
+        let reserve_b = self.pair_reserve_b(klv_pair).get();
+        if reserve_a == 0u64 || reserve_b == 0u64 {
+            return None;
+        }
+        if self.pair_token_a(klv_pair).get() == *token {
+            Some(amount * &reserve_b / &reserve_a)
+        } else {
+            Some(amount * &reserve_a / &reserve_b)
+        }
+    }
+
+    /// Sanity checks on the deposit that sets a pair's price
+    /// The deposit must be worth at least the configured KLV minimum (a side that
+    /// cannot be priced is assumed worth the other; unpriceable pairs are let through).
+    /// When both tokens have a reference price, the implied price must also sit within
+    /// the reference band, adjusted for the pool weights.
+    fn check_initial_liquidity(&self, pair_id: u64, amount_a: &BigUint, amount_b: &BigUint) {
+        let value_a = self.token_klv_value(&self.pair_token_a(pair_id).get(), amount_a);
+        let value_b = self.token_klv_value(&self.pair_token_b(pair_id).get(), amount_b);
+        
+        let min_value = self.param_min_initial_liquidity_klv().get();
+        if min_value > 0u64 {
+            let total_value = match (&value_a, &value_b) {
+                (Some(a), Some(b)) => Some(a + b),
+                (Some(v), None) | (None, Some(v)) => Some(v * 2u64),
+                (None, None) => None,
+            };
+            if let Some(total_value) = total_value {
+                require!(total_value >= min_value, "Initial liquidity below minimum value");
+            }
+        }
+        
+        let band_bps = self.param_reference_band_bps().get();
+        if band_bps == 0 || !self.has_reference_price(pair_id) {
+            return;
+        }
+        if let (Some(value_a), Some(value_b)) = (value_a, value_b) {
+            // Each side's value is proportional to its weight: value_a / w_a == value_b / w_b
+            let (weight_a, weight_b) = self.get_reduced_weights(pair_id, true);
+            let expected = value_a * weight_b as u64;
+            let actual = value_b * weight_a as u64;
+            require!(
+                &actual * BPS_DENOMINATOR >= &expected * (BPS_DENOMINATOR - band_bps)
+                    && &actual * BPS_DENOMINATOR <= &expected * (BPS_DENOMINATOR + band_bps),
+                "Initial price outside reference band"
+            );
+        }
+    }
+
+    /// Both tokens of the pair are KLV or carry an owner-set reference price
+    fn has_reference_price(&self, pair_id: u64) -> bool {
+        let wklv = self.wklv_token_id();
+        [self.pair_token_a(pair_id).get(), self.pair_token_b(pair_id).get()]
+            .iter()
+            .all(|token| *token == wklv || !self.token_reference_price(token).is_empty())
+    }
+
+    /// Add liquidity for `caller` from amounts already held by the contract
+    /// Empty pools take everything (first LP sets the ratio); otherwise amounts
+    /// are matched to the pool ratio and the excess is refunded.
+    fn mint_internal(
+        &self,
+        pair_id: u64,
+        caller: &ManagedAddress,
+        amount_a: &BigUint,
+        amount_b: &BigUint,
+        min_lp_shares: &BigUint,
+    ) -> BigUint {
+        self.require_not_paused();
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        // V5: Handle empty pool case (first LP sets the ratio)
+        let (new_shares, used_a, used_b, refund_a, refund_b) = 
+            if reserve_a == BigUint::zero() && reserve_b == BigUint::zero() {
+                // FIRST LP - They set the price ratio!
+                // shares = sqrt(a * b) - MINIMUM_LIQUIDITY (to prevent dust attacks)
+                let minimum_liquidity = BigUint::from(self.get_minimum_liquidity());
+                let product = amount_a * amount_b;
+                let sqrt_shares = product.sqrt();
+                
+                require!(
+                    sqrt_shares > minimum_liquidity,
+                    "Initial liquidity too small"
+                );
+                self.check_initial_liquidity(pair_id, amount_a, amount_b);
+                
+                let new_shares = sqrt_shares - &minimum_liquidity;
+                
+                // MINIMUM_LIQUIDITY shares are locked for good, like Uniswap's burn to address(0)
+                self.pair_locked_liquidity(pair_id).set(&minimum_liquidity);
+                
+                // Use all provided amounts (no matching needed for first LP)
+                (new_shares, amount_a.clone(), amount_b.clone(), BigUint::zero(), BigUint::zero())
+            } else {
+                // EXISTING POOL - Match to current ratio
+                let total_shares = self.get_total_shares_internal(pair_id);
+                
+                // Calculate how much we can actually use (maintain pool ratio, rounded in the pool's favour)
+                let (used_a, used_b) = math::match_deposit(amount_a, amount_b, &reserve_a, &reserve_b);
+                let refund_a = amount_a - &used_a;
+                let refund_b = amount_b - &used_b;
+                
+                require!(used_a > BigUint::zero() && used_b > BigUint::zero(), "Amounts too small");
+                
+                // Calculate shares based on smaller ratio (fair for existing LPs)
+                let new_shares = math::shares_for_deposit(&used_a, &used_b, &reserve_a, &reserve_b, &total_shares);
+                
+                (new_shares, used_a, used_b, refund_a, refund_b)
+            };
+        
+        // Slippage protection
+        require!(new_shares >= *min_lp_shares, "Slippage: shares below minimum");
+        require!(new_shares > BigUint::zero(), "Shares must be > 0");
+        
+        // Claim any pending fees first (if already an LP)
+        self.claim_pending_fees_internal(pair_id, caller);
+        
+        let old_k = &reserve_a * &reserve_b;
+        let old_shares = self.get_total_shares_internal(pair_id);
+        
+        // Update reserves
+        self.pair_reserve_a(pair_id).update(|r| *r += &used_a);
+        self.pair_reserve_b(pair_id).update(|r| *r += &used_b);
+        
+        // Add LP shares using existing helper (handles fee tracking)
+        self.credit_lp_position(pair_id, caller, &new_shares);
+        self.record_lp_deposit(pair_id, caller, &used_a, &used_b);
+        self.verify_k_per_share_not_decreased(pair_id, &old_k, &old_shares);
+        self.mint_event(pair_id, caller, &used_a, &used_b, &new_shares);
+        
+        // Refund any excess tokens
+        if refund_a > BigUint::zero() {
+            self.send_pair_token(caller, pair_id, true, &refund_a);
+        }
+        if refund_b > BigUint::zero() {
+            self.send_pair_token(caller, pair_id, false, &refund_b);
+        }
+        
+        new_shares
+    }
+
+    /// Gross swap output (before fee) for `amount_in`, on the pair's curve
+    fn get_amount_out_raw(
+        &self,
+        pair_id: u64,
+        is_a_to_b: bool,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        amount_in: &BigUint,
+    ) -> BigUint {
+        match self.pair_pool_type(pair_id).get() {
+            PoolType::ConstantProduct => amount_in * reserve_out / (reserve_in + amount_in),
+            PoolType::Stable => {
+                let amp = self.pair_amplification(pair_id).get();
+                let d = self.stable_get_d(reserve_in, reserve_out, amp);
+                let new_reserve_out = self.stable_get_y(&(reserve_in + amount_in), &d, amp);
+                // Round against the trader
+                if *reserve_out > &new_reserve_out + 1u64 {
+                    reserve_out - &new_reserve_out - 1u64
+                } else {
+                    BigUint::zero()
+                }
+            },
+            PoolType::Weighted => {
+                // out = B_out * (1 - (B_in / (B_in + A_in))^(w_in / w_out))
+                let (exp_in, exp_out) = self.get_reduced_weights(pair_id, is_a_to_b);
+                let ratio = reserve_in * PRECISION / (reserve_in + amount_in);
+                // +1 rounds the kept fraction up, i.e. the output down
+                let kept = self.pow_fraction(&ratio, exp_in, exp_out) + 1u64;
+                if kept >= PRECISION {
+                    BigUint::zero()
+                } else {
+                    reserve_out * &(BigUint::from(PRECISION) - kept) / PRECISION
+                }
+            },
+        }
+    }
+
+    /// Input needed for a gross swap output of `output_raw` (rounded up), on the pair's curve
+    /// Caller must ensure output_raw < reserve_out.
+    fn get_amount_in_raw(
+        &self,
+        pair_id: u64,
+        is_a_to_b: bool,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        output_raw: &BigUint,
+    ) -> BigUint {
+        let new_reserve_out = reserve_out - output_raw;
+        match self.pair_pool_type(pair_id).get() {
+            PoolType::ConstantProduct => (reserve_in * output_raw + &new_reserve_out - 1u64) / &new_reserve_out,
+            PoolType::Stable => {
+                let amp = self.pair_amplification(pair_id).get();
+                let d = self.stable_get_d(reserve_in, reserve_out, amp);
+                let new_reserve_in = self.stable_get_y(&new_reserve_out, &d, amp);
+                // Newton's rounding can land y just under reserve_in for tiny outputs
+                if new_reserve_in > *reserve_in {
+                    &new_reserve_in - reserve_in + 1u64
+                } else {
+                    BigUint::from(1u64)
+                }
+            },
+            PoolType::Weighted => {
+                // in = B_in * ((B_out / (B_out - out))^(w_out / w_in) - 1)
+                let (exp_in, exp_out) = self.get_reduced_weights(pair_id, is_a_to_b);
+                let ratio = (reserve_out * PRECISION + &new_reserve_out - 1u64) / &new_reserve_out;
+                let growth = self.pow_fraction(&ratio, exp_out, exp_in) + 1u64;
+                (reserve_in * &(growth - PRECISION) + PRECISION - 1u64) / PRECISION + 1u64
+            },
+        }
+    }
+
+    /// Whether a swap pays its fee in token A
+    /// Fee-on-input pairs charge the input token, the others the output token.
+    fn fee_is_token_a(&self, pair_id: u64, is_a_to_b: bool) -> bool {
+        if self.pair_fee_on_input(pair_id).get() {
+            is_a_to_b
+        } else {
+            !is_a_to_b
+        }
+    }
+
+    /// Exact-input swap in the pair's fee mode, as (curve_in, curve_out, user_gets, fee)
+    /// Fee-on-input: the fee is cut from amount_in and only the rest goes
+    /// through the curve, so user_gets == curve_out.
+    /// Fee-on-output (pairs that haven't opted in): the curve sees the full
+    /// amount_in and the fee is cut from its output.
+    /// curve_in enters reserve_in and curve_out leaves reserve_out; the fee is
+    /// held outside the reserves in both modes.
+    /// The fee is the trader's: DGKO stakers pay less (see get_trader_fee_bps).
+    fn get_swap_amounts(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        is_a_to_b: bool,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        amount_in: &BigUint,
+    ) -> (BigUint, BigUint, BigUint, BigUint) {
+        let fee_bps = self.get_trader_fee_bps(pair_id, trader);
+        if self.pair_fee_on_input(pair_id).get() {
+            let fee = amount_in * fee_bps / BPS_DENOMINATOR;
+            let curve_in = amount_in - &fee;
+            let curve_out = self.get_amount_out_raw(pair_id, is_a_to_b, reserve_in, reserve_out, &curve_in);
+            (curve_in, curve_out.clone(), curve_out, fee)
+        } else {
+            let curve_out = self.get_amount_out_raw(pair_id, is_a_to_b, reserve_in, reserve_out, amount_in);
+            let fee = &curve_out * fee_bps / BPS_DENOMINATOR;
+            let user_gets = &curve_out - &fee;
+            (amount_in.clone(), curve_out, user_gets, fee)
+        }
+    }
+
+    /// Input for an exact-output swap in the pair's fee mode, as (amount_in, fee)
+    /// Rounds UP at every step, so get_swap_amounts on amount_in yields at least
+    /// desired_output. None when the pair can't deliver desired_output.
+    fn get_swap_input(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        is_a_to_b: bool,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        desired_output: &BigUint,
+    ) -> Option<(BigUint, BigUint)> {
+        let fee_bps = self.get_trader_fee_bps(pair_id, trader);
+        let fee_divisor = BigUint::from(BPS_DENOMINATOR - fee_bps);
+        
+        if self.pair_fee_on_input(pair_id).get() {
+            if desired_output >= reserve_out {
+                return None;
+            }
+            // amount_in - fee must still cover the curve input
+            let curve_in = self.get_amount_in_raw(pair_id, is_a_to_b, reserve_in, reserve_out, desired_output);
+            let amount_in = (&curve_in * BPS_DENOMINATOR + &fee_divisor - 1u64) / &fee_divisor;
+            let fee = &amount_in * fee_bps / BPS_DENOMINATOR;
+            return Some((amount_in, fee));
+        }
+        
+        // Gross output needed so that output - fee >= desired_output
+        let output_raw = (desired_output * BPS_DENOMINATOR + &fee_divisor - 1u64) / &fee_divisor;
+        if output_raw >= *reserve_out {
+            return None;
+        }
+        let amount_in = self.get_amount_in_raw(pair_id, is_a_to_b, reserve_in, reserve_out, &output_raw);
+        let fee = &output_raw * fee_bps / BPS_DENOMINATOR;
+        Some((amount_in, fee))
+    }
+
+    /// Weights of the input and output side, divided by their gcd (e.g. 80/20 -> (4, 1))
+    /// Constant-product and stable pairs count as 50/50 -> (1, 1).
+    fn get_reduced_weights(&self, pair_id: u64, is_a_to_b: bool) -> (u32, u32) {
+        if self.pair_pool_type(pair_id).get() != PoolType::Weighted {
+            return (1, 1);
+        }
+        let weight_a = self.get_pair_weight_a(pair_id);
+        let weight_b = 100 - weight_a;
+        
+        let (mut x, mut y) = (weight_a, weight_b);
+        while y != 0 {
+            let r = x % y;
+            x = y;
+            y = r;
+        }
+        let (reduced_a, reduced_b) = ((weight_a / x) as u32, (weight_b / x) as u32);
+        
+        if is_a_to_b {
+            (reduced_a, reduced_b)
+        } else {
+            (reduced_b, reduced_a)
+        }
+    }
+
+    /// Current token A weight in percent of a weighted pair
+    /// Liquidity bootstrapping pools follow their schedule, rounded to the weight
+    /// step towards the start weight (the weight moves one step at a time).
+    fn get_pair_weight_a(&self, pair_id: u64) -> u64 {
+        if self.pair_lbp_schedule(pair_id).is_empty() {
+            return self.pair_weight_a(pair_id).get();
+        }
+        let schedule = self.pair_lbp_schedule(pair_id).get();
+        let now = self.blockchain().get_block_timestamp();
+        if now <= schedule.start_time {
+            return schedule.start_weight_a;
+        }
+        if now >= schedule.end_time {
+            return schedule.end_weight_a;
+        }
+        
+        let elapsed = now - schedule.start_time;
+        let duration = schedule.end_time - schedule.start_time;
+        let shift = |from: u64, to: u64| {
+            (to - from) * elapsed / duration / WEIGHT_STEP_PERCENT * WEIGHT_STEP_PERCENT
+        };
+        if schedule.end_weight_a < schedule.start_weight_a {
+            schedule.start_weight_a - shift(schedule.end_weight_a, schedule.start_weight_a)
+        } else {
+            schedule.start_weight_a + shift(schedule.start_weight_a, schedule.end_weight_a)
+        }
+    }
+
+    /// Whether a pair's LBP weight schedule hasn't finished yet
+    fn lbp_is_running(&self, pair_id: u64) -> bool {
+        !self.pair_lbp_schedule(pair_id).is_empty()
+            && self.blockchain().get_block_timestamp() < self.pair_lbp_schedule(pair_id).get().end_time
+    }
+
+    /// Weights must leave both sides at least MIN_WEIGHT_PERCENT, in WEIGHT_STEP_PERCENT steps
+    fn require_valid_weight(&self, weight_a: u64) {
+        require!(
+            weight_a >= MIN_WEIGHT_PERCENT && weight_a <= 100 - MIN_WEIGHT_PERCENT && weight_a % WEIGHT_STEP_PERCENT == 0,
+            "Invalid weight"
+        );
+    }
+
+    /// base^(num/den) for a PRECISION-scaled base, result PRECISION-scaled (rounded down)
+    /// Computed as the den-th integer root of base^num * PRECISION^(den - num).
+    fn pow_fraction(&self, base: &BigUint, num: u32, den: u32) -> BigUint {
+        let precision = BigUint::from(PRECISION);
+        // Scale so that the den-th root comes out PRECISION-scaled
+        let target = if den >= num {
+            base.pow(num) * precision.pow(den - num)
+        } else {
+            base.pow(num) / precision.pow(num - den)
+        };
+        if den == 1 {
+            return target;
+        }
+        
+        // Newton's method for the integer den-th root, starting above the root:
+        // base^(num/den) <= 1 for base <= 1, and <= base^num for base > 1
+        let mut x = if *base <= precision {
+            precision.clone()
+        } else {
+            base.pow(num) / precision.pow(num - 1) + 1u64
+        };
+        loop {
+            let next = (&x * (den - 1) as u64 + &target / &x.pow(den - 1)) / den as u64;
+            if next >= x {
+                return x;
+            }
+            x = next;
+        }
+    }
+
+    /// StableSwap invariant D for two balances (Newton's method)
+    /// A·n^n·(x+y) + D = A·n^n·D + D^(n+1) / (n^n·x·y), with n = 2
+    fn stable_get_d(&self, x: &BigUint, y: &BigUint, amp: u64) -> BigUint {
+        let sum = x + y;
+        if sum == 0u64 {
+            return sum;
+        }
+        
+        let ann = BigUint::from(amp * 4);
+        let mut d = sum.clone();
+        for _ in 0..STABLE_MAX_ITERATIONS {
+            let d_p = &d * &d / (x * 2u64) * &d / (y * 2u64);
+            let d_prev = d.clone();
+            d = (&ann * &sum + &d_p * 2u64) * &d / ((&ann - 1u64) * &d + &d_p * 3u64);
+            
+            let diff = if d > d_prev { &d - &d_prev } else { &d_prev - &d };
+            if diff <= 1u64 {
+                break;
+            }
+        }
+        d
+    }
+
+    /// Balance of the other token that keeps invariant D when one balance is `x`
+    fn stable_get_y(&self, x: &BigUint, d: &BigUint, amp: u64) -> BigUint {
+        let ann = BigUint::from(amp * 4);
+        let c = d * d / (x * 2u64) * d / (&ann * 2u64);
+        let b = x + &(d / &ann);
+        
+        let mut y = d.clone();
+        for _ in 0..STABLE_MAX_ITERATIONS {
+            let y_prev = y.clone();
+            let denominator = &y * 2u64 + &b;
+            require!(denominator > *d, "Stable curve did not converge");
+            y = (&y * &y + &c) / (denominator - d);
+            
+            let diff = if y > y_prev { &y - &y_prev } else { &y_prev - &y };
+            if diff <= 1u64 {
+                break;
+            }
+        }
+        y
+    }
+
+    /// Portion of a single-sided deposit to swap so the rest matches the pool ratio
+    /// Solves (1-f)*s^2 + (2-f)*R*s - a*R = 0 for s, with f = fee_bps / 10000:
+    /// s = (sqrt(((2D-F)*R)^2 + 4*(D-F)*D*a*R) - (2D-F)*R) / (2*(D-F))
+    /// On fee-on-input pairs only (1-f)*s reaches the reserve, which gives
+    /// (1-f)^2*s^2 + (2-f)*R*s - a*R = 0:
+    /// s = D*(sqrt(((2D-F)*R)^2 + 4*(D-F)^2*a*R) - (2D-F)*R) / (2*(D-F)^2)
+    fn get_zap_swap_amount(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        reserve_in: &BigUint,
+        amount: &BigUint,
+    ) -> BigUint {
+        let fee_bps = self.get_trader_fee_bps(pair_id, trader);
+        let two_d_minus_f = BigUint::from(2 * BPS_DENOMINATOR - fee_bps);
+        let d_minus_f = BigUint::from(BPS_DENOMINATOR - fee_bps);
+        
+        let b = &two_d_minus_f * reserve_in;
+        if self.pair_fee_on_input(pair_id).get() {
+            let d_minus_f_sq = &d_minus_f * &d_minus_f;
+            let discriminant = &b * &b + &d_minus_f_sq * 4u64 * amount * reserve_in;
+            return (discriminant.sqrt() - &b) * BPS_DENOMINATOR / (d_minus_f_sq * 2u64);
+        }
+        
+        let discriminant = &b * &b + &d_minus_f * BPS_DENOMINATOR * 4u64 * amount * reserve_in;
+        
+        (discriminant.sqrt() - &b) / (d_minus_f * 2u64)
+    }
+
+    /// Core constant-product swap against a pair's reserves
+    /// Updates reserves and distributes the fee, but does NOT transfer anything to
+    /// the user (only the fee splitter may forward the protocol cut) - the caller
+    /// is responsible for paying out the returned amount.
+    /// 
+    /// Fee is taken from the input token on fee-on-input pairs and from the
+    /// output token on the others (see setPairFeeOnInput).
+    /// When a referrer is given, its share of the fee is credited before the
+    /// rest is distributed to LPs / owner / treasury.
+    /// `trader` is whoever the swap is made for: it gets the compliance checks,
+    /// the fee discount, the rebate and the stats credit.
+    /// @return Amount the user receives after fee
+    fn swap_internal(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        is_a_to_b: bool,
+        payment: &BigUint,
+        referrer: &Option<ManagedAddress>,
+    ) -> BigUint {
+        self.require_not_paused();
+        require!(*payment > 0u64, "Payment must be greater than 0");
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        require!(reserve_a > 0u64 && reserve_b > 0u64, "Reserves empty");
+        
+        let (reserve_in, reserve_out) = if is_a_to_b {
+            (&reserve_a, &reserve_b)
+        } else {
+            (&reserve_b, &reserve_a)
+        };
+        
+        let (curve_in, curve_out, user_gets, fee) =
+            self.get_swap_amounts(pair_id, trader, is_a_to_b, reserve_in, reserve_out, payment);
+        require!(curve_out > 0u64 && curve_out < *reserve_out, "Invalid output");
+        let old_k = self.compute_invariant(pair_id, &reserve_a, &reserve_b);
+        self.update_price_accumulator(pair_id, &reserve_a, &reserve_b);
+        
+        let volume_a = if is_a_to_b { payment } else { &curve_out };
+        self.check_compliance(pair_id, trader, volume_a);
+        self.check_swap_guards(pair_id, trader, is_a_to_b, reserve_in, payment);
+        self.check_trade_limits(pair_id, is_a_to_b, reserve_out, &curve_out);
+        
+        require!(user_gets > 0u64, "Output too small after fee");
+        
+        if is_a_to_b {
+            self.pair_reserve_a(pair_id).set(&reserve_a + &curve_in);
+            self.pair_reserve_b(pair_id).set(&reserve_b - &curve_out);
+        } else {
+            self.pair_reserve_b(pair_id).set(&reserve_b + &curve_in);
+            self.pair_reserve_a(pair_id).set(&reserve_a - &curve_out);
+        }
+        self.verify_k_not_decreased(pair_id, &old_k);
+        self.check_twap_guard(pair_id, &reserve_a, &reserve_b);
+        
+        let fee_is_token_a = self.fee_is_token_a(pair_id, is_a_to_b);
+        let referral_portion = match referrer {
+            Some(referrer) => self.credit_referral_fee(pair_id, trader, referrer, &fee, fee_is_token_a),
+            None => BigUint::zero(),
+        };
+        let rebate_portion = self.credit_fee_rebate(pair_id, trader, volume_a, &fee, fee_is_token_a);
+        self.distribute_fee(pair_id, &(&fee - &referral_portion - &rebate_portion), fee_is_token_a);
+        self.record_swap_stats(pair_id, trader, is_a_to_b, payment, &curve_out, &fee, fee_is_token_a);
+        
+        let (token_in, token_out) = if is_a_to_b {
+            (self.pair_token_a(pair_id).get(), self.pair_token_b(pair_id).get())
+        } else {
+            (self.pair_token_b(pair_id).get(), self.pair_token_a(pair_id).get())
+        };
+        self.swap_event(
+            pair_id,
+            trader,
+            &token_in,
+            payment,
+            &token_out,
+            &user_gets,
+            &fee,
+        );
+        
+        user_gets
+    }
+
+    /// Enforce a pair's anti-sandwich guards (see setPairSwapGuards)
+    fn check_swap_guards(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        is_a_to_b: bool,
+        reserve_in: &BigUint,
+        amount_in: &BigUint,
+    ) {
+        let max_impact_bps = self.pair_max_price_impact_bps(pair_id).get();
+        if max_impact_bps > 0 {
+            require!(
+                self.compute_price_impact_bps(reserve_in, amount_in) <= max_impact_bps,
+                "Price impact above pair limit"
+            );
+        }
+        
+        if self.pair_block_direction_lock(pair_id).get() {
+            let block = self.blockchain().get_block_nonce();
+            if self.last_swap_block(pair_id, trader).get() == block {
+                require!(
+                    self.last_swap_direction(pair_id, trader).get() == is_a_to_b,
+                    "Cannot swap both directions in one block"
+                );
+            } else {
+                self.last_swap_block(pair_id, trader).set(block);
+                self.last_swap_direction(pair_id, trader).set(is_a_to_b);
+            }
+        }
+    }
+
+    /// Spot price of token A in token B, scaled by PRECISION (weight-adjusted for weighted pools)
+    fn spot_price_a_in_b(&self, pair_id: u64, reserve_a: &BigUint, reserve_b: &BigUint) -> BigUint {
+        let (weight_a, weight_b) = self.get_reduced_weights(pair_id, true);
+        reserve_b * PRECISION * weight_a as u64 / (reserve_a * weight_b as u64)
+    }
+
+    /// Advance the pair's price accumulator up to now, using the reserves before the change
+    /// Call before anything that moves the price. Rolls the TWAP observations once per window.
+    fn update_price_accumulator(&self, pair_id: u64, reserve_a: &BigUint, reserve_b: &BigUint) {
+        let now = self.blockchain().get_block_timestamp();
+        let last = self.pair_price_cumulative_timestamp(pair_id).get();
+        if last == now {
+            return;
+        }
+        if last > 0 && *reserve_a > 0u64 && *reserve_b > 0u64 {
+            let spot = self.spot_price_a_in_b(pair_id, reserve_a, reserve_b);
+            self.pair_price_cumulative(pair_id).update(|c| *c += spot * (now - last));
+        }
+        self.pair_price_cumulative_timestamp(pair_id).set(now);
+        
+        let current = self.pair_twap_current(pair_id);
+        if current.is_empty() || now - current.get().timestamp >= TWAP_WINDOW_SECONDS {
+            if !current.is_empty() {
+                self.pair_twap_previous(pair_id).set(current.get());
+            }
+            current.set(TwapObservation {
+                price_cumulative: self.pair_price_cumulative(pair_id).get(),
+                timestamp: now,
+            });
+        }
+    }
+
+    /// Time-weighted price of A in B, scaled by PRECISION
+    /// Measured from the newest observation at least TWAP_WINDOW_SECONDS old; None until
+    /// the pair has one.
+    fn get_twap_price(&self, pair_id: u64) -> Option<BigUint> {
+        let now = self.blockchain().get_block_timestamp();
+        let mut observation = None;
+        for mapper in [self.pair_twap_current(pair_id), self.pair_twap_previous(pair_id)] {
+            if !mapper.is_empty() && now - mapper.get().timestamp >= TWAP_WINDOW_SECONDS {
+                observation = Some(mapper.get());
+                break;
+            }
+        }
+        let observation = observation?;
+        
+        // Extend the accumulator to now at the current spot price
+        let mut cumulative = self.pair_price_cumulative(pair_id).get();
+        let last = self.pair_price_cumulative_timestamp(pair_id).get();
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        if now > last && reserve_a > 0u64 && reserve_b > 0u64 {
+            cumulative += self.spot_price_a_in_b(pair_id, &reserve_a, &reserve_b) * (now - last);
+        }
+        Some((cumulative - observation.price_cumulative) / (now - observation.timestamp))
+    }
+
+    /// Reject price moves that take the spot price too far from the TWAP (see setPairTwapGuard)
+    /// A move that ends outside the band is still allowed when it brings the price closer.
+    fn check_twap_guard(&self, pair_id: u64, old_reserve_a: &BigUint, old_reserve_b: &BigUint) {
+        require!(
+            self.twap_guard_allows(
+                pair_id,
+                old_reserve_a,
+                old_reserve_b,
+                &self.pair_reserve_a(pair_id).get(),
+                &self.pair_reserve_b(pair_id).get(),
+            ),
+            "Price moves too far from TWAP"
+        );
+    }
+
+    /// Whether moving a pair's reserves from old to new passes its TWAP guard
+    fn twap_guard_allows(
+        &self,
+        pair_id: u64,
+        old_reserve_a: &BigUint,
+        old_reserve_b: &BigUint,
+        new_reserve_a: &BigUint,
+        new_reserve_b: &BigUint,
+    ) -> bool {
+        let max_deviation_bps = self.pair_max_twap_deviation_bps(pair_id).get();
+        if max_deviation_bps == 0 {
+            return true;
+        }
+        let twap = match self.get_twap_price(pair_id) {
+            Some(twap) if twap > 0u64 => twap,
+            _ => return true,
+        };
+        
+        let price_after = self.spot_price_a_in_b(pair_id, new_reserve_a, new_reserve_b);
+        let deviation_after = self.price_deviation_bps(&price_after, &twap);
+        if deviation_after <= max_deviation_bps {
+            return true;
+        }
+        let price_before = self.spot_price_a_in_b(pair_id, old_reserve_a, old_reserve_b);
+        deviation_after <= self.price_deviation_bps(&price_before, &twap)
+    }
+
+    /// |price - reference| in bps of the reference
+    fn price_deviation_bps(&self, price: &BigUint, reference: &BigUint) -> u64 {
+        let difference = if price > reference { price - reference } else { reference - price };
+        (difference * BPS_DENOMINATOR / reference).to_u64().unwrap_or(u64::MAX)
+    }
+
+    /// Enforce a pair's max trade size and reserve floor (see setPairTradeLimits)
+    fn check_trade_limits(&self, pair_id: u64, is_a_to_b: bool, reserve_out: &BigUint, output: &BigUint) {
+        let max_trade_bps = self.pair_max_trade_bps(pair_id).get();
+        if max_trade_bps > 0 {
+            require!(
+                output * BPS_DENOMINATOR <= reserve_out * max_trade_bps,
+                "Trade exceeds pair max trade size"
+            );
+        }
+        
+        let min_reserve_out = if is_a_to_b {
+            self.pair_min_reserve_b(pair_id).get()
+        } else {
+            self.pair_min_reserve_a(pair_id).get()
+        };
+        require!(reserve_out - output >= min_reserve_out, "Trade breaches pair reserve floor");
+    }
+
+    /// Price impact of a swap in bps: input * 10000 / (reserve_in + input)
+    /// Bounded by BPS_DENOMINATOR, so it always fits in u64.
+    fn compute_price_impact_bps(&self, reserve_in: &BigUint, amount_in: &BigUint) -> u64 {
+        (amount_in * BPS_DENOMINATOR / &(reserve_in + amount_in))
+            .to_u64()
+            .unwrap_or(BPS_DENOMINATOR)
+    }
+
+    /// Credit a referrer with its cut of a swap fee
+    /// Self-referrals (by the trader the swap is made for) earn nothing, and neither
+    /// do fees in a semi-fungible token (the referral ledger is keyed by token id
+    /// only). @return Amount taken out of the fee
+    fn credit_referral_fee(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        referrer: &ManagedAddress,
+        fee: &BigUint,
+        is_token_a: bool,
+    ) -> BigUint {
+        let referral_fee_bps = self.param_referral_fee_bps().get();
+        if referral_fee_bps == 0
+            || referrer == trader
+            || self.pair_token_nonce(pair_id, is_token_a) > 0
+        {
+            return BigUint::zero();
+        }
+        
+        let portion = fee * referral_fee_bps / BPS_DENOMINATOR;
+        if portion == BigUint::zero() {
+            return portion;
+        }
+        
+        let token = self.pair_token(pair_id, is_token_a);
+        self.referral_tokens(referrer).insert(token.clone());
+        self.referral_earned_tokens(referrer).insert(token.clone());
+        self.referral_unclaimed(referrer, &token).update(|b| *b += &portion);
+        self.referral_total_earned(referrer, &token).update(|b| *b += &portion);
+        self.referral_outstanding(&token).update(|b| *b += &portion);
+        
+        portion
+    }
+
+    /// Rebate a slice of a trader's swap fee on pairs with rebate tiers
+    /// The tier comes from the trader's rolling volume before this swap; the swap's
+    /// own volume (in token A) is then added to it. Returns the credited portion of
+    /// the fee. SFT sides and the contract's own buyback swaps earn no rebate.
+    fn credit_fee_rebate(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        volume_a: &BigUint,
+        fee: &BigUint,
+        is_token_a: bool,
+    ) -> BigUint {
+        let tiers = self.pair_rebate_tiers(pair_id).get();
+        if tiers.is_empty() || *trader == self.blockchain().get_sc_address() {
+            return BigUint::zero();
+        }
+        
+        let day = self.blockchain().get_block_timestamp() / SECONDS_PER_DAY;
+        let mut buckets = self.get_rebate_volume_buckets(trader, pair_id, day);
+        let rebate_bps = self.get_rebate_bps(&tiers, &self.sum_volume_buckets(&buckets));
+        
+        let last = buckets.len().checked_sub(1);
+        match last {
+            Some(i) if buckets.get(i).day == day => {
+                let mut bucket = buckets.get(i);
+                bucket.volume += volume_a;
+                let _ = buckets.set(i, &bucket);
+            },
+            _ => buckets.push(VolumeBucket {
+                day,
+                volume: volume_a.clone(),
+            }),
+        }
+        self.rebate_volume(trader, pair_id).set(&buckets);
+        
+        if rebate_bps == 0 || self.pair_token_nonce(pair_id, is_token_a) > 0 {
+            return BigUint::zero();
+        }
+        let portion = fee * rebate_bps / BPS_DENOMINATOR;
+        if portion == BigUint::zero() {
+            return portion;
+        }
+        
+        let token = self.pair_token(pair_id, is_token_a);
+        self.rebate_tokens(trader).insert(token.clone());
+        self.rebate_unclaimed(trader, &token).update(|b| *b += &portion);
+        self.rebate_outstanding(&token).update(|b| *b += &portion);
+        
+        portion
+    }
+
+    /// A trader's volume buckets on a pair, minus those outside the rebate window ending `day`
+    fn get_rebate_volume_buckets(&self, trader: &ManagedAddress, pair_id: u64, day: u64) -> ManagedVec<VolumeBucket<Self::Api>> {
+        let mut buckets = ManagedVec::new();
+        for bucket in self.rebate_volume(trader, pair_id).get().iter() {
+            if bucket.day + REBATE_WINDOW_DAYS > day {
+                buckets.push(bucket);
+            }
+        }
+        buckets
+    }
+
+    fn sum_volume_buckets(&self, buckets: &ManagedVec<VolumeBucket<Self::Api>>) -> BigUint {
+        let mut total = BigUint::zero();
+        for bucket in buckets.iter() {
+            total += &bucket.volume;
+        }
+        total
+    }
+
+    /// Rebate of the highest tier a rolling volume reaches (0 below the first tier)
+    fn get_rebate_bps(&self, tiers: &ManagedVec<RebateTier<Self::Api>>, volume: &BigUint) -> u64 {
+        let mut rebate_bps = 0;
+        for tier in tiers.iter() {
+            if *volume < tier.min_volume {
+                break;
+            }
+            rebate_bps = tier.rebate_bps;
+        }
+        rebate_bps
+    }
+
+    /// Update cumulative and daily volume statistics for a swap
+    /// Volume is counted on both sides: input amount on the input token,
+    /// gross output (before fee) on the output token.
+    #[allow(clippy::too_many_arguments)]
+    fn record_swap_stats(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        is_a_to_b: bool,
+        amount_in: &BigUint,
+        amount_out: &BigUint,
+        fee: &BigUint,
+        fee_is_token_a: bool,
+    ) {
+        let now = self.blockchain().get_block_timestamp();
+        let day = now / SECONDS_PER_DAY;
+        
+        let (volume_a, volume_b) = if is_a_to_b {
+            (amount_in, amount_out)
+        } else {
+            (amount_out, amount_in)
+        };
+        
+        self.pair_volume_a(pair_id).update(|v| *v += volume_a);
+        self.pair_volume_b(pair_id).update(|v| *v += volume_b);
+        self.pair_daily_volume_a(pair_id, day).update(|v| *v += volume_a);
+        self.pair_daily_volume_b(pair_id, day).update(|v| *v += volume_b);
+        
+        if fee_is_token_a {
+            self.pair_total_fees_a(pair_id).update(|f| *f += fee);
+        } else {
+            self.pair_total_fees_b(pair_id).update(|f| *f += fee);
+        }
+        
+        self.pair_swap_count(pair_id).update(|c| *c += 1);
+        self.total_swap_count().update(|c| *c += 1);
+        self.pair_last_swap_timestamp(pair_id).set(now);
+        
+        // Protocol swaps (buybacks) only count towards the pair totals
+        if *trader == self.blockchain().get_sc_address() {
+            return;
+        }
+        self.user_volume_a(trader, pair_id).update(|v| *v += volume_a);
+        self.user_volume_b(trader, pair_id).update(|v| *v += volume_b);
+        self.user_swap_count(trader, pair_id).update(|c| *c += 1);
+        self.user_last_swap_timestamp(trader, pair_id).set(now);
+        
+        self.record_competition_volume(pair_id, trader, volume_a);
+    }
+
+    /// Credit swap volume (in token A) to the pair's running competition, if any
+    fn record_competition_volume(&self, pair_id: u64, trader: &ManagedAddress, volume_a: &BigUint) {
+        if self.pair_active_competition(pair_id).is_empty() {
+            return;
+        }
+        let competition_id = self.pair_active_competition(pair_id).get();
+        let mut competition = self.competition(competition_id).get();
+        let now = self.blockchain().get_block_timestamp();
+        if now < competition.start || now >= competition.end {
+            return;
+        }
+        
+        let volume = self.competition_volume(competition_id, trader).update(|v| {
+            *v += volume_a;
+            v.clone()
+        });
+        self.competition_traders(competition_id).insert(trader.clone());
+        competition.total_volume += volume_a;
+        self.competition(competition_id).set(&competition);
+        
+        if competition.top_n > 0 {
+            self.update_competition_top(competition_id, competition.top_n, trader, &volume);
+        }
+    }
+
+    /// Re-rank a trader in a top-N leaderboard after their volume grew
+    fn update_competition_top(&self, competition_id: u64, top_n: u64, trader: &ManagedAddress, volume: &BigUint) {
+        let top = self.competition_top(competition_id).get();
+        let cap = top_n as usize;
+        let new_entry = CompetitionEntry {
+            address: trader.clone(),
+            volume: volume.clone(),
+        };
+        
+        let mut ranked = ManagedVec::new();
+        let mut placed = false;
+        for entry in top.iter() {
+            if ranked.len() == cap {
+                break;
+            }
+            if entry.address == *trader {
+                continue;
+            }
+            if !placed && *volume > entry.volume {
+                ranked.push(new_entry.clone());
+                placed = true;
+                if ranked.len() == cap {
+                    break;
+                }
+            }
+            ranked.push(entry);
+        }
+        if !placed && ranked.len() < cap {
+            ranked.push(new_entry);
+        }
+        
+        self.competition_top(competition_id).set(&ranked);
+    }
+
+    /// Volume of an address that counts for a competition's payout
+    /// In top-N competitions only ranked traders' volume counts.
+    fn get_competition_reward_volume(&self, competition_id: u64, competition: &Competition<Self::Api>, addr: &ManagedAddress) -> BigUint {
+        if competition.top_n == 0 {
+            return self.competition_volume(competition_id, addr).get();
+        }
+        for entry in self.competition_top(competition_id).get().iter() {
+            if entry.address == *addr {
+                return entry.volume;
+            }
+        }
+        BigUint::zero()
+    }
+
+    /// Reward an address can still claim from a closed competition
+    fn get_competition_claimable(&self, competition_id: u64, addr: &ManagedAddress) -> BigUint {
+        let competition = self.competition(competition_id).get();
+        if !competition.closed
+            || competition.reward_volume == 0u64
+            || self.competition_claimed(competition_id, addr).get()
+        {
+            return BigUint::zero();
+        }
+        let volume = self.get_competition_reward_volume(competition_id, &competition, addr);
+        &competition.reward_amount * &volume / &competition.reward_volume
+    }
+
+    /// Enforce the compliance list for a swap
+    /// volume_a is the swap size in token A (input for A->B, output for B->A)
+    fn check_compliance(&self, pair_id: u64, trader: &ManagedAddress, volume_a: &BigUint) {
+        require!(!self.compliance_restricted().contains(trader), "Address is restricted from trading");
+        
+        if !self.compliance_flagged_pairs().contains(&pair_id) {
+            return;
+        }
+        if self.compliance_daily_limit(pair_id, trader).is_empty() {
+            return;
+        }
+        
+        let day = self.blockchain().get_block_timestamp() / SECONDS_PER_DAY;
+        let used = self.compliance_daily_used(pair_id, trader, day).get() + volume_a;
+        require!(
+            used <= self.compliance_daily_limit(pair_id, trader).get(),
+            "Daily swap limit exceeded"
+        );
+        self.compliance_daily_used(pair_id, trader, day).set(&used);
+    }
+
+    /// Whether swap_internal would accept a swap of `amount_in` for `trader` right now
+    /// Mirrors its checks without failing or writing anything: the flash swap lock,
+    /// compliance, the pair's swap guards and trade limits, and the TWAP band.
+    /// Keeper cranks check this first, so an order that would fail is skipped
+    /// instead of reverting the whole batch.
+    fn swap_would_pass(&self, pair_id: u64, trader: &ManagedAddress, is_a_to_b: bool, amount_in: &BigUint) -> bool {
+        if *amount_in == 0u64
+            || self.flash_swap_active(pair_id).get()
+            || self.compliance_restricted().contains(trader)
+        {
+            return false;
+        }
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        if reserve_a == 0u64 || reserve_b == 0u64 {
+            return false;
+        }
+        let (reserve_in, reserve_out) = if is_a_to_b {
+            (&reserve_a, &reserve_b)
+        } else {
+            (&reserve_b, &reserve_a)
+        };
+        let (curve_in, curve_out, user_gets, _) =
+            self.get_swap_amounts(pair_id, trader, is_a_to_b, reserve_in, reserve_out, amount_in);
+        if curve_out == 0u64 || curve_out >= *reserve_out || user_gets == 0u64 {
+            return false;
+        }
+        
+        // check_compliance: daily limit on flagged pairs, in token A
+        if self.compliance_flagged_pairs().contains(&pair_id) && !self.compliance_daily_limit(pair_id, trader).is_empty() {
+            let volume_a = if is_a_to_b { amount_in } else { &curve_out };
+            let day = self.blockchain().get_block_timestamp() / SECONDS_PER_DAY;
+            let used = self.compliance_daily_used(pair_id, trader, day).get() + volume_a;
+            if used > self.compliance_daily_limit(pair_id, trader).get() {
+                return false;
+            }
+        }
+        
+        // check_swap_guards: price impact cap and direction lock
+        let max_impact_bps = self.pair_max_price_impact_bps(pair_id).get();
+        if max_impact_bps > 0 && self.compute_price_impact_bps(reserve_in, amount_in) > max_impact_bps {
+            return false;
+        }
+        if self.pair_block_direction_lock(pair_id).get()
+            && self.last_swap_block(pair_id, trader).get() == self.blockchain().get_block_nonce()
+            && self.last_swap_direction(pair_id, trader).get() != is_a_to_b
+        {
+            return false;
+        }
+        
+        // check_trade_limits: max trade size and reserve floor
+        let max_trade_bps = self.pair_max_trade_bps(pair_id).get();
+        if max_trade_bps > 0 && &curve_out * BPS_DENOMINATOR > reserve_out * max_trade_bps {
+            return false;
+        }
+        let min_reserve_out = if is_a_to_b {
+            self.pair_min_reserve_b(pair_id).get()
+        } else {
+            self.pair_min_reserve_a(pair_id).get()
+        };
+        if reserve_out - &curve_out < min_reserve_out {
+            return false;
+        }
+        
+        let (new_reserve_a, new_reserve_b) = if is_a_to_b {
+            (&reserve_a + &curve_in, &reserve_b - &curve_out)
+        } else {
+            (&reserve_a - &curve_out, &reserve_b + &curve_in)
+        };
+        self.twap_guard_allows(pair_id, &reserve_a, &reserve_b, &new_reserve_a, &new_reserve_b)
+    }
+
+    /// Approximate rolling 24h volume from daily buckets
+    /// Today's bucket plus the not-yet-elapsed fraction of yesterday's bucket
+    fn get_rolling_24h_volume(&self, pair_id: u64) -> (BigUint, BigUint) {
+        let now = self.blockchain().get_block_timestamp();
+        let today = now / SECONDS_PER_DAY;
+        let elapsed_today = now % SECONDS_PER_DAY;
+        let remaining = SECONDS_PER_DAY - elapsed_today;
+        
+        let mut volume_a = self.pair_daily_volume_a(pair_id, today).get();
+        let mut volume_b = self.pair_daily_volume_b(pair_id, today).get();
+        
+        if today > 0 {
+            let yesterday = today - 1;
+            volume_a += self.pair_daily_volume_a(pair_id, yesterday).get() * remaining / SECONDS_PER_DAY;
+            volume_b += self.pair_daily_volume_b(pair_id, yesterday).get() * remaining / SECONDS_PER_DAY;
+        }
+        
+        (volume_a, volume_b)
+    }
+
+    /// Inverse AMM formula: input needed to receive `desired_output` after fee
+    /// Rounds UP at every step so the pool is never short-changed (see get_swap_input).
+    fn get_amount_in_internal(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        is_a_to_b: bool,
+        desired_output: &BigUint,
+    ) -> BigUint {
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        require!(reserve_a > 0u64 && reserve_b > 0u64, "Reserves empty");
+        
+        let (reserve_in, reserve_out) = if is_a_to_b {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        };
+        
+        match self.get_swap_input(pair_id, trader, is_a_to_b, &reserve_in, &reserve_out, desired_output) {
+            Some((amount_in, _)) => amount_in,
+            None => sc_panic!("Insufficient liquidity for output"),
+        }
+    }
+
+    /// Output after fee and fee of an exact-input swap, as (user_gets, fee)
+    /// Never fails: (0, 0) when the pair can't fill the swap.
+    fn quote_swap_internal(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        is_a_to_b: bool,
+        input_amount: &BigUint,
+    ) -> (BigUint, BigUint) {
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        if reserve_a == BigUint::zero() || reserve_b == BigUint::zero() || *input_amount == BigUint::zero() {
+            return (BigUint::zero(), BigUint::zero());
+        }
+        
+        let (reserve_in, reserve_out) = if is_a_to_b {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        };
+        
+        let (_, curve_out, user_gets, fee) =
+            self.get_swap_amounts(pair_id, trader, is_a_to_b, &reserve_in, &reserve_out, input_amount);
+        
+        if curve_out == BigUint::zero() || curve_out >= reserve_out {
+            return (BigUint::zero(), BigUint::zero());
+        }
+        
+        (user_gets, fee)
+    }
+
+    /// Output of one routing hop from `token_in`, 0 when the pair is unusable
+    /// Returns (output_token, user_gets)
+    fn quote_route_hop(
+        &self,
+        pair_id: u64,
+        trader: &ManagedAddress,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+    ) -> (TokenIdentifier, BigUint) {
+        let is_a_to_b = self.pair_token(pair_id, true) == *token_in;
+        let token_out = self.pair_token(pair_id, !is_a_to_b);
+        if !self.pair_swaps_enabled(pair_id).get() {
+            return (token_out, BigUint::zero());
+        }
+        let (user_gets, _) = self.quote_swap_internal(pair_id, trader, is_a_to_b, amount_in);
+        (token_out, user_gets)
+    }
+
+    /// Helper: Extract payment amounts for both tokens of a pair from multi-token callValue
+    /// Native KLV is wrapped as WKLV and matched like any other token.
+    fn extract_dual_payment(&self, pair_id: u64) -> (BigUint, BigUint) {
+        let token_a = self.pair_token(pair_id, true);
+        let token_b = self.pair_token(pair_id, false);
+        let mut amount_a = BigUint::zero();
+        let mut amount_b = BigUint::zero();
+        
+        let klv_amount = self.call_value().klv_value().clone_value();
+        if klv_amount > 0u64 {
+            if token_a == self.wklv_token_id() {
+                amount_a = klv_amount;
+            } else if token_b == self.wklv_token_id() {
+                amount_b = klv_amount;
+            }
+        }
+        
+        let nonce_a = self.pair_token_nonce(pair_id, true);
+        let nonce_b = self.pair_token_nonce(pair_id, false);
+        for payment in self.call_value().all_kda_transfers().iter() {
+            if payment.token_identifier == token_a && payment.token_nonce == nonce_a {
+                amount_a = payment.amount.clone();
+            } else if payment.token_identifier == token_b && payment.token_nonce == nonce_b {
+                amount_b = payment.amount.clone();
+            }
+        }
+        
+        self.add_token_obligation(&token_a, &amount_a);
+        self.add_token_obligation(&token_b, &amount_b);
+        (amount_a, amount_b)
+    }
+    
+    /// Swap the incoming payment and send the output to the caller
+    /// `expected_a_to_b` pins the direction for the legacy per-direction endpoints.
+    /// @return Amount sent to the caller
+    fn swap_payment_internal(
+        &self,
+        pair_id: u64,
+        expected_a_to_b: Option<bool>,
+        min_output: &BigUint,
+        referrer: &Option<ManagedAddress>,
+    ) -> BigUint {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        
+        let (is_a_to_b, payment) = self.take_pair_payment(pair_id);
+        if let Some(expected) = expected_a_to_b {
+            require!(is_a_to_b == expected, "Wrong token sent");
+        }
+        
+        let caller = self.blockchain().get_caller();
+        let user_gets = self.swap_internal(pair_id, &caller, is_a_to_b, &payment, referrer);
+        
+        // V5: Slippage protection
+        require!(user_gets >= *min_output, "Slippage: output below minimum");
+        
+        self.send_pair_token(&caller, pair_id, !is_a_to_b, &user_gets);
+        user_gets
+    }
+
+    /// Credit the incoming payment (KLV or KDA) to the caller's pending deposit on one side
+    fn deposit_pending_internal(&self, pair_id: u64, is_token_a: bool) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_liquidity_enabled(pair_id).get(), "Liquidity is disabled for this pair");
+        
+        let caller = self.blockchain().get_caller();
+        let (paid_token_a, amount) = self.take_pair_payment(pair_id);
+        
+        if is_token_a {
+            require!(paid_token_a, "Wrong token - expected token A");
+        } else {
+            require!(!paid_token_a, "Wrong token - expected token B");
+        }
+        require!(amount > 0u64, "Amount must be > 0");
+        
+        if is_token_a {
+            self.pending_a(pair_id, &caller).update(|v| *v += &amount);
+            self.pair_pending_total_a(pair_id).update(|t| *t += &amount);
+        } else {
+            self.pending_b(pair_id, &caller).update(|v| *v += &amount);
+            self.pair_pending_total_b(pair_id).update(|t| *t += &amount);
+        }
+        self.track_pending_user_add(pair_id, &caller);
+        self.pending_deposited_at(pair_id, &caller).set(self.blockchain().get_block_timestamp());
+    }
+
+    /// Send a user's pending deposits on both sides back to them
+    fn refund_pending_internal(&self, pair_id: u64, user: &ManagedAddress) {
+        let pending_a = self.pending_a(pair_id, user).get();
+        let pending_b = self.pending_b(pair_id, user).get();
+        
+        require!(pending_a > BigUint::zero() || pending_b > BigUint::zero(), "No pending deposits");
+        
+        self.pending_a(pair_id, user).set(BigUint::zero());
+        self.pending_b(pair_id, user).set(BigUint::zero());
+        self.decrease_pending_totals(pair_id, &pending_a, &pending_b);
+        self.track_pending_user_remove(pair_id, user);
+        
+        if pending_a > BigUint::zero() {
+            self.send_pair_token(user, pair_id, true, &pending_a);
+        }
+        
+        if pending_b > BigUint::zero() {
+            self.send_pair_token(user, pair_id, false, &pending_b);
+        }
+    }
+
+    /// Timestamp from which a user's pending deposits can be expired
+    /// Deposits from before timestamps were recorded count from pending_expiry_start.
+    fn get_pending_expires_at(&self, pair_id: u64, user: &ManagedAddress) -> u64 {
+        let deposited_at = if self.pending_deposited_at(pair_id, user).is_empty() {
+            self.pending_expiry_start().get()
+        } else {
+            self.pending_deposited_at(pair_id, user).get()
+        };
+        deposited_at + self.get_pending_expiry_days() * SECONDS_PER_DAY
+    }
+
+    fn wklv_token_id(&self) -> TokenIdentifier {
+        TokenIdentifier::from(WKLV_TOKEN_ID)
+    }
+
+    /// Normalize a (token, is_klv) pair from the API into the internal representation
+    /// Returns (WKLV, true) for native KLV, (token, false) otherwise.
+    fn wrap_klv_side(&self, token: TokenIdentifier, is_klv: bool) -> (TokenIdentifier, bool) {
+        let wklv = self.wklv_token_id();
+        if is_klv || token == wklv {
+            (wklv, true)
+        } else {
+            (token, false)
+        }
+    }
+
+    /// Move a pair's KLV side(s) stored under another identifier onto the WKLV id
+    fn migrate_pair_to_wklv(&self, pair_id: u64) {
+        let wklv = self.wklv_token_id();
+        let a_needs = self.pair_token_a_is_klv(pair_id).get() && self.pair_token_a(pair_id).get() != wklv;
+        let b_needs = self.pair_token_b_is_klv(pair_id).get() && self.pair_token_b(pair_id).get() != wklv;
+        if !a_needs && !b_needs {
+            return;
+        }
+        
+        self.unindex_pair_tokens(pair_id);
+        if a_needs {
+            self.pair_token_a(pair_id).set(&wklv);
+        }
+        if b_needs {
+            self.pair_token_b(pair_id).set(&wklv);
+        }
+        self.index_pair_tokens(pair_id);
+    }
+
+    /// Internal identifier of one side of a pair (WKLV for a KLV side)
+    fn pair_token(&self, pair_id: u64, is_token_a: bool) -> TokenIdentifier {
+        if is_token_a {
+            self.pair_token_a(pair_id).get()
+        } else {
+            self.pair_token_b(pair_id).get()
+        }
+    }
+
+    /// Token nonce of one side of a pair (0 unless the side is a semi-fungible KDA)
+    fn pair_token_nonce(&self, pair_id: u64, is_token_a: bool) -> u64 {
+        if is_token_a {
+            self.pair_token_nonce_a(pair_id).get()
+        } else {
+            self.pair_token_nonce_b(pair_id).get()
+        }
+    }
+
+    /// Send one side of a pair, unwrapping WKLV to native KLV
+    fn send_pair_token(&self, to: &ManagedAddress, pair_id: u64, is_token_a: bool, amount: &BigUint) {
+        let nonce = self.pair_token_nonce(pair_id, is_token_a);
+        if nonce > 0 {
+            let token = self.pair_token(pair_id, is_token_a);
+            self.remove_token_obligation(&token, amount);
+            self.send().direct_kda(to, &token, nonce, amount);
+            return;
+        }
+        self.send_wrapped(to, &self.pair_token(pair_id, is_token_a), amount);
+    }
+
+    /// Send an internal token id, unwrapping WKLV to native KLV
+    fn send_wrapped(&self, to: &ManagedAddress, token: &TokenIdentifier, amount: &BigUint) {
+        self.send_token_internal(to, token, *token == self.wklv_token_id(), amount);
+    }
+
+    /// Helper: Send token (handles KLV vs KDA)
+    fn send_token_internal(
+        &self,
+        to: &ManagedAddress,
+        token: &TokenIdentifier,
+        is_klv: bool,
+        amount: &BigUint,
+    ) {
+        self.remove_token_obligation(token, amount);
+        if is_klv {
+            self.send().direct_klv(to, amount);
+        } else {
+            self.send().direct_kda(to, token, 0, amount);
+        }
+    }
+
+    /// A new LP account must fit under the pair's LP cap (the LP token custodian is exempt)
+    fn require_lp_slot(&self, pair_id: u64, addr: &ManagedAddress) {
+        if *addr == self.blockchain().get_sc_address() {
+            return;
+        }
+        require!(self.lp_list(pair_id).len() < self.get_max_lps_per_pair(), "Pair has reached its LP limit");
+    }
+
+    /// One page of a pair's LP list, from the 0-based offset `start` (max MAX_PAGE_SIZE entries)
+    fn get_lp_page(&self, pair_id: u64, start: u64, limit: u64) -> ManagedVec<ManagedAddress> {
+        require!(limit > 0 && limit <= MAX_PAGE_SIZE, "Invalid limit");
+        let lp_list = self.lp_list(pair_id);
+        let count = lp_list.len() as u64;
+        
+        let mut page = ManagedVec::new();
+        let mut index = start;
+        while index < count && index < start + limit {
+            // UnorderedSetMapper indexes are 1-based
+            page.push(lp_list.get_by_index((index + 1) as usize));
+            index += 1;
+        }
+        page
+    }
+
+    /// Add shares to an LP and update tracking
+    fn add_lp_shares(&self, pair_id: u64, addr: &ManagedAddress, shares: &BigUint) {
+        let is_new_lp = !self.lp_list(pair_id).contains(addr);
+        
+        if is_new_lp {
+            self.require_lp_slot(pair_id, addr);
+            self.lp_list(pair_id).insert(addr.clone());
+            self.user_pair_ids(addr).insert(pair_id);
+            self.lp_entry_index_a(pair_id, addr).set(self.fee_per_share_a(pair_id).get());
+            self.lp_entry_index_b(pair_id, addr).set(self.fee_per_share_b(pair_id).get());
+            self.lp_shares(pair_id, addr).set(shares);
+        } else {
+            self.lp_shares(pair_id, addr).update(|s| *s += shares);
+        }
+        
+        self.total_lp_shares(pair_id).update(|s| *s += shares);
+    }
+
+    /// Credit newly minted shares to a user
+    /// Pairs with a registered LP token mint KDA LP tokens backed by shares held by
+    /// the contract (custodian); other pairs credit internal lp_shares.
+    fn credit_lp_position(&self, pair_id: u64, addr: &ManagedAddress, shares: &BigUint) {
+        if self.pair_lp_token(pair_id).is_empty() {
+            self.add_lp_shares(pair_id, addr, shares);
+            return;
+        }
+        
+        let custodian = self.blockchain().get_sc_address();
+        let custodian_shares = self.lp_shares(pair_id, &custodian).get();
+        let supply = self.lp_token_supply(pair_id).get();
+        
+        // LP tokens track the custodian position, which grows as fees compound
+        let lp_amount = if supply == BigUint::zero() || custodian_shares == BigUint::zero() {
+            shares.clone()
+        } else {
+            shares * &supply / &custodian_shares
+        };
+        require!(lp_amount > 0u64, "LP token amount too small");
+        
+        self.add_lp_shares(pair_id, &custodian, shares);
+        self.lp_token_supply(pair_id).update(|s| *s += &lp_amount);
+        
+        let lp_token = self.pair_lp_token(pair_id).get();
+        self.send().kda_local_mint(&lp_token, 0, &lp_amount);
+        self.send().direct_kda(addr, &lp_token, 0, &lp_amount);
+    }
+
+    /// Compound the LP-token custodian's accrued fees back into the pool
+    /// Fees are added to reserves at the pool ratio and new shares credited to the
+    /// custodian, so LP token holders earn fees through a rising share-per-token.
+    /// Any unmatched remainder waits in the fee bucket for the next compound.
+    fn compound_lp_token_fees(&self, pair_id: u64) {
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        if self.pair_lp_token(pair_id).is_empty() {
+            return;
+        }
+        
+        let custodian = self.blockchain().get_sc_address();
+        let (fees_a, fees_b) = self.calculate_lp_pending_fees(pair_id, &custodian);
+        if self.lp_list(pair_id).contains(&custodian) {
+            self.lp_entry_index_a(pair_id, &custodian).set(self.fee_per_share_a(pair_id).get());
+            self.lp_entry_index_b(pair_id, &custodian).set(self.fee_per_share_b(pair_id).get());
+        }
+        
+        let bucket_a = self.lp_token_fee_bucket_a(pair_id).get() + fees_a;
+        let bucket_b = self.lp_token_fee_bucket_b(pair_id).get() + fees_b;
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        let total_shares = self.get_total_shares_internal(pair_id);
+        
+        if bucket_a == BigUint::zero() || bucket_b == BigUint::zero()
+            || reserve_a == BigUint::zero() || reserve_b == BigUint::zero()
+        {
+            self.lp_token_fee_bucket_a(pair_id).set(&bucket_a);
+            self.lp_token_fee_bucket_b(pair_id).set(&bucket_b);
+            return;
+        }
+        
+        // Match the bucket to the current pool ratio
+        let optimal_b = &bucket_a * &reserve_b / &reserve_a;
+        let (use_a, use_b) = if optimal_b <= bucket_b {
+            (bucket_a.clone(), optimal_b)
+        } else {
+            (&bucket_b * &reserve_a / &reserve_b, bucket_b.clone())
+        };
+        
+        let shares_a = &use_a * &total_shares / &reserve_a;
+        let shares_b = &use_b * &total_shares / &reserve_b;
+        let new_shares = if shares_a < shares_b { shares_a } else { shares_b };
+        
+        if new_shares == BigUint::zero() {
+            self.lp_token_fee_bucket_a(pair_id).set(&bucket_a);
+            self.lp_token_fee_bucket_b(pair_id).set(&bucket_b);
+            return;
+        }
+        
+        self.pair_reserve_a(pair_id).set(&reserve_a + &use_a);
+        self.pair_reserve_b(pair_id).set(&reserve_b + &use_b);
+        self.add_lp_shares(pair_id, &custodian, &new_shares);
+        
+        self.lp_token_fee_bucket_a(pair_id).set(&bucket_a - &use_a);
+        self.lp_token_fee_bucket_b(pair_id).set(&bucket_b - &use_b);
+    }
+
+    /// Burn `shares` from `holder`'s LP position and pay the reserves to `recipient`
+    /// Claims the holder's pending fees first. Returns (amount_a, amount_b).
+    /// Reverts if either amount is below its minimum (pool ratio moved since signing).
+    fn burn_lp_shares_internal(
+        &self,
+        pair_id: u64,
+        holder: &ManagedAddress,
+        recipient: &ManagedAddress,
+        shares_to_remove: &BigUint,
+        min_amount_a: &BigUint,
+        min_amount_b: &BigUint,
+    ) -> (BigUint, BigUint) {
+        let (amount_a, amount_b) = self.withdraw_lp_shares_internal(
+            pair_id,
+            holder,
+            recipient,
+            shares_to_remove,
+            min_amount_a,
+            min_amount_b,
+        );
+        
+        // Send tokens to LP
+        if amount_a > BigUint::zero() {
+            self.send_pair_token(recipient, pair_id, true, &amount_a);
+        }
+        
+        if amount_b > BigUint::zero() {
+            self.send_pair_token(recipient, pair_id, false, &amount_b);
+        }
+        
+        (amount_a, amount_b)
+    }
+
+    /// Burn side of burn_lp_shares_internal: updates the position and reserves
+    /// but leaves the withdrawn (amount_a, amount_b) in the contract for the
+    /// caller to send or re-deposit. IL compensation is still paid to `recipient`.
+    fn withdraw_lp_shares_internal(
+        &self,
+        pair_id: u64,
+        holder: &ManagedAddress,
+        recipient: &ManagedAddress,
+        shares_to_remove: &BigUint,
+        min_amount_a: &BigUint,
+        min_amount_b: &BigUint,
+    ) -> (BigUint, BigUint) {
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        
+        let lp_shares = self.lp_shares(pair_id, holder).get();
+        
+        require!(*shares_to_remove <= lp_shares, "Insufficient LP shares");
+        require!(
+            *shares_to_remove <= &lp_shares - &self.farm_staked(pair_id, holder).get(),
+            "LP shares are staked - unstake first"
+        );
+        
+        if self.lp_locked_shares(pair_id, holder).get() > 0u64
+            && self.blockchain().get_block_timestamp() >= self.lp_lock_until(pair_id, holder).get()
+        {
+            self.release_lp_lock(pair_id, holder);
+        }
+        require!(
+            *shares_to_remove <= &lp_shares - &self.lp_locked_shares(pair_id, holder).get(),
+            "LP shares are locked"
+        );
+        require!(*shares_to_remove > 0u64, "Shares must be > 0");
+        
+        // Claim pending fees first
+        self.claim_pending_fees_internal(pair_id, holder);
+        
+        let total_shares = self.get_total_shares_internal(pair_id);
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        // Calculate tokens to return
+        let (amount_a, amount_b) = math::amounts_for_shares(shares_to_remove, &reserve_a, &reserve_b, &total_shares);
+        
+        require!(amount_a > 0u64 || amount_b > 0u64, "Withdrawal too small");
+        require!(amount_a >= *min_amount_a, "Slippage: token A below minimum");
+        require!(amount_b >= *min_amount_b, "Slippage: token B below minimum");
+        
+        let (il_comp_a, il_comp_b) = self.compute_il_compensation(
+            pair_id,
+            holder,
+            &lp_shares,
+            shares_to_remove,
+            &reserve_a,
+            &reserve_b,
+            &amount_a,
+            &amount_b,
+        );
+        
+        // Update state
+        let new_lp_shares = &lp_shares - shares_to_remove;
+        if new_lp_shares == BigUint::zero() {
+            self.clear_lp_position(pair_id, holder);
+        } else {
+            self.lp_shares(pair_id, holder).set(&new_lp_shares);
+            self.lp_deposited_a(pair_id, holder).update(|d| *d = &*d * &new_lp_shares / &lp_shares);
+            self.lp_deposited_b(pair_id, holder).update(|d| *d = &*d * &new_lp_shares / &lp_shares);
+        }
+        
+        self.total_lp_shares(pair_id).update(|s| *s -= shares_to_remove);
+        self.pair_reserve_a(pair_id).set(&reserve_a - &amount_a);
+        self.pair_reserve_b(pair_id).set(&reserve_b - &amount_b);
+        self.verify_k_per_share_not_decreased(pair_id, &(&reserve_a * &reserve_b), &total_shares);
+        
+        self.remove_liquidity_event(pair_id, recipient, &amount_a, &amount_b, shares_to_remove);
+        
+        if il_comp_a > 0u64 || il_comp_b > 0u64 {
+            self.il_fund_a(pair_id).update(|f| *f -= &il_comp_a);
+            self.il_fund_b(pair_id).update(|f| *f -= &il_comp_b);
+            if il_comp_a > 0u64 {
+                self.send_pair_token(recipient, pair_id, true, &il_comp_a);
+            }
+            if il_comp_b > 0u64 {
+                self.send_pair_token(recipient, pair_id, false, &il_comp_b);
+            }
+            self.il_compensation_event(pair_id, recipient, &il_comp_a, &il_comp_b);
+        }
+        
+        (amount_a, amount_b)
+    }
+
+    /// Forget an LP's position once its last share is gone
+    fn clear_lp_position(&self, pair_id: u64, holder: &ManagedAddress) {
+        self.lp_shares(pair_id, holder).clear();
+        self.lp_entry_index_a(pair_id, holder).clear();
+        self.lp_entry_index_b(pair_id, holder).clear();
+        self.lp_deposited_a(pair_id, holder).clear();
+        self.lp_deposited_b(pair_id, holder).clear();
+        self.lp_deposit_timestamp(pair_id, holder).clear();
+        self.lp_list(pair_id).swap_remove(holder);
+        self.refresh_user_pair_index(pair_id, holder);
+    }
+
+    /// Track what an LP put into the pool, for IL protection
+    /// Topping up restarts the holding period.
+    fn record_lp_deposit(&self, pair_id: u64, addr: &ManagedAddress, amount_a: &BigUint, amount_b: &BigUint) {
+        if !self.pair_lp_token(pair_id).is_empty() {
+            return;
+        }
+        self.lp_deposited_a(pair_id, addr).update(|d| *d += amount_a);
+        self.lp_deposited_b(pair_id, addr).update(|d| *d += amount_b);
+        self.lp_deposit_timestamp(pair_id, addr).set(self.blockchain().get_block_timestamp());
+    }
+
+    /// IL compensation owed for a removal, as (from fund A, from fund B)
+    /// Measured in token B at the pair's TWAP, so a swap just before the removal
+    /// can't inflate it; nothing is owed without a TWAP or while the spot price is
+    /// more than MAX_IL_TWAP_DEVIATION_BPS away from it. Paid from fund B first.
+    fn compute_il_compensation(
+        &self,
+        pair_id: u64,
+        holder: &ManagedAddress,
+        lp_shares: &BigUint,
+        shares_to_remove: &BigUint,
+        reserve_a: &BigUint,
+        reserve_b: &BigUint,
+        amount_a: &BigUint,
+        amount_b: &BigUint,
+    ) -> (BigUint, BigUint) {
+        let zero = (BigUint::zero(), BigUint::zero());
+        let coverage_bps = self.il_max_coverage_bps().get();
+        if coverage_bps == 0 || *reserve_a == 0u64 || *reserve_b == 0u64 {
+            return zero;
+        }
+        
+        let deposit_ts = self.lp_deposit_timestamp(pair_id, holder).get();
+        if deposit_ts == 0 || self.blockchain().get_block_timestamp() < deposit_ts + self.il_min_duration().get() {
+            return zero;
+        }
+        
+        let twap = match self.get_twap_price(pair_id) {
+            Some(twap) if twap > 0u64 => twap,
+            _ => return zero,
+        };
+        let spot = self.spot_price_a_in_b(pair_id, reserve_a, reserve_b);
+        if self.price_deviation_bps(&spot, &twap) > MAX_IL_TWAP_DEVIATION_BPS {
+            return zero;
+        }
+        
+        let deposited_a = self.lp_deposited_a(pair_id, holder).get() * shares_to_remove / lp_shares;
+        let deposited_b = self.lp_deposited_b(pair_id, holder).get() * shares_to_remove / lp_shares;
+        let hold_value = deposited_a * &twap / PRECISION + deposited_b;
+        let withdrawn_value = amount_a * &twap / PRECISION + amount_b;
+        if hold_value <= withdrawn_value {
+            return zero;
+        }
+        
+        let owed = (hold_value - withdrawn_value) * coverage_bps / BPS_DENOMINATOR;
+        let fund_a = self.il_fund_a(pair_id).get();
+        let fund_b = self.il_fund_b(pair_id).get();
+        let from_b = if owed <= fund_b { owed.clone() } else { fund_b };
+        let remaining_a = (owed - &from_b) * PRECISION / &twap;
+        let from_a = if remaining_a <= fund_a { remaining_a } else { fund_a };
+        
+        (from_a, from_b)
+    }
+
+    /// Contract balance of an internal token id (WKLV reads the native KLV balance)
+    fn get_token_balance(&self, token: &TokenIdentifier) -> BigUint {
+        if *token == self.wklv_token_id() {
+            self.blockchain().get_sc_balance(&KlvOrKdaTokenIdentifier::klv(), 0)
+        } else {
+            self.blockchain().get_sc_balance(&KlvOrKdaTokenIdentifier::kda(token.clone()), 0)
+        }
+    }
+
+    /// Everything the contract owes in a token, summed over all pairs
+    fn get_accounted_balance(&self, token: &TokenIdentifier) -> BigUint {
+        let mut total = self.get_unpaired_accounted_balance(token);
+        for pair_id in self.registered_pair_ids().iter() {
+            total += self.get_pair_accounted_balance(pair_id, token);
+        }
+        
+        total
+    }
+
+    /// What the contract owes in `token` outside of any pair
+    /// (outstanding referral / rebate / airdrop / competition / vesting / launch /
+    /// relayer balances and reward pools)
+    fn get_unpaired_accounted_balance(&self, token: &TokenIdentifier) -> BigUint {
+        let mut total = self.referral_outstanding(token).get();
+        total += self.rebate_outstanding(token).get();
+        total += self.airdrop_outstanding(token).get();
+        total += self.competition_outstanding(token).get();
+        total += self.vesting_outstanding(token).get();
+        total += self.launch_outstanding(token).get();
+        total += self.relayer_allowance_outstanding(token).get();
+        
+        if !self.farm_reward_token().is_empty() && *token == self.farm_reward_token().get() {
+            total += self.farm_reward_pool().get();
+        }
+        if !self.staking_token().is_empty() && *token == self.staking_token().get() {
+            total += self.staking_outstanding().get();
+        }
+        if *token == self.wklv_token_id() {
+            total += self.checkpoint_reward_pool().get();
+        }
+        
+        total
+    }
+
+    /// What the contract owes in `token` on account of one pair
+    /// (reserves, pending deposits, unclaimed fees, IL fund, flash swap repayments and
+    /// escrowed orders)
+    fn get_pair_accounted_balance(&self, pair_id: u64, token: &TokenIdentifier) -> BigUint {
+        let mut total = BigUint::zero();
+        let is_a = self.pair_token(pair_id, true) == *token;
+        let is_b = self.pair_token(pair_id, false) == *token;
+        if !is_a && !is_b {
+            return total;
+        }
+        
+        if is_a {
+            total += self.pair_reserve_a(pair_id).get();
+            total += self.pair_pending_total_a(pair_id).get();
+            total += self.owner_unclaimed_fees_a(pair_id).get();
+            total += self.treasury_unclaimed_fees_a(pair_id).get();
+            total += self.lp_token_fee_bucket_a(pair_id).get();
+            total += self.il_fund_a(pair_id).get();
+            total += self.flash_swap_repaid_a(pair_id).get();
+        }
+        if is_b {
+            total += self.pair_reserve_b(pair_id).get();
+            total += self.pair_pending_total_b(pair_id).get();
+            total += self.owner_unclaimed_fees_b(pair_id).get();
+            total += self.treasury_unclaimed_fees_b(pair_id).get();
+            total += self.lp_token_fee_bucket_b(pair_id).get();
+            total += self.il_fund_b(pair_id).get();
+            total += self.flash_swap_repaid_b(pair_id).get();
+        }
+        
+        for lp in self.lp_list(pair_id).iter() {
+            let (fees_a, fees_b) = self.calculate_lp_pending_fees(pair_id, &lp);
+            if is_a {
+                total += fees_a;
+            }
+            if is_b {
+                total += fees_b;
+            }
+        }
+        
+        for order_id in self.pair_limit_orders(pair_id).iter() {
+            let order = self.limit_order(order_id).get();
+            if (order.is_a_to_b && is_a) || (!order.is_a_to_b && is_b) {
+                total += order.sell_amount;
+            }
+        }
+        
+        for order_id in self.pair_dca_orders(pair_id).iter() {
+            let order = self.dca_order(order_id).get();
+            if (order.is_a_to_b && is_a) || (!order.is_a_to_b && is_b) {
+                total += order.remaining;
+            }
+        }
+        
+        for order_id in self.pair_stream_orders(pair_id).iter() {
+            let order = self.stream_order(order_id).get();
+            if (order.is_a_to_b && is_a) || (!order.is_a_to_b && is_b) {
+                total += order.total_amount - order.amount_sold;
+            }
+        }
+        
+        total
+    }
+
+    /// Contract balance of a token left for one pair, given that pair's obligations
+    fn get_pair_attributable_balance(&self, token: &TokenIdentifier, pair_obligations: &BigUint) -> BigUint {
+        let balance = self.get_token_balance(token);
+        let other_obligations = self.get_accounted_balance(token) - pair_obligations;
+        if balance > other_obligations {
+            balance - other_obligations
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    /// Balance of a token above what the contract owes in it
+    fn get_stray_balance(&self, token: &TokenIdentifier) -> BigUint {
+        require!(self.token_unseeded_pairs(token).get() == 0, "Token obligations not seeded yet");
+        let balance = self.get_token_balance(token);
+        let accounted = self.token_obligations(token).get();
+        if balance > accounted {
+            balance - accounted
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    /// Stray amount of each of a pair's tokens
+    fn get_pair_stray_balances(&self, pair_id: u64) -> (BigUint, BigUint) {
+        (
+            self.get_stray_balance(&self.pair_token(pair_id, true)),
+            self.get_stray_balance(&self.pair_token(pair_id, false)),
+        )
+    }
+
+    /// Start a token's obligations counter from what is owed outside of pairs
+    /// Pairs of the token already counted as unseeded are left for the crank.
+    fn start_token_obligations(&self, token: &TokenIdentifier) {
+        if self.token_unseeded_pairs(token).get() == 0 {
+            self.token_obligations(token).set(self.get_unpaired_accounted_balance(token));
+        }
+    }
+
+    /// Distribute fee between owner and LPs
+    fn distribute_fee(&self, pair_id: u64, fee: &BigUint, is_token_a: bool) {
+        if fee == &BigUint::zero() {
+            return;
+        }
+        
+        let owner_shares = self.owner_shares(pair_id).get();
+        let total_lp_shares = self.total_lp_shares(pair_id).get();
+        let total_shares = &owner_shares + &total_lp_shares;
+        
+        if total_shares == BigUint::zero() {
+            return;
+        }
+        
+        let precision = BigUint::from(PRECISION);
+        
+        let owner_pct = if total_shares > BigUint::zero() {
+            &owner_shares * &precision / &total_shares
+        } else {
+            BigUint::zero()
+        };
+        
+        // Protocol cut first (default 1000 bps = 10% of the fee)
+        let mut protocol_portion = fee * self.get_protocol_fee_bps() / BPS_DENOMINATOR;
+        let remaining = fee - &protocol_portion;
+        
+        let fee_is_klv = if is_token_a {
+            self.pair_token_a_is_klv(pair_id).get()
+        } else {
+            self.pair_token_b_is_klv(pair_id).get()
+        };
+        if fee_is_klv {
+            self.total_protocol_fees_klv().update(|f| *f += &protocol_portion);
+        }
+        
+        // IL fund takes its slice of the protocol cut
+        let il_portion = &protocol_portion * self.il_fund_bps().get() / BPS_DENOMINATOR;
+        if il_portion > 0u64 {
+            if is_token_a {
+                self.il_fund_a(pair_id).update(|f| *f += &il_portion);
+            } else {
+                self.il_fund_b(pair_id).update(|f| *f += &il_portion);
+            }
+            protocol_portion -= il_portion;
+        }
+        
+        // Legacy owner_shares take their pro-rata part of the remainder
+        let owner_portion = &remaining * &owner_pct / &precision;
+        let mut lp_portion = &remaining - &owner_portion;
+        
+        // Locked shares count with their boost: the extra weight gets its own index
+        let boost_weight = self.total_boost_weight(pair_id).get();
+        if boost_weight > 0u64 && total_lp_shares > 0u64 {
+            let boost_portion = &lp_portion * &boost_weight / (&total_lp_shares + &boost_weight);
+            let boost_index_increase = &boost_portion * &precision / &boost_weight;
+            if is_token_a {
+                self.boost_fee_per_weight_a(pair_id).update(|f| *f += boost_index_increase);
+            } else {
+                self.boost_fee_per_weight_b(pair_id).update(|f| *f += boost_index_increase);
+            }
+            lp_portion -= boost_portion;
+        }
+        
+        // Protocol cut goes to the treasury bucket when a treasury or fee splitter
+        // is configured, else to the owner
+        let has_treasury = self.protocol_fees_use_treasury_bucket();
+        
+        if is_token_a {
+            if has_treasury {
+                self.treasury_unclaimed_fees_a(pair_id).update(|f| *f += &protocol_portion);
+                self.owner_unclaimed_fees_a(pair_id).update(|f| *f += &owner_portion);
+            } else {
+                self.owner_unclaimed_fees_a(pair_id).update(|f| *f += &(&owner_portion + &protocol_portion));
+            }
+            
+            if total_lp_shares > BigUint::zero() && lp_portion > BigUint::zero() {
+                let fee_per_share_increase = &lp_portion * &precision / &total_lp_shares;
+                self.fee_per_share_a(pair_id).update(|f| *f += fee_per_share_increase);
+            }
+        } else {
+            if has_treasury {
+                self.treasury_unclaimed_fees_b(pair_id).update(|f| *f += &protocol_portion);
+                self.owner_unclaimed_fees_b(pair_id).update(|f| *f += &owner_portion);
+            } else {
+                self.owner_unclaimed_fees_b(pair_id).update(|f| *f += &(&owner_portion + &protocol_portion));
+            }
+            
+            if total_lp_shares > BigUint::zero() && lp_portion > BigUint::zero() {
+                let fee_per_share_increase = &lp_portion * &precision / &total_lp_shares;
+                self.fee_per_share_b(pair_id).update(|f| *f += fee_per_share_increase);
+            }
+        }
+        
+        if !self.fee_split_recipients().is_empty() {
+            self.forward_fee_split_if_due(pair_id, is_token_a);
+        }
+    }
+
+    /// Whether the protocol cut accrues in the treasury buckets rather than the owner's
+    fn protocol_fees_use_treasury_bucket(&self) -> bool {
+        !self.treasury_address().is_empty() || !self.fee_split_recipients().is_empty()
+    }
+
+    /// Forward one side of a pair's treasury bucket once it reaches the split threshold
+    fn forward_fee_split_if_due(&self, pair_id: u64, is_token_a: bool) {
+        let (bucket, threshold) = if is_token_a {
+            (self.treasury_unclaimed_fees_a(pair_id), self.fee_split_threshold_a(pair_id).get())
+        } else {
+            (self.treasury_unclaimed_fees_b(pair_id), self.fee_split_threshold_b(pair_id).get())
+        };
+        let pending = bucket.get();
+        if pending > 0u64 && pending >= threshold {
+            bucket.clear();
+            self.forward_fee_split(pair_id, is_token_a, &pending);
+        }
+    }
+
+    /// Send an amount of a pair token to the fee splitter recipients by weight
+    /// The last recipient gets the rounding dust.
+    fn forward_fee_split(&self, pair_id: u64, is_token_a: bool, amount: &BigUint) {
+        let recipients = self.fee_split_recipients().get();
+        let token = self.pair_token(pair_id, is_token_a);
+        let last = recipients.len() - 1;
+        let mut remaining = amount.clone();
+        
+        for (i, recipient) in recipients.iter().enumerate() {
+            let share = if i == last {
+                remaining.clone()
+            } else {
+                amount * recipient.weight_bps / BPS_DENOMINATOR
+            };
+            remaining -= &share;
+            if share > 0u64 {
+                self.fee_split_forward_event(pair_id, &recipient.address, &token, &share);
+                self.send_pair_token(&recipient.address, pair_id, is_token_a, &share);
+            }
+        }
+    }
+
+    /// Extra fee weight of an LP's lock: locked_shares * (boost - 1x)
+    fn get_lp_boost_weight(&self, pair_id: u64, addr: &ManagedAddress) -> BigUint {
+        let boost_bps = self.lp_lock_boost_bps(pair_id, addr).get();
+        if boost_bps <= BPS_DENOMINATOR {
+            return BigUint::zero();
+        }
+        self.lp_locked_shares(pair_id, addr).get() * (boost_bps - BPS_DENOMINATOR) / BPS_DENOMINATOR
+    }
+
+    /// Boost fees accrued on an LP's lock weight since its last claim
+    fn get_pending_boost_fees(&self, pair_id: u64, addr: &ManagedAddress) -> (BigUint, BigUint) {
+        let weight = self.get_lp_boost_weight(pair_id, addr);
+        if weight == 0u64 {
+            return (BigUint::zero(), BigUint::zero());
+        }
+        let index_a = self.boost_fee_per_weight_a(pair_id).get();
+        let index_b = self.boost_fee_per_weight_b(pair_id).get();
+        let entry_a = self.lp_boost_entry_a(pair_id, addr).get();
+        let entry_b = self.lp_boost_entry_b(pair_id, addr).get();
+        (
+            (index_a - entry_a) * &weight / PRECISION,
+            (index_b - entry_b) * &weight / PRECISION,
+        )
+    }
+
+    /// Lock shares the LP just received, extending any active lock
+    /// All locked shares take the new expiry and tier; a lock can only be extended.
+    fn lock_lp_shares(&self, pair_id: u64, addr: &ManagedAddress, shares: &BigUint, lock_days: u64) {
+        // LP-token pairs credit the custodian, not the user, so there is nothing to lock
+        require!(self.pair_lp_token(pair_id).is_empty(), "Locks not available on LP token pairs");
+        let boost_bps = match LOCK_TIERS.iter().find(|(days, _)| *days == lock_days) {
+            Some((_, boost_bps)) => *boost_bps,
+            None => sc_panic!("Invalid lock period"),
+        };
+        let lock_until = self.blockchain().get_block_timestamp() + lock_days * SECONDS_PER_DAY;
+        require!(
+            lock_until >= self.lp_lock_until(pair_id, addr).get()
+                && boost_bps >= self.lp_lock_boost_bps(pair_id, addr).get(),
+            "Existing lock is longer"
+        );
+        
+        // Settle fees at the old weight before it changes
+        self.claim_pending_fees_internal(pair_id, addr);
+        let old_weight = self.get_lp_boost_weight(pair_id, addr);
+        
+        self.lp_locked_shares(pair_id, addr).update(|l| *l += shares);
+        self.lp_lock_until(pair_id, addr).set(lock_until);
+        self.lp_lock_boost_bps(pair_id, addr).set(boost_bps);
+        
+        let new_weight = self.get_lp_boost_weight(pair_id, addr);
+        self.total_boost_weight(pair_id).update(|w| *w = &*w + &new_weight - &old_weight);
+    }
+
+    /// Drop an LP's lock and its boost weight (caller checks expiry)
+    fn release_lp_lock(&self, pair_id: u64, addr: &ManagedAddress) {
+        self.claim_pending_fees_internal(pair_id, addr);
+        self.clear_lp_lock(pair_id, addr);
+    }
+
+    /// Drop an LP's lock and its boost weight without settling fees
+    fn clear_lp_lock(&self, pair_id: u64, addr: &ManagedAddress) {
+        let weight = self.get_lp_boost_weight(pair_id, addr);
+        self.total_boost_weight(pair_id).update(|w| *w -= &weight);
+        
+        self.lp_locked_shares(pair_id, addr).clear();
+        self.lp_lock_until(pair_id, addr).clear();
+        self.lp_lock_boost_bps(pair_id, addr).clear();
+        self.lp_boost_entry_a(pair_id, addr).clear();
+        self.lp_boost_entry_b(pair_id, addr).clear();
+    }
+
+    fn claim_pending_fees_internal(&self, pair_id: u64, addr: &ManagedAddress) {
+        self.claim_pending_fees_to(pair_id, addr, addr);
+    }
+
+    /// Settle an LP's pending fees and pay them to `recipient`
+    /// Position holders (see lp_position_holder) can't receive tokens, so their
+    /// fees go to whoever presents the NFT.
+    fn claim_pending_fees_to(&self, pair_id: u64, addr: &ManagedAddress, recipient: &ManagedAddress) {
+        if !self.lp_list(pair_id).contains(addr) {
+            return;
+        }
+        
+        let (pending_a, pending_b) = self.settle_lp_fees(pair_id, addr);
+        
+        if pending_a > BigUint::zero() || pending_b > BigUint::zero() {
+            self.fee_claim_event(pair_id, recipient, &pending_a, &pending_b);
+        }
+        
+        if pending_a > BigUint::zero() {
+            self.send_pair_token(recipient, pair_id, true, &pending_a);
+        }
+        
+        if pending_b > BigUint::zero() {
+            self.send_pair_token(recipient, pair_id, false, &pending_b);
+        }
+    }
+
+    /// Move an LP's entry indexes up to date and return the fees (boost included) they owed
+    /// The caller pays the returned amounts out.
+    fn settle_lp_fees(&self, pair_id: u64, addr: &ManagedAddress) -> (BigUint, BigUint) {
+        let shares = self.lp_shares(pair_id, addr).get();
+        if shares == BigUint::zero() {
+            return (BigUint::zero(), BigUint::zero());
+        }
+        
+        let precision = BigUint::from(PRECISION);
+        
+        let current_index_a = self.fee_per_share_a(pair_id).get();
+        let entry_index_a = self.lp_entry_index_a(pair_id, addr).get();
+        
+        let pending_a = if current_index_a > entry_index_a {
+            (&current_index_a - &entry_index_a) * &shares / &precision
+        } else {
+            BigUint::zero()
+        };
+        
+        let current_index_b = self.fee_per_share_b(pair_id).get();
+        let entry_index_b = self.lp_entry_index_b(pair_id, addr).get();
+        
+        let pending_b = if current_index_b > entry_index_b {
+            (&current_index_b - &entry_index_b) * &shares / &precision
+        } else {
+            BigUint::zero()
+        };
+        
+        self.lp_entry_index_a(pair_id, addr).set(&current_index_a);
+        self.lp_entry_index_b(pair_id, addr).set(&current_index_b);
+        
+        let (boost_a, boost_b) = self.get_pending_boost_fees(pair_id, addr);
+        self.lp_boost_entry_a(pair_id, addr).set(self.boost_fee_per_weight_a(pair_id).get());
+        self.lp_boost_entry_b(pair_id, addr).set(self.boost_fee_per_weight_b(pair_id).get());
+        (pending_a + boost_a, pending_b + boost_b)
+    }
+
+    /// Account that holds a wrapped position's shares, derived from its NFT nonce
+    /// Nobody has the key to it: every action goes through the NFT.
+    fn lp_position_holder(&self, nonce: u64) -> ManagedAddress {
+        let mut data = ManagedBuffer::from(b"lp-position");
+        data.append_bytes(&nonce.to_be_bytes());
+        ManagedAddress::from(self.crypto().sha256(&data))
+    }
+
+    /// Move LP shares between two accounts, with their share of the IL deposit record
+    /// Both sides' fees must already be settled; locks are the caller's business.
+    fn transfer_lp_shares(&self, pair_id: u64, from: &ManagedAddress, to: &ManagedAddress, shares: &BigUint) {
+        let from_shares = self.lp_shares(pair_id, from).get();
+        require!(*shares > 0u64 && *shares <= from_shares, "Insufficient LP shares");
+        
+        let moved_a = self.lp_deposited_a(pair_id, from).get() * shares / &from_shares;
+        let moved_b = self.lp_deposited_b(pair_id, from).get() * shares / &from_shares;
+        let deposit_timestamp = self.lp_deposit_timestamp(pair_id, from).get();
+        
+        if *shares == from_shares {
+            self.lp_shares(pair_id, from).clear();
+            self.lp_entry_index_a(pair_id, from).clear();
+            self.lp_entry_index_b(pair_id, from).clear();
+            self.lp_deposited_a(pair_id, from).clear();
+            self.lp_deposited_b(pair_id, from).clear();
+            self.lp_deposit_timestamp(pair_id, from).clear();
+            self.lp_list(pair_id).swap_remove(from);
+            self.refresh_user_pair_index(pair_id, from);
+        } else {
+            self.lp_shares(pair_id, from).set(&from_shares - shares);
+            self.lp_deposited_a(pair_id, from).update(|d| *d -= &moved_a);
+            self.lp_deposited_b(pair_id, from).update(|d| *d -= &moved_b);
+        }
+        
+        // add_lp_shares also bumps the total, which a transfer leaves unchanged
+        self.add_lp_shares(pair_id, to, shares);
+        self.total_lp_shares(pair_id).update(|s| *s -= shares);
+        
+        self.lp_deposited_a(pair_id, to).update(|d| *d += &moved_a);
+        self.lp_deposited_b(pair_id, to).update(|d| *d += &moved_b);
+        if self.lp_deposit_timestamp(pair_id, to).is_empty() {
+            self.lp_deposit_timestamp(pair_id, to).set(deposit_timestamp);
+        }
+    }
+
+    /// Forget a wrapped position and burn its NFT (its shares must be gone already)
+    fn burn_lp_position(&self, nonce: u64) {
+        let pair_id = self.lp_position_pair(nonce).get();
+        self.lp_position_pair(nonce).clear();
+        self.pair_lp_positions(pair_id).swap_remove(&nonce);
+        self.lp_position_nonce(&self.lp_position_holder(nonce)).clear();
+        
+        let token = self.lp_position_nft_token().get();
+        self.send().kda_local_burn(&token, nonce, &BigUint::from(1u64));
+    }
+
+    fn calculate_lp_pending_fees(&self, pair_id: u64, addr: &ManagedAddress) -> (BigUint, BigUint) {
+        if !self.lp_list(pair_id).contains(addr) {
+            return (BigUint::zero(), BigUint::zero());
+        }
+        
+        let shares = self.lp_shares(pair_id, addr).get();
+        if shares == BigUint::zero() {
+            return (BigUint::zero(), BigUint::zero());
+        }
+        
+        let precision = BigUint::from(PRECISION);
+        
+        let current_index_a = self.fee_per_share_a(pair_id).get();
+        let entry_index_a = self.lp_entry_index_a(pair_id, addr).get();
+        
+        let pending_a = if current_index_a > entry_index_a {
+            (&current_index_a - &entry_index_a) * &shares / &precision
+        } else {
+            BigUint::zero()
+        };
+        
+        let current_index_b = self.fee_per_share_b(pair_id).get();
+        let entry_index_b = self.lp_entry_index_b(pair_id, addr).get();
+        
+        let pending_b = if current_index_b > entry_index_b {
+            (&current_index_b - &entry_index_b) * &shares / &precision
+        } else {
+            BigUint::zero()
+        };
+        
+        let (boost_a, boost_b) = self.get_pending_boost_fees(pair_id, addr);
+        (pending_a + boost_a, pending_b + boost_b)
+    }
+
+    /// Tokens bought along a launch curve for `klv_in`, capped at the target
+    /// Returns (tokens_out, klv_used).
+    fn quote_launch_buy(&self, launch: &Launch<Self::Api>, klv_in: &BigUint) -> (BigUint, BigUint) {
+        let room = &launch.target - &launch.raised;
+        let klv_used = if *klv_in > room { room } else { klv_in.clone() };
+        let curve_tokens = &launch.sale_supply - &launch.sold;
+        let tokens_out = &curve_tokens * &klv_used / (&launch.virtual_klv + &launch.raised + &klv_used);
+        (tokens_out, klv_used)
+    }
+
+    /// KLV returned for selling `tokens_in` back to a launch curve
+    fn quote_launch_sell(&self, launch: &Launch<Self::Api>, tokens_in: &BigUint) -> BigUint {
+        let curve_tokens = &launch.sale_supply - &launch.sold;
+        (&launch.virtual_klv + &launch.raised) * tokens_in / (curve_tokens + tokens_in)
+    }
+
+    /// Amount of a schedule vested at `now` (all of it once revoked)
+    fn get_vested_amount(&self, schedule: &VestingSchedule<Self::Api>, now: u64) -> BigUint {
+        if schedule.revoked {
+            return schedule.total_amount.clone();
+        }
+        if now < schedule.start + schedule.cliff {
+            return BigUint::zero();
+        }
+        let elapsed = now - schedule.start;
+        if elapsed >= schedule.duration {
+            return schedule.total_amount.clone();
+        }
+        &schedule.total_amount * elapsed / schedule.duration
+    }
+
+    fn get_vesting_claimable_internal(&self, schedule_id: u64) -> BigUint {
+        let schedule = self.vesting_schedule(schedule_id).get();
+        self.get_vested_amount(&schedule, self.blockchain().get_block_timestamp()) - schedule.claimed_amount
+    }
+
+    fn get_airdrop_claimable(&self, airdrop_id: u64, addr: &ManagedAddress) -> BigUint {
+        let shares = self.airdrop_shares(airdrop_id, addr).get();
+        if shares == BigUint::zero() {
+            return BigUint::zero();
+        }
+        let airdrop = self.lp_airdrop(airdrop_id).get();
+        &airdrop.reward_amount * &shares / &airdrop.total_shares
+    }
+}
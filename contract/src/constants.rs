@@ -0,0 +1,173 @@
+// ============================================================================
+// CONSTANTS - Fixed values, defaults and safety bounds
+// ============================================================================
+
+// Precision factor for fee calculations (1e12)
+// NOT tunable: every fee_per_share index and LP entry index is scaled by it
+pub const PRECISION: u64 = 1_000_000_000_000;
+
+// Native KLV precision, and the bounds of the token decimals registry
+pub const KLV_DECIMALS: u32 = 6;
+pub const MAX_TOKEN_DECIMALS: u32 = 18;
+
+// getPriceNormalized scales prices to 1e18, whatever the decimals of the tokens
+pub const NORMALIZED_PRICE_DECIMALS: u32 = 18;
+
+// Internal identifier for native KLV ("wrapped KLV"). Every KLV side of a pair is
+// stored under this id; KLV is only unwrapped at the transfer boundary.
+pub const WKLV_TOKEN_ID: &[u8] = b"KLV";
+
+// ----------------------------------------------------------------------------
+// Tunable parameters: defaults + hard safety bounds (see PARAMETERS section)
+// ----------------------------------------------------------------------------
+
+// Minimum liquidity burned on first deposit to prevent dust attacks
+pub const DEFAULT_MINIMUM_LIQUIDITY: u64 = 1000;
+pub const MIN_MINIMUM_LIQUIDITY: u64 = 1000;
+pub const MAX_MINIMUM_LIQUIDITY: u64 = 1_000_000;
+
+// Most LP accounts a pair may have: keeps every loop over a pair's LPs (checkpoint,
+// sync / skim, airdrops) within gas limits. The LP token custodian doesn't count.
+pub const DEFAULT_LP_CAP: usize = 1_000;
+pub const MIN_LP_CAP: usize = 10;
+pub const MAX_LP_CAP: usize = 2_500;
+
+// First deposit of a pair: widest allowed deviation of its implied price from the
+// owner-set reference prices (0 = band check off)
+pub const MAX_REFERENCE_BAND_BPS: u64 = 5_000;
+
+// Basis points denominator (10_000 bps = 100%)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Allowed swap fee range for pairs, in basis points (30 bps = 0.3%)
+pub const DEFAULT_MIN_FEE_BPS: u64 = 1;
+pub const DEFAULT_MAX_FEE_BPS: u64 = 1_000;
+pub const ABSOLUTE_MAX_FEE_BPS: u64 = 2_000;
+
+// Fee tiers new pairs pick from (0.05%, 0.3%, 1%) until the owner edits the list
+pub const DEFAULT_FEE_TIERS_BPS: [u64; 3] = [5, 30, 100];
+
+// Platform cut of every swap fee, in basis points of the fee (1000 = 10%)
+pub const DEFAULT_PROTOCOL_FEE_BPS: u64 = 1_000;
+pub const MAX_PROTOCOL_FEE_BPS: u64 = 3_000;
+
+// StableSwap amplification coefficient (A) bounds for stable pools
+pub const DEFAULT_AMPLIFICATION: u64 = 100;
+pub const MAX_AMPLIFICATION: u64 = 10_000;
+pub const STABLE_MAX_ITERATIONS: usize = 255;
+
+// Weighted pools: token A weight in percent (token B gets the rest), in 5% steps
+pub const DEFAULT_WEIGHT_A_PERCENT: u64 = 80;
+pub const MIN_WEIGHT_PERCENT: u64 = 5;
+pub const WEIGHT_STEP_PERCENT: u64 = 5;
+
+// Liquidity bootstrapping pools: weighted pairs whose weights shift (in 5% steps)
+// over a period, e.g. 95/5 -> 50/50
+pub const MIN_LBP_DURATION: u64 = SECONDS_PER_DAY;
+pub const MAX_LBP_DURATION: u64 = 30 * SECONDS_PER_DAY;
+
+// TWAP oracle: observations are rolled forward once per window and the TWAP is
+// measured from the newest one at least a window old
+pub const TWAP_WINDOW_SECONDS: u64 = 30 * 60;
+
+// LP lockups: lock period in days -> fee boost in bps (10_000 = 1x)
+pub const LOCK_TIERS: [(u64, u64); 3] = [(30, 12_500), (90, 15_000), (180, 20_000)];
+
+// Impermanent-loss fund: slice of the protocol cut (bps of the cut) and max coverage of a loss
+pub const MAX_IL_FUND_BPS: u64 = 5_000;
+pub const MAX_IL_COVERAGE_BPS: u64 = 10_000;
+// Losses are valued at the TWAP, and nothing is paid while spot is further than this from it
+pub const MAX_IL_TWAP_DEVIATION_BPS: u64 = 200;
+
+// Shortest claim window of an LP airdrop
+pub const MIN_AIRDROP_CLAIM_PERIOD: u64 = 7 * SECONDS_PER_DAY;
+
+// Trading competitions: largest ranked leaderboard (top-N payouts)
+pub const MAX_COMPETITION_TOP_N: u64 = 20;
+
+// Referrer cut of the swap fee (in bps of the fee), off until configured
+pub const MAX_REFERRAL_FEE_BPS: u64 = 5_000;
+
+// Volume rebates: rolling window of a trader's per-pair volume, and the largest
+// rebate (in bps of the swap fee) a tier may pay back
+pub const REBATE_WINDOW_DAYS: u64 = 30;
+pub const MAX_REBATE_TIERS: usize = 10;
+pub const MAX_REBATE_BPS: u64 = 5_000;
+
+// DCA keeper incentive, in bps of each executed chunk (paid in the input token)
+pub const DEFAULT_DCA_KEEPER_FEE_BPS: u64 = 10;
+pub const MAX_DCA_KEEPER_FEE_BPS: u64 = 100;
+pub const MIN_DCA_INTERVAL: u64 = 60 * 60;
+
+// Streaming swaps: number of chunks a single order may be split into
+pub const MIN_STREAM_CHUNKS: u64 = 2;
+pub const MAX_STREAM_CHUNKS: u64 = 100;
+
+// DGKO staking: tier discounts are in bps of the swap fee; unstaked tokens wait
+// out a cooldown before they can be withdrawn
+pub const MAX_STAKING_TIERS: usize = 10;
+pub const MAX_STAKING_DISCOUNT_BPS: u64 = 5_000;
+pub const DEFAULT_UNSTAKE_COOLDOWN: u64 = 7 * SECONDS_PER_DAY;
+pub const MAX_UNSTAKE_COOLDOWN: u64 = 30 * SECONDS_PER_DAY;
+
+// Fee splitter: most recipients the protocol fee cut can be forwarded to
+pub const MAX_FEE_SPLIT_RECIPIENTS: usize = 5;
+
+// Buyback-and-burn: largest price impact a protocol buyback may have
+pub const MAX_BUYBACK_SLIPPAGE_BPS: u64 = 1_000;
+
+// Pair metadata field limits, in bytes
+pub const MAX_PAIR_NAME_LEN: usize = 64;
+pub const MAX_PAIR_URI_LEN: usize = 256;
+
+// Maximum number of pairs a multi-hop swap may route through
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+// Longest alias a registered route may be published under, in bytes
+pub const MAX_ROUTE_ALIAS_LEN: usize = 32;
+
+// Prefix of the message a user signs for swapOnBehalf (followed by the contract
+// address and the encoded MetaSwapOrder)
+pub const META_SWAP_DOMAIN: &[u8] = b"DigikoMetaSwap";
+
+// Endpoint invoked on the borrower contract during flashSwap
+pub const FLASH_SWAP_CALLBACK_ENDPOINT: &str = "flashSwapCallback";
+
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// Pending deposits left unfinalized can be refunded by anyone after this many days
+pub const DEFAULT_PENDING_EXPIRY_DAYS: u64 = 30;
+pub const MIN_PENDING_EXPIRY_DAYS: u64 = 1;
+pub const MAX_PENDING_EXPIRY_DAYS: u64 = 365;
+
+// Dead-man switch lower bounds: ~90 days of inactivity, 7 day claim timelock
+pub const MIN_ADMIN_INACTIVITY_PERIOD: u64 = 90 * 24 * 60 * 60;
+pub const MIN_ADMIN_RECOVERY_DELAY: u64 = 7 * 24 * 60 * 60;
+
+// Operator roles (bitmask, see ROLES section). The admin holds all of them.
+pub const ROLE_PAUSER: u64 = 1;
+pub const ROLE_FEE_MANAGER: u64 = 2;
+pub const ROLE_PAIR_MANAGER: u64 = 4;
+pub const ROLE_TREASURY_MANAGER: u64 = 8;
+pub const ALL_ROLES: u64 = ROLE_PAUSER | ROLE_FEE_MANAGER | ROLE_PAIR_MANAGER | ROLE_TREASURY_MANAGER;
+
+// Minimum wait between proposing and executing a timelocked admin action
+pub const MIN_ADMIN_TIMELOCK_DELAY: u64 = SECONDS_PER_DAY;
+
+// Shortest checkpoint interval allowed while the checkpoint pays a bounty
+pub const MIN_PAID_CHECKPOINT_INTERVAL: u64 = 60 * 60;
+
+// Longest trailing window getPairApr averages volume over
+pub const MAX_APR_WINDOW_DAYS: u64 = 90;
+
+// Maximum number of entries returned by paginated views
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+// Most pairs a batch endpoint (claimAllLpFees, removeLiquidityBatch) handles in one call
+pub const MAX_BATCH_PAIRS: usize = 20;
+
+// Fee distribution crank: most LPs paid per call, and the caller's cut of what it
+// pays out (bps of the distributed fees)
+pub const MAX_DISTRIBUTION_BATCH: u64 = 50;
+pub const DEFAULT_DISTRIBUTION_INCENTIVE_BPS: u64 = 10;
+pub const MAX_DISTRIBUTION_INCENTIVE_BPS: u64 = 100;
@@ -0,0 +1,247 @@
+// ============================================================================
+// EVENTS - Log events emitted by the DEX
+// ============================================================================
+// Indexers and the frontend subscribe to these; changing a signature is a
+// breaking change for them.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+#[klever_sc::module]
+pub trait EventsModule {
+    // ========================================================================
+    // EVENTS
+    // ========================================================================
+
+    #[event("swap")]
+    fn swap_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] token_in: &TokenIdentifier,
+        #[indexed] amount_in: &BigUint,
+        #[indexed] token_out: &TokenIdentifier,
+        #[indexed] amount_out: &BigUint,
+        fee: &BigUint,
+    );
+
+    #[event("mint")]
+    fn mint_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] amount_a: &BigUint,
+        #[indexed] amount_b: &BigUint,
+        shares: &BigUint,
+    );
+
+    #[event("removeLiquidity")]
+    fn remove_liquidity_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] amount_a: &BigUint,
+        #[indexed] amount_b: &BigUint,
+        shares: &BigUint,
+    );
+
+    #[event("emergencyWithdraw")]
+    fn emergency_withdraw_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] amount_a: &BigUint,
+        #[indexed] amount_b: &BigUint,
+        shares: &BigUint,
+    );
+
+    #[event("ownerSharesMigrated")]
+    fn owner_shares_migrated_event(&self, #[indexed] pair_id: u64, #[indexed] owner: &ManagedAddress, shares: &BigUint);
+
+    #[event("pairImported")]
+    fn pair_imported_event(&self, #[indexed] pair_id: u64, #[indexed] creator: &ManagedAddress);
+
+    #[event("liquidityMigrated")]
+    fn liquidity_migrated_event(
+        &self,
+        #[indexed] from_pair: u64,
+        #[indexed] to_pair: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] shares_burned: &BigUint,
+        shares_minted: &BigUint,
+    );
+
+    #[event("flashSwap")]
+    fn flash_swap_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] amount_a_out: &BigUint,
+        #[indexed] amount_b_out: &BigUint,
+        #[indexed] amount_a_repaid: &BigUint,
+        amount_b_repaid: &BigUint,
+    );
+
+    /// Emitted after the swap event when a router swapped on behalf of a beneficiary
+    #[event("swapFor")]
+    fn swap_for_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] router: &ManagedAddress,
+        #[indexed] beneficiary: &ManagedAddress,
+        amount_out: &BigUint,
+    );
+
+    #[event("metaSwap")]
+    fn meta_swap_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        #[indexed] relayer: &ManagedAddress,
+        #[indexed] pair_id: u64,
+        #[indexed] nonce: u64,
+        amount_out: &BigUint,
+    );
+
+    #[event("dcaExecuted")]
+    fn dca_executed_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] pair_id: u64,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] amount_in: &BigUint,
+        amount_out: &BigUint,
+    );
+
+    #[event("streamChunkExecuted")]
+    fn stream_chunk_executed_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] pair_id: u64,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] chunk_index: u64,
+        amount_in: &BigUint,
+        amount_out: &BigUint,
+    );
+
+    #[event("buybackBurn")]
+    fn buyback_burn_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] amount_sold: &BigUint,
+        #[indexed] amount_bought: &BigUint,
+        amount_burned: &BigUint,
+    );
+
+    #[event("limitOrderFilled")]
+    fn limit_order_filled_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] pair_id: u64,
+        #[indexed] owner: &ManagedAddress,
+        #[indexed] amount_in: &BigUint,
+        amount_out: &BigUint,
+    );
+
+    /// Emitted when a deprecated V4 compatibility endpoint is used
+    #[event("deprecatedCall")]
+    fn deprecated_call_event(&self, #[indexed] endpoint_name: &ManagedBuffer);
+
+    #[event("pairFeeChange")]
+    fn pair_fee_change_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] old_fee_bps: u64,
+        #[indexed] new_fee_bps: u64,
+        activation_block: u64,
+    );
+
+    #[event("lpAirdropClaimed")]
+    fn lp_airdrop_claimed_event(
+        &self,
+        #[indexed] airdrop_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        amount: &BigUint,
+    );
+
+    #[event("competitionRewardClaimed")]
+    fn competition_reward_claimed_event(
+        &self,
+        #[indexed] competition_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        amount: &BigUint,
+    );
+
+    #[event("launchTrade")]
+    fn launch_trade_event(
+        &self,
+        #[indexed] launch_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] is_buy: bool,
+        #[indexed] klv_amount: &BigUint,
+        token_amount: &BigUint,
+    );
+
+    #[event("launchGraduated")]
+    fn launch_graduated_event(
+        &self,
+        #[indexed] launch_id: u64,
+        #[indexed] pair_id: u64,
+        #[indexed] klv_amount: &BigUint,
+        token_amount: &BigUint,
+    );
+
+    #[event("vestingClaimed")]
+    fn vesting_claimed_event(
+        &self,
+        #[indexed] schedule_id: u64,
+        #[indexed] beneficiary: &ManagedAddress,
+        amount: &BigUint,
+    );
+
+    #[event("ilCompensation")]
+    fn il_compensation_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] recipient: &ManagedAddress,
+        #[indexed] amount_a: &BigUint,
+        amount_b: &BigUint,
+    );
+
+    #[event("dgkoStaked")]
+    fn dgko_staked_event(
+        &self,
+        #[indexed] staker: &ManagedAddress,
+        #[indexed] amount: &BigUint,
+        staked_balance: &BigUint,
+    );
+
+    #[event("dgkoUnstaked")]
+    fn dgko_unstaked_event(
+        &self,
+        #[indexed] staker: &ManagedAddress,
+        #[indexed] amount: &BigUint,
+        #[indexed] unlock_at: u64,
+        staked_balance: &BigUint,
+    );
+
+    #[event("dgkoUnstakeWithdrawn")]
+    fn dgko_unstake_withdrawn_event(&self, #[indexed] staker: &ManagedAddress, amount: &BigUint);
+
+    #[event("feeSplitForward")]
+    fn fee_split_forward_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] recipient: &ManagedAddress,
+        #[indexed] token: &TokenIdentifier,
+        amount: &BigUint,
+    );
+
+    #[event("feeClaim")]
+    fn fee_claim_event(
+        &self,
+        #[indexed] pair_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] fees_a: &BigUint,
+        fees_b: &BigUint,
+    );
+}
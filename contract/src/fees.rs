@@ -0,0 +1,444 @@
+// ============================================================================
+// FEES - Protocol fee claims, buyback, referrals, IL protection and vesting
+// ============================================================================
+// Owner and treasury fee claims (plus legacy owner-share removal and migration),
+// buyback & burn, referral payouts, the impermanent loss fund and vesting.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::constants::*;
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait FeesModule: crate::views::ViewsModule {
+    // ========================================================================
+    // OWNER LIQUIDITY MANAGEMENT (Contract owner special functions)
+    // ========================================================================
+
+    /// Owner removes their liquidity (partial or full)
+    #[endpoint(ownerRemoveLiquidity)]
+    fn owner_remove_liquidity(&self, pair_id: u64, shares_to_remove: BigUint) {
+        let _guard = self.non_reentrant();
+        self.require_admin();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        
+        let owner_shares = self.owner_shares(pair_id).get();
+        require!(shares_to_remove <= owner_shares, "Insufficient owner shares");
+        require!(shares_to_remove > 0u64, "Shares must be > 0");
+        
+        let total_shares = self.get_total_shares_internal(pair_id);
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        // Calculate tokens to return
+        let amount_a = &shares_to_remove * &reserve_a / &total_shares;
+        let amount_b = &shares_to_remove * &reserve_b / &total_shares;
+        
+        // Update state
+        self.owner_shares(pair_id).set(&owner_shares - &shares_to_remove);
+        self.pair_reserve_a(pair_id).set(&reserve_a - &amount_a);
+        self.pair_reserve_b(pair_id).set(&reserve_b - &amount_b);
+        self.verify_k_per_share_not_decreased(pair_id, &(&reserve_a * &reserve_b), &total_shares);
+        
+        // Send tokens to owner
+        let owner = self.get_admin_address();
+        self.remove_liquidity_event(pair_id, &owner, &amount_a, &amount_b, &shares_to_remove);
+        
+        if amount_a > BigUint::zero() {
+            self.send_pair_token(&owner, pair_id, true, &amount_a);
+        }
+        
+        if amount_b > BigUint::zero() {
+            self.send_pair_token(&owner, pair_id, false, &amount_b);
+        }
+    }
+
+    /// Turn a pair's legacy owner_shares into a regular LP position of the admin (owner only)
+    /// Pending fees of an existing admin position are paid out first so the entry
+    /// indices start at the current fee_per_share; LP-token pairs mint LP tokens
+    /// instead. Afterwards owner_shares is 0 and every share follows the LP path.
+    /// @return Shares moved into the LP position
+    #[endpoint(migrateOwnerSharesToLp)]
+    fn migrate_owner_shares_to_lp(&self, pair_id: u64) -> BigUint {
+        let _guard = self.non_reentrant();
+        self.require_admin();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let shares = self.owner_shares(pair_id).get();
+        require!(shares > 0u64, "No owner shares to migrate");
+        
+        self.compound_lp_token_fees(pair_id);
+        let owner = self.get_admin_address();
+        self.claim_pending_fees_internal(pair_id, &owner);
+        
+        self.owner_shares(pair_id).clear();
+        self.credit_lp_position(pair_id, &owner, &shares);
+        self.owner_shares_migrated_event(pair_id, &owner, &shares);
+        
+        shares
+    }
+
+    /// Owner claims accumulated fees
+    #[endpoint(ownerClaimFees)]
+    fn owner_claim_fees(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        self.require_admin();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        let fees_a = self.owner_unclaimed_fees_a(pair_id).get();
+        let fees_b = self.owner_unclaimed_fees_b(pair_id).get();
+        
+        require!(fees_a > 0u64 || fees_b > 0u64, "No fees to claim");
+        
+        // Clear fees first
+        self.owner_unclaimed_fees_a(pair_id).set(BigUint::zero());
+        self.owner_unclaimed_fees_b(pair_id).set(BigUint::zero());
+        
+        let owner = self.get_admin_address();
+        self.fee_claim_event(pair_id, &owner, &fees_a, &fees_b);
+        
+        if fees_a > BigUint::zero() {
+            self.send_pair_token(&owner, pair_id, true, &fees_a);
+        }
+        
+        if fees_b > BigUint::zero() {
+            self.send_pair_token(&owner, pair_id, false, &fees_b);
+        }
+    }
+
+    /// Pay a pair's accumulated protocol fees to the treasury
+    /// With a fee splitter configured, the fees are forwarded to its recipients
+    /// instead, whether or not they reached the pair's threshold.
+    /// Anyone can trigger this - funds only ever go to the treasury address or splitter.
+    #[endpoint(claimTreasuryFees)]
+    fn claim_treasury_fees(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.protocol_fees_use_treasury_bucket(), "Treasury not set");
+        
+        let fees_a = self.treasury_unclaimed_fees_a(pair_id).get();
+        let fees_b = self.treasury_unclaimed_fees_b(pair_id).get();
+        
+        require!(fees_a > 0u64 || fees_b > 0u64, "No fees to claim");
+        
+        self.treasury_unclaimed_fees_a(pair_id).set(BigUint::zero());
+        self.treasury_unclaimed_fees_b(pair_id).set(BigUint::zero());
+        
+        if !self.fee_split_recipients().is_empty() {
+            if fees_a > BigUint::zero() {
+                self.forward_fee_split(pair_id, true, &fees_a);
+            }
+            if fees_b > BigUint::zero() {
+                self.forward_fee_split(pair_id, false, &fees_b);
+            }
+            return;
+        }
+        
+        let treasury = self.treasury_address().get();
+        self.fee_claim_event(pair_id, &treasury, &fees_a, &fees_b);
+        
+        if fees_a > BigUint::zero() {
+            self.send_pair_token(&treasury, pair_id, true, &fees_a);
+        }
+        
+        if fees_b > BigUint::zero() {
+            self.send_pair_token(&treasury, pair_id, false, &fees_b);
+        }
+    }
+
+    // ========================================================================
+    // BUYBACK & BURN (Protocol fees -> buyback token -> burn address)
+    // ========================================================================
+    // The protocol fee cut accrued on a pair containing the buyback token (DGKO)
+    // is swapped into it through that same pair, and everything bought - plus the
+    // cut already accrued in the buyback token - is sent to the burn address.
+
+    /// Configure buyback-and-burn (owner only)
+    /// @param max_slippage_bps - Largest price impact a buyback swap may have (max MAX_BUYBACK_SLIPPAGE_BPS)
+    #[endpoint(setBuybackConfig)]
+    fn set_buyback_config(&self, buyback_token: TokenIdentifier, burn_address: ManagedAddress, max_slippage_bps: u64) {
+        self.require_admin();
+        require!(!burn_address.is_zero(), "Invalid burn address");
+        require!(
+            max_slippage_bps > 0 && max_slippage_bps <= MAX_BUYBACK_SLIPPAGE_BPS,
+            "Invalid buyback slippage"
+        );
+        self.buyback_token().set(&buyback_token);
+        self.buyback_burn_address().set(&burn_address);
+        self.buyback_max_slippage_bps().set(max_slippage_bps);
+    }
+
+    /// Buy the buyback token with a pair's protocol fees and burn it (owner or TREASURY_MANAGER)
+    /// Uses the treasury's unclaimed fees, or the owner's when no treasury is set.
+    /// @param min_out - Minimum amount bought by the swap (slippage protection)
+    /// @return Total amount sent to the burn address
+    #[endpoint(buybackAndBurn)]
+    fn buyback_and_burn(&self, pair_id: u64, min_out: BigUint) -> BigUint {
+        let _guard = self.non_reentrant();
+        self.require_role(ROLE_TREASURY_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        require!(!self.buyback_token().is_empty(), "Buyback not configured");
+        
+        let buyback_token = self.buyback_token().get();
+        let buyback_is_a = if self.pair_token(pair_id, true) == buyback_token {
+            true
+        } else {
+            require!(self.pair_token(pair_id, false) == buyback_token, "Pair does not contain the buyback token");
+            false
+        };
+        
+        // Protocol fee buckets, split into the side to sell and the side to burn as-is
+        let (fees_a, fees_b) = if self.protocol_fees_use_treasury_bucket() {
+            (self.treasury_unclaimed_fees_a(pair_id).take(), self.treasury_unclaimed_fees_b(pair_id).take())
+        } else {
+            (self.owner_unclaimed_fees_a(pair_id).take(), self.owner_unclaimed_fees_b(pair_id).take())
+        };
+        let (to_burn, to_sell) = if buyback_is_a { (fees_a, fees_b) } else { (fees_b, fees_a) };
+        require!(to_burn > 0u64 || to_sell > 0u64, "No protocol fees to use");
+        
+        let mut bought = BigUint::zero();
+        if to_sell > 0u64 {
+            let reserve_in = if buyback_is_a {
+                self.pair_reserve_b(pair_id).get()
+            } else {
+                self.pair_reserve_a(pair_id).get()
+            };
+            require!(
+                self.compute_price_impact_bps(&reserve_in, &to_sell) <= self.buyback_max_slippage_bps().get(),
+                "Buyback price impact above limit"
+            );
+            // Swapped as the contract itself, which earns no rebates, stats or competition volume
+            let protocol = self.blockchain().get_sc_address();
+            bought = self.swap_internal(pair_id, &protocol, !buyback_is_a, &to_sell, &None);
+        }
+        require!(bought >= min_out, "Slippage: output below minimum");
+        
+        let burned = bought.clone() + to_burn;
+        self.buyback_total_burned().update(|t| *t += &burned);
+        self.send_pair_token(&self.buyback_burn_address().get(), pair_id, buyback_is_a, &burned);
+        self.buyback_burn_event(pair_id, &to_sell, &bought, &burned);
+        
+        burned
+    }
+
+    // ========================================================================
+    // REFERRALS
+    // ========================================================================
+
+    /// Withdraw every referral fee balance credited to the caller
+    #[endpoint(claimReferralFees)]
+    fn claim_referral_fees(&self) {
+        let _guard = self.non_reentrant();
+        let caller = self.blockchain().get_caller();
+        require!(!self.referral_tokens(&caller).is_empty(), "No referral fees to claim");
+        
+        for token in self.referral_tokens(&caller).iter() {
+            let amount = self.referral_unclaimed(&caller, &token).take();
+            if amount > BigUint::zero() {
+                self.referral_outstanding(&token).update(|b| *b -= &amount);
+                self.send_wrapped(&caller, &token, &amount);
+            }
+        }
+        self.referral_tokens(&caller).clear();
+    }
+
+    // ========================================================================
+    // FEE REBATES (Volume tiers over a rolling 30-day window)
+    // ========================================================================
+    // On pairs with rebate tiers, every swap adds to the trader's rolling volume
+    // on that pair (in token A, one bucket per day). Traders above a tier get its
+    // rebate_bps of each swap fee credited to a claimable balance; the rest of the
+    // fee is split as usual. Orders run by keepers and relayed meta-swaps count
+    // for the order owner / signer, not for whoever submitted them.
+
+    /// Replace a pair's rebate tiers (owner or FEE_MANAGER)
+    /// Pairs of (min_volume, rebate_bps), both strictly increasing; min_volume is the
+    /// rolling REBATE_WINDOW_DAYS volume in token A, rebate_bps is in bps of the
+    /// swap fee, max MAX_REBATE_BPS. Empty turns rebates off for the pair.
+    /// Volume is only tracked while a pair has tiers.
+    #[endpoint(setPairRebateTiers)]
+    fn set_pair_rebate_tiers(&self, pair_id: u64, tiers: MultiValueEncoded<MultiValue2<BigUint, u64>>) {
+        self.require_role(ROLE_FEE_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(tiers.len() <= MAX_REBATE_TIERS, "Too many rebate tiers");
+        
+        let mut result = ManagedVec::new();
+        let mut last_min_volume = BigUint::zero();
+        let mut last_rebate_bps = 0u64;
+        for tier in tiers.into_iter() {
+            let (min_volume, rebate_bps) = tier.into_tuple();
+            require!(min_volume > last_min_volume, "Tier volumes must increase");
+            require!(rebate_bps > last_rebate_bps, "Tier rebates must increase");
+            require!(rebate_bps <= MAX_REBATE_BPS, "Rebate above hard limit");
+            last_min_volume = min_volume.clone();
+            last_rebate_bps = rebate_bps;
+            result.push(RebateTier { min_volume, rebate_bps });
+        }
+        self.pair_rebate_tiers(pair_id).set(&result);
+    }
+
+    /// Withdraw every fee rebate balance credited to the caller
+    #[endpoint(claimFeeRebates)]
+    fn claim_fee_rebates(&self) {
+        let _guard = self.non_reentrant();
+        let caller = self.blockchain().get_caller();
+        require!(!self.rebate_tokens(&caller).is_empty(), "No fee rebates to claim");
+        
+        for token in self.rebate_tokens(&caller).iter() {
+            let amount = self.rebate_unclaimed(&caller, &token).take();
+            if amount > BigUint::zero() {
+                self.rebate_outstanding(&token).update(|b| *b -= &amount);
+                self.send_wrapped(&caller, &token, &amount);
+            }
+        }
+        self.rebate_tokens(&caller).clear();
+    }
+
+    // ========================================================================
+    // IMPERMANENT LOSS PROTECTION (Per-pair insurance fund)
+    // ========================================================================
+    // A slice of the protocol fee cut is set aside per pair. When an LP removes
+    // liquidity after holding for at least il_min_duration, the value of what they
+    // withdraw is compared (in token B at the pair's TWAP) with simply holding
+    // their deposited amounts; up to il_max_coverage_bps of the shortfall is paid
+    // from the fund, as far as the fund allows. Nothing is paid while the spot
+    // price is far from the TWAP. Pairs with a KDA LP token are not covered
+    // (positions are tracked by the custodian there).
+
+    /// Configure impermanent-loss protection (owner or FEE_MANAGER)
+    /// fund_bps: share of the protocol fee cut sent to the fund (0 = stop funding)
+    /// min_duration: seconds an LP must hold before removals are covered
+    /// max_coverage_bps: share of the computed loss paid back (0 = no payouts)
+    #[endpoint(setIlProtection)]
+    fn set_il_protection(&self, fund_bps: u64, min_duration: u64, max_coverage_bps: u64) {
+        self.require_role(ROLE_FEE_MANAGER);
+        require!(fund_bps <= MAX_IL_FUND_BPS, "IL fund share above hard limit");
+        require!(max_coverage_bps <= MAX_IL_COVERAGE_BPS, "IL coverage above hard limit");
+        self.il_fund_bps().set(fund_bps);
+        self.il_min_duration().set(min_duration);
+        self.il_max_coverage_bps().set(max_coverage_bps);
+    }
+
+    /// Top up a pair's IL fund directly with either pair token
+    #[endpoint(fundIlProtection)]
+    #[payable("*")]
+    fn fund_il_protection(&self, pair_id: u64) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let (is_token_a, amount) = self.take_pair_payment(pair_id);
+        require!(amount > 0u64, "Amount must be > 0");
+        
+        if is_token_a {
+            self.il_fund_a(pair_id).update(|f| *f += &amount);
+        } else {
+            self.il_fund_b(pair_id).update(|f| *f += &amount);
+        }
+    }
+
+    // ========================================================================
+    // VESTING (Linear unlock with cliff, e.g. pair-creator or team incentives)
+    // ========================================================================
+    // Tokens are deposited up front and unlock linearly from `start` over
+    // `duration` seconds, with nothing claimable before the cliff. Revoking a
+    // revocable schedule keeps what has vested for the beneficiary and returns
+    // the rest to the treasury.
+
+    /// Deposit tokens that vest to a beneficiary (owner or TREASURY_MANAGER)
+    /// @param cliff - Seconds after start before anything can be claimed (<= duration)
+    /// @param duration - Seconds after start until everything has vested
+    /// @return The schedule id
+    #[endpoint(createVestingSchedule)]
+    #[payable("*")]
+    fn create_vesting_schedule(
+        &self,
+        beneficiary: ManagedAddress,
+        start: u64,
+        cliff: u64,
+        duration: u64,
+        revocable: bool,
+    ) -> u64 {
+        self.require_role(ROLE_TREASURY_MANAGER);
+        require!(!beneficiary.is_zero(), "Invalid beneficiary");
+        require!(duration > 0, "Duration must be > 0");
+        require!(cliff <= duration, "Cliff longer than duration");
+        
+        let (token, amount) = self.get_single_payment();
+        require!(amount > 0u64, "Amount must be > 0");
+        
+        let schedule_id = self.next_vesting_id().get() + 1;
+        self.next_vesting_id().set(schedule_id);
+        
+        self.vesting_outstanding(&token).update(|o| *o += &amount);
+        self.beneficiary_vesting_ids(&beneficiary).insert(schedule_id);
+        self.vesting_schedule(schedule_id).set(VestingSchedule {
+            beneficiary,
+            token,
+            total_amount: amount,
+            claimed_amount: BigUint::zero(),
+            start,
+            cliff,
+            duration,
+            revocable,
+            revoked: false,
+        });
+        
+        schedule_id
+    }
+
+    /// Claim everything vested so far (beneficiary only)
+    /// The schedule is removed once fully claimed.
+    #[endpoint(claimVested)]
+    fn claim_vested(&self, schedule_id: u64) -> BigUint {
+        let _guard = self.non_reentrant();
+        require!(!self.vesting_schedule(schedule_id).is_empty(), "Vesting schedule does not exist");
+        let mut schedule = self.vesting_schedule(schedule_id).get();
+        let caller = self.blockchain().get_caller();
+        require!(caller == schedule.beneficiary, "Only the beneficiary can claim");
+        
+        let amount = self.get_vesting_claimable_internal(schedule_id);
+        require!(amount > 0u64, "Nothing to claim");
+        
+        schedule.claimed_amount += &amount;
+        if schedule.claimed_amount == schedule.total_amount {
+            self.vesting_schedule(schedule_id).clear();
+            self.beneficiary_vesting_ids(&caller).swap_remove(&schedule_id);
+        } else {
+            self.vesting_schedule(schedule_id).set(&schedule);
+        }
+        self.vesting_outstanding(&schedule.token).update(|o| *o -= &amount);
+        
+        self.send_wrapped(&caller, &schedule.token, &amount);
+        self.vesting_claimed_event(schedule_id, &caller, &amount);
+        amount
+    }
+
+    /// Stop a revocable schedule (owner or TREASURY_MANAGER)
+    /// What has vested stays claimable; the unvested rest goes to the treasury.
+    #[endpoint(revokeVesting)]
+    fn revoke_vesting(&self, schedule_id: u64) {
+        let _guard = self.non_reentrant();
+        self.require_role(ROLE_TREASURY_MANAGER);
+        require!(!self.vesting_schedule(schedule_id).is_empty(), "Vesting schedule does not exist");
+        let mut schedule = self.vesting_schedule(schedule_id).get();
+        require!(schedule.revocable, "Schedule is not revocable");
+        require!(!schedule.revoked, "Schedule already revoked");
+        
+        let vested = self.get_vested_amount(&schedule, self.blockchain().get_block_timestamp());
+        let unvested = &schedule.total_amount - &vested;
+        schedule.total_amount = vested;
+        schedule.revoked = true;
+        
+        if schedule.claimed_amount == schedule.total_amount {
+            self.vesting_schedule(schedule_id).clear();
+            self.beneficiary_vesting_ids(&schedule.beneficiary).swap_remove(&schedule_id);
+        } else {
+            self.vesting_schedule(schedule_id).set(&schedule);
+        }
+        
+        if unvested > 0u64 {
+            self.vesting_outstanding(&schedule.token).update(|o| *o -= &unvested);
+            self.send_wrapped(&self.get_treasury_or_admin(), &schedule.token, &unvested);
+        }
+    }
+}
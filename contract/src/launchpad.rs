@@ -0,0 +1,201 @@
+// ============================================================================
+// LAUNCHPAD - Bonding-curve token sales that graduate into a pair
+// ============================================================================
+// A creator deposits a new KDA token; buyers pay KLV along a constant-product
+// curve until the funding target is raised. The buy that reaches the target
+// creates the token/KLV pair and seeds it with the raised KLV plus every token
+// left, crediting the LP shares to the creator under a lock.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::constants::*;
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait LaunchpadModule: crate::pair_admin::PairAdminModule {
+    // ========================================================================
+    // LAUNCHPAD
+    // ========================================================================
+
+    /// Start a bonding-curve sale of the sent token (PUBLIC)
+    /// `sale_supply` of the payment is sold along the curve; the rest is kept for
+    /// the pair's initial liquidity. The token must not trade on the DEX yet.
+    /// @param target - KLV raise that graduates the launch into a pair
+    /// @param virtual_klv - Virtual KLV reserve of the curve (starting price = virtual_klv / sale_supply)
+    /// @param fee_bps - Swap fee of the pair created at graduation (an approved tier)
+    /// @param lock_days - Lock period of the creator's LP shares (one of LOCK_TIERS)
+    /// @return The launch id
+    #[endpoint(createLaunch)]
+    #[payable("*")]
+    fn create_launch(
+        &self,
+        sale_supply: BigUint,
+        target: BigUint,
+        virtual_klv: BigUint,
+        fee_bps: u64,
+        lock_days: u64,
+    ) -> u64 {
+        self.require_not_paused();
+        let (token, amount) = self.call_value().single_fungible_kda();
+        require!(token != self.wklv_token_id(), "Cannot launch KLV");
+        self.require_token_listable(&token);
+        require!(self.launch_by_token(&token).is_empty(), "Token already has a launch");
+        require!(self.pairs_by_token(&token).is_empty(), "Token already trades on the DEX");
+        
+        require!(sale_supply > 0u64 && amount > sale_supply, "Sale supply must leave tokens for liquidity");
+        require!(target > 0u64 && virtual_klv > 0u64, "Target and virtual KLV must be > 0");
+        require!(target >= self.param_min_initial_liquidity_klv().get(), "Target below minimum initial liquidity");
+        self.require_fee_in_range(fee_bps, true);
+        self.require_fee_tier(fee_bps);
+        require!(LOCK_TIERS.iter().any(|(days, _)| *days == lock_days), "Invalid lock period");
+        
+        let launch_id = self.next_launch_id().get() + 1;
+        self.next_launch_id().set(launch_id);
+        
+        self.add_token_obligation(&token, &amount);
+        self.launch_outstanding(&token).update(|o| *o += &amount);
+        self.launch_by_token(&token).set(launch_id);
+        self.launch(launch_id).set(Launch {
+            creator: self.blockchain().get_caller(),
+            token,
+            liquidity_supply: &amount - &sale_supply,
+            sale_supply,
+            sold: BigUint::zero(),
+            raised: BigUint::zero(),
+            target,
+            virtual_klv,
+            fee_bps,
+            lock_days,
+            pair_id: 0,
+        });
+        
+        launch_id
+    }
+
+    /// Buy launch tokens with KLV
+    /// KLV beyond the funding target is refunded; reaching the target graduates the launch.
+    /// @param min_tokens - Minimum tokens to receive (slippage protection)
+    /// @return Tokens bought
+    #[endpoint(buyLaunch)]
+    #[payable("KLV")]
+    fn buy_launch(&self, launch_id: u64, min_tokens: BigUint) -> BigUint {
+        let _guard = self.non_reentrant();
+        self.require_not_paused();
+        let mut launch = self.require_running_launch(launch_id);
+        let payment = self.call_value().klv_value().clone_value();
+        require!(payment > 0u64, "Payment must be greater than 0");
+        self.add_token_obligation(&self.wklv_token_id(), &payment);
+        
+        let (tokens_out, klv_used) = self.quote_launch_buy(&launch, &payment);
+        require!(tokens_out > 0u64, "Output too small");
+        require!(tokens_out >= min_tokens, "Slippage: output below minimum");
+        
+        launch.sold += &tokens_out;
+        launch.raised += &klv_used;
+        self.launch_outstanding(&self.wklv_token_id()).update(|o| *o += &klv_used);
+        self.launch_outstanding(&launch.token).update(|o| *o -= &tokens_out);
+        
+        let caller = self.blockchain().get_caller();
+        self.send_wrapped(&caller, &launch.token, &tokens_out);
+        let refund = &payment - &klv_used;
+        if refund > 0u64 {
+            self.send_wrapped(&caller, &self.wklv_token_id(), &refund);
+        }
+        self.launch_trade_event(launch_id, &caller, true, &klv_used, &tokens_out);
+        
+        if launch.raised == launch.target {
+            self.graduate_launch(launch_id, &mut launch);
+        }
+        self.launch(launch_id).set(&launch);
+        
+        tokens_out
+    }
+
+    /// Sell launch tokens back to the curve for KLV (before graduation)
+    /// @param min_klv - Minimum KLV to receive (slippage protection)
+    /// @return KLV received
+    #[endpoint(sellLaunch)]
+    #[payable("*")]
+    fn sell_launch(&self, launch_id: u64, min_klv: BigUint) -> BigUint {
+        let _guard = self.non_reentrant();
+        self.require_not_paused();
+        let mut launch = self.require_running_launch(launch_id);
+        let (token, tokens_in) = self.call_value().single_fungible_kda();
+        require!(token == launch.token, "Wrong token");
+        require!(tokens_in <= launch.sold, "More than was sold");
+        self.add_token_obligation(&token, &tokens_in);
+        
+        let klv_out = self.quote_launch_sell(&launch, &tokens_in);
+        require!(klv_out > 0u64 && klv_out <= launch.raised, "Output too small");
+        require!(klv_out >= min_klv, "Slippage: output below minimum");
+        
+        launch.sold -= &tokens_in;
+        launch.raised -= &klv_out;
+        self.launch(launch_id).set(&launch);
+        self.launch_outstanding(&launch.token).update(|o| *o += &tokens_in);
+        self.launch_outstanding(&self.wklv_token_id()).update(|o| *o -= &klv_out);
+        
+        let caller = self.blockchain().get_caller();
+        self.send_wrapped(&caller, &self.wklv_token_id(), &klv_out);
+        self.launch_trade_event(launch_id, &caller, false, &klv_out, &tokens_in);
+        
+        klv_out
+    }
+
+    /// Withdraw a launch nobody holds tokens of (creator only)
+    #[endpoint(cancelLaunch)]
+    fn cancel_launch(&self, launch_id: u64) {
+        let _guard = self.non_reentrant();
+        let launch = self.require_running_launch(launch_id);
+        let caller = self.blockchain().get_caller();
+        require!(caller == launch.creator, "Only the launch creator can cancel");
+        require!(launch.sold == 0u64, "Launch tokens already sold");
+        
+        let amount = &launch.sale_supply + &launch.liquidity_supply;
+        self.launch_outstanding(&launch.token).update(|o| *o -= &amount);
+        self.launch_by_token(&launch.token).clear();
+        self.launch(launch_id).clear();
+        
+        self.send_wrapped(&caller, &launch.token, &amount);
+        // Rounding can leave a little KLV behind once every token is sold back
+        if launch.raised > 0u64 {
+            self.launch_outstanding(&self.wklv_token_id()).update(|o| *o -= &launch.raised);
+            self.send_wrapped(&self.get_treasury_or_admin(), &self.wklv_token_id(), &launch.raised);
+        }
+    }
+
+    fn require_running_launch(&self, launch_id: u64) -> Launch<Self::Api> {
+        require!(!self.launch(launch_id).is_empty(), "Launch does not exist");
+        let launch = self.launch(launch_id).get();
+        require!(launch.pair_id == 0, "Launch already graduated");
+        launch
+    }
+
+    /// Create the token/KLV pair and seed it with the raise and every token left
+    /// The creator receives the LP shares, locked for the launch's lock period.
+    fn graduate_launch(&self, launch_id: u64, launch: &mut Launch<Self::Api>) {
+        self.launch_by_token(&launch.token).clear();
+        let wklv = self.wklv_token_id();
+        let pair_id = self.create_pair_internal(
+            &launch.creator,
+            launch.token.clone(),
+            wklv.clone(),
+            false,
+            true,
+            launch.fee_bps,
+            PoolType::ConstantProduct,
+            None,
+        );
+        
+        let token_amount = &launch.sale_supply - &launch.sold + &launch.liquidity_supply;
+        self.launch_outstanding(&launch.token).update(|o| *o -= &token_amount);
+        self.launch_outstanding(&wklv).update(|o| *o -= &launch.raised);
+        
+        let shares = self.mint_internal(pair_id, &launch.creator, &token_amount, &launch.raised, &BigUint::zero());
+        self.lock_lp_shares(pair_id, &launch.creator, &shares, launch.lock_days);
+        
+        launch.pair_id = pair_id;
+        self.launch_graduated_event(launch_id, pair_id, &launch.raised, &token_amount);
+    }
+}
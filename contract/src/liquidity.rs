@@ -0,0 +1,996 @@
+// ============================================================================
+// LIQUIDITY - Deposits, withdrawals and LP rewards
+// ============================================================================
+// Unified mint, zaps, the pending two-step deposit flow, LP management,
+// liquidity mining and LP airdrops.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::constants::*;
+use crate::math;
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait LiquidityModule: crate::views::ViewsModule {
+    // ========================================================================
+    // V5: UNIFIED MINT - Works for empty pools too!
+    // ========================================================================
+
+    /// Add liquidity in ONE transaction (V5 Enhanced)
+    /// Works for BOTH empty pools (first LP) and existing pools
+    /// 
+    /// For empty pools: First LP sets the price ratio
+    /// For existing pools: Matches pool ratio, refunds excess
+    /// 
+    /// @param pair_id - The trading pair ID
+    /// @param min_lp_shares - Minimum shares expected (slippage protection)
+    /// @param lock_days - Optional lock for the new shares (see getLockTiers). Locked
+    ///                    shares can't be removed until expiry but earn boosted fees.
+    /// @return The number of LP shares minted
+    #[endpoint(mint)]
+    #[payable("*")]
+    fn mint(&self, pair_id: u64, min_lp_shares: BigUint, lock_days: OptionalValue<u64>) -> BigUint {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_liquidity_enabled(pair_id).get(), "Liquidity is disabled for this pair");
+        
+        self.compound_lp_token_fees(pair_id);
+        
+        let caller = self.blockchain().get_caller();
+        
+        // Extract payment amounts from multi-token callValue
+        let (amount_a, amount_b) = self.extract_dual_payment(pair_id);
+        
+        require!(amount_a > BigUint::zero(), "No token A sent");
+        require!(amount_b > BigUint::zero(), "No token B sent");
+        
+        let shares = self.mint_internal(pair_id, &caller, &amount_a, &amount_b, &min_lp_shares);
+        if let Some(lock_days) = lock_days.into_option() {
+            self.lock_lp_shares(pair_id, &caller, &shares, lock_days);
+        }
+        shares
+    }
+
+    /// End an expired lock so its shares become removable and stop earning the boost
+    /// Anyone can call this for any LP; removing liquidity also does it automatically.
+    #[endpoint(releaseExpiredLock)]
+    fn release_expired_lock(&self, pair_id: u64, addr: ManagedAddress) {
+        require!(self.lp_locked_shares(pair_id, &addr).get() > 0u64, "No lock");
+        require!(
+            self.blockchain().get_block_timestamp() >= self.lp_lock_until(pair_id, &addr).get(),
+            "Lock not expired"
+        );
+        self.release_lp_lock(pair_id, &addr);
+    }
+
+    // ========================================================================
+    // SINGLE-SIDED ZAP
+    // ========================================================================
+
+    /// Provide liquidity with only one of the pair's tokens
+    /// Swaps the optimal part of the payment through the same pair, then mints
+    /// LP shares with the balanced amounts. Dust that can't be matched is refunded.
+    /// 
+    /// @param pair_id - The trading pair ID (must already have liquidity)
+    /// @param min_lp_shares - Minimum shares expected (slippage protection)
+    /// @return The number of LP shares minted
+    #[endpoint(zapInSingleToken)]
+    #[payable("*")]
+    fn zap_in_single_token(&self, pair_id: u64, min_lp_shares: BigUint) -> BigUint {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_liquidity_enabled(pair_id).get(), "Liquidity is disabled for this pair");
+        
+        self.compound_lp_token_fees(pair_id);
+        
+        // The optimal split below is solved for x*y=k only
+        require!(
+            self.pair_pool_type(pair_id).get() == PoolType::ConstantProduct,
+            "Zap only supported on constant-product pairs"
+        );
+        
+        let (is_a_to_b, amount) = self.take_pair_payment(pair_id);
+        require!(amount > 0u64, "Amount must be > 0");
+        
+        let reserve_in = if is_a_to_b {
+            self.pair_reserve_a(pair_id).get()
+        } else {
+            self.pair_reserve_b(pair_id).get()
+        };
+        require!(reserve_in > 0u64, "Pool has no liquidity - use mint");
+        
+        let caller = self.blockchain().get_caller();
+        let swap_amount = self.get_zap_swap_amount(pair_id, &caller, &reserve_in, &amount);
+        require!(swap_amount > 0u64 && swap_amount < amount, "Amount too small to zap");
+        
+        let other_amount = self.swap_internal(pair_id, &caller, is_a_to_b, &swap_amount, &None);
+        let remaining = &amount - &swap_amount;
+        
+        if is_a_to_b {
+            self.mint_internal(pair_id, &caller, &remaining, &other_amount, &min_lp_shares)
+        } else {
+            self.mint_internal(pair_id, &caller, &other_amount, &remaining, &min_lp_shares)
+        }
+    }
+
+    // ========================================================================
+    // PENDING LIQUIDITY SYSTEM (V3.1 - Two-step deposits)
+    // ========================================================================
+
+    /// Deposit token A (KLV or KDA) to pending A storage
+    #[endpoint(depositPendingA)]
+    #[payable("*")]
+    fn deposit_pending_a(&self, pair_id: u64) {
+        self.deposit_pending_internal(pair_id, true);
+    }
+
+    /// Deposit KLV to pending A storage
+    /// Kept for compatibility - depositPendingA accepts KLV too.
+    #[endpoint(depositPendingAKlv)]
+    #[payable("KLV")]
+    fn deposit_pending_a_klv(&self, pair_id: u64) {
+        self.deposit_pending_internal(pair_id, true);
+    }
+
+    /// Deposit token B (KLV or KDA) to pending B storage
+    #[endpoint(depositPendingB)]
+    #[payable("*")]
+    fn deposit_pending_b(&self, pair_id: u64) {
+        self.deposit_pending_internal(pair_id, false);
+    }
+
+    /// Deposit KLV to pending B storage
+    /// Kept for compatibility - depositPendingB accepts KLV too.
+    #[endpoint(depositPendingBKlv)]
+    #[payable("KLV")]
+    fn deposit_pending_b_klv(&self, pair_id: u64) {
+        self.deposit_pending_internal(pair_id, false);
+    }
+
+    /// Finalize pending deposits into LP position
+    #[endpoint(finalizeLiquidity)]
+    fn finalize_liquidity(&self, pair_id: u64, min_shares: BigUint) {
+        let _guard = self.non_reentrant();
+        self.require_not_paused();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_liquidity_enabled(pair_id).get(), "Liquidity is disabled for this pair");
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        
+        self.compound_lp_token_fees(pair_id);
+        
+        let caller = self.blockchain().get_caller();
+        let pending_a = self.pending_a(pair_id, &caller).get();
+        let pending_b = self.pending_b(pair_id, &caller).get();
+        
+        require!(pending_a > BigUint::zero(), "No pending token A");
+        require!(pending_b > BigUint::zero(), "No pending token B");
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        // Calculate how much can be matched at current pool ratio
+        let (use_a, use_b, shares) = if reserve_a == BigUint::zero() || reserve_b == BigUint::zero() {
+            // First liquidity - use everything (user sets the ratio)
+            let minimum_liquidity = BigUint::from(self.get_minimum_liquidity());
+            require!(
+                &pending_a * &pending_b >= &minimum_liquidity * &minimum_liquidity,
+                "Initial liquidity too small"
+            );
+            
+            let product = &pending_a * &pending_b;
+            let sqrt_shares = product.sqrt();
+            require!(sqrt_shares > minimum_liquidity, "Initial liquidity too small");
+            self.check_initial_liquidity(pair_id, &pending_a, &pending_b);
+            
+            self.pair_locked_liquidity(pair_id).set(&minimum_liquidity);
+            (pending_a.clone(), pending_b.clone(), sqrt_shares - minimum_liquidity)
+        } else {
+            // Match at current pool ratio (rounded in the pool's favour)
+            let (use_a, use_b) = math::match_deposit(&pending_a, &pending_b, &reserve_a, &reserve_b);
+            
+            require!(use_a > BigUint::zero() && use_b > BigUint::zero(), "Amounts too small to match");
+            
+            let total_shares = self.get_total_shares_internal(pair_id);
+            let shares = math::shares_for_deposit(&use_a, &use_b, &reserve_a, &reserve_b, &total_shares);
+            
+            (use_a, use_b, shares)
+        };
+        
+        // Slippage protection
+        require!(shares >= min_shares, "Slippage: shares below minimum");
+        require!(shares > BigUint::zero(), "Shares must be > 0");
+        
+        // Claim any pending fees first (if already an LP)
+        self.claim_pending_fees_internal(pair_id, &caller);
+        
+        // Update pending (subtract used amounts)
+        let new_pending_a = &pending_a - &use_a;
+        let new_pending_b = &pending_b - &use_b;
+        self.pending_a(pair_id, &caller).set(&new_pending_a);
+        self.pending_b(pair_id, &caller).set(&new_pending_b);
+        self.decrease_pending_totals(pair_id, &use_a, &use_b);
+        
+        self.track_pending_user_remove(pair_id, &caller);
+        
+        let old_k = &reserve_a * &reserve_b;
+        let old_shares = self.get_total_shares_internal(pair_id);
+        
+        // Add to reserves
+        self.pair_reserve_a(pair_id).update(|r| *r += &use_a);
+        self.pair_reserve_b(pair_id).update(|r| *r += &use_b);
+        
+        // Add LP shares to user
+        self.credit_lp_position(pair_id, &caller, &shares);
+        self.record_lp_deposit(pair_id, &caller, &use_a, &use_b);
+        self.verify_k_per_share_not_decreased(pair_id, &old_k, &old_shares);
+        self.mint_event(pair_id, &caller, &use_a, &use_b, &shares);
+    }
+
+    /// Withdraw only pending token A
+    #[endpoint(withdrawPendingA)]
+    fn withdraw_pending_a(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        let caller = self.blockchain().get_caller();
+        let pending_a = self.pending_a(pair_id, &caller).get();
+        
+        require!(pending_a > BigUint::zero(), "No pending token A to withdraw");
+        
+        self.pending_a(pair_id, &caller).set(BigUint::zero());
+        self.decrease_pending_totals(pair_id, &pending_a, &BigUint::zero());
+        self.track_pending_user_remove(pair_id, &caller);
+        
+        self.send_pair_token(&caller, pair_id, true, &pending_a);
+    }
+
+    /// Withdraw only pending token B
+    #[endpoint(withdrawPendingB)]
+    fn withdraw_pending_b(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        let caller = self.blockchain().get_caller();
+        let pending_b = self.pending_b(pair_id, &caller).get();
+        
+        require!(pending_b > BigUint::zero(), "No pending token B to withdraw");
+        
+        self.pending_b(pair_id, &caller).set(BigUint::zero());
+        self.decrease_pending_totals(pair_id, &BigUint::zero(), &pending_b);
+        self.track_pending_user_remove(pair_id, &caller);
+        
+        self.send_pair_token(&caller, pair_id, false, &pending_b);
+    }
+
+    /// Withdraw all pending tokens (both A and B)
+    #[endpoint(withdrawPendingAll)]
+    fn withdraw_pending_all(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        let caller = self.blockchain().get_caller();
+        self.refund_pending_internal(pair_id, &caller);
+    }
+
+    /// Refund a user's stale pending deposits (anyone can call)
+    /// Allowed once the user's last deposit is older than the pending expiry
+    /// period (getPendingExpiry). Tokens always go back to the depositor.
+    #[endpoint(expirePending)]
+    fn expire_pending(&self, pair_id: u64, user: ManagedAddress) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.user_has_pending(pair_id, &user), "No pending deposits");
+        require!(
+            self.blockchain().get_block_timestamp() >= self.get_pending_expires_at(pair_id, &user),
+            "Pending deposit not expired yet"
+        );
+        
+        self.refund_pending_internal(pair_id, &user);
+    }
+
+    // ========================================================================
+    // V4 COMPATIBILITY (Thin wrappers kept so old owner scripts don't break)
+    // ========================================================================
+    // V4's ownerAddLiquidityX + ownerInitializeLiquidity two-step flow maps onto
+    // the pending deposit -> finalize flow. The owner ends up as a regular LP.
+
+    /// @deprecated V4 signature - use depositPendingA (or mint)
+    #[endpoint(ownerAddLiquidityA)]
+    #[payable("*")]
+    fn owner_add_liquidity_a(&self, pair_id: u64) {
+        self.require_admin();
+        self.deprecated_call_event(&ManagedBuffer::from("ownerAddLiquidityA"));
+        self.deposit_pending_a(pair_id);
+    }
+
+    /// @deprecated V4 signature - use depositPendingB (or mint)
+    #[endpoint(ownerAddLiquidityB)]
+    #[payable("*")]
+    fn owner_add_liquidity_b(&self, pair_id: u64) {
+        self.require_admin();
+        self.deprecated_call_event(&ManagedBuffer::from("ownerAddLiquidityB"));
+        self.deposit_pending_b(pair_id);
+    }
+
+    /// @deprecated V4 signature - use depositPendingAKlv (or mint)
+    #[endpoint(ownerAddLiquidityAKlv)]
+    #[payable("KLV")]
+    fn owner_add_liquidity_a_klv(&self, pair_id: u64) {
+        self.require_admin();
+        self.deprecated_call_event(&ManagedBuffer::from("ownerAddLiquidityAKlv"));
+        self.deposit_pending_a_klv(pair_id);
+    }
+
+    /// @deprecated V4 signature - use depositPendingBKlv (or mint)
+    #[endpoint(ownerAddLiquidityBKlv)]
+    #[payable("KLV")]
+    fn owner_add_liquidity_b_klv(&self, pair_id: u64) {
+        self.require_admin();
+        self.deprecated_call_event(&ManagedBuffer::from("ownerAddLiquidityBKlv"));
+        self.deposit_pending_b_klv(pair_id);
+    }
+
+    /// @deprecated V4 signature - use finalizeLiquidity (or mint)
+    /// Finalizes the owner's pending deposits into a regular LP position,
+    /// without slippage protection (V4 had none).
+    #[endpoint(ownerInitializeLiquidity)]
+    fn owner_initialize_liquidity(&self, pair_id: u64) {
+        self.require_admin();
+        self.deprecated_call_event(&ManagedBuffer::from("ownerInitializeLiquidity"));
+        self.finalize_liquidity(pair_id, BigUint::zero());
+    }
+
+    /// Allow or block the legacy single-sided addLiquidity endpoints (owner only)
+    /// Off by default in V5. Even when on they no longer mint shares at a naive
+    /// single-token ratio: deposits go to the pending system, like depositPendingX.
+    #[endpoint(setLegacyAddLiquidityEnabled)]
+    fn set_legacy_add_liquidity_enabled(&self, enabled: bool) {
+        self.require_admin();
+        self.legacy_add_liquidity_enabled().set(enabled);
+    }
+
+    /// @deprecated V3 single-sided deposit - use mint (or depositPendingA)
+    #[endpoint(addLiquidityA)]
+    #[payable("*")]
+    fn add_liquidity_a(&self, pair_id: u64) {
+        self.require_legacy_add_liquidity("addLiquidityA");
+        self.deposit_pending_a(pair_id);
+    }
+
+    /// @deprecated V3 single-sided deposit - use mint (or depositPendingB)
+    #[endpoint(addLiquidityB)]
+    #[payable("*")]
+    fn add_liquidity_b(&self, pair_id: u64) {
+        self.require_legacy_add_liquidity("addLiquidityB");
+        self.deposit_pending_b(pair_id);
+    }
+
+    /// @deprecated V3 single-sided deposit - use mint (or depositPendingAKlv)
+    #[endpoint(addLiquidityAKlv)]
+    #[payable("KLV")]
+    fn add_liquidity_a_klv(&self, pair_id: u64) {
+        self.require_legacy_add_liquidity("addLiquidityAKlv");
+        self.deposit_pending_a_klv(pair_id);
+    }
+
+    /// @deprecated V3 single-sided deposit - use mint (or depositPendingBKlv)
+    #[endpoint(addLiquidityBKlv)]
+    #[payable("KLV")]
+    fn add_liquidity_b_klv(&self, pair_id: u64) {
+        self.require_legacy_add_liquidity("addLiquidityBKlv");
+        self.deposit_pending_b_klv(pair_id);
+    }
+
+    fn require_legacy_add_liquidity(&self, endpoint_name: &str) {
+        require!(
+            self.legacy_add_liquidity_enabled().get(),
+            "Legacy addLiquidity is disabled - use mint()"
+        );
+        self.deprecated_call_event(&ManagedBuffer::from(endpoint_name));
+    }
+
+    /// @deprecated Removed in V5 - owner shares no longer exist
+    #[endpoint(ownerRecalculateShares)]
+    fn owner_recalculate_shares(&self, _pair_id: u64) {
+        sc_panic!("DEPRECATED: ownerRecalculateShares was removed in V5 - use mint()");
+    }
+
+    // ========================================================================
+    // LP MANAGEMENT
+    // ========================================================================
+
+    /// LP removes their liquidity
+    /// Send an LP position NFT along to remove a wrapped position instead: the
+    /// whole position must be removed, its lock must have expired, and the NFT
+    /// is burned (pending fees are paid out too).
+    /// @param min_amount_a - Revert if less token A would be returned (slippage protection)
+    /// @param min_amount_b - Revert if less token B would be returned (slippage protection)
+    #[endpoint(removeLiquidity)]
+    #[payable("*")]
+    fn remove_liquidity(&self, pair_id: u64, shares_to_remove: BigUint, min_amount_a: BigUint, min_amount_b: BigUint) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        let caller = self.blockchain().get_caller();
+        if self.call_value().all_kda_transfers().is_empty() {
+            self.burn_lp_shares_internal(pair_id, &caller, &caller, &shares_to_remove, &min_amount_a, &min_amount_b);
+            return;
+        }
+        
+        let nonce = self.take_lp_position_payment();
+        require!(self.lp_position_pair(nonce).get() == pair_id, "Position is for another pair");
+        let holder = self.lp_position_holder(nonce);
+        require!(
+            shares_to_remove == self.lp_shares(pair_id, &holder).get(),
+            "Must remove the whole position"
+        );
+        
+        self.claim_pending_fees_to(pair_id, &holder, &caller);
+        self.burn_lp_shares_internal(pair_id, &holder, &caller, &shares_to_remove, &min_amount_a, &min_amount_b);
+        self.burn_lp_position(nonce);
+    }
+
+    /// LP removes liquidity from several pairs in one call (max MAX_BATCH_PAIRS)
+    /// Each entry is (pair_id, shares_to_remove, min_amount_a, min_amount_b) and
+    /// goes through the same checks as removeLiquidity; any failing entry reverts
+    /// the whole batch.
+    #[endpoint(removeLiquidityBatch)]
+    fn remove_liquidity_batch(&self, removals: MultiValueEncoded<MultiValue4<u64, BigUint, BigUint, BigUint>>) {
+        let _guard = self.non_reentrant();
+        require!(!removals.is_empty(), "No pairs given");
+        require!(removals.len() <= MAX_BATCH_PAIRS, "Too many pairs");
+        
+        let caller = self.blockchain().get_caller();
+        for removal in removals.into_iter() {
+            let (pair_id, shares_to_remove, min_amount_a, min_amount_b) = removal.into_tuple();
+            require!(self.pair_exists(pair_id), "Pair does not exist");
+            self.burn_lp_shares_internal(pair_id, &caller, &caller, &shares_to_remove, &min_amount_a, &min_amount_b);
+        }
+    }
+
+    /// Withdraw a whole LP position without touching fee accounting (MasterChef-style exit)
+    /// Only share math is used, so it works even if fee_per_share accounting is broken.
+    /// Pending LP and boost fees and any IL compensation are forfeited and stay in the
+    /// contract. Staked shares must be unstaked first; locked shares until the lock expires.
+    /// @return (amount_a, amount_b) sent to the caller
+    #[endpoint(emergencyWithdraw)]
+    fn emergency_withdraw(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        
+        let caller = self.blockchain().get_caller();
+        let shares = self.lp_shares(pair_id, &caller).get();
+        require!(shares > 0u64, "No LP shares");
+        require!(self.farm_staked(pair_id, &caller).get() == 0u64, "LP shares are staked - unstake first");
+        if self.lp_locked_shares(pair_id, &caller).get() > 0u64 {
+            require!(
+                self.blockchain().get_block_timestamp() >= self.lp_lock_until(pair_id, &caller).get(),
+                "LP shares are locked"
+            );
+            self.clear_lp_lock(pair_id, &caller);
+        }
+        
+        let total_shares = self.get_total_shares_internal(pair_id);
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        let (amount_a, amount_b) = math::amounts_for_shares(&shares, &reserve_a, &reserve_b, &total_shares);
+        
+        self.clear_lp_position(pair_id, &caller);
+        self.total_lp_shares(pair_id).update(|s| *s -= &shares);
+        self.pair_reserve_a(pair_id).set(&reserve_a - &amount_a);
+        self.pair_reserve_b(pair_id).set(&reserve_b - &amount_b);
+        self.emergency_withdraw_event(pair_id, &caller, &amount_a, &amount_b, &shares);
+        
+        if amount_a > 0u64 {
+            self.send_pair_token(&caller, pair_id, true, &amount_a);
+        }
+        if amount_b > 0u64 {
+            self.send_pair_token(&caller, pair_id, false, &amount_b);
+        }
+        (amount_a, amount_b).into()
+    }
+
+    /// Remove liquidity by sending the pair's KDA LP token
+    /// The LP tokens are burned and the matching share of reserves is returned
+    /// @param min_amount_a / min_amount_b - Slippage protection, as in removeLiquidity
+    #[endpoint(removeLiquidityLpToken)]
+    #[payable("*")]
+    fn remove_liquidity_lp_token(&self, pair_id: u64, min_amount_a: BigUint, min_amount_b: BigUint) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(!self.pair_lp_token(pair_id).is_empty(), "Pair has no LP token");
+        
+        let (token_id, lp_amount) = self.call_value().single_fungible_kda();
+        let lp_token = self.pair_lp_token(pair_id).get();
+        require!(token_id == lp_token, "Wrong LP token");
+        require!(lp_amount > 0u64, "Amount must be > 0");
+        
+        // Fold the custodian's accrued fees in so burned tokens carry their fee growth
+        self.compound_lp_token_fees(pair_id);
+        
+        let custodian = self.blockchain().get_sc_address();
+        let custodian_shares = self.lp_shares(pair_id, &custodian).get();
+        let supply = self.lp_token_supply(pair_id).get();
+        require!(lp_amount <= supply, "LP amount exceeds supply");
+        let shares = &lp_amount * &custodian_shares / &supply;
+        
+        self.lp_token_supply(pair_id).set(&supply - &lp_amount);
+        self.send().kda_local_burn(&lp_token, 0, &lp_amount);
+        
+        let caller = self.blockchain().get_caller();
+        self.burn_lp_shares_internal(pair_id, &custodian, &caller, &shares, &min_amount_a, &min_amount_b);
+    }
+
+    /// Move liquidity from one pair into another pair of the same two tokens
+    /// (e.g. a lower-fee or different-curve pool) in a single call.
+    /// The shares are burned from `from_pair` with its accrued fees paid out as usual,
+    /// and the withdrawn reserves are minted into `to_pair`. The new position starts
+    /// at `to_pair`'s current fee index, so it neither earns nor loses fees accrued
+    /// before the move. Any amount that doesn't fit `to_pair`'s ratio is refunded.
+    /// @param min_lp_shares - Revert if fewer `to_pair` shares would be minted
+    /// @return The number of LP shares minted in `to_pair`
+    #[endpoint(migrateLiquidity)]
+    fn migrate_liquidity(&self, from_pair: u64, to_pair: u64, shares: BigUint, min_lp_shares: BigUint) -> BigUint {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(from_pair), "Pair does not exist");
+        require!(self.pair_exists(to_pair), "Pair does not exist");
+        require!(from_pair != to_pair, "Same pair");
+        require!(self.pair_liquidity_enabled(to_pair).get(), "Liquidity is disabled for this pair");
+        
+        let from_a = self.pair_token_a(from_pair).get();
+        let from_b = self.pair_token_b(from_pair).get();
+        let to_a = self.pair_token_a(to_pair).get();
+        let to_b = self.pair_token_b(to_pair).get();
+        let same_order = from_a == to_a && from_b == to_b;
+        require!(same_order || (from_a == to_b && from_b == to_a), "Pairs have different tokens");
+        
+        self.compound_lp_token_fees(to_pair);
+        
+        let caller = self.blockchain().get_caller();
+        let (amount_a, amount_b) = self.withdraw_lp_shares_internal(
+            from_pair,
+            &caller,
+            &caller,
+            &shares,
+            &BigUint::zero(),
+            &BigUint::zero(),
+        );
+        require!(amount_a > 0u64 && amount_b > 0u64, "Withdrawal too small");
+        
+        let minted = if same_order {
+            self.mint_internal(to_pair, &caller, &amount_a, &amount_b, &min_lp_shares)
+        } else {
+            self.mint_internal(to_pair, &caller, &amount_b, &amount_a, &min_lp_shares)
+        };
+        
+        self.liquidity_migrated_event(from_pair, to_pair, &caller, &shares, &minted);
+        minted
+    }
+
+    /// LP claims accumulated fees
+    #[endpoint(claimLpFees)]
+    fn claim_lp_fees(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        let caller = self.blockchain().get_caller();
+        require!(self.lp_list(pair_id).contains(&caller), "Not an LP for this pair");
+        
+        self.claim_pending_fees_internal(pair_id, &caller);
+    }
+
+    /// LP claims accumulated fees on several pairs in one call (max MAX_BATCH_PAIRS)
+    /// Pairs the caller has no position in are skipped.
+    #[endpoint(claimAllLpFees)]
+    fn claim_all_lp_fees(&self, pair_ids: MultiValueEncoded<u64>) {
+        let _guard = self.non_reentrant();
+        require!(!pair_ids.is_empty(), "No pairs given");
+        require!(pair_ids.len() <= MAX_BATCH_PAIRS, "Too many pairs");
+        
+        let caller = self.blockchain().get_caller();
+        for pair_id in pair_ids.into_iter() {
+            if self.lp_list(pair_id).contains(&caller) {
+                self.claim_pending_fees_internal(pair_id, &caller);
+            }
+        }
+    }
+
+    // ========================================================================
+    // FEE DISTRIBUTION CRANK (Push pending fees to LPs who never claim)
+    // ========================================================================
+    // Anyone can walk a pair's LP list in bounded batches and pay every LP their
+    // pending fees, keeping a small cut of each payout as the incentive. A cursor
+    // remembers where the last batch stopped and wraps around at the end.
+    // Skipped: the LP token custodian (its fees compound into the pool), wrapped
+    // positions (paid to whoever presents the NFT) and contract accounts.
+
+    /// Pay pending fees to up to max_lps LPs of a pair (PUBLIC)
+    /// Returns the number of LPs paid. The caller receives the incentive cut
+    /// (see getFeeDistributionInfo) in both pair tokens.
+    #[endpoint(distributePendingFees)]
+    fn distribute_pending_fees(&self, pair_id: u64, max_lps: u64) -> u64 {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(max_lps > 0 && max_lps <= MAX_DISTRIBUTION_BATCH, "Invalid batch size");
+        
+        let lp_count = self.lp_list(pair_id).len() as u64;
+        require!(lp_count > 0, "Pair has no LPs");
+        let mut cursor = self.fee_distribution_cursor(pair_id).get();
+        if cursor >= lp_count {
+            cursor = 0;
+        }
+        let end = if cursor + max_lps > lp_count { lp_count } else { cursor + max_lps };
+        
+        let custodian = self.blockchain().get_sc_address();
+        let incentive_bps = self.get_distribution_incentive_bps();
+        let mut incentive_a = BigUint::zero();
+        let mut incentive_b = BigUint::zero();
+        let mut paid = 0u64;
+        for lp in self.get_lp_page(pair_id, cursor, end - cursor).iter() {
+            if lp == custodian
+                || !self.lp_position_nonce(&lp).is_empty()
+                || self.blockchain().is_smart_contract(&lp)
+            {
+                continue;
+            }
+            
+            let (fees_a, fees_b) = self.settle_lp_fees(pair_id, &lp);
+            if fees_a == 0u64 && fees_b == 0u64 {
+                continue;
+            }
+            let cut_a = &fees_a * incentive_bps / BPS_DENOMINATOR;
+            let cut_b = &fees_b * incentive_bps / BPS_DENOMINATOR;
+            let net_a = fees_a - &cut_a;
+            let net_b = fees_b - &cut_b;
+            incentive_a += cut_a;
+            incentive_b += cut_b;
+            
+            self.fee_claim_event(pair_id, &lp, &net_a, &net_b);
+            if net_a > 0u64 {
+                self.send_pair_token(&lp, pair_id, true, &net_a);
+            }
+            if net_b > 0u64 {
+                self.send_pair_token(&lp, pair_id, false, &net_b);
+            }
+            paid += 1;
+        }
+        self.fee_distribution_cursor(pair_id).set(if end >= lp_count { 0 } else { end });
+        
+        let caller = self.blockchain().get_caller();
+        if incentive_a > 0u64 {
+            self.send_pair_token(&caller, pair_id, true, &incentive_a);
+        }
+        if incentive_b > 0u64 {
+            self.send_pair_token(&caller, pair_id, false, &incentive_b);
+        }
+        paid
+    }
+
+    /// Set the caller's cut of fees paid out by distributePendingFees (owner or FEE_MANAGER)
+    /// In bps of the distributed fees, max MAX_DISTRIBUTION_INCENTIVE_BPS.
+    #[endpoint(setDistributionIncentiveBps)]
+    fn set_distribution_incentive_bps(&self, incentive_bps: u64) {
+        self.require_role(ROLE_FEE_MANAGER);
+        require!(incentive_bps <= MAX_DISTRIBUTION_INCENTIVE_BPS, "Incentive above hard limit");
+        self.param_distribution_incentive_bps().set(incentive_bps);
+    }
+
+    // ========================================================================
+    // LIQUIDITY MINING (Stake LP shares, earn the farm reward token)
+    // ========================================================================
+    // Staked shares stay in the LP position (they keep earning swap fees) but are
+    // locked until unstaked. Each farmed pair gets
+    //   reward_per_second * pool_weight / total_weight
+    // spread over its staked shares with the usual accRewardPerShare index.
+
+    /// Set the KDA token paid out as farming rewards (owner only)
+    /// Can only be changed while the reward pool is empty.
+    #[endpoint(setFarmRewardToken)]
+    fn set_farm_reward_token(&self, reward_token: TokenIdentifier) {
+        self.require_admin();
+        require!(self.farm_reward_pool().get() == BigUint::zero(), "Reward pool not empty");
+        self.farm_reward_token().set(&reward_token);
+    }
+
+    /// Set the total farming emission rate, in reward tokens per second (owner only)
+    #[endpoint(setFarmRewardRate)]
+    fn set_farm_reward_rate(&self, reward_per_second: BigUint) {
+        self.require_admin();
+        self.update_all_farm_pools();
+        self.farm_reward_per_second().set(&reward_per_second);
+    }
+
+    /// Set a pair's share of the emissions (owner only)
+    /// Weight 0 stops emissions for the pair; stakers can still unstake and harvest.
+    #[endpoint(setFarmPoolWeight)]
+    fn set_farm_pool_weight(&self, pair_id: u64, weight: u64) {
+        self.require_admin();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        // Settle every pool at the old weights before the split changes
+        self.update_all_farm_pools();
+        
+        let old_weight = self.farm_pool_weight(pair_id).get();
+        self.farm_total_weight().update(|w| *w = *w - old_weight + weight);
+        self.farm_pool_weight(pair_id).set(weight);
+        
+        if weight > 0 {
+            if self.farm_pairs().insert(pair_id) {
+                self.farm_last_update(pair_id).set(self.blockchain().get_block_timestamp());
+            }
+        } else if self.farm_total_staked(pair_id).get() == BigUint::zero() {
+            self.farm_pairs().swap_remove(&pair_id);
+        }
+    }
+
+    /// Top up the farming reward pool with the reward token
+    #[endpoint(fundFarmRewards)]
+    #[payable("*")]
+    fn fund_farm_rewards(&self) {
+        require!(!self.farm_reward_token().is_empty(), "Farm reward token not set");
+        let (token_id, amount) = self.call_value().single_fungible_kda();
+        require!(token_id == self.farm_reward_token().get(), "Wrong reward token");
+        require!(amount > 0u64, "Amount must be > 0");
+        self.add_token_obligation(&token_id, &amount);
+        self.farm_reward_pool().update(|p| *p += &amount);
+    }
+
+    /// Stake LP shares in a pair's farm
+    /// Pending rewards are harvested first.
+    #[endpoint(stake)]
+    fn stake(&self, pair_id: u64, shares: BigUint) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.farm_pool_weight(pair_id).get() > 0, "Pair is not farmed");
+        require!(shares > 0u64, "Shares must be > 0");
+        
+        let caller = self.blockchain().get_caller();
+        let staked = self.farm_staked(pair_id, &caller).get();
+        let free_shares = self.lp_shares(pair_id, &caller).get() - &staked;
+        require!(shares <= free_shares, "Insufficient unstaked LP shares");
+        
+        self.harvest_internal(pair_id, &caller);
+        
+        let new_staked = staked + &shares;
+        self.farm_total_staked(pair_id).update(|t| *t += &shares);
+        self.set_farm_stake(pair_id, &caller, &new_staked);
+    }
+
+    /// Unstake LP shares from a pair's farm
+    /// Pending rewards are harvested first.
+    #[endpoint(unstake)]
+    fn unstake(&self, pair_id: u64, shares: BigUint) {
+        let _guard = self.non_reentrant();
+        let caller = self.blockchain().get_caller();
+        let staked = self.farm_staked(pair_id, &caller).get();
+        require!(shares > 0u64, "Shares must be > 0");
+        require!(shares <= staked, "Insufficient staked shares");
+        
+        self.harvest_internal(pair_id, &caller);
+        
+        let new_staked = staked - &shares;
+        self.farm_total_staked(pair_id).update(|t| *t -= &shares);
+        self.set_farm_stake(pair_id, &caller, &new_staked);
+    }
+
+    /// Claim accrued farming rewards for a pair
+    #[endpoint(harvest)]
+    fn harvest(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.farm_staked(pair_id, &caller).get() > 0u64
+                || self.farm_unpaid_reward(pair_id, &caller).get() > 0u64,
+            "Nothing to harvest"
+        );
+        self.harvest_internal(pair_id, &caller);
+    }
+
+    // ========================================================================
+    // LP AIRDROPS (Snapshot-based retroactive rewards)
+    // ========================================================================
+    // Creating an airdrop records every LP's current shares in the same call, so
+    // the snapshot is exact at that block. LPs then claim their pro-rata part of the
+    // deposited reward until the deadline. Shares held by the LP-token custodian or
+    // wrapped in position NFTs are not snapshotted (their holders aren't known on-chain).
+
+    /// Deposit a reward and snapshot the pair's LP shares (owner or TREASURY_MANAGER)
+    /// @param claim_period - Seconds LPs have to claim before the remainder can be swept
+    /// @return The airdrop id
+    #[endpoint(createLpAirdrop)]
+    #[payable("*")]
+    fn create_lp_airdrop(&self, pair_id: u64, claim_period: u64) -> u64 {
+        self.require_role(ROLE_TREASURY_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(claim_period >= MIN_AIRDROP_CLAIM_PERIOD, "Claim period too short");
+        
+        let (reward_token, reward_amount) = self.get_single_payment();
+        require!(reward_amount > 0u64, "Amount must be > 0");
+        
+        let airdrop_id = self.next_airdrop_id().get() + 1;
+        self.next_airdrop_id().set(airdrop_id);
+        
+        let custodian = self.blockchain().get_sc_address();
+        let mut total_shares = BigUint::zero();
+        for addr in self.lp_list(pair_id).iter() {
+            if addr == custodian || !self.lp_position_nonce(&addr).is_empty() {
+                continue;
+            }
+            let shares = self.lp_shares(pair_id, &addr).get();
+            total_shares += &shares;
+            self.airdrop_shares(airdrop_id, &addr).set(&shares);
+        }
+        require!(total_shares > 0u64, "Pair has no LPs");
+        
+        self.airdrop_outstanding(&reward_token).update(|o| *o += &reward_amount);
+        self.lp_airdrop(airdrop_id).set(LpAirdrop {
+            pair_id,
+            reward_token,
+            reward_amount,
+            snapshot_block: self.blockchain().get_block_nonce(),
+            total_shares,
+            claimed_amount: BigUint::zero(),
+            claim_deadline: self.blockchain().get_block_timestamp() + claim_period,
+        });
+        
+        airdrop_id
+    }
+
+    /// Claim the caller's share of an airdrop
+    #[endpoint(claimLpAirdrop)]
+    fn claim_lp_airdrop(&self, airdrop_id: u64) -> BigUint {
+        let _guard = self.non_reentrant();
+        require!(!self.lp_airdrop(airdrop_id).is_empty(), "Airdrop does not exist");
+        let mut airdrop = self.lp_airdrop(airdrop_id).get();
+        require!(self.blockchain().get_block_timestamp() <= airdrop.claim_deadline, "Airdrop expired");
+        
+        let caller = self.blockchain().get_caller();
+        let amount = self.get_airdrop_claimable(airdrop_id, &caller);
+        require!(amount > 0u64, "Nothing to claim");
+        
+        self.airdrop_shares(airdrop_id, &caller).clear();
+        airdrop.claimed_amount += &amount;
+        self.lp_airdrop(airdrop_id).set(&airdrop);
+        self.airdrop_outstanding(&airdrop.reward_token).update(|o| *o -= &amount);
+        
+        self.send_wrapped(&caller, &airdrop.reward_token, &amount);
+        self.lp_airdrop_claimed_event(airdrop_id, &caller, &amount);
+        amount
+    }
+
+    /// Sweep an expired airdrop's unclaimed remainder to the treasury (owner or TREASURY_MANAGER)
+    #[endpoint(closeLpAirdrop)]
+    fn close_lp_airdrop(&self, airdrop_id: u64) {
+        let _guard = self.non_reentrant();
+        self.require_role(ROLE_TREASURY_MANAGER);
+        require!(!self.lp_airdrop(airdrop_id).is_empty(), "Airdrop does not exist");
+        let airdrop = self.lp_airdrop(airdrop_id).get();
+        require!(self.blockchain().get_block_timestamp() > airdrop.claim_deadline, "Claim period not over");
+        
+        let remainder = &airdrop.reward_amount - &airdrop.claimed_amount;
+        self.lp_airdrop(airdrop_id).clear();
+        self.airdrop_outstanding(&airdrop.reward_token).update(|o| *o -= &remainder);
+        if remainder > 0u64 {
+            self.send_wrapped(&self.get_treasury_or_admin(), &airdrop.reward_token, &remainder);
+        }
+    }
+
+    // ========================================================================
+    // LP POSITION NFTS (Locked positions as transferable NFTs)
+    // ========================================================================
+    // Wrapping moves a caller's locked shares, lock and boost to an account
+    // derived from the new NFT's nonce. Whoever holds the NFT collects the
+    // position's fees and, once the lock expires, removes it (removeLiquidity)
+    // or unwraps it back into plain LP shares. The lock keeps applying to the
+    // shares however often the NFT changes hands.
+
+    /// Wrap the caller's whole active lock on a pair into an LP position NFT
+    /// Pending fees are paid out first. Staked shares can't be wrapped.
+    /// @return The NFT nonce
+    #[endpoint(wrapLockedPosition)]
+    fn wrap_locked_position(&self, pair_id: u64) -> u64 {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(!self.lp_position_nft_token().is_empty(), "LP position NFTs not enabled");
+        
+        let caller = self.blockchain().get_caller();
+        let locked = self.lp_locked_shares(pair_id, &caller).get();
+        let lock_until = self.lp_lock_until(pair_id, &caller).get();
+        let boost_bps = self.lp_lock_boost_bps(pair_id, &caller).get();
+        require!(
+            locked > 0u64 && self.blockchain().get_block_timestamp() < lock_until,
+            "No active lock"
+        );
+        require!(
+            self.lp_shares(pair_id, &caller).get() - &locked >= self.farm_staked(pair_id, &caller).get(),
+            "LP shares are staked - unstake first"
+        );
+        
+        self.claim_pending_fees_internal(pair_id, &caller);
+        
+        let token = self.lp_position_nft_token().get();
+        let attributes = LpPositionAttributes {
+            pair_id,
+            shares: locked.clone(),
+            lock_until,
+            boost_bps,
+        };
+        let nonce = self.send().kda_nft_create_compact(&token, &BigUint::from(1u64), &attributes);
+        let holder = self.lp_position_holder(nonce);
+        
+        // The lock moves as-is, so the pair's total boost weight doesn't change
+        self.lp_locked_shares(pair_id, &caller).clear();
+        self.lp_lock_until(pair_id, &caller).clear();
+        self.lp_lock_boost_bps(pair_id, &caller).clear();
+        self.lp_boost_entry_a(pair_id, &caller).clear();
+        self.lp_boost_entry_b(pair_id, &caller).clear();
+        
+        self.transfer_lp_shares(pair_id, &caller, &holder, &locked);
+        self.lp_locked_shares(pair_id, &holder).set(&locked);
+        self.lp_lock_until(pair_id, &holder).set(lock_until);
+        self.lp_lock_boost_bps(pair_id, &holder).set(boost_bps);
+        self.lp_boost_entry_a(pair_id, &holder).set(self.boost_fee_per_weight_a(pair_id).get());
+        self.lp_boost_entry_b(pair_id, &holder).set(self.boost_fee_per_weight_b(pair_id).get());
+        
+        self.lp_position_pair(nonce).set(pair_id);
+        self.pair_lp_positions(pair_id).insert(nonce);
+        self.lp_position_nonce(&holder).set(nonce);
+        
+        self.send().direct_kda(&caller, &token, nonce, &BigUint::from(1u64));
+        nonce
+    }
+
+    /// Collect a wrapped position's fees (send the NFT; it is returned with the fees)
+    #[endpoint(claimLpPositionFees)]
+    #[payable("*")]
+    fn claim_lp_position_fees(&self) {
+        let _guard = self.non_reentrant();
+        let nonce = self.take_lp_position_payment();
+        let pair_id = self.lp_position_pair(nonce).get();
+        let caller = self.blockchain().get_caller();
+        
+        self.claim_pending_fees_to(pair_id, &self.lp_position_holder(nonce), &caller);
+        
+        let token = self.lp_position_nft_token().get();
+        self.send().direct_kda(&caller, &token, nonce, &BigUint::from(1u64));
+    }
+
+    /// Turn an expired position NFT back into plain LP shares of the sender
+    /// Pending fees are paid out and the NFT is burned.
+    #[endpoint(unwrapLpPosition)]
+    #[payable("*")]
+    fn unwrap_lp_position(&self) {
+        let _guard = self.non_reentrant();
+        let nonce = self.take_lp_position_payment();
+        let pair_id = self.lp_position_pair(nonce).get();
+        let holder = self.lp_position_holder(nonce);
+        require!(
+            self.blockchain().get_block_timestamp() >= self.lp_lock_until(pair_id, &holder).get(),
+            "Position still locked"
+        );
+        
+        let caller = self.blockchain().get_caller();
+        self.claim_pending_fees_to(pair_id, &holder, &caller);
+        self.release_lp_lock(pair_id, &holder);
+        // Settle the caller too, or the new shares would earn fees from before they arrived
+        self.claim_pending_fees_internal(pair_id, &caller);
+        
+        let shares = self.lp_shares(pair_id, &holder).get();
+        self.transfer_lp_shares(pair_id, &holder, &caller, &shares);
+        self.burn_lp_position(nonce);
+    }
+
+    /// Check that the payment is a single known LP position NFT and return its nonce
+    fn take_lp_position_payment(&self) -> u64 {
+        require!(!self.lp_position_nft_token().is_empty(), "LP position NFTs not enabled");
+        let payment = self.call_value().single_kda();
+        require!(
+            payment.token_identifier == self.lp_position_nft_token().get(),
+            "Not an LP position NFT"
+        );
+        require!(!self.lp_position_pair(payment.token_nonce).is_empty(), "Unknown LP position");
+        payment.token_nonce
+    }
+}
@@ -0,0 +1,88 @@
+// ============================================================================
+// SHARE MATH - Explicit rounding for liquidity deposits and withdrawals
+// ============================================================================
+// Every division picks a rounding direction instead of silently truncating:
+//   - amounts the pool RECEIVES round up (the depositor pays the last wei)
+//   - amounts and shares the pool GIVES OUT round down
+// so rounding error always stays in the pool and can never be farmed by
+// repeatedly depositing and withdrawing dust.
+// ============================================================================
+
+use klever_sc::api::{ErrorApi, ErrorApiImpl, ManagedTypeApi};
+use klever_sc::types::BigUint;
+
+fn require_nonzero<M: ManagedTypeApi>(denominator: &BigUint<M>) {
+    if *denominator == 0u64 {
+        M::error_api_impl().signal_error(b"Division by zero");
+    }
+}
+
+/// floor(a * b / denominator)
+pub fn mul_div_floor<M: ManagedTypeApi>(a: &BigUint<M>, b: &BigUint<M>, denominator: &BigUint<M>) -> BigUint<M> {
+    require_nonzero(denominator);
+    a * b / denominator
+}
+
+/// ceil(a * b / denominator)
+pub fn mul_div_ceil<M: ManagedTypeApi>(a: &BigUint<M>, b: &BigUint<M>, denominator: &BigUint<M>) -> BigUint<M> {
+    require_nonzero(denominator);
+    let product = a * b;
+    let quotient = &product / denominator;
+    if &quotient * denominator == product {
+        quotient
+    } else {
+        quotient + 1u64
+    }
+}
+
+/// Split a two-sided deposit into the part matching the pool ratio
+/// One side is used in full; the other side's matching amount rounds up, so
+/// the depositor never adds less than the ratio requires.
+/// Returns (used_a, used_b); the caller refunds the rest.
+pub fn match_deposit<M: ManagedTypeApi>(
+    amount_a: &BigUint<M>,
+    amount_b: &BigUint<M>,
+    reserve_a: &BigUint<M>,
+    reserve_b: &BigUint<M>,
+) -> (BigUint<M>, BigUint<M>) {
+    let needed_b = mul_div_ceil(amount_a, reserve_b, reserve_a);
+    if needed_b <= *amount_b {
+        return (amount_a.clone(), needed_b);
+    }
+    // needed_b > amount_b implies amount_b * reserve_a / reserve_b < amount_a,
+    // so the rounded-up A side still fits in amount_a
+    let needed_a = mul_div_ceil(amount_b, reserve_a, reserve_b);
+    (needed_a, amount_b.clone())
+}
+
+/// Shares minted for a deposit into a non-empty pool
+/// Takes the smaller of the two sides' share of the pool, rounded down.
+pub fn shares_for_deposit<M: ManagedTypeApi>(
+    used_a: &BigUint<M>,
+    used_b: &BigUint<M>,
+    reserve_a: &BigUint<M>,
+    reserve_b: &BigUint<M>,
+    total_shares: &BigUint<M>,
+) -> BigUint<M> {
+    let shares_a = mul_div_floor(used_a, total_shares, reserve_a);
+    let shares_b = mul_div_floor(used_b, total_shares, reserve_b);
+    if shares_a <= shares_b {
+        shares_a
+    } else {
+        shares_b
+    }
+}
+
+/// Reserves paid out for burning `shares` out of `total_shares`, rounded down
+/// Returns (amount_a, amount_b).
+pub fn amounts_for_shares<M: ManagedTypeApi>(
+    shares: &BigUint<M>,
+    reserve_a: &BigUint<M>,
+    reserve_b: &BigUint<M>,
+    total_shares: &BigUint<M>,
+) -> (BigUint<M>, BigUint<M>) {
+    (
+        mul_div_floor(shares, reserve_a, total_shares),
+        mul_div_floor(shares, reserve_b, total_shares),
+    )
+}
@@ -0,0 +1,1648 @@
+// ============================================================================
+// PAIR ADMINISTRATION - Creation, management and governance
+// ============================================================================
+// Pair creation and deletion, owner pair management, sync/skim, the
+// checkpoint crank, parameters, admin handover, roles, compliance and
+// state import.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::constants::*;
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait PairAdminModule: crate::views::ViewsModule {
+    // ========================================================================
+    // PUBLIC: PAIR CREATION (V5 - Anyone can create!)
+    // ========================================================================
+
+    /// Create a new trading pair (PUBLIC - anyone can call!)
+    /// Returns the pair_id for the new pair
+    /// 
+    /// NOTE: Duplicate pairs (same token combination) are allowed unless the owner
+    /// enables unique mode (setRequireUniquePairs). Use findPairByTokens() to check
+    /// if a pair already exists before creating.
+    /// 
+    /// Anti-spam: send exactly the KLV creation fee (see getPairCreationRules).
+    /// Creators are also subject to a cooldown and a cap on live pairs.
+    /// The admin is exempt from all three.
+    /// 
+    /// @param token_a - First token identifier (e.g., "DGKO-ABCD")
+    /// @param token_b - Second token identifier (e.g., "KLV" or "USDT-1234")
+    /// @param token_a_is_klv - True if token A is native KLV
+    /// @param token_b_is_klv - True if token B is native KLV
+    /// @param fee_bps - Swap fee in basis points, one of the approved tiers (see getFeeTiers;
+    ///                  default 5, 30 or 100 = 0.05%, 0.3% or 1%)
+    /// @param pool_type - Optional pricing curve (default ConstantProduct)
+    /// @param curve_param - Optional curve parameter: A coefficient for Stable pools
+    ///                      (default DEFAULT_AMPLIFICATION), token A weight in percent for
+    ///                      Weighted pools (default DEFAULT_WEIGHT_A_PERCENT)
+    #[endpoint(createPair)]
+    #[payable("KLV")]
+    fn create_pair(
+        &self,
+        token_a: TokenIdentifier,
+        token_b: TokenIdentifier,
+        token_a_is_klv: bool,
+        token_b_is_klv: bool,
+        fee_bps: u64,
+        pool_type: OptionalValue<PoolType>,
+        curve_param: OptionalValue<u64>,
+    ) -> u64 {
+        let _guard = self.non_reentrant();
+        require!(
+            self.launch_by_token(&token_a).is_empty() && self.launch_by_token(&token_b).is_empty(),
+            "Token has a launch in progress"
+        );
+        self.require_fee_tier(fee_bps);
+        
+        let caller = self.blockchain().get_caller();
+        self.charge_pair_creation(&caller);
+        
+        self.create_pair_internal(
+            &caller,
+            token_a,
+            token_b,
+            token_a_is_klv,
+            token_b_is_klv,
+            fee_bps,
+            pool_type.into_option().unwrap_or(PoolType::ConstantProduct),
+            curve_param.into_option(),
+        )
+    }
+
+    /// Create a constant-product pair of semi-fungible KDA tokens (PUBLIC)
+    /// Same rules and creation fee as createPair. Each side is a KDA token and
+    /// nonce; a nonce of 0 is a fungible token or KLV, which can't carry a nonce.
+    /// 
+    /// Pairs are indexed per token identifier, so two nonces of one collection
+    /// can't be paired together and findPairByTokens ignores nonces.
+    /// Limit orders, DCA and streams accept SFT inputs; referral fees skip SFT sides.
+    #[endpoint(createSftPair)]
+    #[payable("KLV")]
+    fn create_sft_pair(
+        &self,
+        token_a: TokenIdentifier,
+        nonce_a: u64,
+        token_b: TokenIdentifier,
+        nonce_b: u64,
+        fee_bps: u64,
+    ) -> u64 {
+        let _guard = self.non_reentrant();
+        require!(
+            self.launch_by_token(&token_a).is_empty() && self.launch_by_token(&token_b).is_empty(),
+            "Token has a launch in progress"
+        );
+        self.require_fee_tier(fee_bps);
+        
+        let caller = self.blockchain().get_caller();
+        self.charge_pair_creation(&caller);
+        
+        let pair_id = self.create_pair_internal(
+            &caller,
+            token_a,
+            token_b,
+            false,
+            false,
+            fee_bps,
+            PoolType::ConstantProduct,
+            None,
+        );
+        require!(
+            (nonce_a == 0 || !self.pair_token_a_is_klv(pair_id).get())
+                && (nonce_b == 0 || !self.pair_token_b_is_klv(pair_id).get()),
+            "KLV has no token nonce"
+        );
+        self.pair_token_nonce_a(pair_id).set(nonce_a);
+        self.pair_token_nonce_b(pair_id).set(nonce_b);
+        pair_id
+    }
+
+    /// Create a liquidity bootstrapping pool (PUBLIC)
+    /// A weighted pair whose token A weight moves from start_weight_a to end_weight_a
+    /// between start_time and end_time, in WEIGHT_STEP_PERCENT steps - e.g. 95 -> 50
+    /// for a 95/5 -> 50/50 fair launch of token A. The falling weight pushes the
+    /// price of the launched token down until buyers step in. After end_time the
+    /// pair stays a plain weighted pair at end_weight_a.
+    /// Same rules and creation fee as createPair; the creator then seeds the pool
+    /// with mint, ideally before start_time.
+    #[endpoint(createLbpPair)]
+    #[payable("KLV")]
+    #[allow(clippy::too_many_arguments)]
+    fn create_lbp_pair(
+        &self,
+        token_a: TokenIdentifier,
+        token_b: TokenIdentifier,
+        token_a_is_klv: bool,
+        token_b_is_klv: bool,
+        fee_bps: u64,
+        start_weight_a: u64,
+        end_weight_a: u64,
+        start_time: u64,
+        end_time: u64,
+    ) -> u64 {
+        let _guard = self.non_reentrant();
+        require!(
+            self.launch_by_token(&token_a).is_empty() && self.launch_by_token(&token_b).is_empty(),
+            "Token has a launch in progress"
+        );
+        self.require_fee_tier(fee_bps);
+        self.require_valid_weight(end_weight_a);
+        require!(start_weight_a != end_weight_a, "LBP weights must change");
+        require!(start_time >= self.blockchain().get_block_timestamp(), "LBP cannot start in the past");
+        require!(
+            end_time >= start_time + MIN_LBP_DURATION && end_time <= start_time + MAX_LBP_DURATION,
+            "Invalid LBP duration"
+        );
+        
+        let caller = self.blockchain().get_caller();
+        self.charge_pair_creation(&caller);
+        
+        let pair_id = self.create_pair_internal(
+            &caller,
+            token_a,
+            token_b,
+            token_a_is_klv,
+            token_b_is_klv,
+            fee_bps,
+            PoolType::Weighted,
+            Some(start_weight_a),
+        );
+        self.pair_lbp_schedule(pair_id).set(LbpSchedule {
+            start_weight_a,
+            end_weight_a,
+            start_time,
+            end_time,
+        });
+        pair_id
+    }
+
+    /// Validate and register a new pair for `creator` (creation fee already handled)
+    #[allow(clippy::too_many_arguments)]
+    fn create_pair_internal(
+        &self,
+        creator: &ManagedAddress,
+        token_a: TokenIdentifier,
+        token_b: TokenIdentifier,
+        token_a_is_klv: bool,
+        token_b_is_klv: bool,
+        fee_bps: u64,
+        pool_type: PoolType,
+        curve_param: Option<u64>,
+    ) -> u64 {
+        // The is_klv flags are kept for ABI compatibility only: a side is KLV if it is
+        // flagged OR named "KLV", and is then stored under the WKLV id. This makes
+        // the old "broken pair" (flag and identifier disagreeing) impossible.
+        let (token_a, token_a_is_klv) = self.wrap_klv_side(token_a, token_a_is_klv);
+        let (token_b, token_b_is_klv) = self.wrap_klv_side(token_b, token_b_is_klv);
+        
+        require!(token_a != token_b, "Tokens must be different");
+        self.require_fee_in_range(fee_bps, token_a_is_klv || token_b_is_klv);
+        
+        self.require_token_listable(&token_a);
+        self.require_token_listable(&token_b);
+        
+        if self.require_unique_pairs().get() {
+            require!(
+                self.pair_by_tokens(&token_a, &token_b).is_empty(),
+                "Pair already exists for these tokens"
+            );
+        }
+        
+        let pair_id = self.next_pair_id().get();
+        self.next_pair_id().set(pair_id + 1);
+        
+        // Track who created this pair
+        self.pair_creator(pair_id).set(creator);
+        self.creator_pair_count(creator).update(|c| *c += 1);
+        
+        // Initialize pair storage
+        self.pair_token_a(pair_id).set(&token_a);
+        self.pair_token_b(pair_id).set(&token_b);
+        self.pair_token_a_is_klv(pair_id).set(token_a_is_klv);
+        self.pair_token_b_is_klv(pair_id).set(token_b_is_klv);
+        self.pair_reserve_a(pair_id).set(BigUint::zero());
+        self.pair_reserve_b(pair_id).set(BigUint::zero());
+        self.pair_fee_bps(pair_id).set(fee_bps);
+        self.pair_fee_on_input(pair_id).set(true);
+        self.pair_swaps_enabled(pair_id).set(true);
+        self.pair_liquidity_enabled(pair_id).set(true);
+        
+        match pool_type {
+            PoolType::ConstantProduct => {},
+            PoolType::Stable => {
+                let amplification = curve_param.unwrap_or(DEFAULT_AMPLIFICATION);
+                require!(amplification > 0 && amplification <= MAX_AMPLIFICATION, "Invalid amplification");
+                self.pair_pool_type(pair_id).set(PoolType::Stable);
+                self.pair_amplification(pair_id).set(amplification);
+            },
+            PoolType::Weighted => {
+                let weight_a = curve_param.unwrap_or(DEFAULT_WEIGHT_A_PERCENT);
+                self.require_valid_weight(weight_a);
+                self.pair_pool_type(pair_id).set(PoolType::Weighted);
+                self.pair_weight_a(pair_id).set(weight_a);
+            },
+        }
+        
+        // Initialize share tracking
+        self.owner_shares(pair_id).set(BigUint::zero());
+        self.total_lp_shares(pair_id).set(BigUint::zero());
+        
+        // Initialize fee tracking
+        self.owner_unclaimed_fees_a(pair_id).set(BigUint::zero());
+        self.owner_unclaimed_fees_b(pair_id).set(BigUint::zero());
+        self.fee_per_share_a(pair_id).set(BigUint::zero());
+        self.fee_per_share_b(pair_id).set(BigUint::zero());
+        
+        for (is_a, token) in [(true, &token_a), (false, &token_b)] {
+            if let Some(decimals) = self.get_token_decimals(token) {
+                self.pair_decimals(pair_id, is_a).set(decimals);
+            }
+        }
+        
+        // Add to registered pairs
+        self.registered_pair_ids().insert(pair_id);
+        self.index_pair_tokens(pair_id);
+        
+        pair_id
+    }
+
+    /// Delete an empty trading pair
+    /// Can only be called by pair creator or contract owner
+    /// Requires both reserves to be zero AND no pending user deposits
+    #[endpoint(deletePair)]
+    fn delete_pair(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        require!(!self.pair_obligations_unseeded(pair_id).get(), "Seed pair obligations before deleting");
+        
+        let caller = self.blockchain().get_caller();
+        let creator = self.get_pair_creator_or_owner(pair_id);
+        let owner = self.get_admin_address();
+        
+        require!(
+            caller == creator || caller == owner,
+            "Only pair creator or contract owner can delete"
+        );
+        
+        // Check reserves are zero, apart from what backs the locked MINIMUM_LIQUIDITY
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        require!(
+            self.total_lp_shares(pair_id).get() == BigUint::zero()
+                && (self.pair_locked_liquidity(pair_id).get() > 0u64
+                    || (reserve_a == BigUint::zero() && reserve_b == BigUint::zero())),
+            "Cannot delete pair with liquidity"
+        );
+        
+        // Check no LPs in the pair
+        require!(
+            self.lp_list(pair_id).is_empty(),
+            "Cannot delete pair with active LPs"
+        );
+        
+        // Check no owner shares
+        let owner_shares = self.owner_shares(pair_id).get();
+        require!(
+            owner_shares == BigUint::zero(),
+            "Cannot delete pair with owner shares"
+        );
+        
+        // Check no pending deposits from any user
+        require!(
+            self.pair_pending_users(pair_id).is_empty(),
+            "Cannot delete pair with pending user deposits"
+        );
+        require!(
+            self.pair_pending_total_a(pair_id).get() == BigUint::zero()
+                && self.pair_pending_total_b(pair_id).get() == BigUint::zero(),
+            "Cannot delete pair with pending user deposits"
+        );
+        
+        // Open limit orders still hold escrowed tokens
+        require!(
+            self.pair_limit_orders(pair_id).is_empty(),
+            "Cannot delete pair with open limit orders"
+        );
+        require!(
+            self.pair_dca_orders(pair_id).is_empty(),
+            "Cannot delete pair with open DCA orders"
+        );
+        require!(
+            self.pair_stream_orders(pair_id).is_empty(),
+            "Cannot delete pair with open streaming swaps"
+        );
+        require!(
+            self.pair_active_competition(pair_id).is_empty(),
+            "Cannot delete pair with an open competition"
+        );
+        
+        // Check no unclaimed owner fees
+        let unclaimed_a = self.owner_unclaimed_fees_a(pair_id).get();
+        let unclaimed_b = self.owner_unclaimed_fees_b(pair_id).get();
+        require!(
+            unclaimed_a == BigUint::zero() && unclaimed_b == BigUint::zero(),
+            "Claim owner fees before deleting"
+        );
+        
+        // Check no unclaimed treasury fees
+        require!(
+            self.treasury_unclaimed_fees_a(pair_id).get() == BigUint::zero()
+                && self.treasury_unclaimed_fees_b(pair_id).get() == BigUint::zero(),
+            "Claim treasury fees before deleting"
+        );
+        
+        // Leftover IL fund and the reserves behind the locked MINIMUM_LIQUIDITY go to
+        // the treasury (or the admin) - no LPs remain to claim them
+        let sweep_recipient = self.get_treasury_or_admin();
+        let sweep_a = reserve_a + self.il_fund_a(pair_id).take();
+        let sweep_b = reserve_b + self.il_fund_b(pair_id).take();
+        if sweep_a > 0u64 {
+            self.send_pair_token(&sweep_recipient, pair_id, true, &sweep_a);
+        }
+        if sweep_b > 0u64 {
+            self.send_pair_token(&sweep_recipient, pair_id, false, &sweep_b);
+        }
+        
+        // Free a slot under the creator's live-pair cap
+        if !self.pair_creator(pair_id).is_empty() {
+            let pair_creator = self.pair_creator(pair_id).get();
+            self.creator_pair_count(&pair_creator).update(|c| *c = c.saturating_sub(1));
+        }
+        
+        // Drop from the token index before the token storage is cleared
+        self.registered_pair_ids().swap_remove(&pair_id);
+        self.unindex_pair_tokens(pair_id);
+        
+        // Clear all storage for this pair
+        self.pair_creator(pair_id).clear();
+        self.pair_token_a(pair_id).clear();
+        self.pair_token_b(pair_id).clear();
+        self.pair_token_a_is_klv(pair_id).clear();
+        self.pair_token_b_is_klv(pair_id).clear();
+        self.pair_token_nonce_a(pair_id).clear();
+        self.pair_token_nonce_b(pair_id).clear();
+        self.pair_reserve_a(pair_id).clear();
+        self.pair_reserve_b(pair_id).clear();
+        self.pair_fee_bps(pair_id).clear();
+        self.pair_fee_on_input(pair_id).clear();
+        self.pair_fee_percent(pair_id).clear();
+        self.pair_pool_type(pair_id).clear();
+        self.pair_amplification(pair_id).clear();
+        self.pair_weight_a(pair_id).clear();
+        self.pair_lbp_schedule(pair_id).clear();
+        self.pair_rebate_tiers(pair_id).clear();
+        self.fee_distribution_cursor(pair_id).clear();
+        self.limit_order_cursor(pair_id).clear();
+        self.pair_metadata(pair_id).clear();
+        self.pair_decimals_a(pair_id).clear();
+        self.pair_decimals_b(pair_id).clear();
+        self.verified_pair_ids().swap_remove(&pair_id);
+        self.pair_block_direction_lock(pair_id).clear();
+        self.pair_max_price_impact_bps(pair_id).clear();
+        self.pair_max_twap_deviation_bps(pair_id).clear();
+        self.pair_price_cumulative(pair_id).clear();
+        self.pair_price_cumulative_timestamp(pair_id).clear();
+        self.pair_twap_previous(pair_id).clear();
+        self.pair_twap_current(pair_id).clear();
+        self.pair_max_trade_bps(pair_id).clear();
+        self.pair_min_reserve_a(pair_id).clear();
+        self.pair_min_reserve_b(pair_id).clear();
+        self.pair_pending_fee_bps(pair_id).clear();
+        self.pair_fee_activation_block(pair_id).clear();
+        self.pair_fee_history(pair_id).clear();
+        self.pair_creator_fee_changed_at(pair_id).clear();
+        self.pair_swaps_enabled(pair_id).clear();
+        self.pair_liquidity_enabled(pair_id).clear();
+        self.pair_is_archived(pair_id).clear();
+        self.pair_archived_at(pair_id).clear();
+        self.pair_lp_token(pair_id).clear();
+        self.lp_token_fee_bucket_a(pair_id).clear();
+        self.lp_token_fee_bucket_b(pair_id).clear();
+        self.lp_token_supply(pair_id).clear();
+        self.owner_shares(pair_id).clear();
+        self.total_lp_shares(pair_id).clear();
+        self.pair_locked_liquidity(pair_id).clear();
+        self.owner_unclaimed_fees_a(pair_id).clear();
+        self.owner_unclaimed_fees_b(pair_id).clear();
+        self.treasury_unclaimed_fees_a(pair_id).clear();
+        self.treasury_unclaimed_fees_b(pair_id).clear();
+        self.fee_split_threshold_a(pair_id).clear();
+        self.fee_split_threshold_b(pair_id).clear();
+        self.fee_per_share_a(pair_id).clear();
+        self.fee_per_share_b(pair_id).clear();
+        self.total_boost_weight(pair_id).clear();
+        self.boost_fee_per_weight_a(pair_id).clear();
+        self.boost_fee_per_weight_b(pair_id).clear();
+        self.pair_pending_users(pair_id).clear();
+        self.pair_pending_total_a(pair_id).clear();
+        self.pair_pending_total_b(pair_id).clear();
+        self.lp_fee_merkle_root(pair_id).clear();
+        self.lp_fee_merkle_leaf_count(pair_id).clear();
+        self.lp_fee_merkle_timestamp(pair_id).clear();
+        self.pair_volume_a(pair_id).clear();
+        self.pair_volume_b(pair_id).clear();
+        self.pair_total_fees_a(pair_id).clear();
+        self.pair_total_fees_b(pair_id).clear();
+        self.pair_swap_count(pair_id).clear();
+        self.pair_last_swap_timestamp(pair_id).clear();
+        
+        self.farm_total_weight().update(|w| *w -= self.farm_pool_weight(pair_id).get());
+        self.farm_pairs().swap_remove(&pair_id);
+        self.farm_pool_weight(pair_id).clear();
+        self.farm_total_staked(pair_id).clear();
+        self.farm_acc_reward_per_share(pair_id).clear();
+        self.farm_last_update(pair_id).clear();
+        
+        self.compliance_flagged_pairs().swap_remove(&pair_id);
+        for addr in self.compliance_limited_addresses(pair_id).iter() {
+            self.compliance_daily_limit(pair_id, &addr).clear();
+        }
+        self.compliance_limited_addresses(pair_id).clear();
+    }
+
+    /// Archive a pair instead of deleting it (soft-delete)
+    /// Can only be called by pair creator or contract owner.
+    /// 
+    /// The pair is frozen for good - no swaps, no new liquidity - but all metadata,
+    /// final reserves and statistics stay in storage so indexers referencing the
+    /// pair_id keep working. LPs can still withdraw their liquidity and fees.
+    #[endpoint(archivePair)]
+    fn archive_pair(&self, pair_id: u64) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(!self.pair_is_archived(pair_id).get(), "Pair already archived");
+        
+        let caller = self.blockchain().get_caller();
+        let creator = self.get_pair_creator_or_owner(pair_id);
+        require!(
+            caller == creator || caller == self.get_admin_address(),
+            "Only pair creator or contract owner can archive"
+        );
+        
+        self.pair_swaps_enabled(pair_id).set(false);
+        self.pair_liquidity_enabled(pair_id).set(false);
+        self.pair_is_archived(pair_id).set(true);
+        self.pair_archived_at(pair_id).set(self.blockchain().get_block_timestamp());
+        
+        // Free the token combination for a replacement pair
+        self.unindex_pair_tokens(pair_id);
+    }
+
+    // ========================================================================
+    // ADMIN: PAIR MANAGEMENT (Owner functions)
+    // ========================================================================
+
+    /// Enable or disable both swaps and adding liquidity on a pair (owner or PAIR_MANAGER)
+    #[endpoint(setPairActive)]
+    fn set_pair_active(&self, pair_id: u64, is_active: bool) {
+        self.require_unarchived_pair(pair_id);
+        self.pair_swaps_enabled(pair_id).set(is_active);
+        self.pair_liquidity_enabled(pair_id).set(is_active);
+    }
+
+    /// Enable or disable trading on a pair (owner or PAIR_MANAGER)
+    /// Covers every swap path: direct and routed swaps, flash swaps and order execution.
+    /// Freezing trades alone keeps liquidity withdrawals open during an incident.
+    #[endpoint(setPairSwapsEnabled)]
+    fn set_pair_swaps_enabled(&self, pair_id: u64, enabled: bool) {
+        self.require_unarchived_pair(pair_id);
+        self.pair_swaps_enabled(pair_id).set(enabled);
+    }
+
+    /// Enable or disable adding liquidity to a pair (owner or PAIR_MANAGER)
+    /// Removing liquidity is never blocked.
+    #[endpoint(setPairLiquidityEnabled)]
+    fn set_pair_liquidity_enabled(&self, pair_id: u64, enabled: bool) {
+        self.require_unarchived_pair(pair_id);
+        self.pair_liquidity_enabled(pair_id).set(enabled);
+    }
+
+    fn require_unarchived_pair(&self, pair_id: u64) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(!self.pair_is_archived(pair_id).get(), "Pair is archived");
+    }
+
+    /// Change the amplification coefficient of a Stable pair (owner or PAIR_MANAGER)
+    /// Takes effect immediately, so only adjust it in small steps.
+    #[endpoint(setPairAmplification)]
+    fn set_pair_amplification(&self, pair_id: u64, amplification: u64) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_pool_type(pair_id).get() == PoolType::Stable, "Not a stable pair");
+        require!(amplification > 0 && amplification <= MAX_AMPLIFICATION, "Invalid amplification");
+        self.pair_amplification(pair_id).set(amplification);
+    }
+
+    /// Set a pair's display name, logo URI and project link (pair creator, owner or PAIR_MANAGER)
+    /// Empty values are allowed and clear the field. The frontend should treat
+    /// these as untrusted, user-supplied strings.
+    #[endpoint(setPairMetadata)]
+    fn set_pair_metadata(
+        &self,
+        pair_id: u64,
+        name: ManagedBuffer,
+        logo_uri: ManagedBuffer,
+        project_link: ManagedBuffer,
+    ) {
+        self.require_pair_creator_or_role(pair_id, ROLE_PAIR_MANAGER);
+        require!(name.len() <= MAX_PAIR_NAME_LEN, "Name too long");
+        require!(logo_uri.len() <= MAX_PAIR_URI_LEN, "Logo URI too long");
+        require!(project_link.len() <= MAX_PAIR_URI_LEN, "Project link too long");
+        
+        self.pair_metadata(pair_id).set(PairMetadata {
+            name,
+            logo_uri,
+            project_link,
+            updated_at: self.blockchain().get_block_timestamp(),
+        });
+    }
+
+    /// Mark a pair as official or community (owner only)
+    /// A trust signal for the frontend; it has no effect on trading.
+    #[endpoint(setPairVerified)]
+    fn set_pair_verified(&self, pair_id: u64, verified: bool) {
+        self.require_admin();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        if verified {
+            self.verified_pair_ids().insert(pair_id);
+        } else {
+            self.verified_pair_ids().swap_remove(&pair_id);
+        }
+    }
+
+    /// Configure anti-sandwich guards for a pair (pair creator, owner or PAIR_MANAGER)
+    /// block_direction_lock: an address may only swap one direction per block
+    /// max_price_impact_bps: largest price impact a single swap may have (0 = no cap)
+    #[endpoint(setPairSwapGuards)]
+    fn set_pair_swap_guards(&self, pair_id: u64, block_direction_lock: bool, max_price_impact_bps: u64) {
+        self.require_pair_creator_or_role(pair_id, ROLE_PAIR_MANAGER);
+        require!(max_price_impact_bps <= BPS_DENOMINATOR, "Invalid price impact cap");
+        self.pair_block_direction_lock(pair_id).set(block_direction_lock);
+        self.pair_max_price_impact_bps(pair_id).set(max_price_impact_bps);
+    }
+
+    /// Configure the TWAP guard of a pair (pair creator, owner or PAIR_MANAGER)
+    /// Swaps leaving the spot price more than max_deviation_bps away from the TWAP
+    /// revert, unless they move it back towards the TWAP. 0 = off. The guard only
+    /// applies once the pair has traded across a full TWAP window.
+    #[endpoint(setPairTwapGuard)]
+    fn set_pair_twap_guard(&self, pair_id: u64, max_deviation_bps: u64) {
+        self.require_pair_creator_or_role(pair_id, ROLE_PAIR_MANAGER);
+        require!(max_deviation_bps <= BPS_DENOMINATOR, "Invalid TWAP deviation");
+        self.pair_max_twap_deviation_bps(pair_id).set(max_deviation_bps);
+    }
+
+    /// Configure trade size limits for a pair (pair creator, owner or PAIR_MANAGER)
+    /// max_trade_bps: largest share of the output reserve one swap may take (0 = no cap)
+    /// min_reserve_a / min_reserve_b: swaps that would leave a reserve below its floor revert
+    #[endpoint(setPairTradeLimits)]
+    fn set_pair_trade_limits(&self, pair_id: u64, max_trade_bps: u64, min_reserve_a: BigUint, min_reserve_b: BigUint) {
+        self.require_pair_creator_or_role(pair_id, ROLE_PAIR_MANAGER);
+        require!(max_trade_bps <= BPS_DENOMINATOR, "Invalid max trade size");
+        self.pair_max_trade_bps(pair_id).set(max_trade_bps);
+        self.pair_min_reserve_a(pair_id).set(&min_reserve_a);
+        self.pair_min_reserve_b(pair_id).set(&min_reserve_b);
+    }
+
+    /// Switch a pair between fee-on-input and the legacy fee-on-output model
+    /// (pair creator, owner or PAIR_MANAGER)
+    /// New pairs start on fee-on-input; pairs created before it existed keep
+    /// fee-on-output until they opt in here. Fees already accrued are unaffected.
+    #[endpoint(setPairFeeOnInput)]
+    fn set_pair_fee_on_input(&self, pair_id: u64, enabled: bool) {
+        self.require_pair_creator_or_role(pair_id, ROLE_PAIR_MANAGER);
+        self.pair_fee_on_input(pair_id).set(enabled);
+    }
+
+    /// Change the fee of your own pair within the creator fee band (pair creator only)
+    /// Bounded by setCreatorFeeBand and the global/KLV fee range, with a cooldown
+    /// between changes. Scheduled for the next block like an owner fee change.
+    #[endpoint(setPairFeeByCreator)]
+    fn set_pair_fee_by_creator(&self, pair_id: u64, fee_bps: u64) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let caller = self.blockchain().get_caller();
+        require!(
+            !self.pair_creator(pair_id).is_empty() && self.pair_creator(pair_id).get() == caller,
+            "Only the pair creator can change the fee"
+        );
+        
+        let max_bps = self.param_creator_fee_max_bps().get();
+        require!(max_bps > 0, "Creator fee changes are disabled");
+        require!(
+            fee_bps >= self.param_creator_fee_min_bps().get() && fee_bps <= max_bps,
+            "Fee outside creator band"
+        );
+        
+        let now = self.blockchain().get_block_timestamp();
+        let last_change = self.pair_creator_fee_changed_at(pair_id);
+        if !last_change.is_empty() {
+            require!(
+                now >= last_change.get() + self.param_creator_fee_cooldown().get(),
+                "Fee change cooldown active"
+            );
+        }
+        last_change.set(now);
+        
+        self.set_pair_fee_internal(pair_id, fee_bps);
+    }
+
+    /// Update fee (basis points) for a pair
+    /// Timelocked: applied through executeAdminAction(SetPairFee).
+    /// The new fee is scheduled and only applies from the NEXT block, so quotes
+    /// shown to users in the current block can't silently worsen.
+    fn set_pair_fee_internal(&self, pair_id: u64, fee_bps: u64) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let is_klv_pair = self.pair_token_a_is_klv(pair_id).get() || self.pair_token_b_is_klv(pair_id).get();
+        self.require_fee_in_range(fee_bps, is_klv_pair);
+        
+        // A change still waiting for its block is superseded, so the old fee is the live one
+        let old_fee_bps = self.get_effective_fee_bps(pair_id);
+        
+        // Promote any previously scheduled fee that is already live
+        self.settle_pending_fee(pair_id);
+        
+        let activation_block = self.blockchain().get_block_nonce() + 1;
+        self.pair_pending_fee_bps(pair_id).set(fee_bps);
+        self.pair_fee_activation_block(pair_id).set(activation_block);
+        
+        let timestamp = self.blockchain().get_block_timestamp();
+        self.pair_fee_history(pair_id).push(&FeeChange {
+            timestamp,
+            old_fee_bps,
+            new_fee_bps: fee_bps,
+            activation_block,
+        });
+        self.pair_fee_change_event(pair_id, old_fee_bps, fee_bps, activation_block);
+    }
+
+    /// Register the KDA LP token for a pair (owner or PAIR_MANAGER)
+    /// The token must already be issued with mint/burn roles granted to this contract.
+    /// Once set, mint() and finalizeLiquidity() pay out LP tokens instead of
+    /// internal lp_shares, making positions transferable.
+    #[endpoint(setPairLpToken)]
+    fn set_pair_lp_token(&self, pair_id: u64, lp_token: TokenIdentifier) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_lp_token(pair_id).is_empty(), "LP token already set");
+        require!(
+            lp_token != self.pair_token_a(pair_id).get() && lp_token != self.pair_token_b(pair_id).get(),
+            "LP token cannot be a pair token"
+        );
+        
+        self.pair_lp_token(pair_id).set(&lp_token);
+    }
+
+    /// Register the NFT collection for LP position NFTs (owner only)
+    /// The collection must already be issued with create/burn roles granted to
+    /// this contract. Enables wrapLockedPosition.
+    #[endpoint(setLpPositionNftToken)]
+    fn set_lp_position_nft_token(&self, token: TokenIdentifier) {
+        self.require_admin();
+        require!(self.lp_position_nft_token().is_empty(), "LP position NFT token already set");
+        self.lp_position_nft_token().set(&token);
+    }
+
+    /// Update token A for a pair
+    /// Timelocked: applied through executeAdminAction(UpdatePairTokenA).
+    /// WARNING: Only use on pairs with zero liquidity
+    fn update_pair_token_a_internal(&self, pair_id: u64, new_token_a: TokenIdentifier, is_klv: bool) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        require!(reserve_a == BigUint::zero(), "Cannot update token with existing liquidity");
+        self.require_token_listable(&new_token_a);
+        
+        self.unindex_pair_tokens(pair_id);
+        self.pair_token_a(pair_id).set(&new_token_a);
+        self.pair_token_a_is_klv(pair_id).set(is_klv);
+        self.index_pair_tokens(pair_id);
+    }
+
+    /// Update token B for a pair
+    /// Timelocked: applied through executeAdminAction(UpdatePairTokenB).
+    /// WARNING: Only use on pairs with zero liquidity
+    fn update_pair_token_b_internal(&self, pair_id: u64, new_token_b: TokenIdentifier, is_klv: bool) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        require!(reserve_b == BigUint::zero(), "Cannot update token with existing liquidity");
+        self.require_token_listable(&new_token_b);
+        
+        self.unindex_pair_tokens(pair_id);
+        self.pair_token_b(pair_id).set(&new_token_b);
+        self.pair_token_b_is_klv(pair_id).set(is_klv);
+        self.index_pair_tokens(pair_id);
+    }
+
+    /// Reject createPair for token combinations that already have a pair (owner only)
+    #[endpoint(setRequireUniquePairs)]
+    fn set_require_unique_pairs(&self, require_unique: bool) {
+        self.require_admin();
+        self.require_unique_pairs().set(require_unique);
+    }
+
+    /// Turn the token allowlist on or off for createPair (owner or PAIR_MANAGER)
+    /// While enabled, both tokens of a new pair must be allowlisted (KLV always passes).
+    #[endpoint(setTokenAllowlistEnabled)]
+    fn set_token_allowlist_enabled(&self, enabled: bool) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        self.token_allowlist_enabled().set(enabled);
+    }
+
+    /// Add or remove tokens from the allowlist (owner or PAIR_MANAGER)
+    #[endpoint(setTokensAllowed)]
+    fn set_tokens_allowed(&self, allowed: bool, tokens: MultiValueEncoded<TokenIdentifier>) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        for token in tokens.into_iter() {
+            if allowed {
+                self.token_allowlist().insert(token);
+            } else {
+                self.token_allowlist().swap_remove(&token);
+            }
+        }
+    }
+
+    /// Approve or withdraw fee tiers for new pairs (owner only)
+    /// Existing pairs keep their fee. At least one tier must remain.
+    #[endpoint(setFeeTiersAllowed)]
+    fn set_fee_tiers_allowed(&self, allowed: bool, fee_tiers: MultiValueEncoded<u64>) {
+        self.require_admin();
+        for fee_bps in fee_tiers.into_iter() {
+            if allowed {
+                require!(fee_bps > 0 && fee_bps <= ABSOLUTE_MAX_FEE_BPS, "Fee above hard limit");
+                self.fee_tiers().insert(fee_bps);
+            } else {
+                self.fee_tiers().swap_remove(&fee_bps);
+            }
+        }
+        require!(!self.fee_tiers().is_empty(), "At least one fee tier is required");
+    }
+
+    /// Add or remove tokens from the denylist (owner or PAIR_MANAGER)
+    /// Denylisted tokens can never be used in a new pair, allowlist or not.
+    /// Existing pairs are not touched - use setPairActive to halt them.
+    #[endpoint(setTokensDenied)]
+    fn set_tokens_denied(&self, denied: bool, tokens: MultiValueEncoded<TokenIdentifier>) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        for token in tokens.into_iter() {
+            if denied {
+                self.token_denylist().insert(token);
+            } else {
+                self.token_denylist().swap_remove(&token);
+            }
+        }
+    }
+
+    /// Set the KLV fee charged by createPair, forwarded to the treasury (owner or FEE_MANAGER)
+    /// 0 makes pair creation free again.
+    #[endpoint(setPairCreationFee)]
+    fn set_pair_creation_fee(&self, fee: BigUint) {
+        self.require_role(ROLE_FEE_MANAGER);
+        self.pair_creation_fee().set(&fee);
+    }
+
+    /// Set the minimum seconds between two createPair calls from one address (owner or PAIR_MANAGER)
+    #[endpoint(setPairCreationCooldown)]
+    fn set_pair_creation_cooldown(&self, cooldown_seconds: u64) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        self.pair_creation_cooldown().set(cooldown_seconds);
+    }
+
+    /// Cap the number of live pairs a single address may have created (owner or PAIR_MANAGER)
+    /// 0 = unlimited. Deleting a pair frees a slot.
+    #[endpoint(setMaxPairsPerCreator)]
+    fn set_max_pairs_per_creator(&self, max_pairs: u64) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        self.max_pairs_per_creator().set(max_pairs);
+    }
+
+    /// Backfill the token -> pair_id indexes for pairs created before they existed (owner only)
+    /// Pairs already indexed are left untouched, so this is safe to call repeatedly.
+    #[endpoint(indexExistingPairs)]
+    fn index_existing_pairs(&self) {
+        self.require_admin();
+        for pair_id in self.registered_pair_ids().iter() {
+            self.index_pair_tokens(pair_id);
+        }
+    }
+
+    /// Backfill the per-user position index from a pair's LP list (owner only)
+    /// Only covers LP holders - pending deposits made before the index existed
+    /// show up once the user deposits or withdraws again. Safe to call repeatedly.
+    #[endpoint(indexExistingPositions)]
+    fn index_existing_positions(&self, pair_id: u64) {
+        self.require_admin();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        for addr in self.lp_list(pair_id).iter() {
+            self.user_pair_ids(&addr).insert(pair_id);
+        }
+    }
+
+    /// Pause all trading and liquidity additions contract-wide (owner or PAUSER)
+    /// Circuit breaker: stops every swap, mint, zap, flash swap and
+    /// finalizeLiquidity in one transaction. Withdrawals stay open.
+    #[endpoint(pauseAll)]
+    fn pause_all(&self) {
+        self.require_role(ROLE_PAUSER);
+        self.globally_paused().set(true);
+    }
+
+    /// Lift the contract-wide pause (owner only)
+    /// Per-pair setPairActive flags are left untouched.
+    #[endpoint(unpauseAll)]
+    fn unpause_all(&self) {
+        self.require_admin();
+        self.globally_paused().clear();
+    }
+
+    // ========================================================================
+    // SYNC / SKIM (Stray token recovery)
+    // ========================================================================
+    // Every pair shares the contract's balances, so a token's "stray" amount is its
+    // balance minus everything the contract owes in that token across all pairs:
+    // reserves, pending deposits, unclaimed LP / owner / treasury / referral fees,
+    // LP-token fee buckets, IL funds, flash swap repayments, order escrow
+    // (limit / DCA / streaming) and reward pools.
+    // Those obligations are kept in a per-token counter that moves with every
+    // payment taken and every transfer out, so both endpoints cost the same
+    // however many pairs, LPs and orders hold the token. Neither runs while a
+    // flash swap callback is live on any pair.
+    // Pairs that predate the counters are added to them one at a time by
+    // seedPairObligations; until every pair holding a token is seeded, sync and
+    // skim refuse to touch it.
+
+    /// Add a pre-existing pair's obligations to its tokens' counters (owner only)
+    /// Walks the pair's LPs and orders, so it runs per pair. Best called with trading
+    /// paused: until a token's last pair is seeded its counter only overstates what
+    /// is owed, and the excess stays unskimmable.
+    #[endpoint(seedPairObligations)]
+    fn seed_pair_obligations(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        self.require_admin();
+        require!(self.pair_obligations_unseeded(pair_id).get(), "Pair obligations already seeded");
+        
+        for is_token_a in [true, false] {
+            let token = self.pair_token(pair_id, is_token_a);
+            self.add_token_obligation(&token, &self.get_pair_accounted_balance(pair_id, &token));
+            self.token_unseeded_pairs(&token).update(|n| *n -= 1);
+        }
+        self.pair_obligations_unseeded(pair_id).clear();
+    }
+
+    /// Send the stray balance of a pair's tokens to `to` (owner or TREASURY_MANAGER)
+    #[endpoint(skim)]
+    fn skim(&self, pair_id: u64, to: ManagedAddress) {
+        let _guard = self.non_reentrant();
+        self.require_role(ROLE_TREASURY_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.active_flash_swaps().get() == 0, "Flash swap in progress");
+        
+        let (excess_a, excess_b) = self.get_pair_stray_balances(pair_id);
+        require!(excess_a > 0u64 || excess_b > 0u64, "Nothing to skim");
+        
+        // The stray amount is owed to `to` only for as long as it takes to send it
+        if excess_a > 0u64 {
+            self.add_token_obligation(&self.pair_token(pair_id, true), &excess_a);
+            self.send_pair_token(&to, pair_id, true, &excess_a);
+        }
+        if excess_b > 0u64 {
+            self.add_token_obligation(&self.pair_token(pair_id, false), &excess_b);
+            self.send_pair_token(&to, pair_id, false, &excess_b);
+        }
+    }
+
+    /// Add the stray balance of a pair's tokens to its reserves (donated to LPs)
+    /// Reserves only ever grow; a shortfall is left for the owner to investigate.
+    #[endpoint(sync)]
+    fn sync(&self, pair_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(!self.pair_is_archived(pair_id).get(), "Pair is archived");
+        require!(self.active_flash_swaps().get() == 0, "Flash swap in progress");
+        require!(self.get_total_shares_internal(pair_id) > 0u64, "Pair has no liquidity");
+        
+        let (excess_a, excess_b) = self.get_pair_stray_balances(pair_id);
+        require!(excess_a > 0u64 || excess_b > 0u64, "Reserves already in sync");
+        
+        self.add_token_obligation(&self.pair_token(pair_id, true), &excess_a);
+        self.add_token_obligation(&self.pair_token(pair_id, false), &excess_b);
+        self.pair_reserve_a(pair_id).update(|r| *r += &excess_a);
+        self.pair_reserve_b(pair_id).update(|r| *r += &excess_b);
+    }
+
+    // ========================================================================
+    // CHECKPOINT (Permissionless accounting crank)
+    // ========================================================================
+
+    /// Persist derived aggregates used by views (TVL per token, etc.)
+    /// Anyone can call this - heavy iteration stays out of the swap hot path,
+    /// and views just read the cached results.
+    /// 
+    /// The caller earns the configured KLV bounty if the reward pool can cover it.
+    /// Can only run once per checkpoint interval to prevent bounty farming.
+    #[endpoint(checkpoint)]
+    fn checkpoint(&self) {
+        let _guard = self.non_reentrant();
+        let now = self.blockchain().get_block_timestamp();
+        let last = self.last_checkpoint_timestamp().get();
+        let interval = self.checkpoint_min_interval().get();
+        
+        require!(
+            last == 0 || now >= last + interval,
+            "Checkpoint interval not elapsed"
+        );
+        
+        // Reset previous TVL snapshot
+        for token in self.checkpoint_tokens().iter() {
+            self.checkpoint_token_tvl(&token).clear();
+        }
+        self.checkpoint_tokens().clear();
+        
+        // Rebuild TVL per token from current reserves
+        for pair_id in self.registered_pair_ids().iter() {
+            let token_a = self.pair_token(pair_id, true);
+            let token_b = self.pair_token(pair_id, false);
+            
+            let reserve_a = self.pair_reserve_a(pair_id).get();
+            let reserve_b = self.pair_reserve_b(pair_id).get();
+            
+            self.checkpoint_token_tvl(&token_a).update(|t| *t += &reserve_a);
+            self.checkpoint_token_tvl(&token_b).update(|t| *t += &reserve_b);
+            self.checkpoint_tokens().insert(token_a);
+            self.checkpoint_tokens().insert(token_b);
+        }
+        
+        self.last_checkpoint_timestamp().set(now);
+        
+        // Pay the caller's bounty (skipped silently if the pool is empty)
+        let reward = self.checkpoint_reward().get();
+        let pool = self.checkpoint_reward_pool().get();
+        if reward > BigUint::zero() && pool >= reward {
+            self.checkpoint_reward_pool().set(&pool - &reward);
+            let caller = self.blockchain().get_caller();
+            self.send_wrapped(&caller, &self.wklv_token_id(), &reward);
+        }
+    }
+
+    /// Snapshot the merkle root of a pair's LP pending fees, for off-chain audits (PUBLIC)
+    /// Walks the pair's LP list (bounded by the LP cap), so it runs per pair and
+    /// apart from the TVL checkpoint. No bounty.
+    #[endpoint(checkpointPairFees)]
+    fn checkpoint_pair_fees(&self, pair_id: u64) {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        let (root, leaf_count) = self.compute_lp_fee_merkle_root(pair_id);
+        self.lp_fee_merkle_root(pair_id).set(&root);
+        self.lp_fee_merkle_leaf_count(pair_id).set(leaf_count);
+        self.lp_fee_merkle_timestamp(pair_id).set(self.blockchain().get_block_timestamp());
+    }
+
+    /// Configure checkpoint interval (seconds) and KLV bounty per call (owner only)
+    /// A non-zero bounty needs an interval of at least MIN_PAID_CHECKPOINT_INTERVAL.
+    #[endpoint(setCheckpointConfig)]
+    fn set_checkpoint_config(&self, min_interval: u64, reward: BigUint) {
+        self.require_admin();
+        require!(
+            reward == 0u64 || min_interval >= MIN_PAID_CHECKPOINT_INTERVAL,
+            "Checkpoint interval too short for a bounty"
+        );
+        self.checkpoint_min_interval().set(min_interval);
+        self.checkpoint_reward().set(&reward);
+    }
+
+    /// Fund the checkpoint bounty pool with KLV (anyone can top it up)
+    #[endpoint(fundCheckpointRewardPool)]
+    #[payable("KLV")]
+    fn fund_checkpoint_reward_pool(&self) {
+        let amount = self.call_value().klv_value().clone_value();
+        require!(amount > 0u64, "Amount must be > 0");
+        
+        self.add_token_obligation(&self.wklv_token_id(), &amount);
+        self.checkpoint_reward_pool().update(|p| *p += &amount);
+    }
+
+    /// Withdraw KLV from the checkpoint bounty pool (owner only)
+    #[endpoint(withdrawCheckpointRewardPool)]
+    fn withdraw_checkpoint_reward_pool(&self, amount: BigUint) {
+        let _guard = self.non_reentrant();
+        self.require_admin();
+        let pool = self.checkpoint_reward_pool().get();
+        require!(amount > 0u64 && amount <= pool, "Invalid amount");
+        
+        self.checkpoint_reward_pool().set(&pool - &amount);
+        let owner = self.get_admin_address();
+        self.send_wrapped(&owner, &self.wklv_token_id(), &amount);
+    }
+
+    // ========================================================================
+    // PARAMETERS (Owner-tunable within hard-coded safety bounds)
+    // ========================================================================
+
+    /// Set the liquidity locked on a pair's first deposit (owner only)
+    /// Bounded by MIN_MINIMUM_LIQUIDITY..=MAX_MINIMUM_LIQUIDITY
+    #[endpoint(setMinimumLiquidity)]
+    fn set_minimum_liquidity(&self, minimum_liquidity: u64) {
+        self.require_admin();
+        require!(
+            minimum_liquidity >= MIN_MINIMUM_LIQUIDITY && minimum_liquidity <= MAX_MINIMUM_LIQUIDITY,
+            "Minimum liquidity out of bounds"
+        );
+        self.param_minimum_liquidity().set(minimum_liquidity);
+    }
+
+    /// Set the most LP accounts a pair may have (owner only)
+    /// Bounded by MIN_LP_CAP..=MAX_LP_CAP. Lowering it below a pair's current count
+    /// keeps its LPs but admits no new ones until the count drops below the cap.
+    #[endpoint(setMaxLpsPerPair)]
+    fn set_max_lps_per_pair(&self, max_lps: usize) {
+        self.require_admin();
+        require!(max_lps >= MIN_LP_CAP && max_lps <= MAX_LP_CAP, "LP cap out of bounds");
+        self.param_max_lps_per_pair().set(max_lps);
+    }
+
+    /// Set the minimum KLV-equivalent value of a pair's first deposit (owner only)
+    /// 0 disables the check. Tokens without a KLV price are valued like the other side.
+    #[endpoint(setMinInitialLiquidity)]
+    fn set_min_initial_liquidity(&self, min_value_klv: BigUint) {
+        self.require_admin();
+        self.param_min_initial_liquidity_klv().set(&min_value_klv);
+    }
+
+    /// Register a token's decimals (owner only)
+    /// Copied onto every live pair of the token; used by getPriceNormalized.
+    #[endpoint(setTokenDecimals)]
+    fn set_token_decimals(&self, token: TokenIdentifier, decimals: u32) {
+        self.require_admin();
+        require!(token != self.wklv_token_id(), "KLV decimals are fixed");
+        require!(decimals <= MAX_TOKEN_DECIMALS, "Decimals above hard limit");
+        self.token_decimals(&token).set(decimals);
+        
+        for pair_id in self.pairs_by_token(&token).iter() {
+            if self.pair_token_a(pair_id).get() == token {
+                self.pair_decimals_a(pair_id).set(decimals);
+            }
+            if self.pair_token_b(pair_id).get() == token {
+                self.pair_decimals_b(pair_id).set(decimals);
+            }
+        }
+    }
+
+    /// Set a token's reference KLV price, scaled by PRECISION (owner only)
+    /// First deposits into pairs of referenced tokens are checked against the reference band.
+    /// @param price - KLV per token in base units, * PRECISION; 0 clears the reference
+    #[endpoint(setTokenReferencePrice)]
+    fn set_token_reference_price(&self, token: TokenIdentifier, price: BigUint) {
+        self.require_admin();
+        require!(token != self.wklv_token_id(), "KLV is its own reference");
+        // A band check at graduation could leave the sale stuck below its target
+        require!(self.launch_by_token(&token).is_empty(), "Token has a running launch");
+        if price == 0u64 {
+            self.token_reference_price(&token).clear();
+        } else {
+            self.token_reference_price(&token).set(&price);
+        }
+    }
+
+    /// Set the band pair creators may move their own pair's fee within (owner only)
+    /// max_bps = 0 turns creator fee changes off. The global fee range still applies.
+    /// @param cooldown_seconds - Minimum time between two changes of one pair
+    #[endpoint(setCreatorFeeBand)]
+    fn set_creator_fee_band(&self, min_bps: u64, max_bps: u64, cooldown_seconds: u64) {
+        self.require_admin();
+        require!(min_bps <= max_bps, "Invalid creator fee band");
+        require!(max_bps <= ABSOLUTE_MAX_FEE_BPS, "Fee above hard limit");
+        self.param_creator_fee_min_bps().set(min_bps);
+        self.param_creator_fee_max_bps().set(max_bps);
+        self.param_creator_fee_cooldown().set(cooldown_seconds);
+    }
+
+    /// Set how far a first deposit's implied price may stray from the reference prices (owner only)
+    /// In bps, max MAX_REFERENCE_BAND_BPS; 0 disables the band check.
+    #[endpoint(setReferenceBand)]
+    fn set_reference_band(&self, band_bps: u64) {
+        self.require_admin();
+        require!(band_bps <= MAX_REFERENCE_BAND_BPS, "Reference band above hard limit");
+        self.param_reference_band_bps().set(band_bps);
+    }
+
+    /// Set how long pending deposits may sit before anyone can refund them (owner or PAIR_MANAGER)
+    /// Bounded by MIN_PENDING_EXPIRY_DAYS..=MAX_PENDING_EXPIRY_DAYS
+    #[endpoint(setPendingExpiryDays)]
+    fn set_pending_expiry_days(&self, days: u64) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        require!(
+            days >= MIN_PENDING_EXPIRY_DAYS && days <= MAX_PENDING_EXPIRY_DAYS,
+            "Pending expiry out of bounds"
+        );
+        self.param_pending_expiry_days().set(days);
+    }
+
+    /// Set the keeper incentive on DCA and streaming swap executions, in bps of each chunk (owner or FEE_MANAGER)
+    /// Max MAX_DCA_KEEPER_FEE_BPS.
+    #[endpoint(setDcaKeeperFeeBps)]
+    fn set_dca_keeper_fee_bps(&self, keeper_fee_bps: u64) {
+        self.require_role(ROLE_FEE_MANAGER);
+        require!(keeper_fee_bps <= MAX_DCA_KEEPER_FEE_BPS, "DCA keeper fee above hard limit");
+        self.param_dca_keeper_fee_bps().set(keeper_fee_bps);
+    }
+
+    /// Set the allowed swap fee range for createPair/setPairFee (owner or FEE_MANAGER)
+    /// Existing pairs keep their fee; it only constrains new values.
+    #[endpoint(setFeeBounds)]
+    fn set_fee_bounds(&self, min_fee_bps: u64, max_fee_bps: u64) {
+        self.require_role(ROLE_FEE_MANAGER);
+        require!(min_fee_bps >= 1, "Min fee must be at least 1 bps");
+        require!(min_fee_bps <= max_fee_bps, "Min fee above max fee");
+        require!(max_fee_bps <= ABSOLUTE_MAX_FEE_BPS, "Max fee above hard limit");
+        
+        self.param_min_fee_bps().set(min_fee_bps);
+        self.param_max_fee_bps().set(max_fee_bps);
+    }
+
+    /// Set a stricter max fee for pairs that include KLV (owner or FEE_MANAGER)
+    /// KLV pairs are the main user entry points; KDA/KDA pairs keep the global max.
+    /// Pass 0 to remove the KLV-specific cap. Existing pairs keep their fee.
+    #[endpoint(setKlvPairMaxFee)]
+    fn set_klv_pair_max_fee(&self, max_fee_bps: u64) {
+        self.require_role(ROLE_FEE_MANAGER);
+        if max_fee_bps == 0 {
+            self.param_klv_max_fee_bps().clear();
+            return;
+        }
+        require!(max_fee_bps >= self.get_min_fee_bps(), "KLV max fee below min fee");
+        require!(max_fee_bps <= ABSOLUTE_MAX_FEE_BPS, "Max fee above hard limit");
+        
+        self.param_klv_max_fee_bps().set(max_fee_bps);
+    }
+
+    /// Set the platform cut of swap fees, in bps of the fee (owner or FEE_MANAGER)
+    /// 1000 bps = 10% of the fee (0.1% of a swap on a 1% pair). Max MAX_PROTOCOL_FEE_BPS.
+    #[endpoint(setProtocolFeeBps)]
+    fn set_protocol_fee_bps(&self, protocol_fee_bps: u64) {
+        self.require_role(ROLE_FEE_MANAGER);
+        require!(protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS, "Protocol fee above hard limit");
+        self.param_protocol_fee_bps().set(protocol_fee_bps);
+    }
+
+    /// Set the referrer cut of swap fees, in bps of the fee (owner or FEE_MANAGER)
+    /// 0 disables referral rewards. Max MAX_REFERRAL_FEE_BPS.
+    #[endpoint(setReferralFeeBps)]
+    fn set_referral_fee_bps(&self, referral_fee_bps: u64) {
+        self.require_role(ROLE_FEE_MANAGER);
+        require!(referral_fee_bps <= MAX_REFERRAL_FEE_BPS, "Referral fee above hard limit");
+        self.param_referral_fee_bps().set(referral_fee_bps);
+    }
+
+    /// Route the protocol fee cut to a treasury (e.g. DAO wallet) instead of the owner
+    /// Timelocked: applied through executeAdminAction(SetTreasuryAddress).
+    /// Applies to fees from future swaps; fees already accrued stay where they are.
+    fn set_treasury_address_internal(&self, treasury: &ManagedAddress) {
+        require!(!treasury.is_zero(), "Invalid treasury address");
+        self.treasury_address().set(treasury);
+    }
+
+    /// Forward the protocol fee cut to weighted recipients (team, treasury, burn...)
+    /// Timelocked: applied through executeAdminAction(SetFeeSplit). An empty list
+    /// turns the splitter off. While on, the cut is pushed out on every swap, or
+    /// once it reaches the pair's threshold (setFeeSplitThreshold).
+    fn set_fee_split_internal(&self, recipients: &ManagedVec<FeeSplitRecipient<Self::Api>>) {
+        if recipients.is_empty() {
+            self.fee_split_recipients().clear();
+            return;
+        }
+        require!(recipients.len() <= MAX_FEE_SPLIT_RECIPIENTS, "Too many fee split recipients");
+        
+        let mut total_weight = 0u64;
+        for recipient in recipients.iter() {
+            require!(!recipient.address.is_zero(), "Invalid fee split recipient");
+            require!(recipient.weight_bps > 0, "Fee split weight must be > 0");
+            total_weight += recipient.weight_bps;
+        }
+        require!(total_weight == BPS_DENOMINATOR, "Fee split weights must add up to 10000");
+        
+        self.fee_split_recipients().set(recipients);
+    }
+
+    /// Set how much protocol fee a pair accrues before the splitter forwards it (owner or TREASURY_MANAGER)
+    /// In each pair token's units; 0 forwards on every swap.
+    #[endpoint(setFeeSplitThreshold)]
+    fn set_fee_split_threshold(&self, pair_id: u64, threshold_a: BigUint, threshold_b: BigUint) {
+        self.require_role(ROLE_TREASURY_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        self.fee_split_threshold_a(pair_id).set(&threshold_a);
+        self.fee_split_threshold_b(pair_id).set(&threshold_b);
+    }
+
+    // ========================================================================
+    // ADMIN HANDOVER & TIMELOCK
+    // ========================================================================
+    // Sensitive changes (pair fees, pair tokens, treasury, the delay itself) are
+    // proposed first and can only be executed once the timelock delay has
+    // passed, giving users time to react. Admin handover is two-step so a
+    // mistyped address can't brick the DEX.
+
+    /// Queue a sensitive admin action
+    /// Requires the role matching the action (admin-only for token and delay changes).
+    /// @return The action id to pass to executeAdminAction / cancelAdminAction
+    #[endpoint(proposeAdminAction)]
+    fn propose_admin_action(&self, action: AdminAction<Self::Api>) -> u64 {
+        self.require_action_permission(&action);
+        
+        let action_id = self.timelock_next_action_id().get() + 1;
+        self.timelock_next_action_id().set(action_id);
+        
+        let eta = self.blockchain().get_block_timestamp() + self.get_timelock_delay();
+        self.timelock_action(action_id).set(&action);
+        self.timelock_action_eta(action_id).set(eta);
+        self.timelock_pending_actions().insert(action_id);
+        
+        action_id
+    }
+
+    /// Apply a queued admin action once its timelock has passed
+    #[endpoint(executeAdminAction)]
+    fn execute_admin_action(&self, action_id: u64) {
+        require!(self.timelock_pending_actions().contains(&action_id), "Unknown admin action");
+        let action = self.timelock_action(action_id).get();
+        self.require_action_permission(&action);
+        require!(
+            self.blockchain().get_block_timestamp() >= self.timelock_action_eta(action_id).get(),
+            "Timelock not expired"
+        );
+        
+        self.clear_admin_action(action_id);
+        
+        match action {
+            AdminAction::SetPairFee(pair_id, fee_bps) => self.set_pair_fee_internal(pair_id, fee_bps),
+            AdminAction::UpdatePairTokenA(pair_id, token, is_klv) => {
+                self.update_pair_token_a_internal(pair_id, token, is_klv)
+            },
+            AdminAction::UpdatePairTokenB(pair_id, token, is_klv) => {
+                self.update_pair_token_b_internal(pair_id, token, is_klv)
+            },
+            AdminAction::SetTreasuryAddress(treasury) => self.set_treasury_address_internal(&treasury),
+            AdminAction::SetFeeSplit(recipients) => self.set_fee_split_internal(&recipients),
+            AdminAction::SetTimelockDelay(delay) => {
+                require!(delay >= MIN_ADMIN_TIMELOCK_DELAY, "Timelock delay below minimum");
+                self.admin_timelock_delay().set(delay);
+            },
+        }
+    }
+
+    /// Drop a queued admin action (admin, or the role that may execute it)
+    #[endpoint(cancelAdminAction)]
+    fn cancel_admin_action(&self, action_id: u64) {
+        require!(self.timelock_pending_actions().contains(&action_id), "Unknown admin action");
+        self.require_action_permission(&self.timelock_action(action_id).get());
+        self.clear_admin_action(action_id);
+    }
+
+    /// Step 1 of the admin handover: nominate the new admin (owner only)
+    #[endpoint(transferAdmin)]
+    fn transfer_admin(&self, new_admin: ManagedAddress) {
+        self.require_admin();
+        require!(!new_admin.is_zero(), "Invalid admin address");
+        require!(new_admin != self.get_admin_address(), "Already the admin");
+        self.pending_admin().set(&new_admin);
+    }
+
+    /// Step 2 of the admin handover: the nominated address takes over
+    #[endpoint(acceptAdmin)]
+    fn accept_admin(&self) {
+        require!(!self.pending_admin().is_empty(), "No admin transfer pending");
+        let caller = self.blockchain().get_caller();
+        require!(caller == self.pending_admin().get(), "Only the pending admin");
+        
+        self.admin_address().set(&caller);
+        self.pending_admin().clear();
+        self.last_admin_activity().set(self.blockchain().get_block_timestamp());
+    }
+
+    /// Withdraw a nomination before it is accepted (owner only)
+    #[endpoint(cancelAdminTransfer)]
+    fn cancel_admin_transfer(&self) {
+        self.require_admin();
+        self.pending_admin().clear();
+    }
+
+    // ========================================================================
+    // DEAD-MAN SWITCH (Admin recovery to a backup address)
+    // ========================================================================
+
+    /// Register a backup admin (e.g. community multisig) and the recovery timings (owner only)
+    /// If the admin performs no admin action for `inactivity_period` seconds, the backup
+    /// may start a recovery and claim admin rights after a further `recovery_delay`.
+    /// Any admin action during that window cancels the recovery.
+    /// 
+    /// @param backup - Address allowed to claim admin rights
+    /// @param inactivity_period - Seconds of admin inactivity before recovery can start
+    /// @param recovery_delay - Timelock (seconds) between starting and claiming recovery
+    #[endpoint(setBackupAdmin)]
+    fn set_backup_admin(&self, backup: ManagedAddress, inactivity_period: u64, recovery_delay: u64) {
+        self.require_admin();
+        require!(backup != self.get_admin_address(), "Backup must differ from admin");
+        require!(inactivity_period >= MIN_ADMIN_INACTIVITY_PERIOD, "Inactivity period too short");
+        require!(recovery_delay >= MIN_ADMIN_RECOVERY_DELAY, "Recovery delay too short");
+        
+        self.backup_admin().set(&backup);
+        self.admin_inactivity_period().set(inactivity_period);
+        self.admin_recovery_delay().set(recovery_delay);
+    }
+
+    /// Remove the backup admin and disable the dead-man switch (owner only)
+    #[endpoint(clearBackupAdmin)]
+    fn clear_backup_admin(&self) {
+        self.require_admin();
+        self.backup_admin().clear();
+        self.admin_inactivity_period().clear();
+        self.admin_recovery_delay().clear();
+    }
+
+    /// Proof-of-life for the dead-man switch (owner only)
+    /// Every admin action already counts - this is for quiet periods.
+    #[endpoint(adminHeartbeat)]
+    fn admin_heartbeat(&self) {
+        self.require_admin();
+    }
+
+    /// Backup admin starts the recovery once the admin has been inactive long enough
+    #[endpoint(initiateAdminRecovery)]
+    fn initiate_admin_recovery(&self) {
+        require!(!self.backup_admin().is_empty(), "No backup admin configured");
+        
+        let caller = self.blockchain().get_caller();
+        require!(caller == self.backup_admin().get(), "Only backup admin");
+        require!(self.admin_recovery_started_at().is_empty(), "Recovery already started");
+        
+        let now = self.blockchain().get_block_timestamp();
+        let last_activity = self.last_admin_activity().get();
+        require!(
+            now >= last_activity + self.admin_inactivity_period().get(),
+            "Admin is still active"
+        );
+        
+        self.admin_recovery_started_at().set(now);
+    }
+
+    /// Backup admin takes over admin rights after the recovery timelock
+    #[endpoint(claimAdminRecovery)]
+    fn claim_admin_recovery(&self) {
+        require!(!self.backup_admin().is_empty(), "No backup admin configured");
+        
+        let caller = self.blockchain().get_caller();
+        require!(caller == self.backup_admin().get(), "Only backup admin");
+        require!(!self.admin_recovery_started_at().is_empty(), "Recovery not started");
+        
+        let now = self.blockchain().get_block_timestamp();
+        let started_at = self.admin_recovery_started_at().get();
+        require!(now >= started_at + self.admin_recovery_delay().get(), "Recovery timelock active");
+        
+        self.admin_address().set(&caller);
+        self.last_admin_activity().set(now);
+        self.backup_admin().clear();
+        self.admin_inactivity_period().clear();
+        self.admin_recovery_delay().clear();
+        self.admin_recovery_started_at().clear();
+    }
+
+    // ========================================================================
+    // ROLES (Delegated operator permissions)
+    // ========================================================================
+    // The admin can hand out narrow roles so day-to-day operations don't need
+    // the admin key. Roles are a bitmask per address (see ROLE_* constants);
+    // the admin implicitly holds every role.
+
+    /// Grant one or more roles to an address (owner only)
+    /// @param roles - Bitmask of ROLE_* values (1 = PAUSER, 2 = FEE_MANAGER, 4 = PAIR_MANAGER, 8 = TREASURY_MANAGER)
+    #[endpoint(grantRole)]
+    fn grant_role(&self, addr: ManagedAddress, roles: u64) {
+        self.require_admin();
+        require!(roles != 0 && roles & !ALL_ROLES == 0, "Unknown role");
+        
+        self.address_roles(&addr).update(|r| *r |= roles);
+        self.role_holders().insert(addr);
+    }
+
+    /// Revoke one or more roles from an address (owner only)
+    #[endpoint(revokeRole)]
+    fn revoke_role(&self, addr: ManagedAddress, roles: u64) {
+        self.require_admin();
+        require!(roles != 0 && roles & !ALL_ROLES == 0, "Unknown role");
+        
+        let remaining = self.address_roles(&addr).get() & !roles;
+        if remaining == 0 {
+            self.address_roles(&addr).clear();
+            self.role_holders().swap_remove(&addr);
+        } else {
+            self.address_roles(&addr).set(remaining);
+        }
+    }
+
+    /// Give up all of the caller's own roles (e.g. a compromised operator key)
+    #[endpoint(renounceRoles)]
+    fn renounce_roles(&self) {
+        let caller = self.blockchain().get_caller();
+        self.address_roles(&caller).clear();
+        self.role_holders().swap_remove(&caller);
+    }
+
+    // ========================================================================
+    // COMPLIANCE (Owner-managed trading restrictions, fully visible via views)
+    // ========================================================================
+    // Restricted addresses cannot swap on any pair. On flagged pairs, an address
+    // can additionally be capped to a daily swap volume measured in token A.
+
+    /// Block or unblock an address from trading (owner only)
+    #[endpoint(setAddressRestricted)]
+    fn set_address_restricted(&self, addr: ManagedAddress, restricted: bool) {
+        self.require_admin();
+        if restricted {
+            self.compliance_restricted().insert(addr);
+        } else {
+            self.compliance_restricted().swap_remove(&addr);
+        }
+    }
+
+    /// Flag or unflag a pair for per-address daily limits (owner only)
+    #[endpoint(setPairComplianceFlag)]
+    fn set_pair_compliance_flag(&self, pair_id: u64, flagged: bool) {
+        self.require_admin();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        if flagged {
+            self.compliance_flagged_pairs().insert(pair_id);
+        } else {
+            self.compliance_flagged_pairs().swap_remove(&pair_id);
+        }
+    }
+
+    /// Set an address's daily swap limit on a flagged pair (owner only)
+    /// @param daily_limit_a - Max daily volume in token A units (0 removes the limit)
+    #[endpoint(setAddressDailyLimit)]
+    fn set_address_daily_limit(&self, pair_id: u64, addr: ManagedAddress, daily_limit_a: BigUint) {
+        self.require_admin();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        if daily_limit_a == BigUint::zero() {
+            self.compliance_daily_limit(pair_id, &addr).clear();
+            self.compliance_limited_addresses(pair_id).swap_remove(&addr);
+        } else {
+            self.compliance_daily_limit(pair_id, &addr).set(&daily_limit_a);
+            self.compliance_limited_addresses(pair_id).insert(addr);
+        }
+    }
+
+    // ========================================================================
+    // STATE IMPORT (Verified migration of a pair to a fresh deployment)
+    // ========================================================================
+    // Export a pair on the old deployment with exportPairState and
+    // exportPairPositions, then on the new one: importPairState, importPairPositions
+    // (in batches), and finishPairImport with the pair's tokens attached. The pair
+    // stays unregistered - invisible to every other endpoint - until finish checks
+    // that the positions add up and the contract holds enough tokens to back it.
+    // Farm stakes, open orders and pairs with a KDA LP token are not migrated.
+
+    /// Start importing an exported pair under a new pair id (owner only)
+    /// @return The id reserved for the imported pair
+    #[endpoint(importPairState)]
+    fn import_pair_state(&self, state: PairState<Self::Api>) -> u64 {
+        let _guard = self.non_reentrant();
+        self.require_admin();
+        require!(state.token_a != state.token_b, "Tokens must be different");
+        
+        let pair_id = self.next_pair_id().get();
+        self.next_pair_id().set(pair_id + 1);
+        
+        self.pair_creator(pair_id).set(&state.creator);
+        self.creator_pair_count(&state.creator).update(|c| *c += 1);
+        
+        self.pair_token_a(pair_id).set(&state.token_a);
+        self.pair_token_b(pair_id).set(&state.token_b);
+        self.pair_token_a_is_klv(pair_id).set(state.token_a_is_klv);
+        self.pair_token_b_is_klv(pair_id).set(state.token_b_is_klv);
+        self.pair_token_nonce_a(pair_id).set(state.token_nonce_a);
+        self.pair_token_nonce_b(pair_id).set(state.token_nonce_b);
+        self.pair_pool_type(pair_id).set(state.pool_type);
+        self.pair_amplification(pair_id).set(state.amplification);
+        self.pair_weight_a(pair_id).set(state.weight_a);
+        self.pair_fee_bps(pair_id).set(state.fee_bps);
+        self.pair_fee_on_input(pair_id).set(state.fee_on_input);
+        self.pair_swaps_enabled(pair_id).set(state.swaps_enabled);
+        self.pair_liquidity_enabled(pair_id).set(state.liquidity_enabled);
+        
+        self.pair_reserve_a(pair_id).set(&state.reserve_a);
+        self.pair_reserve_b(pair_id).set(&state.reserve_b);
+        self.owner_shares(pair_id).set(&state.owner_shares);
+        self.total_lp_shares(pair_id).set(&state.total_lp_shares);
+        self.pair_locked_liquidity(pair_id).set(&state.locked_liquidity);
+        self.fee_per_share_a(pair_id).set(&state.fee_per_share_a);
+        self.fee_per_share_b(pair_id).set(&state.fee_per_share_b);
+        self.boost_fee_per_weight_a(pair_id).set(&state.boost_fee_per_weight_a);
+        self.boost_fee_per_weight_b(pair_id).set(&state.boost_fee_per_weight_b);
+        self.owner_unclaimed_fees_a(pair_id).set(&state.owner_unclaimed_fees_a);
+        self.owner_unclaimed_fees_b(pair_id).set(&state.owner_unclaimed_fees_b);
+        self.treasury_unclaimed_fees_a(pair_id).set(&state.treasury_unclaimed_fees_a);
+        self.treasury_unclaimed_fees_b(pair_id).set(&state.treasury_unclaimed_fees_b);
+        self.pair_pending_total_a(pair_id).set(&state.pending_total_a);
+        self.pair_pending_total_b(pair_id).set(&state.pending_total_b);
+        self.il_fund_a(pair_id).set(&state.il_fund_a);
+        self.il_fund_b(pair_id).set(&state.il_fund_b);
+        self.pair_volume_a(pair_id).set(&state.volume_a);
+        self.pair_volume_b(pair_id).set(&state.volume_b);
+        self.pair_total_fees_a(pair_id).set(&state.total_fees_a);
+        self.pair_total_fees_b(pair_id).set(&state.total_fees_b);
+        self.pair_swap_count(pair_id).set(state.swap_count);
+        
+        self.pair_importing(pair_id).set(true);
+        pair_id
+    }
+
+    /// Import a batch of positions into a pair being imported (owner only)
+    /// Each address may appear only once across all batches.
+    #[endpoint(importPairPositions)]
+    fn import_pair_positions(&self, pair_id: u64, positions: MultiValueEncoded<PositionState<Self::Api>>) {
+        self.require_admin();
+        require!(self.pair_importing(pair_id).get(), "Pair is not being imported");
+        
+        for position in positions.into_iter() {
+            let addr = &position.address;
+            require!(
+                !self.lp_list(pair_id).contains(addr) && !self.pair_pending_users(pair_id).contains(addr),
+                "Position already imported"
+            );
+            
+            if position.shares > 0u64 {
+                self.require_lp_slot(pair_id, addr);
+                self.lp_list(pair_id).insert(addr.clone());
+                self.user_pair_ids(addr).insert(pair_id);
+                self.lp_shares(pair_id, addr).set(&position.shares);
+                self.lp_entry_index_a(pair_id, addr).set(&position.entry_index_a);
+                self.lp_entry_index_b(pair_id, addr).set(&position.entry_index_b);
+                self.lp_deposited_a(pair_id, addr).set(&position.deposited_a);
+                self.lp_deposited_b(pair_id, addr).set(&position.deposited_b);
+                self.lp_deposit_timestamp(pair_id, addr).set(position.deposit_timestamp);
+                self.import_shares_total(pair_id).update(|t| *t += &position.shares);
+                
+                if position.locked_shares > 0u64 {
+                    require!(position.locked_shares <= position.shares, "Locked shares exceed shares");
+                    self.lp_locked_shares(pair_id, addr).set(&position.locked_shares);
+                    self.lp_lock_until(pair_id, addr).set(position.lock_until);
+                    self.lp_lock_boost_bps(pair_id, addr).set(position.lock_boost_bps);
+                    self.lp_boost_entry_a(pair_id, addr).set(&position.boost_entry_a);
+                    self.lp_boost_entry_b(pair_id, addr).set(&position.boost_entry_b);
+                    let weight = self.get_lp_boost_weight(pair_id, addr);
+                    self.total_boost_weight(pair_id).update(|w| *w += &weight);
+                }
+            }
+            
+            if position.pending_a > 0u64 || position.pending_b > 0u64 {
+                self.pending_a(pair_id, addr).set(&position.pending_a);
+                self.pending_b(pair_id, addr).set(&position.pending_b);
+                self.pending_deposited_at(pair_id, addr).set(position.pending_deposited_at);
+                self.track_pending_user_add(pair_id, addr);
+                self.import_pending_total_a(pair_id).update(|t| *t += &position.pending_a);
+                self.import_pending_total_b(pair_id).update(|t| *t += &position.pending_b);
+            }
+        }
+    }
+
+    /// Verify and register an imported pair (owner only)
+    /// Attach the pair's tokens (reserves, pending deposits, unclaimed fees, IL fund)
+    /// to this call; it reverts unless the contract then holds enough of both tokens
+    /// and the imported positions add up to the pair totals.
+    #[endpoint(finishPairImport)]
+    #[payable("*")]
+    fn finish_pair_import(&self, pair_id: u64) {
+        self.require_admin();
+        require!(self.pair_importing(pair_id).get(), "Pair is not being imported");
+        
+        require!(
+            self.import_shares_total(pair_id).get() == self.total_lp_shares(pair_id).get(),
+            "Imported shares don't match total"
+        );
+        require!(
+            self.import_pending_total_a(pair_id).get() == self.pair_pending_total_a(pair_id).get()
+                && self.import_pending_total_b(pair_id).get() == self.pair_pending_total_b(pair_id).get(),
+            "Imported pending deposits don't match totals"
+        );
+        
+        self.pair_importing(pair_id).clear();
+        self.import_shares_total(pair_id).clear();
+        self.import_pending_total_a(pair_id).clear();
+        self.import_pending_total_b(pair_id).clear();
+        
+        self.registered_pair_ids().insert(pair_id);
+        self.index_pair_tokens(pair_id);
+        
+        for is_token_a in [true, false] {
+            let token = self.pair_token(pair_id, is_token_a);
+            self.add_token_obligation(&token, &self.get_pair_accounted_balance(pair_id, &token));
+            require!(
+                self.get_token_balance(&token) >= self.token_obligations(&token).get(),
+                "Imported pair is not fully funded"
+            );
+        }
+        
+        self.pair_imported_event(pair_id, &self.pair_creator(pair_id).get());
+    }
+}
@@ -0,0 +1,126 @@
+// ============================================================================
+// STAKING - DGKO stakes for swap fee discounts
+// ============================================================================
+// Stakers lock the staking token (DGKO) in the contract and pay a discounted
+// swap fee on every pair, by the highest tier their staked balance reaches.
+// The discount is read at swap time from the staked balance of whoever the
+// swap is made for: the order owner, meta-swap signer or router beneficiary.
+// Unstaked tokens stop counting at once and can be withdrawn after a cooldown.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::constants::*;
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait StakingModule: crate::views::ViewsModule {
+    // ========================================================================
+    // STAKING CONFIGURATION
+    // ========================================================================
+
+    /// Set the token stakers lock (owner only)
+    /// Can only change while nothing is staked or unstaking.
+    #[endpoint(setStakingToken)]
+    fn set_staking_token(&self, token: TokenIdentifier) {
+        self.require_admin();
+        require!(token != self.wklv_token_id(), "Staking token must be a KDA");
+        require!(self.staking_outstanding().get() == 0u64, "Tokens are still staked");
+        self.staking_token().set(&token);
+    }
+
+    /// Replace the discount tiers (owner only)
+    /// Pairs of (min_stake, discount_bps), both strictly increasing; the discount is
+    /// in bps of the swap fee, max MAX_STAKING_DISCOUNT_BPS. Empty turns discounts off.
+    #[endpoint(setStakingTiers)]
+    fn set_staking_tiers(&self, tiers: MultiValueEncoded<MultiValue2<BigUint, u64>>) {
+        self.require_admin();
+        require!(tiers.len() <= MAX_STAKING_TIERS, "Too many staking tiers");
+        
+        let mut result = ManagedVec::new();
+        let mut last_min_stake = BigUint::zero();
+        let mut last_discount_bps = 0u64;
+        for tier in tiers.into_iter() {
+            let (min_stake, discount_bps) = tier.into_tuple();
+            require!(min_stake > last_min_stake, "Tier stakes must increase");
+            require!(discount_bps > last_discount_bps, "Tier discounts must increase");
+            require!(discount_bps <= MAX_STAKING_DISCOUNT_BPS, "Discount above hard limit");
+            last_min_stake = min_stake.clone();
+            last_discount_bps = discount_bps;
+            result.push(StakingTier { min_stake, discount_bps });
+        }
+        self.staking_tiers().set(&result);
+    }
+
+    /// Set how long unstaked tokens wait before they can be withdrawn (owner only)
+    #[endpoint(setUnstakeCooldown)]
+    fn set_unstake_cooldown(&self, cooldown_seconds: u64) {
+        self.require_admin();
+        require!(cooldown_seconds <= MAX_UNSTAKE_COOLDOWN, "Cooldown above hard limit");
+        self.param_unstake_cooldown().set(cooldown_seconds);
+    }
+
+    // ========================================================================
+    // STAKE / UNSTAKE
+    // ========================================================================
+
+    /// Stake the sent staking token; the discount applies from the next swap
+    #[endpoint(stakeDgko)]
+    #[payable("*")]
+    fn stake_dgko(&self) {
+        self.require_not_paused();
+        require!(!self.staking_token().is_empty(), "Staking is not configured");
+        let (token, amount) = self.call_value().single_fungible_kda();
+        require!(token == self.staking_token().get(), "Wrong staking token");
+        require!(amount > 0u64, "Amount must be > 0");
+        self.add_token_obligation(&token, &amount);
+        
+        let caller = self.blockchain().get_caller();
+        self.staked_balance(&caller).update(|b| *b += &amount);
+        self.staking_outstanding().update(|o| *o += &amount);
+        
+        self.dgko_staked_event(&caller, &amount, &self.staked_balance(&caller).get());
+    }
+
+    /// Start unstaking part of your stake
+    /// It stops counting towards your tier at once. Unstaking more restarts the
+    /// cooldown for everything still waiting.
+    /// @return Timestamp from which withdrawUnstakedDgko pays out
+    #[endpoint(unstakeDgko)]
+    fn unstake_dgko(&self, amount: BigUint) -> u64 {
+        require!(amount > 0u64, "Amount must be > 0");
+        let caller = self.blockchain().get_caller();
+        let staked = self.staked_balance(&caller).get();
+        require!(amount <= staked, "Not enough staked");
+        
+        let remaining = &staked - &amount;
+        self.staked_balance(&caller).set(&remaining);
+        self.unstaking_amount(&caller).update(|u| *u += &amount);
+        let unlock_at = self.blockchain().get_block_timestamp() + self.get_unstake_cooldown();
+        self.unstaking_unlock_at(&caller).set(unlock_at);
+        
+        self.dgko_unstaked_event(&caller, &amount, unlock_at, &remaining);
+        unlock_at
+    }
+
+    /// Withdraw unstaked tokens once their cooldown has passed
+    #[endpoint(withdrawUnstakedDgko)]
+    fn withdraw_unstaked_dgko(&self) -> BigUint {
+        let _guard = self.non_reentrant();
+        let caller = self.blockchain().get_caller();
+        let amount = self.unstaking_amount(&caller).get();
+        require!(amount > 0u64, "Nothing to withdraw");
+        require!(
+            self.blockchain().get_block_timestamp() >= self.unstaking_unlock_at(&caller).get(),
+            "Unstake cooldown active"
+        );
+        
+        self.unstaking_amount(&caller).clear();
+        self.unstaking_unlock_at(&caller).clear();
+        self.staking_outstanding().update(|o| *o -= &amount);
+        
+        self.send_wrapped(&caller, &self.staking_token().get(), &amount);
+        self.dgko_unstake_withdrawn_event(&caller, &amount);
+        amount
+    }
+}
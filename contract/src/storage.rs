@@ -0,0 +1,1042 @@
+// ============================================================================
+// STORAGE - Every storage mapper of the DEX
+// ============================================================================
+// Storage mappers grouped by feature, plus the pending-user bookkeeping
+// helpers that only touch storage.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait StorageModule {
+    // ========================================================================
+    // STORAGE MAPPERS
+    // ========================================================================
+
+    #[storage_mapper("next_pair_id")]
+    fn next_pair_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("registered_pair_ids")]
+    fn registered_pair_ids(&self) -> UnorderedSetMapper<u64>;
+
+    // V5: Track pair creator
+    #[storage_mapper("pair_creator")]
+    fn pair_creator(&self, pair_id: u64) -> SingleValueMapper<ManagedAddress>;
+
+    // V5: Map token pair to pair_id (for duplicate prevention)
+    // Stored under both (token_a, token_b) and (token_b, token_a) -> canonical pair_id
+    #[storage_mapper("pair_by_tokens")]
+    fn pair_by_tokens(&self, token_a: &TokenIdentifier, token_b: &TokenIdentifier) -> SingleValueMapper<u64>;
+
+    // Every live (non-archived) pair containing a token, duplicates included
+    #[storage_mapper("pairs_by_token")]
+    fn pairs_by_token(&self, token: &TokenIdentifier) -> UnorderedSetMapper<u64>;
+
+    #[storage_mapper("require_unique_pairs")]
+    fn require_unique_pairs(&self) -> SingleValueMapper<bool>;
+
+    // Token listing rules for createPair
+    #[storage_mapper("token_allowlist_enabled")]
+    fn token_allowlist_enabled(&self) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("token_allowlist")]
+    fn token_allowlist(&self) -> UnorderedSetMapper<TokenIdentifier>;
+
+    #[storage_mapper("token_denylist")]
+    fn token_denylist(&self) -> UnorderedSetMapper<TokenIdentifier>;
+
+    // Approved swap fees (bps) for new pairs, seeded with DEFAULT_FEE_TIERS_BPS
+    #[storage_mapper("fee_tiers")]
+    fn fee_tiers(&self) -> UnorderedSetMapper<u64>;
+
+    // Anti-spam for public createPair (0 = disabled)
+    #[storage_mapper("pair_creation_fee")]
+    fn pair_creation_fee(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_creation_cooldown")]
+    fn pair_creation_cooldown(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("max_pairs_per_creator")]
+    fn max_pairs_per_creator(&self) -> SingleValueMapper<u64>;
+
+    // Live pairs per creator (pairs created before this counter existed are not included)
+    #[storage_mapper("creator_pair_count")]
+    fn creator_pair_count(&self, creator: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("creator_last_pair_created")]
+    fn creator_last_pair_created(&self, creator: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    // Contract-wide circuit breaker (pauseAll / unpauseAll)
+    #[storage_mapper("globally_paused")]
+    fn globally_paused(&self) -> SingleValueMapper<bool>;
+
+    // Set while a non_reentrant endpoint is running
+    #[storage_mapper("reentrancy_lock")]
+    fn reentrancy_lock(&self) -> SingleValueMapper<bool>;
+
+    // Everything the contract owes in a token, kept up to date on every payment
+    // taken and every transfer out; sync / skim treat the rest as stray
+    #[storage_mapper("token_obligations")]
+    fn token_obligations(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // Set once the obligations counters have a starting point (at init, or by the
+    // upgrade that introduced them)
+    #[storage_mapper("token_obligations_initialized")]
+    fn token_obligations_initialized(&self) -> SingleValueMapper<bool>;
+
+    // Pairs from before the counters whose share seedPairObligations hasn't added yet
+    #[storage_mapper("pair_obligations_unseeded")]
+    fn pair_obligations_unseeded(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    // Number of such pairs per token; sync / skim wait for it to reach zero
+    #[storage_mapper("token_unseeded_pairs")]
+    fn token_unseeded_pairs(&self, token: &TokenIdentifier) -> SingleValueMapper<u64>;
+
+    // Feature flag for the legacy addLiquidityA/B endpoints (off by default)
+    #[storage_mapper("legacy_add_liquidity_enabled")]
+    fn legacy_add_liquidity_enabled(&self) -> SingleValueMapper<bool>;
+
+    // Users with pending deposits on a pair (for safe deletion)
+    #[storage_mapper("pair_pending_users")]
+    fn pair_pending_users(&self, pair_id: u64) -> UnorderedSetMapper<ManagedAddress>;
+
+    // Running sum of every user's pending_a / pending_b (kept in sync on deposit,
+    // finalize and withdraw so nothing has to iterate users)
+    #[storage_mapper("pair_pending_total_a")]
+    fn pair_pending_total_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_pending_total_b")]
+    fn pair_pending_total_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // V5: HELPER FUNCTIONS FOR PENDING USER TRACKING
+    // ========================================================================
+    
+    /// Check if user has any pending deposits (A or B)
+    fn user_has_pending(&self, pair_id: u64, user: &ManagedAddress) -> bool {
+        let pending_a = self.pending_a(pair_id, user).get();
+        let pending_b = self.pending_b(pair_id, user).get();
+        pending_a > BigUint::zero() || pending_b > BigUint::zero()
+    }
+    
+    /// Register a user in the pair's pending registry after a deposit
+    /// Idempotent, so users with deposits from before the registry get picked up too.
+    fn track_pending_user_add(&self, pair_id: u64, user: &ManagedAddress) {
+        if self.user_has_pending(pair_id, user) {
+            self.pair_pending_users(pair_id).insert(user.clone());
+            self.user_pair_ids(user).insert(pair_id);
+        }
+    }
+    
+    /// Subtract finalized/withdrawn amounts from the pair pending totals
+    /// Saturates at zero: deposits made before totals were tracked aren't counted.
+    fn decrease_pending_totals(&self, pair_id: u64, amount_a: &BigUint, amount_b: &BigUint) {
+        self.pair_pending_total_a(pair_id).update(|t| {
+            if *t > *amount_a { *t -= amount_a; } else { *t = BigUint::zero(); }
+        });
+        self.pair_pending_total_b(pair_id).update(|t| {
+            if *t > *amount_b { *t -= amount_b; } else { *t = BigUint::zero(); }
+        });
+    }
+    
+    /// Drop a user from the pair's pending registry once nothing is pending
+    fn track_pending_user_remove(&self, pair_id: u64, user: &ManagedAddress) {
+        if !self.user_has_pending(pair_id, user) {
+            self.pair_pending_users(pair_id).swap_remove(user);
+            self.pending_deposited_at(pair_id, user).clear();
+            self.refresh_user_pair_index(pair_id, user);
+        }
+    }
+
+    /// Drop a pair from a user's position index once they hold neither shares nor pending deposits
+    fn refresh_user_pair_index(&self, pair_id: u64, user: &ManagedAddress) {
+        if !self.lp_list(pair_id).contains(user) && !self.user_has_pending(pair_id, user) {
+            self.user_pair_ids(user).swap_remove(&pair_id);
+        }
+    }
+
+    #[storage_mapper("pair_token_a")]
+    fn pair_token_a(&self, pair_id: u64) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("pair_token_b")]
+    fn pair_token_b(&self, pair_id: u64) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("pair_token_a_is_klv")]
+    fn pair_token_a_is_klv(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("pair_token_b_is_klv")]
+    fn pair_token_b_is_klv(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    // KDA nonce of a semi-fungible side (empty = 0, a fungible token or KLV)
+    #[storage_mapper("pair_token_nonce_a")]
+    fn pair_token_nonce_a(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_token_nonce_b")]
+    fn pair_token_nonce_b(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_reserve_a")]
+    fn pair_reserve_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_reserve_b")]
+    fn pair_reserve_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // Swap fee in basis points (1-1000 bps by default)
+    // Empty = ConstantProduct (every pair created before pool types existed)
+    #[storage_mapper("pair_pool_type")]
+    fn pair_pool_type(&self, pair_id: u64) -> SingleValueMapper<PoolType>;
+
+    #[storage_mapper("pair_amplification")]
+    fn pair_amplification(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Token A weight in percent for Weighted pairs
+    #[storage_mapper("pair_weight_a")]
+    fn pair_weight_a(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Weight schedule of liquidity bootstrapping pools (overrides pair_weight_a)
+    #[storage_mapper("pair_lbp_schedule")]
+    fn pair_lbp_schedule(&self, pair_id: u64) -> SingleValueMapper<LbpSchedule>;
+
+    #[storage_mapper("pair_fee_bps")]
+    fn pair_fee_bps(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Fee charged on the swap input (Uniswap style); empty = legacy fee-on-output
+    #[storage_mapper("pair_fee_on_input")]
+    fn pair_fee_on_input(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("pair_metadata")]
+    fn pair_metadata(&self, pair_id: u64) -> SingleValueMapper<PairMetadata<Self::Api>>;
+
+    // Owner-curated trust flag separating official pools from community pools
+    #[storage_mapper("verified_pair_ids")]
+    fn verified_pair_ids(&self) -> UnorderedSetMapper<u64>;
+
+    // Token decimals copied from the registry when the pair is created or the
+    // registry entry changes (empty = unknown, see get_pair_decimals)
+    #[storage_mapper("pair_decimals_a")]
+    fn pair_decimals_a(&self, pair_id: u64) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("pair_decimals_b")]
+    fn pair_decimals_b(&self, pair_id: u64) -> SingleValueMapper<u32>;
+
+    // Anti-sandwich guards (off by default)
+    #[storage_mapper("pair_block_direction_lock")]
+    fn pair_block_direction_lock(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("pair_max_price_impact_bps")]
+    fn pair_max_price_impact_bps(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // TWAP guard: largest deviation of the post-swap spot price from the TWAP (off by default)
+    #[storage_mapper("pair_max_twap_deviation_bps")]
+    fn pair_max_twap_deviation_bps(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Price accumulator (Uniswap V2 style): spot price of A in B * seconds, summed up to
+    // pair_price_cumulative_timestamp. Advanced before every swap.
+    #[storage_mapper("pair_price_cumulative")]
+    fn pair_price_cumulative(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_price_cumulative_timestamp")]
+    fn pair_price_cumulative_timestamp(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Accumulator snapshots the TWAP is measured from, rolled once per TWAP_WINDOW_SECONDS:
+    // the current one becomes previous when a swap lands a full window after it
+    #[storage_mapper("pair_twap_previous")]
+    fn pair_twap_previous(&self, pair_id: u64) -> SingleValueMapper<TwapObservation<Self::Api>>;
+
+    #[storage_mapper("pair_twap_current")]
+    fn pair_twap_current(&self, pair_id: u64) -> SingleValueMapper<TwapObservation<Self::Api>>;
+
+    // Trade size limits (off by default)
+    #[storage_mapper("pair_max_trade_bps")]
+    fn pair_max_trade_bps(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_min_reserve_a")]
+    fn pair_min_reserve_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_min_reserve_b")]
+    fn pair_min_reserve_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("last_swap_block")]
+    fn last_swap_block(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    // true = A -> B
+    #[storage_mapper("last_swap_direction")]
+    fn last_swap_direction(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    // LEGACY: whole-percent fee from before the bps migration (cleared on upgrade)
+    #[storage_mapper("pair_fee_percent")]
+    fn pair_fee_percent(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Soft-deleted pair: frozen, but storage kept for historical queries
+    #[storage_mapper("pair_is_archived")]
+    fn pair_is_archived(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("pair_archived_at")]
+    fn pair_archived_at(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Scheduled fee change, effective from pair_fee_activation_block
+    #[storage_mapper("pair_pending_fee_bps")]
+    fn pair_pending_fee_bps(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_fee_activation_block")]
+    fn pair_fee_activation_block(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_fee_history")]
+    fn pair_fee_history(&self, pair_id: u64) -> VecMapper<FeeChange>;
+
+    // Timestamp of the creator's last setPairFeeByCreator call (cooldown start)
+    #[storage_mapper("pair_creator_fee_changed_at")]
+    fn pair_creator_fee_changed_at(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Trading and liquidity switches: trading can be frozen while LPs still exit,
+    // and removing liquidity is never blocked. Archived pairs have both off.
+    #[storage_mapper("pair_swaps_enabled")]
+    fn pair_swaps_enabled(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("pair_liquidity_enabled")]
+    fn pair_liquidity_enabled(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    // Legacy single switch, split into the two above by the upgrade migration
+    #[storage_mapper("pair_is_active")]
+    fn pair_is_active(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("owner_shares")]
+    fn owner_shares(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_shares")]
+    fn lp_shares(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("total_lp_shares")]
+    fn total_lp_shares(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // MINIMUM_LIQUIDITY shares locked on the first mint (pairs seeded before this was tracked have none)
+    #[storage_mapper("pair_locked_liquidity")]
+    fn pair_locked_liquidity(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // KDA LP token for the pair (optional, see setPairLpToken)
+    #[storage_mapper("pair_lp_token")]
+    fn pair_lp_token(&self, pair_id: u64) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("lp_token_supply")]
+    fn lp_token_supply(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // Custodian fees waiting to be compounded (unmatched to pool ratio)
+    #[storage_mapper("lp_token_fee_bucket_a")]
+    fn lp_token_fee_bucket_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_token_fee_bucket_b")]
+    fn lp_token_fee_bucket_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_list")]
+    fn lp_list(&self, pair_id: u64) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[storage_mapper("owner_unclaimed_fees_a")]
+    fn owner_unclaimed_fees_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("owner_unclaimed_fees_b")]
+    fn owner_unclaimed_fees_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("treasury_address")]
+    fn treasury_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("treasury_unclaimed_fees_a")]
+    fn treasury_unclaimed_fees_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("treasury_unclaimed_fees_b")]
+    fn treasury_unclaimed_fees_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // Protocol fee splitter: while set, the protocol cut accrues in the treasury
+    // buckets and is pushed to these recipients once it reaches the pair's threshold
+    #[storage_mapper("fee_split_recipients")]
+    fn fee_split_recipients(&self) -> SingleValueMapper<ManagedVec<FeeSplitRecipient<Self::Api>>>;
+
+    #[storage_mapper("fee_split_threshold_a")]
+    fn fee_split_threshold_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("fee_split_threshold_b")]
+    fn fee_split_threshold_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("fee_per_share_a")]
+    fn fee_per_share_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("fee_per_share_b")]
+    fn fee_per_share_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_entry_index_a")]
+    fn lp_entry_index_a(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_entry_index_b")]
+    fn lp_entry_index_b(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    // LP lockups: extra fee weight (locked * (boost - 1x)) and its fee index
+    #[storage_mapper("total_boost_weight")]
+    fn total_boost_weight(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("boost_fee_per_weight_a")]
+    fn boost_fee_per_weight_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("boost_fee_per_weight_b")]
+    fn boost_fee_per_weight_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_locked_shares")]
+    fn lp_locked_shares(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_lock_until")]
+    fn lp_lock_until(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("lp_lock_boost_bps")]
+    fn lp_lock_boost_bps(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("lp_boost_entry_a")]
+    fn lp_boost_entry_a(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_boost_entry_b")]
+    fn lp_boost_entry_b(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    // Pairs where a user holds LP shares or pending deposits (for getUserPositions)
+    #[storage_mapper("user_pair_ids")]
+    fn user_pair_ids(&self, user: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
+    #[storage_mapper("pending_a")]
+    fn pending_a(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pending_b")]
+    fn pending_b(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    // Time of the user's last pending deposit on the pair (cleared once nothing is pending)
+    #[storage_mapper("pending_deposited_at")]
+    fn pending_deposited_at(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    // Stand-in deposit time for pending deposits made before timestamps were recorded
+    #[storage_mapper("pending_expiry_start")]
+    fn pending_expiry_start(&self) -> SingleValueMapper<u64>;
+
+    // ========================================================================
+    // CHECKPOINT STORAGE
+    // ========================================================================
+
+    #[storage_mapper("last_checkpoint_timestamp")]
+    fn last_checkpoint_timestamp(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("checkpoint_min_interval")]
+    fn checkpoint_min_interval(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("checkpoint_reward")]
+    fn checkpoint_reward(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("checkpoint_reward_pool")]
+    fn checkpoint_reward_pool(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("checkpoint_tokens")]
+    fn checkpoint_tokens(&self) -> UnorderedSetMapper<TokenIdentifier>;
+
+    #[storage_mapper("checkpoint_token_tvl")]
+    fn checkpoint_token_tvl(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_fee_merkle_root")]
+    fn lp_fee_merkle_root(&self, pair_id: u64) -> SingleValueMapper<ManagedByteArray<Self::Api, 32>>;
+
+    #[storage_mapper("lp_fee_merkle_leaf_count")]
+    fn lp_fee_merkle_leaf_count(&self, pair_id: u64) -> SingleValueMapper<usize>;
+
+    #[storage_mapper("lp_fee_merkle_timestamp")]
+    fn lp_fee_merkle_timestamp(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // ========================================================================
+    // FEE DISTRIBUTION STORAGE
+    // ========================================================================
+
+    // 0-based lp_list position the next distributePendingFees call starts from
+    #[storage_mapper("fee_distribution_cursor")]
+    fn fee_distribution_cursor(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("param_distribution_incentive_bps")]
+    fn param_distribution_incentive_bps(&self) -> SingleValueMapper<u64>;
+
+    // ========================================================================
+    // PAIR STATISTICS STORAGE
+    // ========================================================================
+
+    #[storage_mapper("pair_volume_a")]
+    fn pair_volume_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_volume_b")]
+    fn pair_volume_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_daily_volume_a")]
+    fn pair_daily_volume_a(&self, pair_id: u64, day: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_daily_volume_b")]
+    fn pair_daily_volume_b(&self, pair_id: u64, day: u64) -> SingleValueMapper<BigUint>;
+
+    // Cumulative swap fees charged (owner + LP portions), per token
+    #[storage_mapper("pair_total_fees_a")]
+    fn pair_total_fees_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_total_fees_b")]
+    fn pair_total_fees_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("pair_swap_count")]
+    fn pair_swap_count(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_last_swap_timestamp")]
+    fn pair_last_swap_timestamp(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Per-trader counters for competitions and loyalty tiers (credited to the
+    // trader a swap is made for, like the compliance checks)
+    #[storage_mapper("user_volume_a")]
+    fn user_volume_a(&self, addr: &ManagedAddress, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("user_volume_b")]
+    fn user_volume_b(&self, addr: &ManagedAddress, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("user_swap_count")]
+    fn user_swap_count(&self, addr: &ManagedAddress, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("user_last_swap_timestamp")]
+    fn user_last_swap_timestamp(&self, addr: &ManagedAddress, pair_id: u64) -> SingleValueMapper<u64>;
+
+    // Contract-wide counters (survive pair deletion)
+    #[storage_mapper("total_swap_count")]
+    fn total_swap_count(&self) -> SingleValueMapper<u64>;
+
+    // Cumulative protocol cut taken on KLV-side fees
+    #[storage_mapper("total_protocol_fees_klv")]
+    fn total_protocol_fees_klv(&self) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // PARAMETER STORAGE (empty = use the DEFAULT_* constant)
+    // ========================================================================
+
+    #[storage_mapper("param_minimum_liquidity")]
+    fn param_minimum_liquidity(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("param_max_lps_per_pair")]
+    fn param_max_lps_per_pair(&self) -> SingleValueMapper<usize>;
+
+    #[storage_mapper("param_min_fee_bps")]
+    fn param_min_fee_bps(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("param_max_fee_bps")]
+    fn param_max_fee_bps(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("param_klv_max_fee_bps")]
+    fn param_klv_max_fee_bps(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("param_protocol_fee_bps")]
+    fn param_protocol_fee_bps(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("param_pending_expiry_days")]
+    fn param_pending_expiry_days(&self) -> SingleValueMapper<u64>;
+
+    // Band pair creators may move their own pair's fee within (max 0 = creators can't)
+    #[storage_mapper("param_creator_fee_min_bps")]
+    fn param_creator_fee_min_bps(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("param_creator_fee_max_bps")]
+    fn param_creator_fee_max_bps(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("param_creator_fee_cooldown")]
+    fn param_creator_fee_cooldown(&self) -> SingleValueMapper<u64>;
+
+    // Minimum KLV-equivalent value of a pair's first deposit (empty/0 = no minimum)
+    #[storage_mapper("param_min_initial_liquidity_klv")]
+    fn param_min_initial_liquidity_klv(&self) -> SingleValueMapper<BigUint>;
+
+    // Allowed deviation of a first deposit's implied price from the reference prices (0 = off)
+    #[storage_mapper("param_reference_band_bps")]
+    fn param_reference_band_bps(&self) -> SingleValueMapper<u64>;
+
+    // Owner-set decimals of a token (empty = unknown; KLV is always KLV_DECIMALS)
+    #[storage_mapper("token_decimals")]
+    fn token_decimals(&self, token: &TokenIdentifier) -> SingleValueMapper<u32>;
+
+    // Owner-set KLV price of a token, scaled by PRECISION (empty = no reference)
+    #[storage_mapper("token_reference_price")]
+    fn token_reference_price(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // ADMIN / DEAD-MAN SWITCH STORAGE
+    // ========================================================================
+
+    // Overrides the contract owner as admin once a backup has claimed recovery
+    #[storage_mapper("admin_address")]
+    fn admin_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("last_admin_activity")]
+    fn last_admin_activity(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("backup_admin")]
+    fn backup_admin(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("admin_inactivity_period")]
+    fn admin_inactivity_period(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("admin_recovery_delay")]
+    fn admin_recovery_delay(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("admin_recovery_started_at")]
+    fn admin_recovery_started_at(&self) -> SingleValueMapper<u64>;
+
+    // ========================================================================
+    // TIMELOCK / HANDOVER STORAGE
+    // ========================================================================
+
+    #[storage_mapper("pending_admin")]
+    fn pending_admin(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("admin_timelock_delay")]
+    fn admin_timelock_delay(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("timelock_next_action_id")]
+    fn timelock_next_action_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("timelock_action")]
+    fn timelock_action(&self, action_id: u64) -> SingleValueMapper<AdminAction<Self::Api>>;
+
+    #[storage_mapper("timelock_action_eta")]
+    fn timelock_action_eta(&self, action_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("timelock_pending_actions")]
+    fn timelock_pending_actions(&self) -> UnorderedSetMapper<u64>;
+
+    // ========================================================================
+    // REFERRAL STORAGE
+    // ========================================================================
+
+    #[storage_mapper("param_referral_fee_bps")]
+    fn param_referral_fee_bps(&self) -> SingleValueMapper<u64>;
+
+    // Tokens with an unclaimed referral balance
+    #[storage_mapper("referral_tokens")]
+    fn referral_tokens(&self, referrer: &ManagedAddress) -> UnorderedSetMapper<TokenIdentifier>;
+
+    // Every token a referrer has ever earned in (for the earnings view)
+    #[storage_mapper("referral_earned_tokens")]
+    fn referral_earned_tokens(&self, referrer: &ManagedAddress) -> UnorderedSetMapper<TokenIdentifier>;
+
+    #[storage_mapper("referral_unclaimed")]
+    fn referral_unclaimed(&self, referrer: &ManagedAddress, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("referral_total_earned")]
+    fn referral_total_earned(&self, referrer: &ManagedAddress, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // Unclaimed referral fees across all referrers (counted as owed by sync / skim)
+    #[storage_mapper("referral_outstanding")]
+    fn referral_outstanding(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // LP AIRDROP STORAGE
+    // ========================================================================
+
+    #[storage_mapper("next_airdrop_id")]
+    fn next_airdrop_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("lp_airdrop")]
+    fn lp_airdrop(&self, airdrop_id: u64) -> SingleValueMapper<LpAirdrop<Self::Api>>;
+
+    /// Snapshotted shares not yet claimed
+    #[storage_mapper("airdrop_shares")]
+    fn airdrop_shares(&self, airdrop_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    // Deposited airdrop rewards not yet claimed or swept (counted as owed by sync / skim)
+    #[storage_mapper("airdrop_outstanding")]
+    fn airdrop_outstanding(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // LAUNCHPAD STORAGE
+    // ========================================================================
+
+    #[storage_mapper("next_launch_id")]
+    fn next_launch_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("launch")]
+    fn launch(&self, launch_id: u64) -> SingleValueMapper<Launch<Self::Api>>;
+
+    // Running launch of a token (cleared at graduation or cancellation)
+    #[storage_mapper("launch_by_token")]
+    fn launch_by_token(&self, token: &TokenIdentifier) -> SingleValueMapper<u64>;
+
+    // Tokens and KLV held by running launches (counted as owed by sync / skim)
+    #[storage_mapper("launch_outstanding")]
+    fn launch_outstanding(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // VESTING STORAGE
+    // ========================================================================
+
+    #[storage_mapper("next_vesting_id")]
+    fn next_vesting_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("vesting_schedule")]
+    fn vesting_schedule(&self, schedule_id: u64) -> SingleValueMapper<VestingSchedule<Self::Api>>;
+
+    #[storage_mapper("beneficiary_vesting_ids")]
+    fn beneficiary_vesting_ids(&self, beneficiary: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
+    // Deposited vesting tokens not yet claimed (counted as owed by sync / skim)
+    #[storage_mapper("vesting_outstanding")]
+    fn vesting_outstanding(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // TRADING COMPETITION STORAGE
+    // ========================================================================
+
+    #[storage_mapper("next_competition_id")]
+    fn next_competition_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("competition")]
+    fn competition(&self, competition_id: u64) -> SingleValueMapper<Competition<Self::Api>>;
+
+    // Competition a pair's swaps currently count towards (empty = none)
+    #[storage_mapper("pair_active_competition")]
+    fn pair_active_competition(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("competition_volume")]
+    fn competition_volume(&self, competition_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("competition_traders")]
+    fn competition_traders(&self, competition_id: u64) -> UnorderedSetMapper<ManagedAddress>;
+
+    // Largest volumes, descending (top-N competitions only)
+    #[storage_mapper("competition_top")]
+    fn competition_top(&self, competition_id: u64) -> SingleValueMapper<ManagedVec<CompetitionEntry<Self::Api>>>;
+
+    #[storage_mapper("competition_claimed")]
+    fn competition_claimed(&self, competition_id: u64, addr: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    // Competition pots not yet claimed (counted as owed by sync / skim)
+    #[storage_mapper("competition_outstanding")]
+    fn competition_outstanding(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // LP POSITION NFT STORAGE
+    // ========================================================================
+
+    // NFT collection for wrapped positions (create/burn roles granted to this contract)
+    #[storage_mapper("lp_position_nft_token")]
+    fn lp_position_nft_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("lp_position_pair")]
+    fn lp_position_pair(&self, nonce: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_lp_positions")]
+    fn pair_lp_positions(&self, pair_id: u64) -> UnorderedSetMapper<u64>;
+
+    // Nonce behind a position holder address (empty for real accounts)
+    #[storage_mapper("lp_position_nonce")]
+    fn lp_position_nonce(&self, holder: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    // ========================================================================
+    // STATE IMPORT STORAGE
+    // ========================================================================
+
+    /// Set while a pair is being imported (it isn't registered yet)
+    #[storage_mapper("pair_importing")]
+    fn pair_importing(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    /// Running sums of the imported positions, checked by finishPairImport
+    #[storage_mapper("import_shares_total")]
+    fn import_shares_total(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("import_pending_total_a")]
+    fn import_pending_total_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("import_pending_total_b")]
+    fn import_pending_total_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // BUYBACK STORAGE
+    // ========================================================================
+
+    #[storage_mapper("buyback_token")]
+    fn buyback_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("buyback_burn_address")]
+    fn buyback_burn_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("buyback_max_slippage_bps")]
+    fn buyback_max_slippage_bps(&self) -> SingleValueMapper<u64>;
+
+    // Sum of everything sent to the burn address, in the buyback token
+    #[storage_mapper("buyback_total_burned")]
+    fn buyback_total_burned(&self) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // ROUTER STORAGE
+    // ========================================================================
+
+    // Aggregator contracts allowed to call swapFor
+    #[storage_mapper("whitelisted_routers")]
+    fn whitelisted_routers(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    // Route registry: alias -> path, plus the preferred alias of each token pair
+    #[storage_mapper("registered_route")]
+    fn registered_route(&self, alias: &ManagedBuffer) -> SingleValueMapper<RegisteredRoute<Self::Api>>;
+
+    #[storage_mapper("route_aliases")]
+    fn route_aliases(&self) -> UnorderedSetMapper<ManagedBuffer>;
+
+    #[storage_mapper("preferred_route")]
+    fn preferred_route(&self, token_in: &TokenIdentifier, token_out: &TokenIdentifier) -> SingleValueMapper<ManagedBuffer>;
+
+    // ========================================================================
+    // META-SWAP STORAGE
+    // ========================================================================
+
+    // Funds a user set aside for relayed swaps (spent only by signed orders)
+    #[storage_mapper("relayer_allowance")]
+    fn relayer_allowance(&self, user: &ManagedAddress, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // Counted as owed by sync / skim
+    #[storage_mapper("relayer_allowance_outstanding")]
+    fn relayer_allowance_outstanding(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // Nonce the user's next signed order must carry
+    #[storage_mapper("meta_swap_nonce")]
+    fn meta_swap_nonce(&self, user: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    // ========================================================================
+    // LIMIT ORDER STORAGE
+    // ========================================================================
+
+    #[storage_mapper("next_limit_order_id")]
+    fn next_limit_order_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("limit_order")]
+    fn limit_order(&self, order_id: u64) -> SingleValueMapper<LimitOrder<Self::Api>>;
+
+    #[storage_mapper("pair_limit_orders")]
+    fn pair_limit_orders(&self, pair_id: u64) -> UnorderedSetMapper<u64>;
+
+    // Where the next fillOrders batch starts in pair_limit_orders
+    #[storage_mapper("limit_order_cursor")]
+    fn limit_order_cursor(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("user_limit_orders")]
+    fn user_limit_orders(&self, addr: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
+    // ========================================================================
+    // DCA STORAGE
+    // ========================================================================
+
+    #[storage_mapper("next_dca_order_id")]
+    fn next_dca_order_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("dca_order")]
+    fn dca_order(&self, order_id: u64) -> SingleValueMapper<DcaOrder<Self::Api>>;
+
+    // Every active order, walked by executeDcaOrders
+    #[storage_mapper("dca_order_ids")]
+    fn dca_order_ids(&self) -> UnorderedSetMapper<u64>;
+
+    // Where the next executeDcaOrders batch starts in dca_order_ids
+    #[storage_mapper("dca_order_cursor")]
+    fn dca_order_cursor(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_dca_orders")]
+    fn pair_dca_orders(&self, pair_id: u64) -> UnorderedSetMapper<u64>;
+
+    #[storage_mapper("user_dca_orders")]
+    fn user_dca_orders(&self, addr: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
+    #[storage_mapper("param_dca_keeper_fee_bps")]
+    fn param_dca_keeper_fee_bps(&self) -> SingleValueMapper<u64>;
+
+    // ========================================================================
+    // STREAMING SWAP STORAGE
+    // ========================================================================
+
+    #[storage_mapper("next_stream_order_id")]
+    fn next_stream_order_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("stream_order")]
+    fn stream_order(&self, order_id: u64) -> SingleValueMapper<StreamOrder<Self::Api>>;
+
+    // Every open order, walked by executeStreamOrders
+    #[storage_mapper("stream_order_ids")]
+    fn stream_order_ids(&self) -> UnorderedSetMapper<u64>;
+
+    // Where the next executeStreamOrders batch starts in stream_order_ids
+    #[storage_mapper("stream_order_cursor")]
+    fn stream_order_cursor(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("pair_stream_orders")]
+    fn pair_stream_orders(&self, pair_id: u64) -> UnorderedSetMapper<u64>;
+
+    #[storage_mapper("user_stream_orders")]
+    fn user_stream_orders(&self, addr: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
+    // ========================================================================
+    // FEE REBATE STORAGE
+    // ========================================================================
+
+    // Ordered by min_volume, lowest first
+    #[storage_mapper("pair_rebate_tiers")]
+    fn pair_rebate_tiers(&self, pair_id: u64) -> SingleValueMapper<ManagedVec<RebateTier<Self::Api>>>;
+
+    // Daily volume buckets of the last REBATE_WINDOW_DAYS, oldest first (pairs with tiers only)
+    #[storage_mapper("rebate_volume")]
+    fn rebate_volume(&self, addr: &ManagedAddress, pair_id: u64) -> SingleValueMapper<ManagedVec<VolumeBucket<Self::Api>>>;
+
+    #[storage_mapper("rebate_tokens")]
+    fn rebate_tokens(&self, addr: &ManagedAddress) -> UnorderedSetMapper<TokenIdentifier>;
+
+    #[storage_mapper("rebate_unclaimed")]
+    fn rebate_unclaimed(&self, addr: &ManagedAddress, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // Counted as owed by sync / skim
+    #[storage_mapper("rebate_outstanding")]
+    fn rebate_outstanding(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // STAKING STORAGE
+    // ========================================================================
+
+    // Token stakers lock for swap fee discounts (DGKO)
+    #[storage_mapper("staking_token")]
+    fn staking_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    // Ordered by min_stake, lowest first
+    #[storage_mapper("staking_tiers")]
+    fn staking_tiers(&self) -> SingleValueMapper<ManagedVec<StakingTier<Self::Api>>>;
+
+    #[storage_mapper("param_unstake_cooldown")]
+    fn param_unstake_cooldown(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("staked_balance")]
+    fn staked_balance(&self, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    // Unstaked tokens waiting out the cooldown (they earn no discount)
+    #[storage_mapper("unstaking_amount")]
+    fn unstaking_amount(&self, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("unstaking_unlock_at")]
+    fn unstaking_unlock_at(&self, addr: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    // Staked plus unstaking tokens (counted as owed by sync / skim)
+    #[storage_mapper("staking_outstanding")]
+    fn staking_outstanding(&self) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // FARMING STORAGE
+    // ========================================================================
+
+    #[storage_mapper("farm_reward_token")]
+    fn farm_reward_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("farm_reward_per_second")]
+    fn farm_reward_per_second(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("farm_reward_pool")]
+    fn farm_reward_pool(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("farm_total_weight")]
+    fn farm_total_weight(&self) -> SingleValueMapper<u64>;
+
+    // Pairs with a non-zero weight or remaining stakers
+    #[storage_mapper("farm_pairs")]
+    fn farm_pairs(&self) -> UnorderedSetMapper<u64>;
+
+    #[storage_mapper("farm_pool_weight")]
+    fn farm_pool_weight(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("farm_total_staked")]
+    fn farm_total_staked(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // Scaled by PRECISION
+    #[storage_mapper("farm_acc_reward_per_share")]
+    fn farm_acc_reward_per_share(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("farm_last_update")]
+    fn farm_last_update(&self, pair_id: u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("farm_staked")]
+    fn farm_staked(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("farm_reward_debt")]
+    fn farm_reward_debt(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    // Rewards earned but not paid because the reward pool ran dry
+    #[storage_mapper("farm_unpaid_reward")]
+    fn farm_unpaid_reward(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // IL PROTECTION STORAGE
+    // ========================================================================
+
+    // Share of the protocol fee cut sent to the IL fund, in bps of the cut
+    #[storage_mapper("il_fund_bps")]
+    fn il_fund_bps(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("il_min_duration")]
+    fn il_min_duration(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("il_max_coverage_bps")]
+    fn il_max_coverage_bps(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("il_fund_a")]
+    fn il_fund_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("il_fund_b")]
+    fn il_fund_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // Amounts deposited by the LP, scaled down pro-rata on partial removals
+    #[storage_mapper("lp_deposited_a")]
+    fn lp_deposited_a(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_deposited_b")]
+    fn lp_deposited_b(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    // Last deposit time (the holding period restarts on every top-up)
+    #[storage_mapper("lp_deposit_timestamp")]
+    fn lp_deposit_timestamp(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    // ========================================================================
+    // ROLE STORAGE
+    // ========================================================================
+
+    #[storage_mapper("address_roles")]
+    fn address_roles(&self, addr: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("role_holders")]
+    fn role_holders(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    // ========================================================================
+    // COMPLIANCE STORAGE
+    // ========================================================================
+
+    #[storage_mapper("compliance_restricted")]
+    fn compliance_restricted(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[storage_mapper("compliance_flagged_pairs")]
+    fn compliance_flagged_pairs(&self) -> UnorderedSetMapper<u64>;
+
+    #[storage_mapper("compliance_limited_addresses")]
+    fn compliance_limited_addresses(&self, pair_id: u64) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[storage_mapper("compliance_daily_limit")]
+    fn compliance_daily_limit(&self, pair_id: u64, addr: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("compliance_daily_used")]
+    fn compliance_daily_used(&self, pair_id: u64, addr: &ManagedAddress, day: u64) -> SingleValueMapper<BigUint>;
+
+    // ========================================================================
+    // FLASH SWAP STORAGE
+    // ========================================================================
+
+    // Set only while a flash swap callback is running on the pair
+    #[storage_mapper("flash_swap_active")]
+    fn flash_swap_active(&self, pair_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("flash_swap_repaid_a")]
+    fn flash_swap_repaid_a(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("flash_swap_repaid_b")]
+    fn flash_swap_repaid_b(&self, pair_id: u64) -> SingleValueMapper<BigUint>;
+
+    // Flash swap callbacks running on any pair (sync / skim wait for zero)
+    #[storage_mapper("active_flash_swaps")]
+    fn active_flash_swaps(&self) -> SingleValueMapper<u64>;
+}
@@ -0,0 +1,1071 @@
+// ============================================================================
+// SWAP - Trading endpoints
+// ============================================================================
+// Direct swaps, multi-hop routing, aggregator entry points, relayed
+// meta-swaps, flash swaps, limit orders, DCA, streaming swaps and trading
+// competitions.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::constants::*;
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait SwapModule: crate::views::ViewsModule {
+    // ========================================================================
+    // SWAP FUNCTIONS
+    // ========================================================================
+
+    /// Swap whatever was sent (KLV or KDA) for the other token of the pair
+    /// Direction is inferred from the payment token; tokens not in the pair are rejected.
+    /// @param pair_id - The pair to swap on
+    /// @param min_output - Minimum amount of the output token to receive (slippage protection)
+    /// @param deadline - Block timestamp after which the swap is rejected (0 = no deadline)
+    /// @param referrer - Optional wallet/aggregator address credited with a share of the fee
+    #[endpoint(swap)]
+    #[payable("*")]
+    fn swap(&self, pair_id: u64, min_output: BigUint, deadline: u64, referrer: OptionalValue<ManagedAddress>) -> BigUint {
+        let _guard = self.non_reentrant();
+        require!(
+            deadline == 0 || self.blockchain().get_block_timestamp() <= deadline,
+            "Swap deadline passed"
+        );
+        self.swap_payment_internal(pair_id, None, &min_output, &referrer.into_option())
+    }
+
+    /// Swap token A for token B
+    /// Kept for compatibility - same as swap() with the direction pinned to A -> B.
+    /// @param pair_id - The pair to swap on
+    /// @param min_output - Minimum amount of token B to receive (slippage protection)
+    /// @param referrer - Optional wallet/aggregator address credited with a share of the fee
+    #[endpoint(swapAtoB)]
+    #[payable("*")]
+    fn swap_a_to_b(&self, pair_id: u64, min_output: BigUint, referrer: OptionalValue<ManagedAddress>) {
+        let _guard = self.non_reentrant();
+        self.swap_payment_internal(pair_id, Some(true), &min_output, &referrer.into_option());
+    }
+
+    /// Swap token B for token A
+    /// Kept for compatibility - same as swap() with the direction pinned to B -> A.
+    /// @param pair_id - The pair to swap on
+    /// @param min_output - Minimum amount of token A to receive (slippage protection)
+    /// @param referrer - Optional wallet/aggregator address credited with a share of the fee
+    #[endpoint(swapBtoA)]
+    #[payable("*")]
+    fn swap_b_to_a(&self, pair_id: u64, min_output: BigUint, referrer: OptionalValue<ManagedAddress>) {
+        let _guard = self.non_reentrant();
+        self.swap_payment_internal(pair_id, Some(false), &min_output, &referrer.into_option());
+    }
+
+    /// Swap KLV for token B (when token_a is KLV)
+    /// Kept for compatibility - same as swap() with the direction pinned to A -> B.
+    /// @param pair_id - The pair to swap on
+    /// @param min_output - Minimum amount of token B to receive (slippage protection)
+    /// @param referrer - Optional wallet/aggregator address credited with a share of the fee
+    #[endpoint(swapKlvToB)]
+    #[payable("KLV")]
+    fn swap_klv_to_b(&self, pair_id: u64, min_output: BigUint, referrer: OptionalValue<ManagedAddress>) {
+        let _guard = self.non_reentrant();
+        self.swap_payment_internal(pair_id, Some(true), &min_output, &referrer.into_option());
+    }
+
+    /// Swap KLV for token A (when token_b is KLV)
+    /// Kept for compatibility - same as swap() with the direction pinned to B -> A.
+    /// @param pair_id - The pair to swap on
+    /// @param min_output - Minimum amount of token A to receive (slippage protection)
+    /// @param referrer - Optional wallet/aggregator address credited with a share of the fee
+    #[endpoint(swapKlvToA)]
+    #[payable("KLV")]
+    fn swap_klv_to_a(&self, pair_id: u64, min_output: BigUint, referrer: OptionalValue<ManagedAddress>) {
+        let _guard = self.non_reentrant();
+        self.swap_payment_internal(pair_id, Some(false), &min_output, &referrer.into_option());
+    }
+
+    /// Swap for an EXACT amount of output token (like Uniswap's swapTokensForExactTokens)
+    /// Send the maximum input you are willing to pay (KLV or KDA). The contract
+    /// computes the required input with the inverse AMM formula and refunds the rest.
+    /// 
+    /// @param pair_id - The pair to swap on
+    /// @param amount_out - Exact amount of output token to receive (after fee)
+    /// @param referrer - Optional wallet/aggregator address credited with a share of the fee
+    #[endpoint(swapTokensForExactOut)]
+    #[payable("*")]
+    fn swap_tokens_for_exact_out(&self, pair_id: u64, amount_out: BigUint, referrer: OptionalValue<ManagedAddress>) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        require!(amount_out > 0u64, "Output must be greater than 0");
+        
+        let (is_a_to_b, max_input) = self.take_pair_payment(pair_id);
+        
+        let caller = self.blockchain().get_caller();
+        let required_input = self.get_amount_in_internal(pair_id, &caller, is_a_to_b, &amount_out);
+        require!(required_input <= max_input, "Slippage: input above maximum");
+        
+        let user_gets = self.swap_internal(pair_id, &caller, is_a_to_b, &required_input, &referrer.into_option());
+        require!(user_gets >= amount_out, "Slippage: output below requested");
+        
+        // Refund unused input
+        let refund = &max_input - &required_input;
+        if refund > BigUint::zero() {
+            self.send_pair_token(&caller, pair_id, is_a_to_b, &refund);
+        }
+        
+        if is_a_to_b {
+            self.send_pair_token(&caller, pair_id, false, &user_gets);
+        } else {
+            self.send_pair_token(&caller, pair_id, true, &user_gets);
+        }
+    }
+
+    // ========================================================================
+    // MULTI-HOP ROUTER
+    // ========================================================================
+
+    /// Swap through several pairs in ONE transaction (e.g. DGKO -> KLV -> BABYDGKO)
+    /// The output of each hop is used as the input of the next pair in the path.
+    /// Only the final output is transferred to the caller.
+    /// 
+    /// Accepts either KLV or a single KDA payment. The direction of every hop is
+    /// inferred from the token flowing into that pair.
+    /// 
+    /// @param min_output - Minimum amount of the final token to receive (slippage protection)
+    /// @param path - Ordered list of pair_ids to route through (var-args, must be last)
+    #[endpoint(swapExactInMultiHop)]
+    #[payable("*")]
+    fn swap_exact_in_multi_hop(&self, min_output: BigUint, path: MultiValueEncoded<u64>) {
+        let _guard = self.non_reentrant();
+        let (token_in, nonce_in, amount_in) = self.get_single_payment_with_nonce();
+        
+        let mut hops = ManagedVec::new();
+        for pair_id in path.into_iter() {
+            hops.push(pair_id);
+        }
+        self.multi_hop_internal(token_in, nonce_in, amount_in, &hops, &min_output);
+    }
+
+    /// Multi-hop swap along a route registered by governance (see setRoute)
+    /// Same as swapExactInMultiHop, but the path is looked up by alias, so
+    /// integrators keep working when governance re-points a route at recreated pairs.
+    /// @param route_alias - Alias of the registered route (getRouteAliases)
+    #[endpoint(swapExactInRoute)]
+    #[payable("*")]
+    fn swap_exact_in_route(&self, min_output: BigUint, route_alias: ManagedBuffer) {
+        let _guard = self.non_reentrant();
+        require!(!self.registered_route(&route_alias).is_empty(), "Unknown route");
+        let route = self.registered_route(&route_alias).get();
+        
+        let (token_in, nonce_in, amount_in) = self.get_single_payment_with_nonce();
+        require!(token_in == route.token_in, "Payment does not match route");
+        self.multi_hop_internal(token_in, nonce_in, amount_in, &route.path, &min_output);
+    }
+
+    /// Register or replace a named route (owner or PAIR_MANAGER)
+    /// The path must chain from token_in through every pair; its final token
+    /// becomes the route's token_out. The route is also made the preferred one
+    /// between those two tokens (getPreferredRoute).
+    #[endpoint(setRoute)]
+    fn set_route(&self, route_alias: ManagedBuffer, token_in: TokenIdentifier, path: MultiValueEncoded<u64>) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        require!(!route_alias.is_empty(), "Route alias cannot be empty");
+        require!(route_alias.len() <= MAX_ROUTE_ALIAS_LEN, "Route alias too long");
+        let hop_count = path.len();
+        require!(hop_count > 0, "Path cannot be empty");
+        require!(hop_count <= MAX_ROUTE_HOPS, "Path too long");
+        
+        let mut hops = ManagedVec::new();
+        let mut current_token = token_in.clone();
+        for pair_id in path.into_iter() {
+            require!(self.pair_exists(pair_id), "Pair does not exist");
+            let is_a_to_b = self.resolve_swap_direction(pair_id, &current_token);
+            current_token = self.pair_token(pair_id, !is_a_to_b);
+            hops.push(pair_id);
+        }
+        require!(current_token != token_in, "Route must end in another token");
+        
+        // Replacing an alias drops its old token-pair preference
+        self.clear_route_preference(&route_alias);
+        self.preferred_route(&token_in, &current_token).set(&route_alias);
+        self.route_aliases().insert(route_alias.clone());
+        self.registered_route(&route_alias).set(RegisteredRoute {
+            token_in,
+            token_out: current_token,
+            path: hops,
+        });
+    }
+
+    /// Remove a named route (owner or PAIR_MANAGER)
+    #[endpoint(removeRoute)]
+    fn remove_route(&self, route_alias: ManagedBuffer) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        require!(!self.registered_route(&route_alias).is_empty(), "Unknown route");
+        
+        self.clear_route_preference(&route_alias);
+        self.route_aliases().swap_remove(&route_alias);
+        self.registered_route(&route_alias).clear();
+    }
+
+    /// Helper: Drop the token-pair preference pointing at an alias, if any
+    fn clear_route_preference(&self, route_alias: &ManagedBuffer) {
+        if self.registered_route(route_alias).is_empty() {
+            return;
+        }
+        let old = self.registered_route(route_alias).get();
+        let preferred = self.preferred_route(&old.token_in, &old.token_out);
+        if preferred.get() == *route_alias {
+            preferred.clear();
+        }
+    }
+
+    /// Helper: Run a payment through a path of pairs and send the final output to the caller
+    fn multi_hop_internal(
+        &self,
+        token_in: TokenIdentifier,
+        nonce_in: u64,
+        amount_in: BigUint,
+        path: &ManagedVec<u64>,
+        min_output: &BigUint,
+    ) {
+        let hop_count = path.len();
+        require!(hop_count > 0, "Path cannot be empty");
+        require!(hop_count <= MAX_ROUTE_HOPS, "Path too long");
+        
+        let caller = self.blockchain().get_caller();
+        let mut current_token = token_in;
+        let mut current_nonce = nonce_in;
+        let mut amount = amount_in;
+        let mut last_hop = (0u64, false);
+        
+        for pair_id in path.iter() {
+            require!(self.pair_exists(pair_id), "Pair does not exist");
+            require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+            
+            let is_a_to_b = self.resolve_pair_side(pair_id, &current_token, current_nonce);
+            amount = self.swap_internal(pair_id, &caller, is_a_to_b, &amount, &None);
+            
+            // Output of this hop becomes the input of the next one
+            current_token = self.pair_token(pair_id, !is_a_to_b);
+            current_nonce = self.pair_token_nonce(pair_id, !is_a_to_b);
+            last_hop = (pair_id, !is_a_to_b);
+        }
+        
+        // Slippage protection on the final output only
+        require!(amount >= *min_output, "Slippage: output below minimum");
+        
+        self.send_pair_token(&caller, last_hop.0, last_hop.1, &amount);
+    }
+
+    /// Arbitrage a cycle of pairs that starts and ends in the same token (keepers)
+    /// Runs amount_in of token_in through every pair of the path without any
+    /// payment: the cycle's output first pays back amount_in and only the profit
+    /// is sent to the caller. Reverts unless the round trip earns at least min_profit,
+    /// so it can only ever move diverging prices (e.g. of duplicate pairs) together.
+    /// 
+    /// @param token_in - Token the cycle starts and ends in
+    /// @param amount_in - Amount run through the first pair
+    /// @param min_profit - Minimum profit in token_in
+    /// @param path - Ordered list of pair_ids forming the cycle (var-args, must be last)
+    /// @return Profit sent to the caller
+    #[endpoint(arb)]
+    fn arb(
+        &self,
+        token_in: TokenIdentifier,
+        amount_in: BigUint,
+        min_profit: BigUint,
+        path: MultiValueEncoded<u64>,
+    ) -> BigUint {
+        let _guard = self.non_reentrant();
+        let hop_count = path.len();
+        require!(hop_count >= 2, "Cycle needs at least two pairs");
+        require!(hop_count <= MAX_ROUTE_HOPS, "Path too long");
+        
+        let caller = self.blockchain().get_caller();
+        let mut current_token = token_in.clone();
+        let mut current_nonce = 0u64;
+        let mut amount = amount_in.clone();
+        for pair_id in path.into_iter() {
+            require!(self.pair_exists(pair_id), "Pair does not exist");
+            require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+            
+            let is_a_to_b = self.resolve_pair_side(pair_id, &current_token, current_nonce);
+            amount = self.swap_internal(pair_id, &caller, is_a_to_b, &amount, &None);
+            current_token = self.pair_token(pair_id, !is_a_to_b);
+            current_nonce = self.pair_token_nonce(pair_id, !is_a_to_b);
+        }
+        require!(
+            current_token == token_in && current_nonce == 0,
+            "Path must return to the input token"
+        );
+        require!(amount > amount_in, "Arbitrage not profitable");
+        
+        let profit = amount - &amount_in;
+        require!(profit >= min_profit, "Profit below minimum");
+        
+        self.send_wrapped(&caller, &token_in, &profit);
+        profit
+    }
+
+    // ========================================================================
+    // AGGREGATOR INTEGRATION (Whitelisted router contracts)
+    // ========================================================================
+    // Whitelisted routers swap on behalf of their users: the output goes to a
+    // beneficiary instead of the caller, optionally through a post-swap callback
+    //   <callback_endpoint>(router, pair_id, amount_out)
+    // invoked on the beneficiary contract with the output attached.
+
+    /// Add or remove an aggregator contract from the router whitelist (owner or PAIR_MANAGER)
+    #[endpoint(setRouterWhitelisted)]
+    fn set_router_whitelisted(&self, router: ManagedAddress, whitelisted: bool) {
+        self.require_role(ROLE_PAIR_MANAGER);
+        if whitelisted {
+            require!(self.blockchain().is_smart_contract(&router), "Router must be a contract");
+            self.whitelisted_routers().insert(router);
+        } else {
+            self.whitelisted_routers().swap_remove(&router);
+        }
+    }
+
+    /// Swap on behalf of a third party (whitelisted routers only)
+    /// Same as swap(), but the output is credited to `beneficiary`. Compliance
+    /// restrictions apply to both the router and the beneficiary; the swap is the
+    /// beneficiary's for daily limits, fee discount, rebates and stats.
+    /// @param callback_endpoint - Endpoint called on the beneficiary contract with the output
+    ///                           (it runs under the reentrancy lock, so it can't call back into swaps)
+    #[endpoint(swapFor)]
+    #[payable("*")]
+    fn swap_for(
+        &self,
+        pair_id: u64,
+        min_output: BigUint,
+        deadline: u64,
+        beneficiary: ManagedAddress,
+        callback_endpoint: OptionalValue<ManagedBuffer>,
+    ) -> BigUint {
+        let _guard = self.non_reentrant();
+        let router = self.blockchain().get_caller();
+        require!(self.whitelisted_routers().contains(&router), "Caller is not a whitelisted router");
+        require!(
+            deadline == 0 || self.blockchain().get_block_timestamp() <= deadline,
+            "Swap deadline passed"
+        );
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        require!(!beneficiary.is_zero(), "Invalid beneficiary");
+        require!(
+            !self.compliance_restricted().contains(&router),
+            "Address is restricted from trading"
+        );
+        
+        let (is_a_to_b, payment) = self.take_pair_payment(pair_id);
+        let user_gets = self.swap_internal(pair_id, &beneficiary, is_a_to_b, &payment, &None);
+        require!(user_gets >= min_output, "Slippage: output below minimum");
+        
+        let token_out = self.pair_token(pair_id, !is_a_to_b);
+        match callback_endpoint.into_option() {
+            Some(endpoint) => {
+                require!(
+                    self.blockchain().is_smart_contract(&beneficiary),
+                    "Callback target must be a contract"
+                );
+                let contract_call = self.send()
+                    .contract_call::<()>(beneficiary.clone(), endpoint)
+                    .argument(&router)
+                    .argument(&pair_id)
+                    .argument(&user_gets);
+                self.remove_token_obligation(&token_out, &user_gets);
+                if token_out == self.wklv_token_id() {
+                    contract_call.with_klv_transfer(user_gets.clone()).execute_on_dest_context::<()>();
+                } else {
+                    contract_call
+                        .with_kda_transfer(KdaTokenPayment::new(
+                            token_out,
+                            self.pair_token_nonce(pair_id, !is_a_to_b),
+                            user_gets.clone(),
+                        ))
+                        .execute_on_dest_context::<()>();
+                }
+            },
+            None => self.send_pair_token(&beneficiary, pair_id, !is_a_to_b, &user_gets),
+        }
+        
+        self.swap_for_event(pair_id, &router, &beneficiary, &user_gets);
+        user_gets
+    }
+
+    // ========================================================================
+    // META-SWAPS (Signed orders submitted by relayers)
+    // ========================================================================
+    // Users without KLV for gas set funds aside as a relayer allowance and sign
+    // swap orders off-chain (see getMetaSwapMessage). Any relayer may submit an
+    // order and pays the gas; the output goes straight to the user. Orders carry
+    // the user's next nonce, so each can be used once and in order.
+
+    /// Set the sent KLV or KDA aside for relayed swaps
+    #[endpoint(depositRelayerAllowance)]
+    #[payable("*")]
+    fn deposit_relayer_allowance(&self) {
+        let (token, amount) = self.get_single_payment();
+        require!(amount > 0u64, "Amount must be > 0");
+        
+        let caller = self.blockchain().get_caller();
+        self.relayer_allowance(&caller, &token).update(|a| *a += &amount);
+        self.relayer_allowance_outstanding(&token).update(|o| *o += &amount);
+    }
+
+    /// Take back part of your relayer allowance
+    #[endpoint(withdrawRelayerAllowance)]
+    fn withdraw_relayer_allowance(&self, token: TokenIdentifier, amount: BigUint) {
+        let _guard = self.non_reentrant();
+        let caller = self.blockchain().get_caller();
+        let allowance = self.relayer_allowance(&caller, &token).get();
+        require!(amount > 0u64 && amount <= allowance, "Amount exceeds allowance");
+        
+        self.relayer_allowance(&caller, &token).set(&allowance - &amount);
+        self.relayer_allowance_outstanding(&token).update(|o| *o -= &amount);
+        self.send_wrapped(&caller, &token, &amount);
+    }
+
+    /// Skip your next meta-swap nonce, invalidating an order already signed with it
+    #[endpoint(cancelMetaSwapNonce)]
+    fn cancel_meta_swap_nonce(&self) {
+        let caller = self.blockchain().get_caller();
+        self.meta_swap_nonce(&caller).update(|n| *n += 1);
+    }
+
+    /// Execute a user's signed swap order (PUBLIC - relayers)
+    /// @param user_signature - Ed25519 signature of getMetaSwapMessage(order) by order.user
+    /// @return Amount sent to the user
+    #[endpoint(swapOnBehalf)]
+    fn swap_on_behalf(&self, user_signature: ManagedBuffer, order: MetaSwapOrder<Self::Api>) -> BigUint {
+        let _guard = self.non_reentrant();
+        require!(self.blockchain().get_block_timestamp() <= order.expiry, "Meta-swap order expired");
+        require!(order.nonce == self.meta_swap_nonce(&order.user).get(), "Invalid meta-swap nonce");
+        require!(
+            !self.compliance_restricted().contains(&order.user),
+            "Address is restricted from trading"
+        );
+        self.crypto().verify_ed25519(
+            order.user.as_managed_buffer(),
+            &self.meta_swap_message(&order),
+            &user_signature,
+        );
+        self.meta_swap_nonce(&order.user).set(order.nonce + 1);
+        
+        let pair_id = order.pair_id;
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        let is_a_to_b = self.resolve_pair_side(pair_id, &order.token_in, 0);
+        
+        let allowance = self.relayer_allowance(&order.user, &order.token_in).get();
+        require!(order.amount_in <= allowance, "Amount exceeds allowance");
+        self.relayer_allowance(&order.user, &order.token_in).set(&allowance - &order.amount_in);
+        self.relayer_allowance_outstanding(&order.token_in).update(|o| *o -= &order.amount_in);
+        
+        let user_gets = self.swap_internal(pair_id, &order.user, is_a_to_b, &order.amount_in, &None);
+        require!(user_gets >= order.min_out, "Slippage: output below minimum");
+        self.send_pair_token(&order.user, pair_id, !is_a_to_b, &user_gets);
+        
+        let relayer = self.blockchain().get_caller();
+        self.meta_swap_event(&order.user, &relayer, pair_id, order.nonce, &user_gets);
+        user_gets
+    }
+
+    // ========================================================================
+    // FLASH SWAPS
+    // ========================================================================
+    // Reserves are sent optimistically to a callee contract, which receives
+    // flashSwapCallback(initiator, pair_id, amount_a, amount_b, callback_args...)
+    // and must pay back through repayFlashSwap before returning. Afterwards the
+    // pool must hold at least the old k once the fee on the repaid amounts is taken.
+    // The callback runs under the reentrancy lock, so repayFlashSwap is the only
+    // endpoint it can call back into; nothing else may touch the reserves meanwhile.
+
+    /// Borrow reserves from a pair for the duration of a callback
+    /// @param amount_a / amount_b - Reserves to send (KLV can't be sent together with a KDA)
+    /// @param callback_contract - Contract receiving the tokens and the callback
+    /// @param callback_args - Raw arguments forwarded to the callback
+    #[endpoint(flashSwap)]
+    fn flash_swap(
+        &self,
+        pair_id: u64,
+        amount_a: BigUint,
+        amount_b: BigUint,
+        callback_contract: ManagedAddress,
+        callback_args: MultiValueEncoded<ManagedBuffer>,
+    ) {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        require!(!self.flash_swap_active(pair_id).get(), "Flash swap in progress");
+        self.require_not_paused();
+        require!(
+            self.blockchain().is_smart_contract(&callback_contract),
+            "Callback target must be a contract"
+        );
+        require!(amount_a > 0u64 || amount_b > 0u64, "Nothing to borrow");
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        require!(amount_a < reserve_a && amount_b < reserve_b, "Insufficient reserves");
+        
+        let token_a = self.pair_token_a(pair_id).get();
+        let token_b = self.pair_token_b(pair_id).get();
+        let token_a_is_klv = self.pair_token_a_is_klv(pair_id).get();
+        let token_b_is_klv = self.pair_token_b_is_klv(pair_id).get();
+        let sends_klv = (token_a_is_klv && amount_a > 0u64) || (token_b_is_klv && amount_b > 0u64);
+        require!(
+            !sends_klv || amount_a == 0u64 || amount_b == 0u64,
+            "Cannot send KLV and KDA in one flash swap"
+        );
+        
+        self.flash_swap_active(pair_id).set(true);
+        self.active_flash_swaps().update(|n| *n += 1);
+        self.remove_token_obligation(&token_a, &amount_a);
+        self.remove_token_obligation(&token_b, &amount_b);
+        
+        let caller = self.blockchain().get_caller();
+        let mut contract_call = self.send()
+            .contract_call::<()>(callback_contract, FLASH_SWAP_CALLBACK_ENDPOINT)
+            .argument(&caller)
+            .argument(&pair_id)
+            .argument(&amount_a)
+            .argument(&amount_b);
+        for arg in callback_args.into_iter() {
+            contract_call.push_raw_argument(arg);
+        }
+        
+        if sends_klv {
+            let klv_amount = if amount_a > 0u64 { amount_a.clone() } else { amount_b.clone() };
+            contract_call.with_klv_transfer(klv_amount).execute_on_dest_context::<()>();
+        } else {
+            let mut payments = ManagedVec::new();
+            if amount_a > 0u64 {
+                let nonce_a = self.pair_token_nonce(pair_id, true);
+                payments.push(KdaTokenPayment::new(token_a, nonce_a, amount_a.clone()));
+            }
+            if amount_b > 0u64 {
+                let nonce_b = self.pair_token_nonce(pair_id, false);
+                payments.push(KdaTokenPayment::new(token_b, nonce_b, amount_b.clone()));
+            }
+            contract_call.with_multi_token_transfer(payments).execute_on_dest_context::<()>();
+        }
+        
+        self.flash_swap_active(pair_id).clear();
+        self.active_flash_swaps().update(|n| *n -= 1);
+        let repaid_a = self.flash_swap_repaid_a(pair_id).take();
+        let repaid_b = self.flash_swap_repaid_b(pair_id).take();
+        
+        // Fee is charged on what came back, like a swap input
+        let fee_bps = self.get_effective_fee_bps(pair_id);
+        let fee_a = &repaid_a * fee_bps / BPS_DENOMINATOR;
+        let fee_b = &repaid_b * fee_bps / BPS_DENOMINATOR;
+        
+        let balance_a = &reserve_a + &repaid_a - &fee_a;
+        let balance_b = &reserve_b + &repaid_b - &fee_b;
+        require!(balance_a >= amount_a && balance_b >= amount_b, "Flash swap not repaid");
+        
+        self.update_price_accumulator(pair_id, &reserve_a, &reserve_b);
+        self.pair_reserve_a(pair_id).set(balance_a - &amount_a);
+        self.pair_reserve_b(pair_id).set(balance_b - &amount_b);
+        self.verify_k_not_decreased(pair_id, &self.compute_invariant(pair_id, &reserve_a, &reserve_b));
+        self.check_twap_guard(pair_id, &reserve_a, &reserve_b);
+        self.distribute_fee(pair_id, &fee_a, true);
+        self.distribute_fee(pair_id, &fee_b, false);
+        
+        self.flash_swap_event(pair_id, &caller, &amount_a, &amount_b, &repaid_a, &repaid_b);
+    }
+
+    /// Pay back a flash swap from inside flashSwapCallback
+    /// Accepts either pair token; may be called several times.
+    #[endpoint(repayFlashSwap)]
+    #[payable("*")]
+    fn repay_flash_swap(&self, pair_id: u64) {
+        require!(self.flash_swap_active(pair_id).get(), "No flash swap in progress");
+        
+        let (is_token_a, amount) = self.take_pair_payment(pair_id);
+        require!(amount > 0u64, "Amount must be > 0");
+        
+        if is_token_a {
+            self.flash_swap_repaid_a(pair_id).update(|r| *r += &amount);
+        } else {
+            self.flash_swap_repaid_b(pair_id).update(|r| *r += &amount);
+        }
+    }
+
+    // ========================================================================
+    // LIMIT ORDERS (Escrowed, filled against the AMM by keepers)
+    // ========================================================================
+    // Orders sell their full escrowed amount in one go. Prices are expressed as
+    // output per unit of input, scaled by PRECISION, net of the swap fee.
+
+    /// Place a limit order on a pair
+    /// Send the token to sell (KLV or KDA); the full payment is escrowed.
+    /// @param pair_id - The pair to trade on
+    /// @param min_price - Minimum output per input (scaled by PRECISION) to fill at
+    /// @param expires_at - Timestamp after which the order is refunded instead of filled (0 = never)
+    /// @return The new order id
+    #[endpoint(placeLimitOrder)]
+    #[payable("*")]
+    fn place_limit_order(&self, pair_id: u64, min_price: BigUint, expires_at: u64) -> u64 {
+        self.require_not_paused();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        require!(min_price > 0u64, "Price must be > 0");
+        
+        let now = self.blockchain().get_block_timestamp();
+        require!(expires_at == 0 || expires_at > now, "Expiry must be in the future");
+        
+        let caller = self.blockchain().get_caller();
+        require!(!self.compliance_restricted().contains(&caller), "Address is restricted from trading");
+        
+        let (is_a_to_b, sell_amount) = self.take_pair_payment(pair_id);
+        require!(sell_amount > 0u64, "Payment must be greater than 0");
+        
+        let order_id = self.next_limit_order_id().get() + 1;
+        self.next_limit_order_id().set(order_id);
+        
+        self.limit_order(order_id).set(LimitOrder {
+            owner: caller.clone(),
+            pair_id,
+            is_a_to_b,
+            sell_amount,
+            min_price,
+            expires_at,
+        });
+        self.pair_limit_orders(pair_id).insert(order_id);
+        self.user_limit_orders(&caller).insert(order_id);
+        
+        order_id
+    }
+
+    /// Cancel an open limit order and refund the escrow (order owner only)
+    #[endpoint(cancelLimitOrder)]
+    fn cancel_limit_order(&self, order_id: u64) {
+        require!(!self.limit_order(order_id).is_empty(), "Order does not exist");
+        let order = self.limit_order(order_id).get();
+        require!(order.owner == self.blockchain().get_caller(), "Not the order owner");
+        
+        self.refund_limit_order(order_id, &order);
+    }
+
+    /// Keeper crank: fill orders whose limit price the pool currently meets
+    /// Expired orders and orders of restricted owners met along the way are
+    /// refunded; orders the pair's guards would reject are skipped. Each call
+    /// resumes where the previous one stopped, so orders that can't be filled
+    /// don't starve the rest. Anyone can call this.
+    /// @param pair_id - The pair whose order book to process
+    /// @param max_orders - Maximum number of orders to inspect in this call
+    /// @return Number of orders filled
+    #[endpoint(fillOrders)]
+    fn fill_orders(&self, pair_id: u64, max_orders: u64) -> u64 {
+        let _guard = self.non_reentrant();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        require!(max_orders > 0 && max_orders <= MAX_PAGE_SIZE, "Invalid max_orders");
+        
+        let now = self.blockchain().get_block_timestamp();
+        let order_ids = self.next_order_batch(
+            self.pair_limit_orders(pair_id),
+            self.limit_order_cursor(pair_id),
+            max_orders,
+        );
+        
+        let mut filled = 0u64;
+        for order_id in order_ids.iter() {
+            let order = self.limit_order(order_id).get();
+            
+            if (order.expires_at != 0 && now > order.expires_at)
+                || self.compliance_restricted().contains(&order.owner)
+            {
+                self.refund_limit_order(order_id, &order);
+                continue;
+            }
+            
+            // Swaps are the owner's: their fee discount, limits and stats
+            if !self.swap_would_pass(pair_id, &order.owner, order.is_a_to_b, &order.sell_amount) {
+                continue;
+            }
+            let (quoted_out, _) = self.quote_swap_internal(pair_id, &order.owner, order.is_a_to_b, &order.sell_amount);
+            if &quoted_out * PRECISION / &order.sell_amount < order.min_price {
+                continue;
+            }
+            
+            let user_gets = self.swap_internal(pair_id, &order.owner, order.is_a_to_b, &order.sell_amount, &None);
+            
+            self.remove_limit_order(order_id, &order);
+            self.send_pair_token(&order.owner, pair_id, !order.is_a_to_b, &user_gets);
+            self.limit_order_filled_event(order_id, pair_id, &order.owner, &order.sell_amount, &user_gets);
+            filled += 1;
+        }
+        
+        filled
+    }
+
+    // ========================================================================
+    // DCA ORDERS (Recurring swaps executed by keepers)
+    // ========================================================================
+    // The whole budget is escrowed up front. Every `interval` seconds a keeper can
+    // swap the next chunk and earns the DCA keeper fee out of it; the output goes
+    // straight to the order owner.
+
+    /// Schedule a recurring swap
+    /// Send the full budget (KLV or KDA); it's sold in chunks of amount_per_swap.
+    /// @param interval - Seconds between executions (min MIN_DCA_INTERVAL)
+    /// @param min_price - Skip executions below this output per input (scaled by PRECISION, 0 = any)
+    /// @return The new order id
+    #[endpoint(createDcaOrder)]
+    #[payable("*")]
+    fn create_dca_order(&self, pair_id: u64, amount_per_swap: BigUint, interval: u64, min_price: BigUint) -> u64 {
+        self.require_not_paused();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        require!(interval >= MIN_DCA_INTERVAL, "Interval too short");
+        
+        let caller = self.blockchain().get_caller();
+        require!(!self.compliance_restricted().contains(&caller), "Address is restricted from trading");
+        
+        let (is_a_to_b, budget) = self.take_pair_payment(pair_id);
+        require!(amount_per_swap > 0u64, "Amount per swap must be > 0");
+        require!(budget >= amount_per_swap, "Budget below amount per swap");
+        
+        let order_id = self.next_dca_order_id().get() + 1;
+        self.next_dca_order_id().set(order_id);
+        
+        self.dca_order(order_id).set(DcaOrder {
+            owner: caller.clone(),
+            pair_id,
+            is_a_to_b,
+            amount_per_swap,
+            interval,
+            remaining: budget,
+            next_execution: self.blockchain().get_block_timestamp(),
+            min_price,
+        });
+        self.dca_order_ids().insert(order_id);
+        self.pair_dca_orders(pair_id).insert(order_id);
+        self.user_dca_orders(&caller).insert(order_id);
+        
+        order_id
+    }
+
+    /// Cancel a DCA order and refund the unspent budget (order owner only)
+    #[endpoint(cancelDcaOrder)]
+    fn cancel_dca_order(&self, order_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(!self.dca_order(order_id).is_empty(), "Order does not exist");
+        let order = self.dca_order(order_id).get();
+        require!(order.owner == self.blockchain().get_caller(), "Not the order owner");
+        
+        self.remove_dca_order(order_id, &order);
+        self.send_pair_token(&order.owner, order.pair_id, order.is_a_to_b, &order.remaining);
+    }
+
+    /// Keeper crank: execute DCA orders that are due
+    /// Orders on inactive pairs, below their min_price or whose chunk the pair's
+    /// guards would reject are skipped until a later call; orders of restricted
+    /// owners are refunded. Each call resumes where the
+    /// previous one stopped, so skipped orders don't starve the rest.
+    /// @param limit - Maximum number of orders to inspect in this call
+    /// @return Number of executions performed
+    #[endpoint(executeDcaOrders)]
+    fn execute_dca_orders(&self, limit: u64) -> u64 {
+        let _guard = self.non_reentrant();
+        self.require_not_paused();
+        require!(limit > 0 && limit <= MAX_PAGE_SIZE, "Invalid limit");
+        
+        let keeper = self.blockchain().get_caller();
+        let keeper_fee_bps = self.get_dca_keeper_fee_bps();
+        let now = self.blockchain().get_block_timestamp();
+        let order_ids = self.next_order_batch(self.dca_order_ids(), self.dca_order_cursor(), limit);
+        
+        let mut executed = 0u64;
+        for order_id in order_ids.iter() {
+            let mut order = self.dca_order(order_id).get();
+            let pair_id = order.pair_id;
+            if order.next_execution > now {
+                continue;
+            }
+            if self.compliance_restricted().contains(&order.owner) {
+                self.remove_dca_order(order_id, &order);
+                self.send_pair_token(&order.owner, pair_id, order.is_a_to_b, &order.remaining);
+                continue;
+            }
+            if !self.pair_swaps_enabled(pair_id).get() {
+                continue;
+            }
+            
+            let chunk = if order.remaining < order.amount_per_swap {
+                order.remaining.clone()
+            } else {
+                order.amount_per_swap.clone()
+            };
+            let keeper_fee = &chunk * keeper_fee_bps / BPS_DENOMINATOR;
+            let swap_amount = &chunk - &keeper_fee;
+            
+            // Swaps are the owner's: their fee discount, limits and stats
+            if !self.swap_would_pass(pair_id, &order.owner, order.is_a_to_b, &swap_amount) {
+                continue;
+            }
+            let (quoted_out, _) = self.quote_swap_internal(pair_id, &order.owner, order.is_a_to_b, &swap_amount);
+            if order.min_price > 0u64 && &quoted_out * PRECISION / &swap_amount < order.min_price {
+                continue;
+            }
+            
+            let user_gets = self.swap_internal(pair_id, &order.owner, order.is_a_to_b, &swap_amount, &None);
+            self.send_pair_token(&order.owner, pair_id, !order.is_a_to_b, &user_gets);
+            if keeper_fee > 0u64 {
+                self.send_pair_token(&keeper, pair_id, order.is_a_to_b, &keeper_fee);
+            }
+            self.dca_executed_event(order_id, pair_id, &order.owner, &swap_amount, &user_gets);
+            executed += 1;
+            
+            order.remaining -= &chunk;
+            if order.remaining == 0u64 {
+                self.remove_dca_order(order_id, &order);
+            } else {
+                order.next_execution = now + order.interval;
+                self.dca_order(order_id).set(&order);
+            }
+        }
+        
+        executed
+    }
+
+    // ========================================================================
+    // STREAMING SWAPS (TWAMM-lite: large swaps sold in chunks over time)
+    // ========================================================================
+    // The full input is escrowed and sold in `chunks` equal parts, at most one
+    // per `interval` seconds, so arbitrageurs can restore the price between
+    // chunks. Keepers earn the same fee as for DCA executions.
+
+    /// Open a streaming swap
+    /// Send the full input (KLV or KDA).
+    /// @param chunks - Number of parts to split the swap into (MIN_STREAM_CHUNKS..=MAX_STREAM_CHUNKS)
+    /// @param interval - Seconds between chunks (> 0)
+    /// @param min_price - Skip chunks below this output per input (scaled by PRECISION, 0 = any)
+    /// @return The new order id
+    #[endpoint(createStreamOrder)]
+    #[payable("*")]
+    fn create_stream_order(&self, pair_id: u64, chunks: u64, interval: u64, min_price: BigUint) -> u64 {
+        self.require_not_paused();
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_swaps_enabled(pair_id).get(), "Swaps are disabled for this pair");
+        require!(chunks >= MIN_STREAM_CHUNKS && chunks <= MAX_STREAM_CHUNKS, "Invalid number of chunks");
+        require!(interval > 0, "Interval must be > 0");
+        
+        let caller = self.blockchain().get_caller();
+        require!(!self.compliance_restricted().contains(&caller), "Address is restricted from trading");
+        
+        let (is_a_to_b, total_amount) = self.take_pair_payment(pair_id);
+        require!(total_amount >= chunks, "Amount too small to split");
+        
+        let order_id = self.next_stream_order_id().get() + 1;
+        self.next_stream_order_id().set(order_id);
+        
+        self.stream_order(order_id).set(StreamOrder {
+            owner: caller.clone(),
+            pair_id,
+            is_a_to_b,
+            total_amount,
+            chunks_total: chunks,
+            chunks_done: 0,
+            amount_sold: BigUint::zero(),
+            amount_received: BigUint::zero(),
+            interval,
+            next_execution: self.blockchain().get_block_timestamp(),
+            min_price,
+        });
+        self.stream_order_ids().insert(order_id);
+        self.pair_stream_orders(pair_id).insert(order_id);
+        self.user_stream_orders(&caller).insert(order_id);
+        
+        order_id
+    }
+
+    /// Cancel a streaming swap and refund the unsold input (order owner only)
+    #[endpoint(cancelStreamOrder)]
+    fn cancel_stream_order(&self, order_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(!self.stream_order(order_id).is_empty(), "Order does not exist");
+        let order = self.stream_order(order_id).get();
+        require!(order.owner == self.blockchain().get_caller(), "Not the order owner");
+        
+        self.remove_stream_order(order_id, &order);
+        let unsold = &order.total_amount - &order.amount_sold;
+        if unsold > 0u64 {
+            self.send_pair_token(&order.owner, order.pair_id, order.is_a_to_b, &unsold);
+        }
+    }
+
+    /// Keeper crank: execute the next chunk of every due streaming swap
+    /// Orders on inactive pairs, below their min_price or whose chunk the pair's
+    /// guards would reject are skipped until a later call; orders of restricted
+    /// owners are refunded. Each call resumes where the
+    /// previous one stopped instead of rescanning from the first order.
+    /// @param limit - Maximum number of orders to inspect in this call
+    /// @return Number of chunks executed
+    #[endpoint(executeStreamOrders)]
+    fn execute_stream_orders(&self, limit: u64) -> u64 {
+        let _guard = self.non_reentrant();
+        self.require_not_paused();
+        require!(limit > 0 && limit <= MAX_PAGE_SIZE, "Invalid limit");
+        
+        let keeper = self.blockchain().get_caller();
+        let keeper_fee_bps = self.get_dca_keeper_fee_bps();
+        let now = self.blockchain().get_block_timestamp();
+        let order_ids = self.next_order_batch(self.stream_order_ids(), self.stream_order_cursor(), limit);
+        
+        let mut executed = 0u64;
+        for order_id in order_ids.iter() {
+            let mut order = self.stream_order(order_id).get();
+            let pair_id = order.pair_id;
+            if order.next_execution > now {
+                continue;
+            }
+            if self.compliance_restricted().contains(&order.owner) {
+                self.remove_stream_order(order_id, &order);
+                self.send_pair_token(&order.owner, pair_id, order.is_a_to_b, &(&order.total_amount - &order.amount_sold));
+                continue;
+            }
+            if !self.pair_swaps_enabled(pair_id).get() {
+                continue;
+            }
+            
+            // The last chunk also takes the rounding remainder
+            let chunk = if order.chunks_done + 1 == order.chunks_total {
+                &order.total_amount - &order.amount_sold
+            } else {
+                &order.total_amount / order.chunks_total
+            };
+            let keeper_fee = &chunk * keeper_fee_bps / BPS_DENOMINATOR;
+            let swap_amount = &chunk - &keeper_fee;
+            
+            // Swaps are the owner's: their fee discount, limits and stats
+            if !self.swap_would_pass(pair_id, &order.owner, order.is_a_to_b, &swap_amount) {
+                continue;
+            }
+            let (quoted_out, _) = self.quote_swap_internal(pair_id, &order.owner, order.is_a_to_b, &swap_amount);
+            if order.min_price > 0u64 && &quoted_out * PRECISION / &swap_amount < order.min_price {
+                continue;
+            }
+            
+            let user_gets = self.swap_internal(pair_id, &order.owner, order.is_a_to_b, &swap_amount, &None);
+            self.send_pair_token(&order.owner, pair_id, !order.is_a_to_b, &user_gets);
+            if keeper_fee > 0u64 {
+                self.send_pair_token(&keeper, pair_id, order.is_a_to_b, &keeper_fee);
+            }
+            executed += 1;
+            
+            order.chunks_done += 1;
+            order.amount_sold += &chunk;
+            order.amount_received += &user_gets;
+            self.stream_chunk_executed_event(order_id, pair_id, &order.owner, order.chunks_done, &swap_amount, &user_gets);
+            
+            if order.chunks_done == order.chunks_total {
+                self.remove_stream_order(order_id, &order);
+            } else {
+                order.next_execution = now + order.interval;
+                self.stream_order(order_id).set(&order);
+            }
+        }
+        
+        executed
+    }
+
+    // ========================================================================
+    // TRADING COMPETITIONS (Time-boxed volume epochs with a reward pot)
+    // ========================================================================
+    // One competition per pair at a time. Swap volume (in token A) made between
+    // start and end accrues per trader; once closed, either every trader or only
+    // the top N by volume claim the pot pro rata to their volume. Swaps are
+    // credited to the trader they're made for, like getUserTradeStats.
+
+    /// Open a competition on a pair with the sent tokens as its pot (owner or TREASURY_MANAGER)
+    /// @param top_n - 0 to share the pot among all traders, else only the N largest volumes (max MAX_COMPETITION_TOP_N)
+    /// @return The competition id
+    #[endpoint(openCompetition)]
+    #[payable("*")]
+    fn open_competition(&self, pair_id: u64, start: u64, end: u64, top_n: u64) -> u64 {
+        self.require_role(ROLE_TREASURY_MANAGER);
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_active_competition(pair_id).is_empty(), "Pair already has an open competition");
+        require!(start < end && end > self.blockchain().get_block_timestamp(), "Invalid competition period");
+        require!(top_n <= MAX_COMPETITION_TOP_N, "Top N above limit");
+        
+        let (reward_token, reward_amount) = self.get_single_payment();
+        require!(reward_amount > 0u64, "Amount must be > 0");
+        
+        let competition_id = self.next_competition_id().get() + 1;
+        self.next_competition_id().set(competition_id);
+        
+        self.competition_outstanding(&reward_token).update(|o| *o += &reward_amount);
+        self.competition(competition_id).set(Competition {
+            pair_id,
+            reward_token,
+            reward_amount,
+            start,
+            end,
+            top_n,
+            total_volume: BigUint::zero(),
+            reward_volume: BigUint::zero(),
+            claimed_amount: BigUint::zero(),
+            closed: false,
+        });
+        self.pair_active_competition(pair_id).set(competition_id);
+        
+        competition_id
+    }
+
+    /// Close a competition once it has ended and fix its payout (anyone)
+    /// A competition nobody traded in returns its pot to the treasury.
+    #[endpoint(closeCompetition)]
+    fn close_competition(&self, competition_id: u64) {
+        let _guard = self.non_reentrant();
+        require!(!self.competition(competition_id).is_empty(), "Competition does not exist");
+        let mut competition = self.competition(competition_id).get();
+        require!(!competition.closed, "Competition already closed");
+        require!(self.blockchain().get_block_timestamp() >= competition.end, "Competition not over");
+        
+        competition.reward_volume = if competition.top_n == 0 {
+            competition.total_volume.clone()
+        } else {
+            let mut ranked_volume = BigUint::zero();
+            for entry in self.competition_top(competition_id).get().iter() {
+                ranked_volume += entry.volume;
+            }
+            ranked_volume
+        };
+        competition.closed = true;
+        self.pair_active_competition(competition.pair_id).clear();
+        
+        if competition.reward_volume == 0u64 {
+            competition.claimed_amount = competition.reward_amount.clone();
+            self.competition_outstanding(&competition.reward_token).update(|o| *o -= &competition.reward_amount);
+            self.send_wrapped(&self.get_treasury_or_admin(), &competition.reward_token, &competition.reward_amount);
+        }
+        self.competition(competition_id).set(&competition);
+    }
+
+    /// Claim the caller's reward from a closed competition
+    #[endpoint(claimCompetitionReward)]
+    fn claim_competition_reward(&self, competition_id: u64) -> BigUint {
+        let _guard = self.non_reentrant();
+        require!(!self.competition(competition_id).is_empty(), "Competition does not exist");
+        require!(self.competition(competition_id).get().closed, "Competition not closed");
+        
+        let caller = self.blockchain().get_caller();
+        let amount = self.get_competition_claimable(competition_id, &caller);
+        require!(amount > 0u64, "Nothing to claim");
+        
+        self.competition_claimed(competition_id, &caller).set(true);
+        let mut competition = self.competition(competition_id).get();
+        competition.claimed_amount += &amount;
+        self.competition(competition_id).set(&competition);
+        self.competition_outstanding(&competition.reward_token).update(|o| *o -= &amount);
+        
+        self.send_wrapped(&caller, &competition.reward_token, &amount);
+        self.competition_reward_claimed_event(competition_id, &caller, &amount);
+        amount
+    }
+}
@@ -0,0 +1,392 @@
+// ============================================================================
+// TYPES - Structs and enums stored, returned or passed to endpoints
+// ============================================================================
+
+use klever_sc::imports::*;
+use klever_sc::derive_imports::*;
+
+/// Sensitive admin operation queued behind the timelock
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub enum AdminAction<M: ManagedTypeApi> {
+    /// (pair_id, fee_bps)
+    SetPairFee(u64, u64),
+    /// (pair_id, new_token, is_klv)
+    UpdatePairTokenA(u64, TokenIdentifier<M>, bool),
+    /// (pair_id, new_token, is_klv)
+    UpdatePairTokenB(u64, TokenIdentifier<M>, bool),
+    SetTreasuryAddress(ManagedAddress<M>),
+    /// New delay in seconds (>= MIN_ADMIN_TIMELOCK_DELAY)
+    SetTimelockDelay(u64),
+    /// Fee splitter recipients (empty = off, see FeeSplitRecipient)
+    SetFeeSplit(ManagedVec<M, FeeSplitRecipient<M>>),
+}
+
+/// One recipient of the protocol fee splitter (team, treasury, burn address...)
+/// Weights of all recipients add up to BPS_DENOMINATOR.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, ManagedVecItem, Clone)]
+pub struct FeeSplitRecipient<M: ManagedTypeApi> {
+    pub address: ManagedAddress<M>,
+    pub weight_bps: u64,
+}
+
+/// Swap fee discount for stakers holding at least `min_stake` (see setStakingTiers)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, ManagedVecItem, Clone)]
+pub struct StakingTier<M: ManagedTypeApi> {
+    pub min_stake: BigUint<M>,
+    /// Discount in bps of the pair's swap fee
+    pub discount_bps: u64,
+}
+
+/// Pricing curve of a pair
+/// Pairs created before pool types existed decode as ConstantProduct.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone, Copy, PartialEq, Eq)]
+pub enum PoolType {
+    /// x * y = k
+    ConstantProduct,
+    /// Curve-style StableSwap invariant for pegged assets (same decimals on both sides)
+    Stable,
+    /// Balancer-style x^wa * y^wb = k with asymmetric weights (e.g. 80/20)
+    Weighted,
+}
+
+/// Fee rebate for traders with at least `min_volume` over the rebate window (see setPairRebateTiers)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, ManagedVecItem, Clone)]
+pub struct RebateTier<M: ManagedTypeApi> {
+    /// Rolling volume, in the pair's token A
+    pub min_volume: BigUint<M>,
+    /// Rebate in bps of the swap fee paid
+    pub rebate_bps: u64,
+}
+
+/// One day of a trader's volume on a pair
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, ManagedVecItem, Clone)]
+pub struct VolumeBucket<M: ManagedTypeApi> {
+    pub day: u64,
+    pub volume: BigUint<M>,
+}
+
+/// Weight schedule of a liquidity bootstrapping pool (see createLbpPair)
+/// Token A's weight moves linearly from start to end weight between the two times.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct LbpSchedule {
+    pub start_weight_a: u64,
+    pub end_weight_a: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+/// Full pair snapshot returned by paginated views
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct PairInfo<M: ManagedTypeApi> {
+    pub pair_id: u64,
+    pub token_a: TokenIdentifier<M>,
+    pub token_b: TokenIdentifier<M>,
+    pub token_a_is_klv: bool,
+    pub token_b_is_klv: bool,
+    pub reserve_a: BigUint<M>,
+    pub reserve_b: BigUint<M>,
+    pub fee_bps: u64,
+    pub is_active: bool,
+    pub creator: ManagedAddress<M>,
+    pub total_shares: BigUint<M>,
+    pub lp_count: usize,
+    pub is_verified: bool,
+}
+
+/// Community-supplied display info for a pair (see setPairMetadata)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct PairMetadata<M: ManagedTypeApi> {
+    pub name: ManagedBuffer<M>,
+    pub logo_uri: ManagedBuffer<M>,
+    pub project_link: ManagedBuffer<M>,
+    pub updated_at: u64,
+}
+
+/// One entry of a pair's fee history (see getFeeHistory)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct FeeChange {
+    pub timestamp: u64,
+    pub old_fee_bps: u64,
+    pub new_fee_bps: u64,
+    /// First block the new fee applies to
+    pub activation_block: u64,
+}
+
+/// Snapshot of a pair's price accumulator (see getPairTwap)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct TwapObservation<M: ManagedTypeApi> {
+    /// Sum of spot_price * seconds, spot price of A in B scaled by PRECISION
+    pub price_cumulative: BigUint<M>,
+    pub timestamp: u64,
+}
+
+/// Escrowed limit order, filled by keepers once the pool price reaches min_price
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct LimitOrder<M: ManagedTypeApi> {
+    pub owner: ManagedAddress<M>,
+    pub pair_id: u64,
+    pub is_a_to_b: bool,
+    pub sell_amount: BigUint<M>,
+    /// Output per input after fee, scaled by PRECISION
+    pub min_price: BigUint<M>,
+    /// 0 = no expiry
+    pub expires_at: u64,
+}
+
+/// Governance-maintained multi-hop path, published under an alias (see setRoute)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct RegisteredRoute<M: ManagedTypeApi> {
+    pub token_in: TokenIdentifier<M>,
+    pub token_out: TokenIdentifier<M>,
+    /// Ordered pair_ids, as passed to swapExactInMultiHop
+    pub path: ManagedVec<M, u64>,
+}
+
+/// Swap signed by a user and submitted by a relayer (see swapOnBehalf)
+/// The input is taken from the user's relayer allowance.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct MetaSwapOrder<M: ManagedTypeApi> {
+    pub user: ManagedAddress<M>,
+    pub pair_id: u64,
+    pub token_in: TokenIdentifier<M>,
+    pub amount_in: BigUint<M>,
+    pub min_out: BigUint<M>,
+    /// Must equal the user's next meta-swap nonce
+    pub nonce: u64,
+    /// Last timestamp the order may be submitted at
+    pub expiry: u64,
+}
+
+/// Recurring swap funded from an escrowed budget, executed by keepers
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct DcaOrder<M: ManagedTypeApi> {
+    pub owner: ManagedAddress<M>,
+    pub pair_id: u64,
+    pub is_a_to_b: bool,
+    /// Input sold per execution (the last one may be smaller)
+    pub amount_per_swap: BigUint<M>,
+    /// Seconds between executions
+    pub interval: u64,
+    /// Escrowed budget not yet swapped
+    pub remaining: BigUint<M>,
+    pub next_execution: u64,
+    /// Output per input after fee, scaled by PRECISION (0 = any price)
+    pub min_price: BigUint<M>,
+}
+
+/// Large swap split into equal chunks executed by keepers over time (TWAMM-lite)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct StreamOrder<M: ManagedTypeApi> {
+    pub owner: ManagedAddress<M>,
+    pub pair_id: u64,
+    pub is_a_to_b: bool,
+    pub total_amount: BigUint<M>,
+    pub chunks_total: u64,
+    pub chunks_done: u64,
+    /// Input spent so far, keeper fees included
+    pub amount_sold: BigUint<M>,
+    pub amount_received: BigUint<M>,
+    /// Seconds between chunks
+    pub interval: u64,
+    pub next_execution: u64,
+    /// Output per input after fee, scaled by PRECISION (0 = any price)
+    pub min_price: BigUint<M>,
+}
+
+/// Accounting invariants of one pair, returned by auditPair
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct PairAudit<M: ManagedTypeApi> {
+    pub reserve_a: BigUint<M>,
+    pub reserve_b: BigUint<M>,
+    /// Everything the pair owes: reserves, pending deposits, unclaimed fees, IL fund, orders
+    pub obligations_a: BigUint<M>,
+    pub obligations_b: BigUint<M>,
+    /// Contract balance left for this pair once every other obligation is covered
+    pub attributable_a: BigUint<M>,
+    pub attributable_b: BigUint<M>,
+    pub owner_shares: BigUint<M>,
+    pub total_lp_shares: BigUint<M>,
+    /// Sum of the individual LP positions (should equal total_lp_shares)
+    pub lp_shares_sum: BigUint<M>,
+    /// Owner + treasury unclaimed protocol fees
+    pub protocol_unclaimed_a: BigUint<M>,
+    pub protocol_unclaimed_b: BigUint<M>,
+    pub lp_unclaimed_a: BigUint<M>,
+    pub lp_unclaimed_b: BigUint<M>,
+    /// attributable_x < obligations_x
+    pub underfunded_a: bool,
+    pub underfunded_b: bool,
+    pub shares_mismatch: bool,
+    /// Legacy owner_shares left over from V4 single-sided deposits
+    pub has_legacy_owner_shares: bool,
+    /// Any of the flags above
+    pub mismatch: bool,
+}
+
+/// Reward airdropped to a pair's LPs pro rata to their shares at snapshot time
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct LpAirdrop<M: ManagedTypeApi> {
+    pub pair_id: u64,
+    pub reward_token: TokenIdentifier<M>,
+    pub reward_amount: BigUint<M>,
+    pub snapshot_block: u64,
+    /// Sum of the snapshotted shares
+    pub total_shares: BigUint<M>,
+    pub claimed_amount: BigUint<M>,
+    /// After this timestamp the unclaimed remainder can be swept (closeLpAirdrop)
+    pub claim_deadline: u64,
+}
+
+/// Bonding-curve sale of a new token that graduates into a KLV pair (see createLaunch)
+/// The curve is constant product over (virtual_klv + raised, sale_supply - sold).
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct Launch<M: ManagedTypeApi> {
+    pub creator: ManagedAddress<M>,
+    pub token: TokenIdentifier<M>,
+    /// Tokens sold along the curve
+    pub sale_supply: BigUint<M>,
+    /// Tokens set aside for the pair at graduation (on top of the unsold curve tokens)
+    pub liquidity_supply: BigUint<M>,
+    pub sold: BigUint<M>,
+    pub raised: BigUint<M>,
+    /// KLV raise that graduates the launch
+    pub target: BigUint<M>,
+    /// KLV the curve pretends to hold; sets the starting price (virtual_klv / sale_supply)
+    pub virtual_klv: BigUint<M>,
+    pub fee_bps: u64,
+    /// LOCK_TIERS period the creator's LP shares are locked for at graduation
+    pub lock_days: u64,
+    /// Pair created at graduation (0 while the sale runs)
+    pub pair_id: u64,
+}
+
+/// Linear vesting of deposited tokens to a beneficiary (see createVestingSchedule)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct VestingSchedule<M: ManagedTypeApi> {
+    pub beneficiary: ManagedAddress<M>,
+    pub token: TokenIdentifier<M>,
+    /// Cut down to the vested amount when revoked
+    pub total_amount: BigUint<M>,
+    pub claimed_amount: BigUint<M>,
+    pub start: u64,
+    /// Seconds after start before anything can be claimed
+    pub cliff: u64,
+    /// Seconds after start until everything has vested
+    pub duration: u64,
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+/// Time-boxed trading competition on one pair (see openCompetition)
+/// Volume is measured in the pair's token A.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct Competition<M: ManagedTypeApi> {
+    pub pair_id: u64,
+    pub reward_token: TokenIdentifier<M>,
+    pub reward_amount: BigUint<M>,
+    pub start: u64,
+    pub end: u64,
+    /// 0 = every trader shares the pot pro rata; N = only the N largest volumes do
+    pub top_n: u64,
+    pub total_volume: BigUint<M>,
+    /// Volume the pot is split over, fixed when the competition is closed
+    pub reward_volume: BigUint<M>,
+    pub claimed_amount: BigUint<M>,
+    pub closed: bool,
+}
+
+/// A trader's volume in a competition leaderboard
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, ManagedVecItem, Clone)]
+pub struct CompetitionEntry<M: ManagedTypeApi> {
+    pub address: ManagedAddress<M>,
+    pub volume: BigUint<M>,
+}
+
+/// Attributes of an LP position NFT, as shown by wallets (see wrapLockedPosition)
+/// A snapshot taken at wrap time; the NFT is burned whenever its shares leave the position.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct LpPositionAttributes<M: ManagedTypeApi> {
+    pub pair_id: u64,
+    pub shares: BigUint<M>,
+    pub lock_until: u64,
+    pub boost_bps: u64,
+}
+
+/// Pair-level state carried across deployments (see exportPairState / importPairState)
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct PairState<M: ManagedTypeApi> {
+    pub token_a: TokenIdentifier<M>,
+    pub token_b: TokenIdentifier<M>,
+    pub token_a_is_klv: bool,
+    pub token_b_is_klv: bool,
+    pub token_nonce_a: u64,
+    pub token_nonce_b: u64,
+    pub creator: ManagedAddress<M>,
+    pub pool_type: PoolType,
+    pub amplification: u64,
+    pub weight_a: u64,
+    pub fee_bps: u64,
+    pub fee_on_input: bool,
+    pub swaps_enabled: bool,
+    pub liquidity_enabled: bool,
+    pub reserve_a: BigUint<M>,
+    pub reserve_b: BigUint<M>,
+    pub owner_shares: BigUint<M>,
+    pub total_lp_shares: BigUint<M>,
+    pub locked_liquidity: BigUint<M>,
+    pub fee_per_share_a: BigUint<M>,
+    pub fee_per_share_b: BigUint<M>,
+    pub boost_fee_per_weight_a: BigUint<M>,
+    pub boost_fee_per_weight_b: BigUint<M>,
+    pub owner_unclaimed_fees_a: BigUint<M>,
+    pub owner_unclaimed_fees_b: BigUint<M>,
+    pub treasury_unclaimed_fees_a: BigUint<M>,
+    pub treasury_unclaimed_fees_b: BigUint<M>,
+    pub pending_total_a: BigUint<M>,
+    pub pending_total_b: BigUint<M>,
+    pub il_fund_a: BigUint<M>,
+    pub il_fund_b: BigUint<M>,
+    pub volume_a: BigUint<M>,
+    pub volume_b: BigUint<M>,
+    pub total_fees_a: BigUint<M>,
+    pub total_fees_b: BigUint<M>,
+    pub swap_count: u64,
+}
+
+/// One address's position in a pair: LP shares, lock and pending deposits
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone)]
+pub struct PositionState<M: ManagedTypeApi> {
+    pub address: ManagedAddress<M>,
+    pub shares: BigUint<M>,
+    pub entry_index_a: BigUint<M>,
+    pub entry_index_b: BigUint<M>,
+    pub locked_shares: BigUint<M>,
+    pub lock_until: u64,
+    pub lock_boost_bps: u64,
+    pub boost_entry_a: BigUint<M>,
+    pub boost_entry_b: BigUint<M>,
+    pub deposited_a: BigUint<M>,
+    pub deposited_b: BigUint<M>,
+    pub deposit_timestamp: u64,
+    pub pending_a: BigUint<M>,
+    pub pending_b: BigUint<M>,
+    pub pending_deposited_at: u64,
+}
+
+/// Holds the reentrancy lock for the rest of an endpoint and releases it when dropped
+/// Bind it to a named variable (`let _guard = ...`) - `let _ = ...` drops it at once.
+pub struct ReentrancyGuard<SA: StorageMapperApi> {
+    lock: SingleValueMapper<SA, bool>,
+}
+
+impl<SA: StorageMapperApi> ReentrancyGuard<SA> {
+    pub(crate) fn new(lock: SingleValueMapper<SA, bool>) -> Self {
+        ReentrancyGuard { lock }
+    }
+}
+
+impl<SA: StorageMapperApi> Drop for ReentrancyGuard<SA> {
+    fn drop(&mut self) {
+        self.lock.clear();
+    }
+}
@@ -0,0 +1,2148 @@
+// ============================================================================
+// VIEWS - Read-only queries
+// ============================================================================
+// Everything the frontend and integrators read. Views never write storage.
+// ============================================================================
+
+use klever_sc::imports::*;
+
+use crate::constants::*;
+use crate::types::*;
+
+#[klever_sc::module]
+pub trait ViewsModule: crate::common::CommonModule {
+    // ========================================================================
+    // VIEW FUNCTIONS
+    // ========================================================================
+
+    #[view(getReserves)]
+    fn get_reserves(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        (
+            self.pair_reserve_a(pair_id).get(),
+            self.pair_reserve_b(pair_id).get(),
+        ).into()
+    }
+
+    /// Returns (token_a, token_b, token_a_is_klv, token_b_is_klv, reserve_a, reserve_b, fee_bps, is_active, is_verified)
+    /// is_active = swaps enabled; see getPairPermissions for the liquidity switch.
+    #[view(getPairInfo)]
+    fn get_pair_info(&self, pair_id: u64) -> MultiValue9<TokenIdentifier, TokenIdentifier, bool, bool, BigUint, BigUint, u64, bool, bool> {
+        (
+            self.pair_token_a(pair_id).get(),
+            self.pair_token_b(pair_id).get(),
+            self.pair_token_a_is_klv(pair_id).get(),
+            self.pair_token_b_is_klv(pair_id).get(),
+            self.pair_reserve_a(pair_id).get(),
+            self.pair_reserve_b(pair_id).get(),
+            self.get_effective_fee_bps(pair_id),
+            self.pair_swaps_enabled(pair_id).get(),
+            self.verified_pair_ids().contains(&pair_id),
+        ).into()
+    }
+
+    /// Pairs the owner has marked as official (see setPairVerified)
+    #[view(getVerifiedPairs)]
+    fn get_verified_pairs(&self) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.verified_pair_ids().iter() {
+            result.push(pair_id);
+        }
+        result
+    }
+
+    /// V5: Extended pair info including creator
+    #[view(getPairInfoExtended)]
+    fn get_pair_info_extended(&self, pair_id: u64) -> MultiValue9<TokenIdentifier, TokenIdentifier, bool, bool, BigUint, BigUint, u64, bool, ManagedAddress> {
+        (
+            self.pair_token_a(pair_id).get(),
+            self.pair_token_b(pair_id).get(),
+            self.pair_token_a_is_klv(pair_id).get(),
+            self.pair_token_b_is_klv(pair_id).get(),
+            self.pair_reserve_a(pair_id).get(),
+            self.pair_reserve_b(pair_id).get(),
+            self.get_effective_fee_bps(pair_id),
+            self.pair_swaps_enabled(pair_id).get(),
+            self.get_pair_creator_or_owner(pair_id),
+        ).into()
+    }
+
+    /// KDA nonces of both sides of a pair (0 = fungible token or KLV)
+    /// Returns (nonce_a, nonce_b)
+    #[view(getPairTokenNonces)]
+    fn get_pair_token_nonces(&self, pair_id: u64) -> MultiValue2<u64, u64> {
+        (
+            self.pair_token_nonce_a(pair_id).get(),
+            self.pair_token_nonce_b(pair_id).get(),
+        ).into()
+    }
+
+    /// Current and scheduled fee for a pair
+    /// Returns (current_fee_bps, pending_fee_bps, activation_block)
+    /// pending_fee and activation_block are 0 when no change is scheduled.
+    /// Frontends should warn users when a pending fee is about to activate.
+    #[view(getPairFees)]
+    fn get_pair_fees(&self, pair_id: u64) -> MultiValue3<u64, u64, u64> {
+        let current_fee = self.get_effective_fee_bps(pair_id);
+        let current_block = self.blockchain().get_block_nonce();
+        let activation_block = self.pair_fee_activation_block(pair_id).get();
+        
+        if self.pair_pending_fee_bps(pair_id).is_empty() || current_block >= activation_block {
+            return (current_fee, 0u64, 0u64).into();
+        }
+        
+        (current_fee, self.pair_pending_fee_bps(pair_id).get(), activation_block).into()
+    }
+
+    /// Whether a pair takes its swap fee from the input token (see setPairFeeOnInput)
+    #[view(isPairFeeOnInput)]
+    fn is_pair_fee_on_input(&self, pair_id: u64) -> bool {
+        self.pair_fee_on_input(pair_id).get()
+    }
+
+    /// Every fee change scheduled on a pair, oldest first
+    #[view(getFeeHistory)]
+    fn get_fee_history(&self, pair_id: u64) -> MultiValueEncoded<FeeChange> {
+        let mut result = MultiValueEncoded::new();
+        for change in self.pair_fee_history(pair_id).iter() {
+            result.push(change);
+        }
+        result
+    }
+
+    /// All pairs with pair_id >= start_id, up to `limit` entries (max 100)
+    /// Lets the frontend load every pair in one or two calls instead of one per pair.
+    /// To fetch the next page, pass (last returned pair_id + 1) as start_id.
+    #[view(getPairsInfoPaginated)]
+    fn get_pairs_info_paginated(&self, start_id: u64, limit: u64) -> MultiValueEncoded<PairInfo<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        let limit = if limit > MAX_PAGE_SIZE { MAX_PAGE_SIZE } else { limit };
+        let next_pair_id = self.next_pair_id().get();
+        
+        let mut pair_id = if start_id == 0 { 1 } else { start_id };
+        let mut count = 0u64;
+        while pair_id < next_pair_id && count < limit {
+            if self.pair_exists(pair_id) {
+                result.push(self.build_pair_info(pair_id));
+                count += 1;
+            }
+            pair_id += 1;
+        }
+        result
+    }
+
+    /// Pair snapshots for an arbitrary list of pair ids (max 100 per call)
+    /// Replaces the V2-era numbered views (getReservesPair1..N etc.): one call
+    /// with real arguments instead of one parameter-less endpoint per pair.
+    /// Unknown ids are skipped.
+    #[view(getManyPairInfo)]
+    fn get_many_pair_info(&self, pair_ids: MultiValueEncoded<u64>) -> MultiValueEncoded<PairInfo<Self::Api>> {
+        require!(pair_ids.len() as u64 <= MAX_PAGE_SIZE, "Too many pair ids");
+        
+        let mut result = MultiValueEncoded::new();
+        for pair_id in pair_ids.into_iter() {
+            if self.pair_exists(pair_id) {
+                result.push(self.build_pair_info(pair_id));
+            }
+        }
+        result
+    }
+
+    /// Snapshots of every pair currently open for trading (archived pairs are never active)
+    #[view(getAllActivePairs)]
+    fn get_all_active_pairs(&self) -> MultiValueEncoded<PairInfo<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.registered_pair_ids().iter() {
+            if self.pair_swaps_enabled(pair_id).get() {
+                result.push(self.build_pair_info(pair_id));
+            }
+        }
+        result
+    }
+
+    /// Which operations a pair currently allows
+    /// Returns (can_swap, can_add_liquidity, can_withdraw_pending, can_remove_liquidity)
+    /// 
+    /// Swaps and adding liquidity (mint, depositPending*, finalizeLiquidity) are
+    /// switched separately (see setPairSwapsEnabled / setPairLiquidityEnabled);
+    /// users can always take funds out.
+    #[view(getPairPermissions)]
+    fn get_pair_permissions(&self, pair_id: u64) -> MultiValue4<bool, bool, bool, bool> {
+        if !self.pair_exists(pair_id) {
+            return (false, false, false, false).into();
+        }
+        
+        let paused = self.globally_paused().get();
+        (
+            self.pair_swaps_enabled(pair_id).get() && !paused,
+            self.pair_liquidity_enabled(pair_id).get() && !paused,
+            true,
+            true,
+        ).into()
+    }
+
+    /// Trading statistics for a pair
+    /// Returns (volume_a, volume_b, volume_a_24h, volume_b_24h, fees_a, fees_b, swap_count, last_swap_timestamp)
+    /// Volumes and fees are cumulative since tracking started; 24h values are
+    /// approximated from daily buckets.
+    #[view(getPairStats)]
+    fn get_pair_stats(&self, pair_id: u64) -> MultiValue8<BigUint, BigUint, BigUint, BigUint, BigUint, BigUint, u64, u64> {
+        let (volume_a_24h, volume_b_24h) = self.get_rolling_24h_volume(pair_id);
+        (
+            self.pair_volume_a(pair_id).get(),
+            self.pair_volume_b(pair_id).get(),
+            volume_a_24h,
+            volume_b_24h,
+            self.pair_total_fees_a(pair_id).get(),
+            self.pair_total_fees_b(pair_id).get(),
+            self.pair_swap_count(pair_id).get(),
+            self.pair_last_swap_timestamp(pair_id).get(),
+        ).into()
+    }
+
+    /// An address's trading on a pair since tracking started
+    /// Returns (volume_a, volume_b, swap_count, last_swap_timestamp)
+    /// Swaps are credited to the trader they're made for: orders filled by a keeper
+    /// (limit, DCA, streaming) count for the order owner, relayed meta-swaps for
+    /// the signer. Protocol buybacks count for nobody.
+    #[view(getUserTradeStats)]
+    fn get_user_trade_stats(&self, addr: ManagedAddress, pair_id: u64) -> MultiValue4<BigUint, BigUint, u64, u64> {
+        (
+            self.user_volume_a(&addr, pair_id).get(),
+            self.user_volume_b(&addr, pair_id).get(),
+            self.user_swap_count(&addr, pair_id).get(),
+            self.user_last_swap_timestamp(&addr, pair_id).get(),
+        ).into()
+    }
+
+    /// Contract-wide dashboard figures in one call
+    /// Returns (pair_count, active_pair_count, klv_reserves, protocol_fees_klv, swap_count)
+    /// klv_reserves sums the KLV side of every pair (KLV TVL is twice that).
+    /// protocol_fees_klv only counts fees charged in KLV, since other tokens
+    /// cannot be summed; per-pair fees are in getPairStats.
+    #[view(getContractStats)]
+    fn get_contract_stats(&self) -> MultiValue5<u64, u64, BigUint, BigUint, u64> {
+        let mut active_pair_count = 0u64;
+        let mut klv_reserves = BigUint::zero();
+        for pair_id in self.registered_pair_ids().iter() {
+            if self.pair_swaps_enabled(pair_id).get() {
+                active_pair_count += 1;
+            }
+            if self.pair_token_a_is_klv(pair_id).get() {
+                klv_reserves += self.pair_reserve_a(pair_id).get();
+            }
+            if self.pair_token_b_is_klv(pair_id).get() {
+                klv_reserves += self.pair_reserve_b(pair_id).get();
+            }
+        }
+        
+        (
+            self.registered_pair_ids().len() as u64,
+            active_pair_count,
+            klv_reserves,
+            self.total_protocol_fees_klv().get(),
+            self.total_swap_count().get(),
+        ).into()
+    }
+
+    /// Volume of a pair for a single UTC day (day = timestamp / 86400)
+    /// Returns (volume_a, volume_b)
+    #[view(getPairDailyVolume)]
+    fn get_pair_daily_volume(&self, pair_id: u64, day: u64) -> MultiValue2<BigUint, BigUint> {
+        (
+            self.pair_daily_volume_a(pair_id, day).get(),
+            self.pair_daily_volume_b(pair_id, day).get(),
+        ).into()
+    }
+
+    /// Estimated annualized fee APR of a pair's LPs, from its recent volume
+    /// Averages the token A volume of the last `window_days` complete UTC days
+    /// (max MAX_APR_WINDOW_DAYS), charges the pair's current fee minus the protocol
+    /// cut and annualizes it against the pool's value in token A. Staking
+    /// discounts, rebates and referral cuts are not deducted.
+    /// Returns (apr_bps, window_volume_a, tvl_a); apr_bps is 0 for an empty pool.
+    #[view(getPairApr)]
+    fn get_pair_apr(&self, pair_id: u64, window_days: u64) -> MultiValue3<BigUint, BigUint, BigUint> {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(window_days > 0 && window_days <= MAX_APR_WINDOW_DAYS, "Invalid window");
+        
+        let today = self.blockchain().get_block_timestamp() / SECONDS_PER_DAY;
+        let first_day = if today > window_days { today - window_days } else { 0 };
+        let mut volume_a = BigUint::zero();
+        for day in first_day..today {
+            volume_a += self.pair_daily_volume_a(pair_id, day).get();
+        }
+        
+        // Pool value in token A: stable pools trade near 1:1, the others hold
+        // their sides in proportion to the weights (50/50 for constant product)
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let tvl_a = if self.pair_pool_type(pair_id).get() == PoolType::Stable {
+            &reserve_a + &self.pair_reserve_b(pair_id).get()
+        } else {
+            let (weight_a, weight_b) = self.get_reduced_weights(pair_id, true);
+            &reserve_a * (weight_a + weight_b) as u64 / weight_a as u64
+        };
+        if tvl_a == 0u64 {
+            return (BigUint::zero(), volume_a, tvl_a).into();
+        }
+        
+        let lp_fees_a = &volume_a * self.get_pair_fee_bps(pair_id) / BPS_DENOMINATOR
+            * (BPS_DENOMINATOR - self.get_protocol_fee_bps())
+            / BPS_DENOMINATOR;
+        let apr_bps = lp_fees_a * 365u64 * BPS_DENOMINATOR / (&tvl_a * window_days);
+        (apr_bps, volume_a, tvl_a).into()
+    }
+
+    #[view(getTotalShares)]
+    fn get_total_shares(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        (
+            self.owner_shares(pair_id).get(),
+            self.total_lp_shares(pair_id).get(),
+        ).into()
+    }
+
+    #[view(getOwnerFees)]
+    fn get_owner_fees(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        (
+            self.owner_unclaimed_fees_a(pair_id).get(),
+            self.owner_unclaimed_fees_b(pair_id).get(),
+        ).into()
+    }
+
+    #[view(getLpPosition)]
+    fn get_lp_position(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue4<BigUint, BigUint, BigUint, BigUint> {
+        let shares = self.lp_shares(pair_id, &addr).get();
+        let total_shares = self.get_total_shares_internal(pair_id);
+        let precision = BigUint::from(PRECISION);
+        
+        let pending_a = if shares > BigUint::zero() && self.lp_list(pair_id).contains(&addr) {
+            let current_index = self.fee_per_share_a(pair_id).get();
+            let entry_index = self.lp_entry_index_a(pair_id, &addr).get();
+            if current_index > entry_index {
+                (&current_index - &entry_index) * &shares / &precision
+            } else {
+                BigUint::zero()
+            }
+        } else {
+            BigUint::zero()
+        };
+        
+        let pending_b = if shares > BigUint::zero() && self.lp_list(pair_id).contains(&addr) {
+            let current_index = self.fee_per_share_b(pair_id).get();
+            let entry_index = self.lp_entry_index_b(pair_id, &addr).get();
+            if current_index > entry_index {
+                (&current_index - &entry_index) * &shares / &precision
+            } else {
+                BigUint::zero()
+            }
+        } else {
+            BigUint::zero()
+        };
+        
+        let pool_share_pct = if total_shares > BigUint::zero() {
+            shares.clone() * BigUint::from(100_000_000u64) / &total_shares
+        } else {
+            BigUint::zero()
+        };
+        
+        let (boost_a, boost_b) = self.get_pending_boost_fees(pair_id, &addr);
+        (shares, pending_a + boost_a, pending_b + boost_b, pool_share_pct).into()
+    }
+
+    /// Every position an address holds, without probing each pair
+    /// Returns (pair_id, shares, pending_fees_a, pending_fees_b, pending_a, pending_b) per pair
+    #[view(getUserPositions)]
+    fn get_user_positions(
+        &self,
+        addr: ManagedAddress,
+    ) -> MultiValueEncoded<MultiValue6<u64, BigUint, BigUint, BigUint, BigUint, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.user_pair_ids(&addr).iter() {
+            let (shares, fees_a, fees_b, _) = self.get_lp_position(pair_id, addr.clone()).into_tuple();
+            result.push((
+                pair_id,
+                shares,
+                fees_a,
+                fees_b,
+                self.pending_a(pair_id, &addr).get(),
+                self.pending_b(pair_id, &addr).get(),
+            ).into());
+        }
+        result
+    }
+
+    /// An LP's lock on a pair
+    /// Returns (locked_shares, lock_until, boost_bps) - boost 10000 = 1x
+    #[view(getLpLock)]
+    fn get_lp_lock(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue3<BigUint, u64, u64> {
+        (
+            self.lp_locked_shares(pair_id, &addr).get(),
+            self.lp_lock_until(pair_id, &addr).get(),
+            self.lp_lock_boost_bps(pair_id, &addr).get(),
+        ).into()
+    }
+
+    /// Available lock periods for mint
+    /// Returns (lock_days, boost_bps) per tier
+    #[view(getLockTiers)]
+    fn get_lock_tiers(&self) -> MultiValueEncoded<MultiValue2<u64, u64>> {
+        let mut result = MultiValueEncoded::new();
+        for (days, boost_bps) in LOCK_TIERS.iter() {
+            result.push((*days, *boost_bps).into());
+        }
+        result
+    }
+
+    #[view(getPendingDeposits)]
+    fn get_pending_deposits(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue2<BigUint, BigUint> {
+        (
+            self.pending_a(pair_id, &addr).get(),
+            self.pending_b(pair_id, &addr).get(),
+        ).into()
+    }
+
+    /// When a user's pending deposits become refundable through expirePending
+    /// Returns 0 when the user has nothing pending.
+    #[view(getPendingExpiry)]
+    fn get_pending_expiry(&self, pair_id: u64, addr: ManagedAddress) -> u64 {
+        if !self.user_has_pending(pair_id, &addr) {
+            return 0;
+        }
+        self.get_pending_expires_at(pair_id, &addr)
+    }
+
+    /// Sum of all users' pending deposits for a pair
+    /// Returns (pending_total_a, pending_total_b) - capital waiting to be finalized
+    #[view(getPairPendingTotals)]
+    fn get_pair_pending_totals(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        (
+            self.pair_pending_total_a(pair_id).get(),
+            self.pair_pending_total_b(pair_id).get(),
+        ).into()
+    }
+
+    #[view(getLpCount)]
+    fn get_lp_count(&self, pair_id: u64) -> usize {
+        self.lp_list(pair_id).len()
+    }
+
+    #[view(isLp)]
+    fn is_lp(&self, pair_id: u64, addr: ManagedAddress) -> bool {
+        self.lp_list(pair_id).contains(&addr)
+    }
+
+    /// Every LP of a pair (at most the LP cap); prefer getLpListPaginated
+    #[view(getLpList)]
+    fn get_lp_list(&self, pair_id: u64) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        for addr in self.lp_list(pair_id).iter() {
+            result.push(addr);
+        }
+        result
+    }
+
+    /// Up to `limit` LPs of a pair (max MAX_PAGE_SIZE), from the 0-based offset `start`
+    /// Page through until fewer than `limit` come back. Removing an LP moves the last
+    /// one into its slot, so a page can shift between calls.
+    #[view(getLpListPaginated)]
+    fn get_lp_list_paginated(&self, pair_id: u64, start: u64, limit: u64) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        for addr in self.get_lp_page(pair_id, start, limit).iter() {
+            result.push(addr);
+        }
+        result
+    }
+
+    /// Fee distribution crank state of a pair
+    /// Returns (next lp_list offset, LP count, caller incentive in bps of the distributed fees)
+    #[view(getFeeDistributionInfo)]
+    fn get_fee_distribution_info(&self, pair_id: u64) -> MultiValue3<u64, usize, u64> {
+        (
+            self.fee_distribution_cursor(pair_id).get(),
+            self.lp_list(pair_id).len(),
+            self.get_distribution_incentive_bps(),
+        ).into()
+    }
+
+    /// Max LP accounts per pair (see setMaxLpsPerPair)
+    #[view(getMaxLpsPerPair)]
+    fn get_max_lps_per_pair_view(&self) -> usize {
+        self.get_max_lps_per_pair()
+    }
+
+    #[view(getLpInfo)]
+    fn get_lp_info(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue4<ManagedAddress, BigUint, BigUint, BigUint> {
+        let shares = self.lp_shares(pair_id, &addr).get();
+        let (fees_a, fees_b) = self.calculate_lp_pending_fees(pair_id, &addr);
+        (addr, shares, fees_a, fees_b).into()
+    }
+
+    /// Raw fee accounting for an LP position, for independent verification
+    /// Returns (shares, entry_index_a, entry_index_b, fee_per_share_a, fee_per_share_b)
+    /// 
+    /// pending_fees_x = (fee_per_share_x - entry_index_x) * shares / PRECISION
+    #[view(getLpAccounting)]
+    fn get_lp_accounting(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue5<BigUint, BigUint, BigUint, BigUint, BigUint> {
+        (
+            self.lp_shares(pair_id, &addr).get(),
+            self.lp_entry_index_a(pair_id, &addr).get(),
+            self.lp_entry_index_b(pair_id, &addr).get(),
+            self.fee_per_share_a(pair_id).get(),
+            self.fee_per_share_b(pair_id).get(),
+        ).into()
+    }
+
+    /// LP token details for a pair
+    /// Returns (lp_token, lp_token_supply, custodian_shares)
+    /// Shares per LP token = custodian_shares / lp_token_supply
+    #[view(getPairLpToken)]
+    fn get_pair_lp_token(&self, pair_id: u64) -> MultiValue3<TokenIdentifier, BigUint, BigUint> {
+        let custodian = self.blockchain().get_sc_address();
+        (
+            self.pair_lp_token(pair_id).get(),
+            self.lp_token_supply(pair_id).get(),
+            self.lp_shares(pair_id, &custodian).get(),
+        ).into()
+    }
+
+    /// (address, shares, pending_fees_a, pending_fees_b) of every LP (at most the LP cap)
+    /// Prefer getAllLpInfoPaginated.
+    #[view(getAllLpInfo)]
+    fn get_all_lp_info(&self, pair_id: u64) -> MultiValueEncoded<MultiValue4<ManagedAddress, BigUint, BigUint, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+        for addr in self.lp_list(pair_id).iter() {
+            let shares = self.lp_shares(pair_id, &addr).get();
+            let (fees_a, fees_b) = self.calculate_lp_pending_fees(pair_id, &addr);
+            result.push((addr, shares, fees_a, fees_b).into());
+        }
+        result
+    }
+
+    /// getAllLpInfo for one page of the LP list (see getLpListPaginated)
+    #[view(getAllLpInfoPaginated)]
+    fn get_all_lp_info_paginated(
+        &self,
+        pair_id: u64,
+        start: u64,
+        limit: u64,
+    ) -> MultiValueEncoded<MultiValue4<ManagedAddress, BigUint, BigUint, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+        for addr in self.get_lp_page(pair_id, start, limit).iter() {
+            let shares = self.lp_shares(pair_id, &addr).get();
+            let (fees_a, fees_b) = self.calculate_lp_pending_fees(pair_id, &addr);
+            result.push((addr, shares, fees_a, fees_b).into());
+        }
+        result
+    }
+
+    #[view(getRegisteredPairs)]
+    fn get_registered_pairs(&self) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.registered_pair_ids().iter() {
+            result.push(pair_id);
+        }
+        result
+    }
+
+    #[view(getAllPairIds)]
+    fn get_all_pair_ids(&self) -> MultiValueEncoded<u64> {
+        self.get_registered_pairs()
+    }
+
+    #[view(getNextPairId)]
+    fn get_next_pair_id(&self) -> u64 {
+        self.next_pair_id().get()
+    }
+
+    /// V5: Get pair creator
+    #[view(getPairCreator)]
+    fn get_pair_creator_view(&self, pair_id: u64) -> ManagedAddress {
+        self.get_pair_creator_or_owner(pair_id)
+    }
+
+    /// V5: Check if pool is empty (ready for first LP to set price)
+    #[view(isPoolEmpty)]
+    fn is_pool_empty(&self, pair_id: u64) -> bool {
+        if !self.pair_exists(pair_id) {
+            return false;
+        }
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        reserve_a == BigUint::zero() && reserve_b == BigUint::zero()
+    }
+
+    /// Shares locked on the pair's first mint (MINIMUM_LIQUIDITY)
+    /// Counted in the total share supply but owned by nobody.
+    #[view(getLockedLiquidity)]
+    fn get_locked_liquidity(&self, pair_id: u64) -> BigUint {
+        self.pair_locked_liquidity(pair_id).get()
+    }
+
+    /// V5: Get pairs created by a specific address
+    #[view(getPairsByCreator)]
+    fn get_pairs_by_creator(&self, creator: ManagedAddress) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.registered_pair_ids().iter() {
+            if self.get_pair_creator_or_owner(pair_id) == creator {
+                result.push(pair_id);
+            }
+        }
+        result
+    }
+
+    /// V5: Find pairs that contain both specified tokens (in either order)
+    /// Returns all pair_ids that have token_a and token_b (in either A/B position)
+    /// Useful for checking if a pair already exists before creating
+    #[view(findPairsByTokens)]
+    fn find_pairs_by_tokens(&self, token_a: TokenIdentifier, token_b: TokenIdentifier) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.registered_pair_ids().iter() {
+            let pair_token_a = self.pair_token_a(pair_id).get();
+            let pair_token_b = self.pair_token_b(pair_id).get();
+            
+            // Check both orderings
+            let matches = (pair_token_a == token_a && pair_token_b == token_b) ||
+                         (pair_token_a == token_b && pair_token_b == token_a);
+            
+            if matches {
+                result.push(pair_id);
+            }
+        }
+        result
+    }
+
+    /// Canonical pair for a token combination (either order)
+    /// Returns 0 if no pair exists. Prefer this over findPairsByTokens - it's a
+    /// single storage read instead of a scan over all pairs.
+    #[view(findPairByTokens)]
+    fn find_pair_by_tokens(&self, token_a: TokenIdentifier, token_b: TokenIdentifier) -> u64 {
+        self.pair_by_tokens(&token_a, &token_b).get()
+    }
+
+    /// Pricing curve of a pair
+    /// Returns (pool_type, curve_param): amplification for Stable pairs, token A weight
+    /// in percent for Weighted pairs (the current one for LBPs), 0 for constant-product pairs
+    #[view(getPairCurve)]
+    fn get_pair_curve(&self, pair_id: u64) -> MultiValue2<PoolType, u64> {
+        let pool_type = self.pair_pool_type(pair_id).get();
+        let curve_param = match pool_type {
+            PoolType::ConstantProduct => 0,
+            PoolType::Stable => self.pair_amplification(pair_id).get(),
+            PoolType::Weighted => self.get_pair_weight_a(pair_id),
+        };
+        (pool_type, curve_param).into()
+    }
+
+    /// Weight schedule and current state of a liquidity bootstrapping pool
+    /// Returns (start_weight_a, end_weight_a, start_time, end_time, current_weight_a,
+    /// price_a_in_b, price_normalized): price_a_in_b is the weight-adjusted spot price
+    /// of token A in token B scaled by PRECISION, price_normalized the same per whole
+    /// token scaled to 1e18 (see getPriceNormalized). Prices are 0 for an empty pool.
+    #[view(getLbpInfo)]
+    fn get_lbp_info(&self, pair_id: u64) -> MultiValue7<u64, u64, u64, u64, u64, BigUint, BigUint> {
+        require!(!self.pair_lbp_schedule(pair_id).is_empty(), "Pair is not an LBP");
+        let schedule = self.pair_lbp_schedule(pair_id).get();
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        let price_a_in_b = if reserve_a == 0u64 || reserve_b == 0u64 {
+            BigUint::zero()
+        } else {
+            self.spot_price_a_in_b(pair_id, &reserve_a, &reserve_b)
+        };
+        (
+            schedule.start_weight_a,
+            schedule.end_weight_a,
+            schedule.start_time,
+            schedule.end_time,
+            self.get_pair_weight_a(pair_id),
+            price_a_in_b,
+            self.get_price_normalized(pair_id),
+        ).into()
+    }
+
+    /// Pair-level state for migrating the pair to a new deployment
+    /// Pairs with a KDA LP token can't be migrated (the token is bound to this contract).
+    #[view(exportPairState)]
+    fn export_pair_state(&self, pair_id: u64) -> PairState<Self::Api> {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(self.pair_lp_token(pair_id).is_empty(), "Pairs with an LP token can't be exported");
+        require!(self.pair_lp_positions(pair_id).is_empty(), "Pairs with LP position NFTs can't be exported");
+        require!(!self.lbp_is_running(pair_id), "Pairs with a running LBP can't be exported");
+        
+        PairState {
+            token_a: self.pair_token_a(pair_id).get(),
+            token_b: self.pair_token_b(pair_id).get(),
+            token_a_is_klv: self.pair_token_a_is_klv(pair_id).get(),
+            token_b_is_klv: self.pair_token_b_is_klv(pair_id).get(),
+            token_nonce_a: self.pair_token_nonce_a(pair_id).get(),
+            token_nonce_b: self.pair_token_nonce_b(pair_id).get(),
+            creator: self.get_pair_creator_or_owner(pair_id),
+            pool_type: self.pair_pool_type(pair_id).get(),
+            amplification: self.pair_amplification(pair_id).get(),
+            weight_a: self.get_pair_weight_a(pair_id),
+            fee_bps: self.pair_fee_bps(pair_id).get(),
+            fee_on_input: self.pair_fee_on_input(pair_id).get(),
+            swaps_enabled: self.pair_swaps_enabled(pair_id).get(),
+            liquidity_enabled: self.pair_liquidity_enabled(pair_id).get(),
+            reserve_a: self.pair_reserve_a(pair_id).get(),
+            reserve_b: self.pair_reserve_b(pair_id).get(),
+            owner_shares: self.owner_shares(pair_id).get(),
+            total_lp_shares: self.total_lp_shares(pair_id).get(),
+            locked_liquidity: self.pair_locked_liquidity(pair_id).get(),
+            fee_per_share_a: self.fee_per_share_a(pair_id).get(),
+            fee_per_share_b: self.fee_per_share_b(pair_id).get(),
+            boost_fee_per_weight_a: self.boost_fee_per_weight_a(pair_id).get(),
+            boost_fee_per_weight_b: self.boost_fee_per_weight_b(pair_id).get(),
+            owner_unclaimed_fees_a: self.owner_unclaimed_fees_a(pair_id).get(),
+            owner_unclaimed_fees_b: self.owner_unclaimed_fees_b(pair_id).get(),
+            treasury_unclaimed_fees_a: self.treasury_unclaimed_fees_a(pair_id).get(),
+            treasury_unclaimed_fees_b: self.treasury_unclaimed_fees_b(pair_id).get(),
+            pending_total_a: self.pair_pending_total_a(pair_id).get(),
+            pending_total_b: self.pair_pending_total_b(pair_id).get(),
+            il_fund_a: self.il_fund_a(pair_id).get(),
+            il_fund_b: self.il_fund_b(pair_id).get(),
+            volume_a: self.pair_volume_a(pair_id).get(),
+            volume_b: self.pair_volume_b(pair_id).get(),
+            total_fees_a: self.pair_total_fees_a(pair_id).get(),
+            total_fees_b: self.pair_total_fees_b(pair_id).get(),
+            swap_count: self.pair_swap_count(pair_id).get(),
+        }
+    }
+
+    /// Positions of a pair for migration: LPs first, then pending-only depositors
+    /// Page through with start/limit (max MAX_PAGE_SIZE) until fewer than `limit` come back.
+    #[view(exportPairPositions)]
+    fn export_pair_positions(
+        &self,
+        pair_id: u64,
+        start: u64,
+        limit: u64,
+    ) -> MultiValueEncoded<PositionState<Self::Api>> {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        require!(limit > 0 && limit <= MAX_PAGE_SIZE, "Invalid limit");
+        
+        let lp_list = self.lp_list(pair_id);
+        let pending_only = self
+            .pair_pending_users(pair_id)
+            .iter()
+            .filter(|addr| !lp_list.contains(addr));
+        
+        let mut result = MultiValueEncoded::new();
+        for addr in lp_list.iter().chain(pending_only).skip(start as usize).take(limit as usize) {
+            result.push(PositionState {
+                shares: self.lp_shares(pair_id, &addr).get(),
+                entry_index_a: self.lp_entry_index_a(pair_id, &addr).get(),
+                entry_index_b: self.lp_entry_index_b(pair_id, &addr).get(),
+                locked_shares: self.lp_locked_shares(pair_id, &addr).get(),
+                lock_until: self.lp_lock_until(pair_id, &addr).get(),
+                lock_boost_bps: self.lp_lock_boost_bps(pair_id, &addr).get(),
+                boost_entry_a: self.lp_boost_entry_a(pair_id, &addr).get(),
+                boost_entry_b: self.lp_boost_entry_b(pair_id, &addr).get(),
+                deposited_a: self.lp_deposited_a(pair_id, &addr).get(),
+                deposited_b: self.lp_deposited_b(pair_id, &addr).get(),
+                deposit_timestamp: self.lp_deposit_timestamp(pair_id, &addr).get(),
+                pending_a: self.pending_a(pair_id, &addr).get(),
+                pending_b: self.pending_b(pair_id, &addr).get(),
+                pending_deposited_at: self.pending_deposited_at(pair_id, &addr).get(),
+                address: addr,
+            });
+        }
+        result
+    }
+
+    /// Display metadata of a pair (all fields empty when never set)
+    #[view(getPairMetadata)]
+    fn get_pair_metadata(&self, pair_id: u64) -> PairMetadata<Self::Api> {
+        if self.pair_metadata(pair_id).is_empty() {
+            return PairMetadata {
+                name: ManagedBuffer::new(),
+                logo_uri: ManagedBuffer::new(),
+                project_link: ManagedBuffer::new(),
+                updated_at: 0,
+            };
+        }
+        self.pair_metadata(pair_id).get()
+    }
+
+    /// Anti-sandwich guards of a pair
+    /// Returns: (block_direction_lock, max_price_impact_bps)
+    #[view(getPairSwapGuards)]
+    fn get_pair_swap_guards(&self, pair_id: u64) -> MultiValue2<bool, u64> {
+        (
+            self.pair_block_direction_lock(pair_id).get(),
+            self.pair_max_price_impact_bps(pair_id).get(),
+        ).into()
+    }
+
+    /// TWAP of token A in token B and the pair's TWAP guard
+    /// Returns (twap, spot_price, max_deviation_bps), prices in precision units (1e12 = 1.0).
+    /// twap is 0 until the pair has traded across a full TWAP window; with regular
+    /// trading it covers between one and two TWAP_WINDOW_SECONDS.
+    #[view(getPairTwap)]
+    fn get_pair_twap(&self, pair_id: u64) -> MultiValue3<BigUint, BigUint, u64> {
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        let spot_price = if reserve_a > 0u64 && reserve_b > 0u64 {
+            self.spot_price_a_in_b(pair_id, &reserve_a, &reserve_b)
+        } else {
+            BigUint::zero()
+        };
+        (
+            self.get_twap_price(pair_id).unwrap_or_else(BigUint::zero),
+            spot_price,
+            self.pair_max_twap_deviation_bps(pair_id).get(),
+        ).into()
+    }
+
+    /// Spot price of one whole token A in whole token B, scaled to 1e18
+    /// Corrects the raw reserve ratio for the decimals of both tokens (see
+    /// setTokenDecimals), weight-adjusted for weighted pools.
+    /// 0 when the pair is empty or either token's decimals are unknown.
+    #[view(getPriceNormalized)]
+    fn get_price_normalized(&self, pair_id: u64) -> BigUint {
+        if !self.pair_exists(pair_id) {
+            return BigUint::zero();
+        }
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        if reserve_a == 0u64 || reserve_b == 0u64 {
+            return BigUint::zero();
+        }
+        
+        let (weight_a, weight_b) = self.get_reduced_weights(pair_id, true);
+        self.normalize_price_a_in_b(pair_id, &(reserve_b * weight_a as u64), &(reserve_a * weight_b as u64))
+            .unwrap_or_else(BigUint::zero)
+    }
+
+    /// Registered decimals of a token (KLV is always KLV_DECIMALS)
+    /// Returns (is_known, decimals)
+    #[view(getTokenDecimals)]
+    fn get_token_decimals_view(&self, token: TokenIdentifier) -> MultiValue2<bool, u32> {
+        match self.get_token_decimals(&token) {
+            Some(decimals) => (true, decimals).into(),
+            None => (false, 0u32).into(),
+        }
+    }
+
+    /// Raw price accumulator, for integrations computing their own TWAP windows
+    /// Returns (price_cumulative, timestamp): sum of spot price of A in B * seconds
+    /// up to timestamp. Sample it twice and divide the difference by the elapsed time.
+    #[view(getPairPriceCumulative)]
+    fn get_pair_price_cumulative(&self, pair_id: u64) -> MultiValue2<BigUint, u64> {
+        (
+            self.pair_price_cumulative(pair_id).get(),
+            self.pair_price_cumulative_timestamp(pair_id).get(),
+        ).into()
+    }
+
+    /// Trade size limits of a pair
+    /// Returns: (max_trade_bps, min_reserve_a, min_reserve_b)
+    #[view(getPairTradeLimits)]
+    fn get_pair_trade_limits(&self, pair_id: u64) -> MultiValue3<u64, BigUint, BigUint> {
+        (
+            self.pair_max_trade_bps(pair_id).get(),
+            self.pair_min_reserve_a(pair_id).get(),
+            self.pair_min_reserve_b(pair_id).get(),
+        ).into()
+    }
+
+    /// Every live pair containing a token (archived pairs excluded)
+    /// Lets routers discover hop candidates on-chain without scanning all pair_ids.
+    #[view(getPairsForToken)]
+    fn get_pairs_for_token(&self, token: TokenIdentifier) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.pairs_by_token(&token).iter() {
+            result.push(pair_id);
+        }
+        result
+    }
+
+    /// Best 1- or 2-hop route between two tokens for an exact input
+    /// Returns (expected_output, path) - path is the pair_id list to pass to
+    /// swapExactInMultiHop (or swap for a single hop). Output 0 and an empty
+    /// path when no active route exists. Use "KLV" for native KLV.
+    /// Walks the pairs_by_token index, so it's meant for off-chain queries.
+    #[view(getBestRoute)]
+    fn get_best_route(
+        &self,
+        token_in: TokenIdentifier,
+        token_out: TokenIdentifier,
+        amount_in: BigUint,
+        max_hops: u64,
+    ) -> MultiValue2<BigUint, ManagedVec<Self::Api, u64>> {
+        require!(max_hops == 1 || max_hops == 2, "max_hops must be 1 or 2");
+        
+        let caller = self.blockchain().get_caller();
+        let mut best_output = BigUint::zero();
+        let mut best_path = ManagedVec::new();
+        if token_in == token_out || amount_in == BigUint::zero() {
+            return (best_output, best_path).into();
+        }
+        
+        for first_pair in self.pairs_by_token(&token_in).iter() {
+            let (mid_token, mid_amount) = self.quote_route_hop(first_pair, &caller, &token_in, &amount_in);
+            if mid_amount == BigUint::zero() {
+                continue;
+            }
+            
+            if mid_token == token_out {
+                if mid_amount > best_output {
+                    best_output = mid_amount;
+                    best_path = ManagedVec::new();
+                    best_path.push(first_pair);
+                }
+                continue;
+            }
+            
+            if max_hops < 2 {
+                continue;
+            }
+            
+            for second_pair in self.pairs_by_token(&mid_token).iter() {
+                if second_pair == first_pair {
+                    continue;
+                }
+                let (final_token, final_amount) = self.quote_route_hop(second_pair, &caller, &mid_token, &mid_amount);
+                if final_token == token_out && final_amount > best_output {
+                    best_output = final_amount;
+                    best_path = ManagedVec::new();
+                    best_path.push(first_pair);
+                    best_path.push(second_pair);
+                }
+            }
+        }
+        
+        (best_output, best_path).into()
+    }
+
+    /// Whether createPair rejects duplicate token combinations
+    /// Whether the legacy addLiquidityA/B endpoints are enabled
+    #[view(getLegacyAddLiquidityEnabled)]
+    fn get_legacy_add_liquidity_enabled(&self) -> bool {
+        self.legacy_add_liquidity_enabled().get()
+    }
+
+    #[view(getRequireUniquePairs)]
+    fn get_require_unique_pairs(&self) -> bool {
+        self.require_unique_pairs().get()
+    }
+
+    /// Archive status of a pair
+    /// Returns (is_archived, archived_at_timestamp)
+    #[view(getPairArchiveInfo)]
+    fn get_pair_archive_info(&self, pair_id: u64) -> MultiValue2<bool, u64> {
+        (
+            self.pair_is_archived(pair_id).get(),
+            self.pair_archived_at(pair_id).get(),
+        ).into()
+    }
+
+    /// All archived pair_ids
+    #[view(getArchivedPairs)]
+    fn get_archived_pairs(&self) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.registered_pair_ids().iter() {
+            if self.pair_is_archived(pair_id).get() {
+                result.push(pair_id);
+            }
+        }
+        result
+    }
+
+    /// V5: Get number of users with pending deposits for a pair
+    /// If > 0, pair cannot be deleted (would lose user funds)
+    #[view(getPendingUserCount)]
+    fn get_pending_user_count(&self, pair_id: u64) -> u64 {
+        self.pair_pending_users(pair_id).len() as u64
+    }
+
+    /// Every address with a pending deposit on a pair
+    #[view(getPendingUsers)]
+    fn get_pending_users(&self, pair_id: u64) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        for user in self.pair_pending_users(pair_id).iter() {
+            result.push(user);
+        }
+        result
+    }
+
+    /// V5: Check if a pair can be safely deleted
+    /// Returns true if: reserves are 0, no LPs, no pending deposits, no unclaimed fees
+    #[view(canDeletePair)]
+    fn can_delete_pair(&self, pair_id: u64) -> bool {
+        if !self.pair_exists(pair_id) {
+            return false;
+        }
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        let owner_shares = self.owner_shares(pair_id).get();
+        let pending_total_a = self.pair_pending_total_a(pair_id).get();
+        let pending_total_b = self.pair_pending_total_b(pair_id).get();
+        let unclaimed_a = self.owner_unclaimed_fees_a(pair_id).get();
+        let unclaimed_b = self.owner_unclaimed_fees_b(pair_id).get();
+        let treasury_a = self.treasury_unclaimed_fees_a(pair_id).get();
+        let treasury_b = self.treasury_unclaimed_fees_b(pair_id).get();
+        
+        self.total_lp_shares(pair_id).get() == BigUint::zero() &&
+        (self.pair_locked_liquidity(pair_id).get() > 0u64
+            || (reserve_a == BigUint::zero() && reserve_b == BigUint::zero())) &&
+        self.lp_list(pair_id).is_empty() &&
+        owner_shares == BigUint::zero() &&
+        self.pair_pending_users(pair_id).is_empty() &&
+        pending_total_a == BigUint::zero() &&
+        pending_total_b == BigUint::zero() &&
+        self.pair_limit_orders(pair_id).is_empty() &&
+        self.pair_dca_orders(pair_id).is_empty() &&
+        self.pair_stream_orders(pair_id).is_empty() &&
+        self.pair_active_competition(pair_id).is_empty() &&
+        unclaimed_a == BigUint::zero() &&
+        unclaimed_b == BigUint::zero() &&
+        treasury_a == BigUint::zero() &&
+        treasury_b == BigUint::zero()
+    }
+
+    /// V5: Preview first liquidity - what shares would user get for amounts?
+    /// Only valid for empty pools. Returns 0 if pool is not empty.
+    #[view(previewFirstLiquidity)]
+    fn preview_first_liquidity(&self, pair_id: u64, amount_a: BigUint, amount_b: BigUint) -> BigUint {
+        if !self.pair_exists(pair_id) {
+            return BigUint::zero();
+        }
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        // Only for empty pools
+        if reserve_a > BigUint::zero() || reserve_b > BigUint::zero() {
+            return BigUint::zero();
+        }
+        
+        // Calculate: sqrt(amount_a * amount_b) - MINIMUM_LIQUIDITY
+        let minimum_liquidity = BigUint::from(self.get_minimum_liquidity());
+        let product = &amount_a * &amount_b;
+        let sqrt_shares = product.sqrt();
+        
+        if sqrt_shares > minimum_liquidity {
+            sqrt_shares - minimum_liquidity
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    /// V5: Get the implied price ratio for first liquidity
+    /// Returns price in precision units (1e18 = 1.0)
+    /// price = amount_b / amount_a * 1e18
+    #[view(previewFirstPrice)]
+    fn preview_first_price(&self, amount_a: BigUint, amount_b: BigUint) -> BigUint {
+        if amount_a == BigUint::zero() {
+            return BigUint::zero();
+        }
+        let precision = BigUint::from(PRECISION);
+        &amount_b * &precision / &amount_a
+    }
+
+    /// V5: Quote swap output - preview what you'll receive for a given input
+    /// Returns (output_after_fee, fee_amount)
+    /// Use this to calculate min_output for slippage protection
+    #[view(quoteSwap)]
+    fn quote_swap(&self, pair_id: u64, input_amount: BigUint, is_a_to_b: bool) -> MultiValue2<BigUint, BigUint> {
+        if !self.pair_exists(pair_id) {
+            return (BigUint::zero(), BigUint::zero()).into();
+        }
+        
+        let caller = self.blockchain().get_caller();
+        self.quote_swap_internal(pair_id, &caller, is_a_to_b, &input_amount).into()
+    }
+
+    /// Detailed swap quote including price impact
+    /// Returns (amount_out, fee_amount, price_impact_bps, resulting_price)
+    /// 
+    /// price_impact_bps = how far the execution price (before fee) is below the
+    /// current spot price: input * 10000 / (reserve_in + input).
+    /// resulting_price = reserve_out / reserve_in after the swap, in precision
+    /// units (1e12 = 1.0), i.e. the output-token price of the input token.
+    /// Frontends should warn users before large trades using these values.
+    #[view(getSwapQuote)]
+    fn get_swap_quote(
+        &self,
+        pair_id: u64,
+        input_amount: BigUint,
+        is_a_to_b: bool,
+    ) -> MultiValue4<BigUint, BigUint, u64, BigUint> {
+        let empty = (BigUint::zero(), BigUint::zero(), 0u64, BigUint::zero());
+        if !self.pair_exists(pair_id) || input_amount == BigUint::zero() {
+            return empty.into();
+        }
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        if reserve_a == BigUint::zero() || reserve_b == BigUint::zero() {
+            return empty.into();
+        }
+        
+        let (reserve_in, reserve_out) = if is_a_to_b {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        };
+        
+        let caller = self.blockchain().get_caller();
+        let (curve_in, curve_out, user_gets, fee) =
+            self.get_swap_amounts(pair_id, &caller, is_a_to_b, &reserve_in, &reserve_out, &input_amount);
+        
+        if curve_out == BigUint::zero() || curve_out >= reserve_out {
+            return empty.into();
+        }
+        
+        let price_impact_bps = self.compute_price_impact_bps(&reserve_in, &input_amount);
+        
+        // The fee is accounted for outside the reserves
+        let new_reserve_in = &reserve_in + &curve_in;
+        let new_reserve_out = &reserve_out - &curve_out;
+        // Weighted spot price is (B_out / w_out) / (B_in / w_in)
+        let (exp_in, exp_out) = self.get_reduced_weights(pair_id, is_a_to_b);
+        let resulting_price = &new_reserve_out * PRECISION * exp_in as u64 / (&new_reserve_in * exp_out as u64);
+        
+        (user_gets, fee, price_impact_bps, resulting_price).into()
+    }
+
+    /// Step-by-step swap quote spelling out the pair's fee model
+    /// Returns (gross_output, fee, net_output, effective_price, spot_price)
+    /// 
+    /// Fee-on-output pairs apply the curve to the FULL input and take the fee
+    /// from the curve output: fee = gross_output * fee_bps / 10000 (output token),
+    /// net_output = gross_output - fee.
+    /// Fee-on-input pairs (see isPairFeeOnInput) take fee = amount_in * fee_bps / 10000
+    /// (input token) first and apply the curve to the rest, so net_output == gross_output.
+    /// Use this view instead of reimplementing the math in client SDKs.
+    /// effective_price = net_output / amount_in and spot_price = reserve_out / reserve_in
+    /// before the swap (weight-adjusted for weighted pools), both in precision
+    /// units (1e12 = 1.0) of output token per input token.
+    /// All zeros when the pair doesn't exist, is empty or can't fill the amount.
+    #[view(getDetailedQuote)]
+    fn get_detailed_quote(
+        &self,
+        pair_id: u64,
+        amount_in: BigUint,
+        a_to_b: bool,
+    ) -> MultiValue5<BigUint, BigUint, BigUint, BigUint, BigUint> {
+        let empty = (BigUint::zero(), BigUint::zero(), BigUint::zero(), BigUint::zero(), BigUint::zero());
+        if !self.pair_exists(pair_id) || amount_in == BigUint::zero() {
+            return empty.into();
+        }
+        
+        let (reserve_in, reserve_out) = if a_to_b {
+            (self.pair_reserve_a(pair_id).get(), self.pair_reserve_b(pair_id).get())
+        } else {
+            (self.pair_reserve_b(pair_id).get(), self.pair_reserve_a(pair_id).get())
+        };
+        
+        if reserve_in == BigUint::zero() || reserve_out == BigUint::zero() {
+            return empty.into();
+        }
+        
+        let caller = self.blockchain().get_caller();
+        let (_, gross_output, net_output, fee) =
+            self.get_swap_amounts(pair_id, &caller, a_to_b, &reserve_in, &reserve_out, &amount_in);
+        if gross_output == BigUint::zero() || gross_output >= reserve_out {
+            return empty.into();
+        }
+        
+        let effective_price = &net_output * PRECISION / &amount_in;
+        
+        let (exp_in, exp_out) = self.get_reduced_weights(pair_id, a_to_b);
+        let spot_price = &reserve_out * PRECISION * exp_in as u64 / (&reserve_in * exp_out as u64);
+        
+        (gross_output, fee, net_output, effective_price, spot_price).into()
+    }
+
+    /// V5: Quote reverse swap - how much input needed for desired output?
+    /// Returns (required_input, fee_amount), rounded like swapTokensForExactOut
+    #[view(quoteSwapReverse)]
+    fn quote_swap_reverse(&self, pair_id: u64, desired_output: BigUint, is_a_to_b: bool) -> MultiValue2<BigUint, BigUint> {
+        if !self.pair_exists(pair_id) {
+            return (BigUint::zero(), BigUint::zero()).into();
+        }
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        if reserve_a == BigUint::zero() || reserve_b == BigUint::zero() || desired_output == BigUint::zero() {
+            return (BigUint::zero(), BigUint::zero()).into();
+        }
+        
+        let (reserve_in, reserve_out) = if is_a_to_b {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        };
+        
+        let caller = self.blockchain().get_caller();
+        match self.get_swap_input(pair_id, &caller, is_a_to_b, &reserve_in, &reserve_out, &desired_output) {
+            Some((required_input, fee)) => (required_input, fee).into(),
+            None => (BigUint::zero(), BigUint::zero()).into(),
+        }
+    }
+
+    /// Exact-output swap quote: what the trader pays to receive `desired_out`
+    /// Returns (required_input, fee_amount, price_impact_bps, resulting_price)
+    /// 
+    /// Same rounding as swapTokensForExactOut, so sending required_input there
+    /// delivers at least desired_out. The other fields follow getSwapQuote.
+    /// All zeros when the pair can't deliver the amount.
+    #[view(getSwapQuoteExactOut)]
+    fn get_swap_quote_exact_out(
+        &self,
+        pair_id: u64,
+        desired_out: BigUint,
+        is_a_to_b: bool,
+    ) -> MultiValue4<BigUint, BigUint, u64, BigUint> {
+        let empty = (BigUint::zero(), BigUint::zero(), 0u64, BigUint::zero());
+        if !self.pair_exists(pair_id) || desired_out == BigUint::zero() {
+            return empty.into();
+        }
+        
+        let reserve_a = self.pair_reserve_a(pair_id).get();
+        let reserve_b = self.pair_reserve_b(pair_id).get();
+        
+        if reserve_a == BigUint::zero() || reserve_b == BigUint::zero() {
+            return empty.into();
+        }
+        
+        let (reserve_in, reserve_out) = if is_a_to_b {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        };
+        
+        let caller = self.blockchain().get_caller();
+        let (required_input, fee) =
+            match self.get_swap_input(pair_id, &caller, is_a_to_b, &reserve_in, &reserve_out, &desired_out) {
+                Some(quote) => quote,
+                None => return empty.into(),
+            };
+        let price_impact_bps = self.compute_price_impact_bps(&reserve_in, &required_input);
+        
+        // Reserves as the swap would actually leave them
+        let (curve_in, curve_out, _, _) =
+            self.get_swap_amounts(pair_id, &caller, is_a_to_b, &reserve_in, &reserve_out, &required_input);
+        let new_reserve_in = &reserve_in + &curve_in;
+        let new_reserve_out = &reserve_out - &curve_out;
+        let (exp_in, exp_out) = self.get_reduced_weights(pair_id, is_a_to_b);
+        let resulting_price = &new_reserve_out * PRECISION * exp_in as u64 / (&new_reserve_in * exp_out as u64);
+        
+        (required_input, fee, price_impact_bps, resulting_price).into()
+    }
+
+    /// Get total liquidity locked for a token across all pairs (as of last checkpoint)
+    #[view(getTokenTvl)]
+    fn get_token_tvl(&self, token: TokenIdentifier) -> BigUint {
+        self.checkpoint_token_tvl(&token).get()
+    }
+
+    /// Get TVL for every token seen at the last checkpoint
+    /// Returns (token, tvl) pairs
+    #[view(getAllTokenTvl)]
+    fn get_all_token_tvl(&self) -> MultiValueEncoded<MultiValue2<TokenIdentifier, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+        for token in self.checkpoint_tokens().iter() {
+            let tvl = self.checkpoint_token_tvl(&token).get();
+            result.push((token, tvl).into());
+        }
+        result
+    }
+
+    /// Merkle root of LP pending fees stored by the last checkpointPairFees
+    /// Returns (root, leaf_count, timestamp)
+    /// 
+    /// Leaves: sha256(address || nested(pending_fees_a) || nested(pending_fees_b)),
+    /// in lp_list order. Nodes: sha256(min(left, right) || max(left, right)),
+    /// an odd node is carried up unchanged.
+    #[view(getLpFeeMerkleRoot)]
+    fn get_lp_fee_merkle_root(&self, pair_id: u64) -> MultiValue3<ManagedByteArray<Self::Api, 32>, usize, u64> {
+        (
+            self.lp_fee_merkle_root(pair_id).get(),
+            self.lp_fee_merkle_leaf_count(pair_id).get(),
+            self.lp_fee_merkle_timestamp(pair_id).get(),
+        ).into()
+    }
+
+    /// Verify an LP's pending fees against the last checkpointPairFees merkle root
+    /// @param proof - Sibling hashes from leaf to root
+    #[view(verifyLpFeeProof)]
+    fn verify_lp_fee_proof(
+        &self,
+        pair_id: u64,
+        addr: ManagedAddress,
+        pending_fees_a: BigUint,
+        pending_fees_b: BigUint,
+        proof: MultiValueEncoded<ManagedByteArray<Self::Api, 32>>,
+    ) -> bool {
+        if self.lp_fee_merkle_root(pair_id).is_empty() {
+            return false;
+        }
+        
+        let mut node = self.lp_fee_leaf(&addr, &pending_fees_a, &pending_fees_b);
+        for sibling in proof.into_iter() {
+            node = self.hash_sorted_pair(&node, &sibling);
+        }
+        
+        node == self.lp_fee_merkle_root(pair_id).get()
+    }
+
+    /// Get checkpoint status
+    /// Returns (last_timestamp, min_interval, reward_per_call, reward_pool)
+    #[view(getCheckpointInfo)]
+    fn get_checkpoint_info(&self) -> MultiValue4<u64, u64, BigUint, BigUint> {
+        (
+            self.last_checkpoint_timestamp().get(),
+            self.checkpoint_min_interval().get(),
+            self.checkpoint_reward().get(),
+            self.checkpoint_reward_pool().get(),
+        ).into()
+    }
+
+    /// Current protocol parameters
+    /// Returns (precision, minimum_liquidity, min_fee_bps, max_fee_bps, klv_max_fee_bps, protocol_fee_bps)
+    #[view(getParameters)]
+    fn get_parameters(&self) -> MultiValue6<u64, u64, u64, u64, u64, u64> {
+        (
+            PRECISION,
+            self.get_minimum_liquidity(),
+            self.get_min_fee_bps(),
+            self.get_max_fee_bps(),
+            self.get_klv_max_fee_bps(),
+            self.get_protocol_fee_bps(),
+        ).into()
+    }
+
+    /// Sanity rules for a pair's first deposit
+    /// Returns (min_value_klv, reference_band_bps) - 0 = disabled
+    #[view(getInitialLiquidityRules)]
+    fn get_initial_liquidity_rules(&self) -> MultiValue2<BigUint, u64> {
+        (
+            self.param_min_initial_liquidity_klv().get(),
+            self.param_reference_band_bps().get(),
+        ).into()
+    }
+
+    /// Creator fee band and when a pair's creator may next change its fee
+    /// Returns (min_bps, max_bps, cooldown_seconds, next_change_at) - max 0 = disabled,
+    /// next_change_at 0 = now
+    #[view(getCreatorFeeRules)]
+    fn get_creator_fee_rules(&self, pair_id: u64) -> MultiValue4<u64, u64, u64, u64> {
+        let cooldown = self.param_creator_fee_cooldown().get();
+        let next_change_at = if self.pair_creator_fee_changed_at(pair_id).is_empty() {
+            0
+        } else {
+            self.pair_creator_fee_changed_at(pair_id).get() + cooldown
+        };
+        (
+            self.param_creator_fee_min_bps().get(),
+            self.param_creator_fee_max_bps().get(),
+            cooldown,
+            next_change_at,
+        ).into()
+    }
+
+    /// Owner-set reference KLV price of a token, scaled by PRECISION (0 = none)
+    #[view(getTokenReferencePrice)]
+    fn get_token_reference_price(&self, token: TokenIdentifier) -> BigUint {
+        self.token_reference_price(&token).get()
+    }
+
+    /// Swap fees (bps) a new pair may be created with
+    #[view(getFeeTiers)]
+    fn get_fee_tiers(&self) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for fee_bps in self.fee_tiers().iter() {
+            result.push(fee_bps);
+        }
+        result
+    }
+
+    /// Anti-spam rules for public pair creation
+    /// Returns (creation_fee_klv, cooldown_seconds, max_pairs_per_creator) - 0 = disabled
+    #[view(getPairCreationRules)]
+    fn get_pair_creation_rules(&self) -> MultiValue3<BigUint, u64, u64> {
+        (
+            self.pair_creation_fee().get(),
+            self.pair_creation_cooldown().get(),
+            self.max_pairs_per_creator().get(),
+        ).into()
+    }
+
+    /// Token listing status
+    /// Returns (allowlist_enabled, is_allowlisted, is_denylisted)
+    #[view(getTokenListing)]
+    fn get_token_listing(&self, token: TokenIdentifier) -> MultiValue3<bool, bool, bool> {
+        (
+            self.token_allowlist_enabled().get(),
+            self.token_allowlist().contains(&token),
+            self.token_denylist().contains(&token),
+        ).into()
+    }
+
+    #[view(getTokenAllowlist)]
+    fn get_token_allowlist(&self) -> MultiValueEncoded<TokenIdentifier> {
+        let mut result = MultiValueEncoded::new();
+        for token in self.token_allowlist().iter() {
+            result.push(token);
+        }
+        result
+    }
+
+    #[view(getTokenDenylist)]
+    fn get_token_denylist(&self) -> MultiValueEncoded<TokenIdentifier> {
+        let mut result = MultiValueEncoded::new();
+        for token in self.token_denylist().iter() {
+            result.push(token);
+        }
+        result
+    }
+
+    /// Pair creation history of an address
+    /// Returns (live_pairs_created, last_created_at)
+    #[view(getCreatorStats)]
+    fn get_creator_stats(&self, creator: ManagedAddress) -> MultiValue2<u64, u64> {
+        (
+            self.creator_pair_count(&creator).get(),
+            self.creator_last_pair_created(&creator).get(),
+        ).into()
+    }
+
+    /// Treasury receiving the protocol fee cut (zero address = owner receives it)
+    #[view(getTreasuryAddress)]
+    fn get_treasury_address(&self) -> ManagedAddress {
+        if self.treasury_address().is_empty() {
+            ManagedAddress::zero()
+        } else {
+            self.treasury_address().get()
+        }
+    }
+
+    /// Fee splitter recipients and their weights in bps (empty = off)
+    #[view(getFeeSplit)]
+    fn get_fee_split(&self) -> MultiValueEncoded<MultiValue2<ManagedAddress, u64>> {
+        let mut result = MultiValueEncoded::new();
+        if self.fee_split_recipients().is_empty() {
+            return result;
+        }
+        for recipient in self.fee_split_recipients().get().iter() {
+            result.push((recipient.address, recipient.weight_bps).into());
+        }
+        result
+    }
+
+    /// Protocol fee a pair accrues before the splitter forwards it
+    /// Returns: (threshold_a, threshold_b)
+    #[view(getFeeSplitThreshold)]
+    fn get_fee_split_threshold(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        (self.fee_split_threshold_a(pair_id).get(), self.fee_split_threshold_b(pair_id).get()).into()
+    }
+
+    /// Protocol fees waiting to be sent to the treasury
+    #[view(getTreasuryFees)]
+    fn get_treasury_fees(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        (
+            self.treasury_unclaimed_fees_a(pair_id).get(),
+            self.treasury_unclaimed_fees_b(pair_id).get(),
+        ).into()
+    }
+
+    /// Get current admin address
+    /// Role bitmask held by an address (the admin implicitly holds all roles)
+    #[view(getRoles)]
+    fn get_roles(&self, addr: ManagedAddress) -> u64 {
+        if addr == self.get_admin_address() {
+            return ALL_ROLES;
+        }
+        self.address_roles(&addr).get()
+    }
+
+    /// Every address holding at least one role
+    /// Returns (address, roles_bitmask) pairs
+    #[view(getRoleHolders)]
+    fn get_role_holders(&self) -> MultiValueEncoded<MultiValue2<ManagedAddress, u64>> {
+        let mut result = MultiValueEncoded::new();
+        for addr in self.role_holders().iter() {
+            let roles = self.address_roles(&addr).get();
+            result.push((addr, roles).into());
+        }
+        result
+    }
+
+    /// Queued timelocked admin actions
+    /// Returns (action_id, action, executable_after_timestamp) for each
+    #[view(getPendingAdminActions)]
+    fn get_pending_admin_actions(&self) -> MultiValueEncoded<MultiValue3<u64, AdminAction<Self::Api>, u64>> {
+        let mut result = MultiValueEncoded::new();
+        for action_id in self.timelock_pending_actions().iter() {
+            result.push((
+                action_id,
+                self.timelock_action(action_id).get(),
+                self.timelock_action_eta(action_id).get(),
+            ).into());
+        }
+        result
+    }
+
+    #[view(getTimelockDelay)]
+    fn get_timelock_delay_view(&self) -> u64 {
+        self.get_timelock_delay()
+    }
+
+    /// Nominated admin awaiting acceptAdmin (zero address if none)
+    #[view(getPendingAdmin)]
+    fn get_pending_admin(&self) -> ManagedAddress {
+        if self.pending_admin().is_empty() {
+            ManagedAddress::zero()
+        } else {
+            self.pending_admin().get()
+        }
+    }
+
+    /// Referral earnings of an address, one entry per token
+    /// Returns (token, claimable, total_earned) for each token ever earned in
+    #[view(getReferralEarnings)]
+    fn get_referral_earnings(&self, referrer: ManagedAddress) -> MultiValueEncoded<MultiValue3<TokenIdentifier, BigUint, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+        for token in self.referral_earned_tokens(&referrer).iter() {
+            let claimable = self.referral_unclaimed(&referrer, &token).get();
+            let total = self.referral_total_earned(&referrer, &token).get();
+            result.push((token, claimable, total).into());
+        }
+        result
+    }
+
+    /// Rebate tiers of a pair as (min_volume in token A, rebate_bps of the swap fee), lowest first
+    #[view(getPairRebateTiers)]
+    fn get_pair_rebate_tiers(&self, pair_id: u64) -> MultiValueEncoded<MultiValue2<BigUint, u64>> {
+        let mut result = MultiValueEncoded::new();
+        for tier in self.pair_rebate_tiers(pair_id).get().iter() {
+            result.push((tier.min_volume, tier.rebate_bps).into());
+        }
+        result
+    }
+
+    /// A trader's standing on a pair's rebate tiers
+    /// Returns (rolling volume in token A, rebate_bps the next swap earns)
+    #[view(getTraderRebateInfo)]
+    fn get_trader_rebate_info(&self, addr: ManagedAddress, pair_id: u64) -> MultiValue2<BigUint, u64> {
+        let day = self.blockchain().get_block_timestamp() / SECONDS_PER_DAY;
+        let buckets = self.get_rebate_volume_buckets(&addr, pair_id, day);
+        let volume = self.sum_volume_buckets(&buckets);
+        let rebate_bps = self.get_rebate_bps(&self.pair_rebate_tiers(pair_id).get(), &volume);
+        (volume, rebate_bps).into()
+    }
+
+    /// Claimable fee rebates of an address as (token, amount)
+    #[view(getFeeRebates)]
+    fn get_fee_rebates(&self, addr: ManagedAddress) -> MultiValueEncoded<MultiValue2<TokenIdentifier, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+        for token in self.rebate_tokens(&addr).iter() {
+            let amount = self.rebate_unclaimed(&addr, &token).get();
+            result.push((token, amount).into());
+        }
+        result
+    }
+
+    /// Aggregator contracts allowed to call swapFor
+    #[view(getWhitelistedRouters)]
+    fn get_whitelisted_routers(&self) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        for router in self.whitelisted_routers().iter() {
+            result.push(router);
+        }
+        result
+    }
+
+    /// Aliases of every route in the registry (see swapExactInRoute)
+    #[view(getRouteAliases)]
+    fn get_route_aliases(&self) -> MultiValueEncoded<ManagedBuffer> {
+        let mut result = MultiValueEncoded::new();
+        for alias in self.route_aliases().iter() {
+            result.push(alias);
+        }
+        result
+    }
+
+    /// Registered route behind an alias (token_in, token_out and the pair_id path)
+    #[view(getRoute)]
+    fn get_route(&self, route_alias: ManagedBuffer) -> RegisteredRoute<Self::Api> {
+        require!(!self.registered_route(&route_alias).is_empty(), "Unknown route");
+        self.registered_route(&route_alias).get()
+    }
+
+    /// Alias of the preferred route from token_in to token_out (empty when none)
+    /// Use "KLV" for native KLV.
+    #[view(getPreferredRoute)]
+    fn get_preferred_route(&self, token_in: TokenIdentifier, token_out: TokenIdentifier) -> ManagedBuffer {
+        self.preferred_route(&token_in, &token_out).get()
+    }
+
+    /// Buyback-and-burn settings and running total
+    /// Returns (buyback_token, burn_address, max_slippage_bps, total_burned)
+    #[view(getBuybackInfo)]
+    fn get_buyback_info(&self) -> MultiValue4<TokenIdentifier, ManagedAddress, u64, BigUint> {
+        let burn_address = if self.buyback_burn_address().is_empty() {
+            ManagedAddress::zero()
+        } else {
+            self.buyback_burn_address().get()
+        };
+        (
+            self.buyback_token().get(),
+            burn_address,
+            self.buyback_max_slippage_bps().get(),
+            self.buyback_total_burned().get(),
+        ).into()
+    }
+
+    #[view(getReferralFeeBps)]
+    fn get_referral_fee_bps(&self) -> u64 {
+        self.param_referral_fee_bps().get()
+    }
+
+    /// Stray balances sync / skim would move for a pair
+    /// Returns: (stray_a, stray_b)
+    #[view(getStrayBalances)]
+    fn get_stray_balances(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        self.get_pair_stray_balances(pair_id).into()
+    }
+
+    /// Per-pair accounting check for monitoring
+    /// Pairs share the contract's balances, so a pair's attributable balance is what
+    /// remains after every other pair's and the contract's obligations are covered.
+    /// `mismatch` flags drift such as the dilution left by the legacy single-sided
+    /// addLiquidity paths. Iterates the pair's LPs and orders - intended for off-chain calls.
+    #[view(auditPair)]
+    fn audit_pair(&self, pair_id: u64) -> PairAudit<Self::Api> {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        
+        let token_a = self.pair_token(pair_id, true);
+        let token_b = self.pair_token(pair_id, false);
+        let obligations_a = self.get_pair_accounted_balance(pair_id, &token_a);
+        let obligations_b = self.get_pair_accounted_balance(pair_id, &token_b);
+        let attributable_a = self.get_pair_attributable_balance(&token_a, &obligations_a);
+        let attributable_b = self.get_pair_attributable_balance(&token_b, &obligations_b);
+        
+        let mut lp_shares_sum = BigUint::zero();
+        let mut lp_unclaimed_a = BigUint::zero();
+        let mut lp_unclaimed_b = BigUint::zero();
+        for lp in self.lp_list(pair_id).iter() {
+            lp_shares_sum += self.lp_shares(pair_id, &lp).get();
+            let (fees_a, fees_b) = self.calculate_lp_pending_fees(pair_id, &lp);
+            lp_unclaimed_a += fees_a;
+            lp_unclaimed_b += fees_b;
+        }
+        
+        let owner_shares = self.owner_shares(pair_id).get();
+        let total_lp_shares = self.total_lp_shares(pair_id).get();
+        let underfunded_a = attributable_a < obligations_a;
+        let underfunded_b = attributable_b < obligations_b;
+        let shares_mismatch = lp_shares_sum != total_lp_shares;
+        let has_legacy_owner_shares = owner_shares > 0u64;
+        
+        PairAudit {
+            reserve_a: self.pair_reserve_a(pair_id).get(),
+            reserve_b: self.pair_reserve_b(pair_id).get(),
+            obligations_a,
+            obligations_b,
+            attributable_a,
+            attributable_b,
+            owner_shares,
+            total_lp_shares,
+            lp_shares_sum,
+            protocol_unclaimed_a: self.owner_unclaimed_fees_a(pair_id).get()
+                + self.treasury_unclaimed_fees_a(pair_id).get(),
+            protocol_unclaimed_b: self.owner_unclaimed_fees_b(pair_id).get()
+                + self.treasury_unclaimed_fees_b(pair_id).get(),
+            lp_unclaimed_a,
+            lp_unclaimed_b,
+            underfunded_a,
+            underfunded_b,
+            shares_mismatch,
+            has_legacy_owner_shares,
+            mismatch: underfunded_a || underfunded_b || shares_mismatch || has_legacy_owner_shares,
+        }
+    }
+
+    /// Get IL protection settings
+    /// Returns: (fund_bps, min_duration, max_coverage_bps)
+    #[view(getIlProtectionParams)]
+    fn get_il_protection_params(&self) -> MultiValue3<u64, u64, u64> {
+        (
+            self.il_fund_bps().get(),
+            self.il_min_duration().get(),
+            self.il_max_coverage_bps().get(),
+        ).into()
+    }
+
+    /// Get a pair's IL fund balances
+    /// Returns: (fund_a, fund_b)
+    #[view(getIlFund)]
+    fn get_il_fund(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        (self.il_fund_a(pair_id).get(), self.il_fund_b(pair_id).get()).into()
+    }
+
+    /// Get an LP airdrop (fails once closed)
+    #[view(getLpAirdrop)]
+    fn get_lp_airdrop(&self, airdrop_id: u64) -> LpAirdrop<Self::Api> {
+        require!(!self.lp_airdrop(airdrop_id).is_empty(), "Airdrop does not exist");
+        self.lp_airdrop(airdrop_id).get()
+    }
+
+    /// Get a wrapped LP position by NFT nonce
+    /// Returns: (pair_id, shares, lock_until, boost_bps, pending_fees_a, pending_fees_b)
+    #[view(getLpPositionNft)]
+    fn get_lp_position_nft(&self, nonce: u64) -> MultiValue6<u64, BigUint, u64, u64, BigUint, BigUint> {
+        require!(!self.lp_position_pair(nonce).is_empty(), "Unknown LP position");
+        let pair_id = self.lp_position_pair(nonce).get();
+        let holder = self.lp_position_holder(nonce);
+        let (pending_a, pending_b) = self.calculate_lp_pending_fees(pair_id, &holder);
+        (
+            pair_id,
+            self.lp_shares(pair_id, &holder).get(),
+            self.lp_lock_until(pair_id, &holder).get(),
+            self.lp_lock_boost_bps(pair_id, &holder).get(),
+            pending_a,
+            pending_b,
+        ).into()
+    }
+
+    /// Amount `addr` can still claim from an airdrop (0 once claimed or closed)
+    #[view(getLpAirdropClaimable)]
+    fn get_lp_airdrop_claimable(&self, airdrop_id: u64, addr: ManagedAddress) -> BigUint {
+        if self.lp_airdrop(airdrop_id).is_empty() {
+            return BigUint::zero();
+        }
+        self.get_airdrop_claimable(airdrop_id, &addr)
+    }
+
+    /// Get the deposit snapshot IL protection measures an LP against
+    /// Returns: (deposited_a, deposited_b, deposit_timestamp, covered_from)
+    #[view(getLpIlSnapshot)]
+    fn get_lp_il_snapshot(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue4<BigUint, BigUint, u64, u64> {
+        let deposit_ts = self.lp_deposit_timestamp(pair_id, &addr).get();
+        let covered_from = if deposit_ts == 0 { 0 } else { deposit_ts + self.il_min_duration().get() };
+        (
+            self.lp_deposited_a(pair_id, &addr).get(),
+            self.lp_deposited_b(pair_id, &addr).get(),
+            deposit_ts,
+            covered_from,
+        ).into()
+    }
+
+    /// Get a launch (also after graduation, with its pair_id)
+    #[view(getLaunch)]
+    fn get_launch(&self, launch_id: u64) -> Launch<Self::Api> {
+        require!(!self.launch(launch_id).is_empty(), "Launch does not exist");
+        self.launch(launch_id).get()
+    }
+
+    /// Quote a launch buy or sell along its curve
+    /// Buys are capped at the funding target; returns (amount_out, amount_in_used).
+    #[view(getLaunchQuote)]
+    fn get_launch_quote(&self, launch_id: u64, amount_in: BigUint, is_buy: bool) -> MultiValue2<BigUint, BigUint> {
+        require!(!self.launch(launch_id).is_empty(), "Launch does not exist");
+        let launch = self.launch(launch_id).get();
+        if launch.pair_id != 0 {
+            return (BigUint::zero(), BigUint::zero()).into();
+        }
+        if is_buy {
+            self.quote_launch_buy(&launch, &amount_in).into()
+        } else {
+            (self.quote_launch_sell(&launch, &amount_in), amount_in).into()
+        }
+    }
+
+    /// Get a vesting schedule (fails once fully claimed)
+    #[view(getVestingSchedule)]
+    fn get_vesting_schedule(&self, schedule_id: u64) -> VestingSchedule<Self::Api> {
+        require!(!self.vesting_schedule(schedule_id).is_empty(), "Vesting schedule does not exist");
+        self.vesting_schedule(schedule_id).get()
+    }
+
+    /// Amount a schedule's beneficiary can claim right now
+    #[view(getVestingClaimable)]
+    fn get_vesting_claimable(&self, schedule_id: u64) -> BigUint {
+        if self.vesting_schedule(schedule_id).is_empty() {
+            return BigUint::zero();
+        }
+        self.get_vesting_claimable_internal(schedule_id)
+    }
+
+    /// Open vesting schedules of a beneficiary
+    #[view(getBeneficiaryVestingIds)]
+    fn get_beneficiary_vesting_ids(&self, beneficiary: ManagedAddress) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for schedule_id in self.beneficiary_vesting_ids(&beneficiary).iter() {
+            result.push(schedule_id);
+        }
+        result
+    }
+
+    /// Get a trading competition
+    #[view(getCompetition)]
+    fn get_competition(&self, competition_id: u64) -> Competition<Self::Api> {
+        require!(!self.competition(competition_id).is_empty(), "Competition does not exist");
+        self.competition(competition_id).get()
+    }
+
+    /// Competition a pair's swaps currently count towards (0 = none)
+    #[view(getPairActiveCompetition)]
+    fn get_pair_active_competition(&self, pair_id: u64) -> u64 {
+        self.pair_active_competition(pair_id).get()
+    }
+
+    /// Competition leaderboard for the frontend
+    /// Top-N competitions return the ranked list (largest volume first, start/limit
+    /// ignored); pro-rata ones page through every trader unsorted (max MAX_PAGE_SIZE).
+    #[view(getCompetitionLeaderboard)]
+    fn get_competition_leaderboard(
+        &self,
+        competition_id: u64,
+        start: u64,
+        limit: u64,
+    ) -> MultiValueEncoded<CompetitionEntry<Self::Api>> {
+        require!(!self.competition(competition_id).is_empty(), "Competition does not exist");
+        let mut result = MultiValueEncoded::new();
+        if self.competition(competition_id).get().top_n > 0 {
+            for entry in self.competition_top(competition_id).get().iter() {
+                result.push(entry);
+            }
+            return result;
+        }
+        
+        require!(limit > 0 && limit <= MAX_PAGE_SIZE, "Invalid limit");
+        for addr in self.competition_traders(competition_id).iter().skip(start as usize).take(limit as usize) {
+            result.push(CompetitionEntry {
+                volume: self.competition_volume(competition_id, &addr).get(),
+                address: addr,
+            });
+        }
+        result
+    }
+
+    /// A trader's standing in a competition
+    /// Returns: (volume, claimable_reward, claimed)
+    #[view(getCompetitionTrader)]
+    fn get_competition_trader(&self, competition_id: u64, addr: ManagedAddress) -> MultiValue3<BigUint, BigUint, bool> {
+        if self.competition(competition_id).is_empty() {
+            return (BigUint::zero(), BigUint::zero(), false).into();
+        }
+        (
+            self.competition_volume(competition_id, &addr).get(),
+            self.get_competition_claimable(competition_id, &addr),
+            self.competition_claimed(competition_id, &addr).get(),
+        ).into()
+    }
+
+    /// Get a limit order by id
+    #[view(getLimitOrder)]
+    fn get_limit_order(&self, order_id: u64) -> OptionalValue<LimitOrder<Self::Api>> {
+        if self.limit_order(order_id).is_empty() {
+            OptionalValue::None
+        } else {
+            OptionalValue::Some(self.limit_order(order_id).get())
+        }
+    }
+
+    /// Open limit order ids on a pair
+    #[view(getPairLimitOrders)]
+    fn get_pair_limit_orders(&self, pair_id: u64) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for order_id in self.pair_limit_orders(pair_id).iter() {
+            result.push(order_id);
+        }
+        result
+    }
+
+    /// Open limit order ids placed by an address
+    #[view(getUserLimitOrders)]
+    fn get_user_limit_orders(&self, addr: ManagedAddress) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for order_id in self.user_limit_orders(&addr).iter() {
+            result.push(order_id);
+        }
+        result
+    }
+
+    /// Get a DCA order by id
+    #[view(getDcaOrder)]
+    fn get_dca_order(&self, order_id: u64) -> OptionalValue<DcaOrder<Self::Api>> {
+        if self.dca_order(order_id).is_empty() {
+            OptionalValue::None
+        } else {
+            OptionalValue::Some(self.dca_order(order_id).get())
+        }
+    }
+
+    /// Active DCA schedules of an address, as (order_id, order)
+    #[view(getUserDcaOrders)]
+    fn get_user_dca_orders(&self, addr: ManagedAddress) -> MultiValueEncoded<MultiValue2<u64, DcaOrder<Self::Api>>> {
+        let mut result = MultiValueEncoded::new();
+        for order_id in self.user_dca_orders(&addr).iter() {
+            result.push((order_id, self.dca_order(order_id).get()).into());
+        }
+        result
+    }
+
+    /// Progress of a streaming swap
+    #[view(getStreamOrder)]
+    fn get_stream_order(&self, order_id: u64) -> OptionalValue<StreamOrder<Self::Api>> {
+        if self.stream_order(order_id).is_empty() {
+            OptionalValue::None
+        } else {
+            OptionalValue::Some(self.stream_order(order_id).get())
+        }
+    }
+
+    /// Open streaming swaps of an address, as (order_id, order)
+    #[view(getUserStreamOrders)]
+    fn get_user_stream_orders(&self, addr: ManagedAddress) -> MultiValueEncoded<MultiValue2<u64, StreamOrder<Self::Api>>> {
+        let mut result = MultiValueEncoded::new();
+        for order_id in self.user_stream_orders(&addr).iter() {
+            result.push((order_id, self.stream_order(order_id).get()).into());
+        }
+        result
+    }
+
+    #[view(getDcaKeeperFeeBps)]
+    fn get_dca_keeper_fee_bps_view(&self) -> u64 {
+        self.get_dca_keeper_fee_bps()
+    }
+
+    /// Farming configuration
+    /// Returns (reward_token, reward_per_second, total_weight, reward_pool)
+    #[view(getFarmInfo)]
+    fn get_farm_info(&self) -> MultiValue4<TokenIdentifier, BigUint, u64, BigUint> {
+        (
+            self.farm_reward_token().get(),
+            self.farm_reward_per_second().get(),
+            self.farm_total_weight().get(),
+            self.farm_reward_pool().get(),
+        ).into()
+    }
+
+    /// Farm state for a pair
+    /// Returns (weight, total_staked, acc_reward_per_share, last_update)
+    #[view(getFarmPool)]
+    fn get_farm_pool(&self, pair_id: u64) -> MultiValue4<u64, BigUint, BigUint, u64> {
+        (
+            self.farm_pool_weight(pair_id).get(),
+            self.farm_total_staked(pair_id).get(),
+            self.farm_acc_reward_per_share(pair_id).get(),
+            self.farm_last_update(pair_id).get(),
+        ).into()
+    }
+
+    /// A staker's farm position
+    /// Returns (staked_shares, pending_reward) - pending includes emissions up to now
+    #[view(getFarmPosition)]
+    fn get_farm_position(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue2<BigUint, BigUint> {
+        let staked = self.farm_staked(pair_id, &addr).get();
+        let mut acc = self.farm_acc_reward_per_share(pair_id).get();
+        
+        let now = self.blockchain().get_block_timestamp();
+        let last = self.farm_last_update(pair_id).get();
+        let total_staked = self.farm_total_staked(pair_id).get();
+        let weight = self.farm_pool_weight(pair_id).get();
+        let total_weight = self.farm_total_weight().get();
+        if now > last && total_staked > 0u64 && weight > 0 && total_weight > 0 {
+            let reward = self.farm_reward_per_second().get() * (now - last) * weight / total_weight;
+            acc += reward * PRECISION / &total_staked;
+        }
+        
+        let pending = &staked * &acc / PRECISION - self.farm_reward_debt(pair_id, &addr).get()
+            + self.farm_unpaid_reward(pair_id, &addr).get();
+        (staked, pending).into()
+    }
+
+    #[view(isGloballyPaused)]
+    fn is_globally_paused(&self) -> bool {
+        self.globally_paused().get()
+    }
+
+    #[view(getAdmin)]
+    fn get_admin(&self) -> ManagedAddress {
+        self.get_admin_address()
+    }
+
+    /// Dead-man switch status
+    /// Returns (backup_admin, inactivity_period, recovery_delay, last_admin_activity, recovery_started_at)
+    /// backup_admin is the zero address when no backup is configured
+    #[view(getDeadManSwitchInfo)]
+    fn get_dead_man_switch_info(&self) -> MultiValue5<ManagedAddress, u64, u64, u64, u64> {
+        let backup = if self.backup_admin().is_empty() {
+            ManagedAddress::zero()
+        } else {
+            self.backup_admin().get()
+        };
+        (
+            backup,
+            self.admin_inactivity_period().get(),
+            self.admin_recovery_delay().get(),
+            self.last_admin_activity().get(),
+            self.admin_recovery_started_at().get(),
+        ).into()
+    }
+
+    #[view(getRestrictedAddresses)]
+    fn get_restricted_addresses(&self) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        for addr in self.compliance_restricted().iter() {
+            result.push(addr);
+        }
+        result
+    }
+
+    #[view(isAddressRestricted)]
+    fn is_address_restricted(&self, addr: ManagedAddress) -> bool {
+        self.compliance_restricted().contains(&addr)
+    }
+
+    #[view(getComplianceFlaggedPairs)]
+    fn get_compliance_flagged_pairs(&self) -> MultiValueEncoded<u64> {
+        let mut result = MultiValueEncoded::new();
+        for pair_id in self.compliance_flagged_pairs().iter() {
+            result.push(pair_id);
+        }
+        result
+    }
+
+    /// Staking token, unstake cooldown (seconds) and total staked plus unstaking
+    #[view(getStakingConfig)]
+    fn get_staking_config(&self) -> MultiValue3<TokenIdentifier, u64, BigUint> {
+        (
+            self.staking_token().get(),
+            self.get_unstake_cooldown(),
+            self.staking_outstanding().get(),
+        ).into()
+    }
+
+    /// Discount tiers as (min_stake, discount_bps of the swap fee), lowest first
+    #[view(getStakingTiers)]
+    fn get_staking_tiers(&self) -> MultiValueEncoded<MultiValue2<BigUint, u64>> {
+        let mut result = MultiValueEncoded::new();
+        for tier in self.staking_tiers().get().iter() {
+            result.push((tier.min_stake, tier.discount_bps).into());
+        }
+        result
+    }
+
+    /// A staker's position
+    /// Returns (staked, unstaking, unlock_at, discount_bps)
+    #[view(getStakerInfo)]
+    fn get_staker_info(&self, addr: ManagedAddress) -> MultiValue4<BigUint, BigUint, u64, u64> {
+        (
+            self.staked_balance(&addr).get(),
+            self.unstaking_amount(&addr).get(),
+            self.unstaking_unlock_at(&addr).get(),
+            self.get_staking_discount_bps(&addr),
+        ).into()
+    }
+
+    /// Swap fee (bps) an address pays on a pair after its staking discount
+    #[view(getTraderFeeBps)]
+    fn get_trader_fee_bps_view(&self, pair_id: u64, addr: ManagedAddress) -> u64 {
+        self.get_trader_fee_bps(pair_id, &addr)
+    }
+
+    /// Nonce the user's next meta-swap order must carry
+    #[view(getMetaSwapNonce)]
+    fn get_meta_swap_nonce(&self, user: ManagedAddress) -> u64 {
+        self.meta_swap_nonce(&user).get()
+    }
+
+    /// Funds a user set aside for relayed swaps in a token
+    #[view(getRelayerAllowance)]
+    fn get_relayer_allowance(&self, user: ManagedAddress, token: TokenIdentifier) -> BigUint {
+        self.relayer_allowance(&user, &token).get()
+    }
+
+    /// Exact bytes a user signs (Ed25519) to authorize a meta-swap order
+    #[view(getMetaSwapMessage)]
+    fn get_meta_swap_message(&self, order: MetaSwapOrder<Self::Api>) -> ManagedBuffer {
+        self.meta_swap_message(&order)
+    }
+
+    /// Addresses with a daily limit on a pair
+    /// Returns (address, daily_limit_a, used_today_a) for each limited address
+    /// Limits only apply while the pair is flagged.
+    #[view(getPairComplianceLimits)]
+    fn get_pair_compliance_limits(&self, pair_id: u64) -> MultiValueEncoded<MultiValue3<ManagedAddress, BigUint, BigUint>> {
+        let day = self.blockchain().get_block_timestamp() / SECONDS_PER_DAY;
+        let mut result = MultiValueEncoded::new();
+        for addr in self.compliance_limited_addresses(pair_id).iter() {
+            let limit = self.compliance_daily_limit(pair_id, &addr).get();
+            let used = self.compliance_daily_used(pair_id, &addr, day).get();
+            result.push((addr, limit, used).into());
+        }
+        result
+    }
+
+    /// Compliance status of an address on a pair
+    /// Returns (is_restricted, pair_is_flagged, daily_limit_a, used_today_a)
+    /// daily_limit_a is 0 when the address has no limit on this pair
+    #[view(getAddressCompliance)]
+    fn get_address_compliance(&self, pair_id: u64, addr: ManagedAddress) -> MultiValue4<bool, bool, BigUint, BigUint> {
+        let day = self.blockchain().get_block_timestamp() / SECONDS_PER_DAY;
+        (
+            self.compliance_restricted().contains(&addr),
+            self.compliance_flagged_pairs().contains(&pair_id),
+            self.compliance_daily_limit(pair_id, &addr).get(),
+            self.compliance_daily_used(pair_id, &addr, day).get(),
+        ).into()
+    }
+
+    // ========================================================================
+    // PRICE FEED (Stable interface for other contracts)
+    // ========================================================================
+    // Consumed through digiko_swap_proxy::price_feed. These signatures are part
+    // of the public ABI: extend with new views instead of changing them.
+    // Prices are for one whole base token in whole units of the other pair
+    // token, scaled to 1e18 using the registered decimals (see setTokenDecimals).
+    // Every view returns 0 when it has no price to give.
+
+    /// Current spot price of base_token in the other token of the pair
+    /// Weight-adjusted for weighted pools. Fails if base_token is not in the pair.
+    #[view(getSpotPrice)]
+    fn get_spot_price(&self, pair_id: u64, base_token: TokenIdentifier) -> BigUint {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let price_a_in_b = self.get_price_normalized(pair_id);
+        self.price_in_base_token(pair_id, &base_token, price_a_in_b)
+    }
+
+    /// Time-weighted average price of base_token over the TWAP window (see getPairTwap)
+    /// 0 until the pair has traded across a full window.
+    #[view(getTwapPrice)]
+    fn get_twap_price_view(&self, pair_id: u64, base_token: TokenIdentifier) -> BigUint {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        let price_a_in_b = self
+            .get_twap_price(pair_id)
+            .and_then(|twap| self.normalize_price_a_in_b(pair_id, &twap, &BigUint::from(PRECISION)))
+            .unwrap_or_else(BigUint::zero);
+        self.price_in_base_token(pair_id, &base_token, price_a_in_b)
+    }
+
+    /// Reserves of both sides scaled to 18 decimals
+    /// Returns (reserve_a, reserve_b) - both 0 while either token's decimals are unknown
+    #[view(getReservesNormalized)]
+    fn get_reserves_normalized(&self, pair_id: u64) -> MultiValue2<BigUint, BigUint> {
+        require!(self.pair_exists(pair_id), "Pair does not exist");
+        match (self.get_pair_decimals(pair_id, true), self.get_pair_decimals(pair_id, false)) {
+            (Some(decimals_a), Some(decimals_b)) => {
+                let ten = BigUint::from(10u64);
+                (
+                    self.pair_reserve_a(pair_id).get() * ten.pow(NORMALIZED_PRICE_DECIMALS - decimals_a),
+                    self.pair_reserve_b(pair_id).get() * ten.pow(NORMALIZED_PRICE_DECIMALS - decimals_b),
+                ).into()
+            },
+            _ => (BigUint::zero(), BigUint::zero()).into(),
+        }
+    }
+}
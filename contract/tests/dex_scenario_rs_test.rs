@@ -0,0 +1,3090 @@
+// ============================================================================
+// CORE SCENARIOS
+// ============================================================================
+// Whitebox tests for the main V5 flows: pair creation and its fee tiers,
+// minting into empty and seeded pools, every swap direction (KDA, semi-fungible
+// KDA and native KLV), single-unit exact-out swaps on stable pools, liquidity
+// bootstrapping pools, decimal-normalized prices and the price feed, keeper
+// arbitrage, governance-registered routes, the TWAP guard, creator fee changes,
+// DGKO staking discounts, volume fee rebates (never earned by protocol
+// buybacks), router swaps on behalf of a user, signed and relayed meta-swaps,
+// the limit order, DCA and streaming swap cranks (skipping orders the pair
+// guards reject), the protocol / LP fee split, its forwarding and the LP fee
+// distribution crank, the pair APR estimate, IL protection against price
+// manipulation, the checkpoint bounty and per-pair LP fee roots, stray-token
+// sync and skim (and seeding upgraded pairs into their obligations), the flash
+// swap lock on reserve writers, the reentrancy lock on payout endpoints,
+// trading competitions, launchpad graduation, first-deposit sanity checks on
+// both the mint and pending-deposit paths, the LP cap and LP list pages, legacy
+// owner-share migration, removing liquidity (also with swaps frozen or as an
+// emergency exit) and deleting an emptied pair.
+//
+//   cargo test --test dex_scenario_rs_test
+// ============================================================================
+
+use digiko_swap::*;
+use digiko_swap::{
+    common::CommonModule,
+    fees::FeesModule,
+    launchpad::LaunchpadModule,
+    liquidity::LiquidityModule,
+    pair_admin::PairAdminModule,
+    staking::StakingModule,
+    storage::StorageModule,
+    swap::SwapModule,
+    views::ViewsModule,
+};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use klever_sc::codec::multi_types::OptionalValue;
+use klever_sc::types::{Address, ManagedBuffer, ManagedVec, MultiValueEncoded};
+use klever_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, whitebox::*, DebugApi,
+};
+
+const WASM_PATH: &str = "output/digiko-swap.wasm";
+
+const TOKEN_A: &[u8] = b"TKA-000001";
+const TOKEN_B: &[u8] = b"TKB-000002";
+const KLV: &[u8] = b"KLV";
+const SFT: &[u8] = b"SFT-000003";
+const DGKO: &[u8] = b"DGKO-000004";
+const SFT_NONCE: u64 = 7;
+
+const FEE_BPS: u64 = 30;
+const PROTOCOL_FEE_BPS: u64 = 1_000; // DEFAULT_PROTOCOL_FEE_BPS
+const MINIMUM_LIQUIDITY: u64 = 1_000; // DEFAULT_MINIMUM_LIQUIDITY
+const USER_BALANCE: u64 = 1_000_000_000_000;
+
+struct DexSetup<DexObjBuilder>
+where
+    DexObjBuilder: 'static + Copy + Fn() -> digiko_swap::ContractObj<DebugApi>,
+{
+    pub blockchain_wrapper: BlockchainStateWrapper,
+    pub owner_address: Address,
+    pub lp1: Address,
+    pub lp2: Address,
+    pub trader: Address,
+    pub dex_wrapper: ContractObjWrapper<digiko_swap::ContractObj<DebugApi>, DexObjBuilder>,
+}
+
+fn setup_dex<DexObjBuilder>(dex_builder: DexObjBuilder) -> DexSetup<DexObjBuilder>
+where
+    DexObjBuilder: 'static + Copy + Fn() -> digiko_swap::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0u64);
+    let klv_balance = rust_biguint!(USER_BALANCE);
+    let mut blockchain_wrapper = BlockchainStateWrapper::new();
+    let owner_address = blockchain_wrapper.create_user_account(&rust_zero);
+    let lp1 = blockchain_wrapper.create_user_account(&klv_balance);
+    let lp2 = blockchain_wrapper.create_user_account(&klv_balance);
+    let trader = blockchain_wrapper.create_user_account(&klv_balance);
+    let dex_wrapper = blockchain_wrapper.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        dex_builder,
+        WASM_PATH,
+    );
+
+    for user in [&lp1, &lp2, &trader] {
+        blockchain_wrapper.set_kda_balance(user, TOKEN_A, &rust_biguint!(USER_BALANCE));
+        blockchain_wrapper.set_kda_balance(user, TOKEN_B, &rust_biguint!(USER_BALANCE));
+    }
+
+    blockchain_wrapper
+        .execute_tx(&owner_address, &dex_wrapper, &rust_zero, |sc| {
+            sc.init();
+        })
+        .assert_ok();
+
+    DexSetup {
+        blockchain_wrapper,
+        owner_address,
+        lp1,
+        lp2,
+        trader,
+        dex_wrapper,
+    }
+}
+
+impl<DexObjBuilder> DexSetup<DexObjBuilder>
+where
+    DexObjBuilder: 'static + Copy + Fn() -> digiko_swap::ContractObj<DebugApi>,
+{
+    fn create_pair(&mut self, creator: &Address, token_a: &[u8], token_b: &[u8]) -> u64 {
+        let mut pair_id = 0u64;
+        self.blockchain_wrapper
+            .execute_tx(creator, &self.dex_wrapper, &rust_biguint!(0u64), |sc| {
+                pair_id = sc.create_pair(
+                    managed_token_id!(token_a),
+                    managed_token_id!(token_b),
+                    token_a == KLV,
+                    token_b == KLV,
+                    FEE_BPS,
+                    OptionalValue::None,
+                    OptionalValue::None,
+                );
+            })
+            .assert_ok();
+        pair_id
+    }
+
+    /// Mint with both KDA tokens in one multi-transfer
+    fn mint(&mut self, user: &Address, pair_id: u64, amount_a: u64, amount_b: u64) -> u64 {
+        let transfers = [
+            TxTokenTransfer {
+                token_identifier: TOKEN_A.to_vec(),
+                nonce: 0,
+                value: rust_biguint!(amount_a),
+            },
+            TxTokenTransfer {
+                token_identifier: TOKEN_B.to_vec(),
+                nonce: 0,
+                value: rust_biguint!(amount_b),
+            },
+        ];
+        let mut shares = 0u64;
+        self.blockchain_wrapper
+            .execute_kda_multi_transfer(user, &self.dex_wrapper, &transfers, |sc| {
+                shares = sc
+                    .mint(pair_id, managed_biguint!(0u64), OptionalValue::None)
+                    .to_u64()
+                    .unwrap();
+            })
+            .assert_ok();
+        shares
+    }
+
+    /// Seed a KLV pair through the two-step pending flow (KLV can't ride a KDA multi-transfer)
+    fn seed_klv_pair(&mut self, pair_id: u64, klv_is_a: bool, amount: u64) {
+        let user = self.lp1.clone();
+        let (kda_token, klv_endpoint_is_a) = if klv_is_a { (TOKEN_B, true) } else { (TOKEN_A, false) };
+
+        self.blockchain_wrapper
+            .execute_tx(&user, &self.dex_wrapper, &rust_biguint!(amount), |sc| {
+                if klv_endpoint_is_a {
+                    sc.deposit_pending_a_klv(pair_id);
+                } else {
+                    sc.deposit_pending_b_klv(pair_id);
+                }
+            })
+            .assert_ok();
+        self.blockchain_wrapper
+            .execute_kda_transfer(&user, &self.dex_wrapper, kda_token, 0, &rust_biguint!(amount), |sc| {
+                if klv_endpoint_is_a {
+                    sc.deposit_pending_b(pair_id);
+                } else {
+                    sc.deposit_pending_a(pair_id);
+                }
+            })
+            .assert_ok();
+        self.blockchain_wrapper
+            .execute_tx(&user, &self.dex_wrapper, &rust_biguint!(0u64), |sc| {
+                sc.finalize_liquidity(pair_id, managed_biguint!(0u64));
+            })
+            .assert_ok();
+    }
+
+    /// (user_gets, fee) as quoted before the swap
+    fn quote(&mut self, pair_id: u64, amount_in: u64, is_a_to_b: bool) -> (u64, u64) {
+        let mut quote = (0u64, 0u64);
+        self.blockchain_wrapper
+            .execute_query(&self.dex_wrapper, |sc| {
+                let (user_gets, fee) = sc
+                    .quote_swap(pair_id, managed_biguint!(amount_in), is_a_to_b)
+                    .into_tuple();
+                quote = (user_gets.to_u64().unwrap(), fee.to_u64().unwrap());
+            })
+            .assert_ok();
+        quote
+    }
+
+    fn reserves(&mut self, pair_id: u64) -> (u64, u64) {
+        let mut reserves = (0u64, 0u64);
+        self.blockchain_wrapper
+            .execute_query(&self.dex_wrapper, |sc| {
+                reserves = (
+                    sc.pair_reserve_a(pair_id).get().to_u64().unwrap(),
+                    sc.pair_reserve_b(pair_id).get().to_u64().unwrap(),
+                );
+            })
+            .assert_ok();
+        reserves
+    }
+
+    fn lp_shares(&mut self, pair_id: u64, user: &Address) -> u64 {
+        let mut shares = 0u64;
+        self.blockchain_wrapper
+            .execute_query(&self.dex_wrapper, |sc| {
+                shares = sc
+                    .lp_shares(pair_id, &managed_address!(user))
+                    .get()
+                    .to_u64()
+                    .unwrap();
+            })
+            .assert_ok();
+        shares
+    }
+}
+
+#[test]
+fn create_pair_registers_sequential_pairs() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let creator = setup.lp1.clone();
+
+    assert_eq!(setup.create_pair(&creator, TOKEN_A, TOKEN_B), 1);
+    assert_eq!(setup.create_pair(&creator, KLV, TOKEN_B), 2);
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert!(sc.pair_exists(1));
+            assert!(sc.pair_exists(2));
+            assert_eq!(sc.pair_token_a(1).get(), managed_token_id!(TOKEN_A));
+            assert_eq!(sc.pair_token_b(1).get(), managed_token_id!(TOKEN_B));
+            assert_eq!(sc.pair_fee_bps(1).get(), FEE_BPS);
+            assert!(sc.pair_swaps_enabled(1).get());
+            assert!(sc.pair_liquidity_enabled(1).get());
+            assert_eq!(sc.pair_creator(1).get(), managed_address!(&creator));
+            // KLV sides are stored under the WKLV id and flagged
+            assert_eq!(sc.pair_token_a(2).get(), managed_token_id!(KLV));
+            assert!(sc.pair_token_a_is_klv(2).get());
+        })
+        .assert_ok();
+
+    // Same tokens twice is rejected once unique mode is on
+    let owner = setup.owner_address.clone();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_require_unique_pairs(true);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&creator, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.create_pair(
+                managed_token_id!(TOKEN_A),
+                managed_token_id!(TOKEN_B),
+                false,
+                false,
+                FEE_BPS,
+                OptionalValue::None,
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Pair already exists for these tokens");
+}
+
+#[test]
+fn create_pair_only_accepts_approved_fee_tiers() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let creator = setup.lp1.clone();
+    let create_with_fee = |sc: digiko_swap::ContractObj<DebugApi>, fee_bps: u64| {
+        sc.create_pair(
+            managed_token_id!(TOKEN_A),
+            managed_token_id!(TOKEN_B),
+            false,
+            false,
+            fee_bps,
+            OptionalValue::None,
+            OptionalValue::None,
+        );
+    };
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&creator, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            create_with_fee(sc, 50);
+        })
+        .assert_user_error("Fee is not an approved tier");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push(50u64);
+            sc.set_fee_tiers_allowed(true, tiers);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&creator, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            create_with_fee(sc, 50);
+        })
+        .assert_ok();
+
+    // Withdrawing a tier stops new pairs using it
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push(100u64);
+            sc.set_fee_tiers_allowed(false, tiers);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&creator, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            create_with_fee(sc, 100);
+        })
+        .assert_user_error("Fee is not an approved tier");
+}
+
+#[test]
+fn mint_on_empty_pool_locks_minimum_liquidity() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+
+    // sqrt(1_000_000 * 4_000_000) = 2_000_000, minus the locked minimum
+    let shares = setup.mint(&lp1, pair_id, 1_000_000, 4_000_000);
+    assert_eq!(shares, 2_000_000 - MINIMUM_LIQUIDITY);
+    assert_eq!(setup.reserves(pair_id), (1_000_000, 4_000_000));
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.pair_locked_liquidity(pair_id).get(), managed_biguint!(MINIMUM_LIQUIDITY));
+            assert_eq!(sc.total_lp_shares(pair_id).get(), managed_biguint!(shares));
+            assert!(sc.lp_list(pair_id).contains(&managed_address!(&lp1)));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&lp1, TOKEN_A, &rust_biguint!(USER_BALANCE - 1_000_000));
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&lp1, TOKEN_B, &rust_biguint!(USER_BALANCE - 4_000_000));
+}
+
+#[test]
+fn mint_on_existing_pool_matches_ratio_and_refunds() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 1_000_000, 4_000_000);
+
+    // 1:4 pool: 100_000 A needs 400_000 B, the extra 100_000 B is refunded
+    let shares = setup.mint(&lp2, pair_id, 100_000, 500_000);
+    // 10% of the 2_000_000 total shares (locked minimum included)
+    assert_eq!(shares, 200_000);
+    assert_eq!(setup.reserves(pair_id), (1_100_000, 4_400_000));
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&lp2, TOKEN_A, &rust_biguint!(USER_BALANCE - 100_000));
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&lp2, TOKEN_B, &rust_biguint!(USER_BALANCE - 400_000));
+}
+
+#[test]
+fn swap_a_to_b_and_b_to_a_pay_the_quote() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    let amount_in = 100_000u64;
+    let (out_b, _) = setup.quote(pair_id, amount_in, true);
+    assert!(out_b > 0 && out_b < amount_in);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(out_b), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_B, &rust_biguint!(USER_BALANCE + out_b));
+
+    let (out_a, _) = setup.quote(pair_id, amount_in, false);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_B, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_b_to_a(pair_id, managed_biguint!(out_a), OptionalValue::None);
+        })
+        .assert_ok();
+    setup.blockchain_wrapper.check_kda_balance(
+        &trader,
+        TOKEN_A,
+        &rust_biguint!(USER_BALANCE - amount_in + out_a),
+    );
+
+    // Slippage protection: asking for more than the quote reverts
+    let (quoted, _) = setup.quote(pair_id, amount_in, true);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(quoted + 1), OptionalValue::None);
+        })
+        .assert_user_error("Slippage: output below minimum");
+}
+
+#[test]
+fn swap_klv_to_b_and_klv_to_a_pay_the_quote() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let amount_in = 50_000u64;
+
+    // KLV is token A: swapKlvToB sells A
+    let klv_a_pair = setup.create_pair(&lp1, KLV, TOKEN_B);
+    setup.seed_klv_pair(klv_a_pair, true, 5_000_000);
+    let (out_b, fee_a) = setup.quote(klv_a_pair, amount_in, true);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(amount_in), |sc| {
+            sc.swap_klv_to_b(klv_a_pair, managed_biguint!(out_b), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_B, &rust_biguint!(USER_BALANCE + out_b));
+
+    // KLV is token B: swapKlvToA sells B
+    let klv_b_pair = setup.create_pair(&lp1, TOKEN_A, KLV);
+    setup.seed_klv_pair(klv_b_pair, false, 5_000_000);
+    let (out_a, fee_b) = setup.quote(klv_b_pair, amount_in, false);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(amount_in), |sc| {
+            sc.swap_klv_to_a(klv_b_pair, managed_biguint!(out_a), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_A, &rust_biguint!(USER_BALANCE + out_a));
+
+    // KLV went into the reserves on both pairs, minus the fee taken from the input
+    assert_eq!(setup.reserves(klv_a_pair).0, 5_000_000 + amount_in - fee_a);
+    assert_eq!(setup.reserves(klv_b_pair).1, 5_000_000 + amount_in - fee_b);
+}
+
+#[test]
+fn stable_exact_out_quotes_a_single_unit() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let mut pair_id = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            pair_id = sc.create_pair(
+                managed_token_id!(TOKEN_A),
+                managed_token_id!(TOKEN_B),
+                false,
+                false,
+                FEE_BPS,
+                OptionalValue::Some(PoolType::Stable),
+                OptionalValue::Some(10_000u64),
+            );
+        })
+        .assert_ok();
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // A balanced, high-A pool: the inverse curve must not underflow
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100u64), |sc| {
+            sc.swap_tokens_for_exact_out(pair_id, managed_biguint!(1u64), OptionalValue::None);
+        })
+        .assert_ok();
+    let (reserve_a, reserve_b) = setup.reserves(pair_id);
+    assert!(reserve_a > 10_000_000 && reserve_b < 10_000_000);
+}
+
+#[test]
+fn sft_pair_swaps_the_listed_nonce_only() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    for user in [&lp1, &trader] {
+        setup
+            .blockchain_wrapper
+            .set_nft_balance(user, SFT, SFT_NONCE, &rust_biguint!(USER_BALANCE), &());
+        setup
+            .blockchain_wrapper
+            .set_nft_balance(user, SFT, SFT_NONCE + 1, &rust_biguint!(USER_BALANCE), &());
+    }
+
+    let mut pair_id = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            pair_id = sc.create_sft_pair(
+                managed_token_id!(SFT),
+                SFT_NONCE,
+                managed_token_id!(TOKEN_B),
+                0,
+                FEE_BPS,
+            );
+        })
+        .assert_ok();
+
+    let transfers = [
+        TxTokenTransfer {
+            token_identifier: SFT.to_vec(),
+            nonce: SFT_NONCE,
+            value: rust_biguint!(10_000_000u64),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_B.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(10_000_000u64),
+        },
+    ];
+    setup
+        .blockchain_wrapper
+        .execute_kda_multi_transfer(&lp1, &setup.dex_wrapper, &transfers, |sc| {
+            sc.mint(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+    assert_eq!(setup.reserves(pair_id), (10_000_000, 10_000_000));
+
+    // Buying the SFT side pays out the listed nonce
+    let amount_in = 100_000u64;
+    let (out_sft, _) = setup.quote(pair_id, amount_in, false);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_B, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap(pair_id, managed_biguint!(out_sft), 0, OptionalValue::None);
+        })
+        .assert_ok();
+    setup.blockchain_wrapper.check_nft_balance::<()>(
+        &trader,
+        SFT,
+        SFT_NONCE,
+        &rust_biguint!(USER_BALANCE + out_sft),
+        None,
+    );
+
+    // Another nonce of the same collection is not the pair token
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, SFT, SFT_NONCE + 1, &rust_biguint!(amount_in), |sc| {
+            sc.swap(pair_id, managed_biguint!(0u64), 0, OptionalValue::None);
+        })
+        .assert_user_error("Wrong token nonce");
+}
+
+#[test]
+fn swap_fee_splits_between_protocol_and_lps() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    let shares1 = setup.mint(&lp1, pair_id, 3_000_000, 3_000_000);
+    let shares2 = setup.mint(&lp2, pair_id, 1_000_000, 1_000_000);
+
+    // New pairs take the fee from the input, so an A -> B swap pays it in token A
+    let amount_in = 1_000_000u64;
+    let (_, fee) = setup.quote(pair_id, amount_in, true);
+    assert!(fee > 0);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+
+    let protocol_portion = fee * PROTOCOL_FEE_BPS / 10_000;
+    let lp_portion = fee - protocol_portion;
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            // No treasury configured: the protocol cut accrues to the owner
+            assert_eq!(sc.owner_unclaimed_fees_a(pair_id).get(), managed_biguint!(protocol_portion));
+            assert_eq!(sc.owner_unclaimed_fees_b(pair_id).get(), managed_biguint!(0u64));
+
+            let (fees1, _) = sc.calculate_lp_pending_fees(pair_id, &managed_address!(&lp1));
+            let (fees2, _) = sc.calculate_lp_pending_fees(pair_id, &managed_address!(&lp2));
+            let fees1 = fees1.to_u64().unwrap();
+            let fees2 = fees2.to_u64().unwrap();
+
+            // LPs share the rest pro rata, losing at most a wei each to rounding
+            assert!(fees1 + fees2 <= lp_portion);
+            assert!(lp_portion - (fees1 + fees2) <= 2);
+            assert!(fees1.abs_diff(lp_portion * shares1 / (shares1 + shares2)) <= 1);
+            assert!(fees2.abs_diff(lp_portion * shares2 / (shares1 + shares2)) <= 1);
+        })
+        .assert_ok();
+
+    // Claiming pays the LP out and resets its pending fees
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.claim_lp_fees(pair_id);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (pending_a, pending_b) = sc.calculate_lp_pending_fees(pair_id, &managed_address!(&lp2));
+            assert_eq!(pending_a, managed_biguint!(0u64));
+            assert_eq!(pending_b, managed_biguint!(0u64));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn distribution_crank_pays_lps_in_batches_for_a_cut() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 3_000_000, 3_000_000);
+    setup.mint(&lp2, pair_id, 1_000_000, 1_000_000);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(1_000_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+
+    let mut pending = [0u64; 2];
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            for (i, lp) in [&lp1, &lp2].into_iter().enumerate() {
+                let (fees_a, _) = sc.calculate_lp_pending_fees(pair_id, &managed_address!(lp));
+                pending[i] = fees_a.to_u64().unwrap();
+            }
+        })
+        .assert_ok();
+    assert!(pending[0] > 0 && pending[1] > 0);
+    let cut = |fees: u64| fees * 10 / 10_000;
+
+    let distribute = |setup: &mut DexSetup<_>, max_lps: u64| {
+        let mut paid = 0u64;
+        setup
+            .blockchain_wrapper
+            .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+                paid = sc.distribute_pending_fees(pair_id, max_lps);
+            })
+            .assert_ok();
+        paid
+    };
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.distribute_pending_fees(pair_id, 0);
+        })
+        .assert_user_error("Invalid batch size");
+
+    // One LP per call: the cursor moves on, then wraps around
+    assert_eq!(distribute(&mut setup, 1), 1);
+    setup.blockchain_wrapper.check_kda_balance(
+        &lp1,
+        TOKEN_A,
+        &rust_biguint!(USER_BALANCE - 3_000_000 + pending[0] - cut(pending[0])),
+    );
+    assert_eq!(distribute(&mut setup, 1), 1);
+    setup.blockchain_wrapper.check_kda_balance(
+        &lp2,
+        TOKEN_A,
+        &rust_biguint!(USER_BALANCE - 1_000_000 + pending[1] - cut(pending[1])),
+    );
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&keeper, TOKEN_A, &rust_biguint!(cut(pending[0]) + cut(pending[1])));
+
+    // Nothing left to pay on the next round
+    assert_eq!(distribute(&mut setup, 2), 0);
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (cursor, lp_count, _) = sc.get_fee_distribution_info(pair_id).into_tuple();
+            assert_eq!((cursor, lp_count), (0, 2));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn pair_apr_annualizes_the_lp_share_of_recent_fees() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(1_000_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+
+    let apr = |setup: &mut DexSetup<_>, window_days: u64| {
+        let mut result = (0u64, 0u64, 0u64);
+        setup
+            .blockchain_wrapper
+            .execute_query(&setup.dex_wrapper, |sc| {
+                let (apr_bps, volume_a, tvl_a) = sc.get_pair_apr(pair_id, window_days).into_tuple();
+                result = (
+                    apr_bps.to_u64().unwrap(),
+                    volume_a.to_u64().unwrap(),
+                    tvl_a.to_u64().unwrap(),
+                );
+            })
+            .assert_ok();
+        result
+    };
+
+    // Today's partial day doesn't count yet
+    assert_eq!(apr(&mut setup, 1).1, 0);
+
+    // 1M of volume at 0.3% leaves the LPs 2_700 after the 10% protocol cut
+    setup.blockchain_wrapper.set_block_timestamp(24 * 60 * 60);
+    let (apr_bps, volume_a, tvl_a) = apr(&mut setup, 1);
+    // The fee stays outside the reserves: 10M + 997_000 of token A, doubled
+    assert_eq!((volume_a, tvl_a), (1_000_000, 2 * 10_997_000));
+    assert_eq!(apr_bps, 2_700 * 365 * 10_000 / tvl_a);
+    assert_eq!(apr(&mut setup, 7).0, 2_700 * 365 * 10_000 / (tvl_a * 7));
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let _ = sc.get_pair_apr(pair_id, 0);
+        })
+        .assert_user_error("Invalid window");
+}
+
+#[test]
+fn il_compensation_is_refused_while_spot_is_off_the_twap() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.blockchain_wrapper.set_block_timestamp(10);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+    let shares = setup.mint(&lp2, pair_id, 1_000_000, 1_000_000);
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_il_protection(0, 0, 10_000);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_B, 0, &rust_biguint!(1_000_000u64), |sc| {
+            sc.fund_il_protection(pair_id);
+        })
+        .assert_ok();
+
+    // Small swaps half an hour apart give the pair a TWAP near 1
+    for timestamp in [100u64, 2_000u64] {
+        setup.blockchain_wrapper.set_block_timestamp(timestamp);
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(1_000u64), |sc| {
+                sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+            })
+            .assert_ok();
+    }
+
+    // Crashing the spot price right before removing doesn't draw on the fund
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(5_000_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.remove_liquidity(pair_id, managed_biguint!(shares), managed_biguint!(0u64), managed_biguint!(0u64));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (fund_a, fund_b) = sc.get_il_fund(pair_id).into_tuple();
+            assert_eq!(fund_a, managed_biguint!(0u64));
+            assert_eq!(fund_b, managed_biguint!(1_000_000u64));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn checkpoint_bounty_requires_a_minimum_interval() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let trader = setup.trader.clone();
+
+    // An interval of 0 would let anyone drain the pool in one block
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_checkpoint_config(0, managed_biguint!(100u64));
+        })
+        .assert_user_error("Checkpoint interval too short for a bounty");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_checkpoint_config(60 * 60, managed_biguint!(100u64));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(1_000u64), |sc| {
+            sc.fund_checkpoint_reward_pool();
+        })
+        .assert_ok();
+
+    setup.blockchain_wrapper.set_block_timestamp(1_000);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.checkpoint();
+            assert_eq!(sc.checkpoint_reward_pool().get(), managed_biguint!(900u64));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.checkpoint();
+        })
+        .assert_user_error("Checkpoint interval not elapsed");
+}
+
+#[test]
+fn lp_fee_merkle_root_is_checkpointed_per_pair() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+    setup.blockchain_wrapper.set_block_timestamp(1_000);
+
+    // The TVL checkpoint no longer walks LP lists
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.checkpoint();
+            let (_, leaf_count, timestamp) = sc.get_lp_fee_merkle_root(pair_id).into_tuple();
+            assert_eq!((leaf_count, timestamp), (0, 0));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.checkpoint_pair_fees(pair_id);
+            let (_, leaf_count, timestamp) = sc.get_lp_fee_merkle_root(pair_id).into_tuple();
+            assert_eq!(leaf_count, sc.lp_list(pair_id).len());
+            assert_eq!(timestamp, 1_000);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.checkpoint_pair_fees(pair_id + 1);
+        })
+        .assert_user_error("Pair does not exist");
+}
+
+#[test]
+fn sync_and_skim_move_only_tokens_nobody_is_owed() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let owner = setup.owner_address.clone();
+    let dex = setup.dex_wrapper.address_ref().clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+            sc.place_limit_order(pair_id, managed_biguint!(1u64), 0);
+        })
+        .assert_ok();
+
+    // Tokens sent straight to the contract are stray; the escrowed order is not
+    setup.blockchain_wrapper.set_kda_balance(&dex, TOKEN_A, &rust_biguint!(10_105_000u64));
+    setup.blockchain_wrapper.set_kda_balance(&dex, TOKEN_B, &rust_biguint!(10_002_000u64));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (stray_a, stray_b) = sc.get_stray_balances(pair_id).into_tuple();
+            assert_eq!((stray_a, stray_b), (managed_biguint!(5_000u64), managed_biguint!(2_000u64)));
+        })
+        .assert_ok();
+
+    // No sync while a flash swap callback is live on any pair
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.active_flash_swaps().set(1u64);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.sync(pair_id);
+        })
+        .assert_user_error("Flash swap in progress");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.active_flash_swaps().clear();
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.sync(pair_id);
+        })
+        .assert_ok();
+    assert_eq!(setup.reserves(pair_id), (10_005_000, 10_002_000));
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.sync(pair_id);
+        })
+        .assert_user_error("Reserves already in sync");
+
+    // Skim sends a later donation out, leaving the synced reserves and the order alone
+    setup.blockchain_wrapper.set_kda_balance(&dex, TOKEN_A, &rust_biguint!(10_105_700u64));
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.skim(pair_id, managed_address!(&owner));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&owner, TOKEN_A, &rust_biguint!(700u64));
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&dex, TOKEN_A, &rust_biguint!(10_105_000u64));
+}
+
+#[test]
+fn upgraded_pairs_are_seeded_into_obligations_one_at_a_time() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let owner = setup.owner_address.clone();
+    let dex = setup.dex_wrapper.address_ref().clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+    let klv_pair_id = setup.create_pair(&lp1, KLV, TOKEN_B);
+    setup.seed_klv_pair(klv_pair_id, true, 1_000_000);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+            sc.place_limit_order(pair_id, managed_biguint!(1u64), 0);
+        })
+        .assert_ok();
+
+    // Upgrade a deployment from before the counters: both pairs wait for the crank
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.token_obligations(&managed_token_id!(TOKEN_A)).clear();
+            sc.token_obligations(&managed_token_id!(TOKEN_B)).clear();
+            sc.token_obligations_initialized().clear();
+            sc.upgrade();
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.sync(pair_id);
+        })
+        .assert_user_error("Token obligations not seeded yet");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.seed_pair_obligations(pair_id);
+        })
+        .assert_user_error("Endpoint can only be called by owner");
+
+    // Token B still has the KLV pair to go after the first pair is seeded
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.seed_pair_obligations(pair_id);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.seed_pair_obligations(pair_id);
+        })
+        .assert_user_error("Pair obligations already seeded");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.sync(pair_id);
+        })
+        .assert_user_error("Token obligations not seeded yet");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.seed_pair_obligations(klv_pair_id);
+        })
+        .assert_ok();
+
+    // The seeded counters cover reserves and escrow, so only a donation is stray
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.sync(pair_id);
+        })
+        .assert_user_error("Reserves already in sync");
+    setup.blockchain_wrapper.set_kda_balance(&dex, TOKEN_A, &rust_biguint!(10_105_000u64));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (stray_a, stray_b) = sc.get_stray_balances(pair_id).into_tuple();
+            assert_eq!((stray_a, stray_b), (managed_biguint!(5_000u64), managed_biguint!(0u64)));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn reserve_writers_wait_for_a_running_flash_swap() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let owner = setup.owner_address.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+    for token in [TOKEN_A, TOKEN_B] {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(&lp2, &setup.dex_wrapper, token, 0, &rust_biguint!(500_000u64), |sc| {
+                if token == TOKEN_A {
+                    sc.deposit_pending_a(pair_id);
+                } else {
+                    sc.deposit_pending_b(pair_id);
+                }
+            })
+            .assert_ok();
+    }
+
+    // As seen from inside flashSwapCallback
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.flash_swap_active(pair_id).set(true);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.finalize_liquidity(pair_id, managed_biguint!(0u64));
+        })
+        .assert_user_error("Flash swap in progress");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.owner_remove_liquidity(pair_id, managed_biguint!(1u64));
+        })
+        .assert_user_error("Flash swap in progress");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.flash_swap_active(pair_id).clear();
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.finalize_liquidity(pair_id, managed_biguint!(0u64));
+        })
+        .assert_ok();
+    assert_eq!(setup.reserves(pair_id), (10_500_000, 10_500_000));
+}
+
+#[test]
+fn payout_endpoints_reject_reentry() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // As seen from a transfer hook or callback nested in a locked call
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.reentrancy_lock().set(true);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.finalize_liquidity(pair_id, managed_biguint!(0u64));
+        })
+        .assert_user_error("Reentrant call");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.harvest(pair_id);
+        })
+        .assert_user_error("Reentrant call");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.fill_orders(pair_id, 1);
+        })
+        .assert_user_error("Reentrant call");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.owner_remove_liquidity(pair_id, managed_biguint!(1u64));
+        })
+        .assert_user_error("Reentrant call");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.reentrancy_lock().clear();
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.fill_orders(pair_id, 1), 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn normalized_price_corrects_for_token_decimals() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    // 1 TKA (8 decimals) against 2 TKB (4 decimals)
+    setup.mint(&lp1, pair_id, 100_000_000, 20_000);
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.get_price_normalized(pair_id), managed_biguint!(0u64));
+        })
+        .assert_ok();
+
+    // Registering the decimals reaches pairs that already exist
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_token_decimals(managed_token_id!(TOKEN_A), 8);
+            sc.set_token_decimals(managed_token_id!(TOKEN_B), 4);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.pair_decimals_a(pair_id).get(), 8);
+            assert_eq!(
+                sc.get_price_normalized(pair_id),
+                managed_biguint!(2_000_000_000_000_000_000u64)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn price_feed_quotes_either_token_of_a_pair() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    // 1 TKA (8 decimals) against 2 TKB (4 decimals)
+    setup.mint(&lp1, pair_id, 100_000_000, 20_000);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_token_decimals(managed_token_id!(TOKEN_A), 8);
+            sc.set_token_decimals(managed_token_id!(TOKEN_B), 4);
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(
+                sc.get_spot_price(pair_id, managed_token_id!(TOKEN_A)),
+                managed_biguint!(2_000_000_000_000_000_000u64)
+            );
+            assert_eq!(
+                sc.get_spot_price(pair_id, managed_token_id!(TOKEN_B)),
+                managed_biguint!(500_000_000_000_000_000u64)
+            );
+            // No TWAP before a full window of trading
+            assert_eq!(sc.get_twap_price_view(pair_id, managed_token_id!(TOKEN_A)), managed_biguint!(0u64));
+
+            let (reserve_a, reserve_b) = sc.get_reserves_normalized(pair_id).into_tuple();
+            assert_eq!(reserve_a, managed_biguint!(1_000_000_000_000_000_000u64));
+            assert_eq!(reserve_b, managed_biguint!(2_000_000_000_000_000_000u64));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn lbp_weights_step_down_and_lower_the_price_over_time() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let day = 24 * 60 * 60;
+    setup.blockchain_wrapper.set_block_timestamp(1_000);
+
+    let create_lbp = |sc: digiko_swap::ContractObj<DebugApi>, start_time: u64, end_time: u64| {
+        sc.create_lbp_pair(
+            managed_token_id!(TOKEN_A),
+            managed_token_id!(TOKEN_B),
+            false,
+            false,
+            FEE_BPS,
+            95,
+            50,
+            start_time,
+            end_time,
+        )
+    };
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            create_lbp(sc, 999, 999 + 10 * day);
+        })
+        .assert_user_error("LBP cannot start in the past");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            create_lbp(sc, 1_000, 1_000 + 100 * day);
+        })
+        .assert_user_error("Invalid LBP duration");
+
+    let mut pair_id = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            pair_id = create_lbp(sc, 1_000, 1_000 + 10 * day);
+        })
+        .assert_ok();
+    setup.mint(&lp1, pair_id, 1_000_000, 1_000_000);
+
+    // (current_weight_a, spot price of A in B scaled by 1e12)
+    let lbp_state = |setup: &mut DexSetup<_>| {
+        let mut state = (0u64, 0u64);
+        setup
+            .blockchain_wrapper
+            .execute_query(&setup.dex_wrapper, |sc| {
+                let (_, _, _, _, weight_a, price_a_in_b, _) = sc.get_lbp_info(pair_id).into_tuple();
+                state = (weight_a, price_a_in_b.to_u64().unwrap());
+            })
+            .assert_ok();
+        state
+    };
+    assert_eq!(lbp_state(&mut setup), (95, 19_000_000_000_000));
+
+    // Halfway the 45 point shift has moved 22.5, snapped to 20
+    setup.blockchain_wrapper.set_block_timestamp(1_000 + 5 * day);
+    assert_eq!(lbp_state(&mut setup), (75, 3_000_000_000_000));
+
+    // Buyers trade against the current weights
+    let amount_in = 10_000u64;
+    let (out_a, _) = setup.quote(pair_id, amount_in, false);
+    assert!(out_a > 0);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_B, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_b_to_a(pair_id, managed_biguint!(out_a), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_A, &rust_biguint!(USER_BALANCE + out_a));
+
+    // After the schedule the pair stays at 50/50
+    setup.blockchain_wrapper.set_block_timestamp(1_000 + 11 * day);
+    let (weight_a, _) = lbp_state(&mut setup);
+    assert_eq!(weight_a, 50);
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (_, curve_param) = sc.get_pair_curve(pair_id).into_tuple();
+            assert_eq!(curve_param, 50);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn arb_cycle_pays_the_keeper_only_the_profit() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    // Duplicate pairs: TKB is twice as cheap on the second one
+    let pair_1 = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    let pair_2 = setup.create_pair(&lp2, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_1, 10_000_000, 10_000_000);
+    setup.mint(&lp2, pair_2, 10_000_000, 20_000_000);
+
+    let arb = |sc: digiko_swap::ContractObj<DebugApi>, min_profit: u64, path: [u64; 2]| {
+        let mut pairs = MultiValueEncoded::new();
+        for pair_id in path {
+            pairs.push(pair_id);
+        }
+        sc.arb(managed_token_id!(TOKEN_A), managed_biguint!(100_000u64), managed_biguint!(min_profit), pairs)
+    };
+
+    // Buying TKB where it's expensive loses money
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            arb(sc, 0, [pair_1, pair_2]);
+        })
+        .assert_user_error("Arbitrage not profitable");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            arb(sc, 1_000_000, [pair_2, pair_1]);
+        })
+        .assert_user_error("Profit below minimum");
+
+    let mut profit = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            profit = arb(sc, 1, [pair_2, pair_1]).to_u64().unwrap();
+        })
+        .assert_ok();
+    assert!(profit > 0);
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_A, &rust_biguint!(USER_BALANCE + profit));
+
+    // Both pairs moved towards each other's price
+    let (reserve_a_1, reserve_b_1) = setup.reserves(pair_1);
+    let (reserve_a_2, reserve_b_2) = setup.reserves(pair_2);
+    assert!(reserve_b_1 > 10_000_000 && reserve_a_1 < 10_000_000);
+    assert!(reserve_a_2 > 10_000_000 && reserve_b_2 < 20_000_000);
+}
+
+#[test]
+fn registered_route_follows_its_alias_to_a_recreated_pair() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let pair_1 = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    let pair_2 = setup.create_pair(&lp2, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_1, 10_000_000, 10_000_000);
+    setup.mint(&lp2, pair_2, 10_000_000, 20_000_000);
+
+    let set_route = |sc: digiko_swap::ContractObj<DebugApi>, token_in: &[u8], pair_id: u64| {
+        let mut path = MultiValueEncoded::new();
+        path.push(pair_id);
+        sc.set_route(ManagedBuffer::from(b"A-B"), managed_token_id!(token_in), path);
+    };
+
+    // Governance only, and the path has to start from token_in
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            set_route(sc, TOKEN_A, pair_1);
+        })
+        .assert_user_error("Caller is missing the required role");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            set_route(sc, KLV, pair_1);
+        })
+        .assert_user_error("Token not in pair");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            set_route(sc, TOKEN_A, pair_1);
+        })
+        .assert_ok();
+
+    // Re-pointing the alias moves the swap to the other pair
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            set_route(sc, TOKEN_A, pair_2);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let route = sc.get_route(ManagedBuffer::from(b"A-B"));
+            assert_eq!(route.token_out, managed_token_id!(TOKEN_B));
+            assert_eq!(route.path.len(), 1);
+            assert_eq!(route.path.get(0), pair_2);
+            assert_eq!(
+                sc.get_preferred_route(managed_token_id!(TOKEN_A), managed_token_id!(TOKEN_B)),
+                ManagedBuffer::from(b"A-B")
+            );
+        })
+        .assert_ok();
+
+    let amount_in = 100_000u64;
+    let (user_gets, _) = setup.quote(pair_2, amount_in, true);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_B, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_exact_in_route(managed_biguint!(0u64), ManagedBuffer::from(b"A-B"));
+        })
+        .assert_user_error("Payment does not match route");
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_exact_in_route(managed_biguint!(user_gets), ManagedBuffer::from(b"A-B"));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_B, &rust_biguint!(USER_BALANCE + user_gets));
+    assert_eq!(setup.reserves(pair_1), (10_000_000, 10_000_000));
+
+    // Removing the route drops its token-pair preference too
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.remove_route(ManagedBuffer::from(b"A-B"));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert!(sc
+                .get_preferred_route(managed_token_id!(TOKEN_A), managed_token_id!(TOKEN_B))
+                .is_empty());
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_exact_in_route(managed_biguint!(0u64), ManagedBuffer::from(b"A-B"));
+        })
+        .assert_user_error("Unknown route");
+}
+
+#[test]
+fn twap_guard_rejects_swaps_far_from_the_twap() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_twap_guard(pair_id, 500);
+        })
+        .assert_ok();
+
+    // No TWAP yet: the guard stays out of the way until a full window has passed
+    for timestamp in [100, 100 + 30 * 60] {
+        setup.blockchain_wrapper.set_block_timestamp(timestamp);
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(1_000u64), |sc| {
+                sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+            })
+            .assert_ok();
+    }
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (twap, _, max_deviation_bps) = sc.get_pair_twap(pair_id).into_tuple();
+            assert!(twap > 0u64);
+            assert_eq!(max_deviation_bps, 500);
+        })
+        .assert_ok();
+
+    // ~10% of the reserve in one swap moves the price ~17% off the TWAP
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(1_000_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_user_error("Price moves too far from TWAP");
+
+    // A 1% swap stays inside the 5% band
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn creator_fee_changes_respect_band_and_cooldown() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+
+    // Off until the owner configures a band
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_fee_by_creator(pair_id, 50);
+        })
+        .assert_user_error("Creator fee changes are disabled");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_creator_fee_band(10, 100, 3_600);
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_fee_by_creator(pair_id, 50);
+        })
+        .assert_user_error("Only the pair creator can change the fee");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_fee_by_creator(pair_id, 200);
+        })
+        .assert_user_error("Fee outside creator band");
+
+    setup.blockchain_wrapper.set_block_timestamp(1_000);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_fee_by_creator(pair_id, 50);
+        })
+        .assert_ok();
+    setup.blockchain_wrapper.set_block_nonce(1);
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.get_effective_fee_bps(pair_id), 50);
+        })
+        .assert_ok();
+
+    // A second change has to wait out the cooldown
+    setup.blockchain_wrapper.set_block_timestamp(2_000);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_fee_by_creator(pair_id, 60);
+        })
+        .assert_user_error("Fee change cooldown active");
+    setup.blockchain_wrapper.set_block_timestamp(4_600);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_fee_by_creator(pair_id, 60);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn dgko_stakers_pay_a_discounted_swap_fee() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    setup
+        .blockchain_wrapper
+        .set_kda_balance(&trader, DGKO, &rust_biguint!(10_000u64));
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_staking_token(managed_token_id!(DGKO));
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push((managed_biguint!(1_000u64), 2_000u64).into());
+            tiers.push((managed_biguint!(5_000u64), 5_000u64).into());
+            sc.set_staking_tiers(tiers);
+        })
+        .assert_ok();
+
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // 5_000 staked reaches the top tier: half the 30 bps fee
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, DGKO, 0, &rust_biguint!(5_000u64), |sc| {
+            sc.stake_dgko();
+        })
+        .assert_ok();
+    let amount_in = 100_000u64;
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap(pair_id, managed_biguint!(0u64), 0, OptionalValue::None);
+        })
+        .assert_ok();
+    let discounted_fee = amount_in * (FEE_BPS / 2) / 10_000;
+    assert_eq!(setup.reserves(pair_id).0, 10_000_000 + amount_in - discounted_fee);
+
+    // A keeper filling the trader's limit order doesn't change whose discount applies
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.place_limit_order(pair_id, managed_biguint!(1u64), 0);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.fill_orders(pair_id, 1), 1);
+        })
+        .assert_ok();
+    assert_eq!(setup.reserves(pair_id).0, 10_000_000 + 2 * (amount_in - discounted_fee));
+
+    // Nor does a whitelisted router swapping on the trader's behalf
+    let router_wrapper = setup.blockchain_wrapper.create_sc_account(
+        &rust_biguint!(0u64),
+        Some(&owner),
+        digiko_swap::contract_obj,
+        WASM_PATH,
+    );
+    let router = router_wrapper.address_ref().clone();
+    setup.blockchain_wrapper.set_kda_balance(&router, TOKEN_A, &rust_biguint!(amount_in));
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_router_whitelisted(managed_address!(&router), true);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&router, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_for(pair_id, managed_biguint!(0u64), 0, managed_address!(&trader), OptionalValue::None);
+        })
+        .assert_ok();
+    assert_eq!(setup.reserves(pair_id).0, 10_000_000 + 3 * (amount_in - discounted_fee));
+
+    // Unstaked tokens stop counting at once and wait out the cooldown
+    setup.blockchain_wrapper.set_block_timestamp(1_000);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.unstake_dgko(managed_biguint!(4_500u64));
+            assert_eq!(sc.get_trader_fee_bps(pair_id, &managed_address!(&trader)), FEE_BPS);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_unstaked_dgko();
+        })
+        .assert_user_error("Unstake cooldown active");
+    setup.blockchain_wrapper.set_block_timestamp(1_000 + 7 * 24 * 60 * 60);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_unstaked_dgko();
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, DGKO, &rust_biguint!(9_500u64));
+}
+
+#[test]
+fn rolling_volume_tiers_credit_claimable_fee_rebates() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+    setup.blockchain_wrapper.set_block_timestamp(1_000);
+
+    let set_tiers = |sc: digiko_swap::ContractObj<DebugApi>| {
+        let mut tiers = MultiValueEncoded::new();
+        tiers.push((managed_biguint!(200_000u64), 1_000u64).into());
+        tiers.push((managed_biguint!(1_000_000u64), 2_000u64).into());
+        sc.set_pair_rebate_tiers(pair_id, tiers);
+    };
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            set_tiers(sc);
+        })
+        .assert_user_error("Caller is missing the required role");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            set_tiers(sc);
+        })
+        .assert_ok();
+
+    // The first two swaps build volume, the third earns 10% of its fee back,
+    // also when it's a limit order a keeper fills
+    let amount_in = 100_000u64;
+    for _ in 0..2 {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+                sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+            })
+            .assert_ok();
+    }
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.place_limit_order(pair_id, managed_biguint!(1u64), 0);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.fill_orders(pair_id, 1), 1);
+        })
+        .assert_ok();
+    let rebate = amount_in * FEE_BPS / 10_000 / 10;
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (volume, rebate_bps) = sc.get_trader_rebate_info(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(volume, managed_biguint!(3 * amount_in));
+            assert_eq!(rebate_bps, 1_000);
+            assert_eq!(
+                sc.rebate_unclaimed(&managed_address!(&trader), &managed_token_id!(TOKEN_A)).get(),
+                managed_biguint!(rebate)
+            );
+            assert_eq!(
+                sc.rebate_unclaimed(&managed_address!(&keeper), &managed_token_id!(TOKEN_A)).get(),
+                managed_biguint!(0u64)
+            );
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.claim_fee_rebates();
+        })
+        .assert_ok();
+    setup.blockchain_wrapper.check_kda_balance(
+        &trader,
+        TOKEN_A,
+        &rust_biguint!(USER_BALANCE - 3 * amount_in + rebate),
+    );
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.claim_fee_rebates();
+        })
+        .assert_user_error("No fee rebates to claim");
+
+    // Volume older than the 30-day window no longer counts
+    setup.blockchain_wrapper.set_block_timestamp(1_000 + 30 * 24 * 60 * 60);
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (volume, rebate_bps) = sc.get_trader_rebate_info(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(volume, managed_biguint!(0u64));
+            assert_eq!(rebate_bps, 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn buybacks_earn_no_rebates_or_trader_stats() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let burn = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let dex = setup.dex_wrapper.address_ref().clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // Every swap on the pair qualifies for a rebate
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push((managed_biguint!(1u64), 1_000u64).into());
+            sc.set_pair_rebate_tiers(pair_id, tiers);
+            sc.set_buyback_config(managed_token_id!(TOKEN_B), managed_address!(&burn), 100);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(1_000_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+
+    // The owner's protocol fees in token A are sold for token B and burned
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            let burned = sc.buyback_and_burn(pair_id, managed_biguint!(1u64));
+            assert!(burned > 0u64);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            for addr in [&owner, &dex] {
+                let (volume_a, _, swap_count, _) = sc.get_user_trade_stats(managed_address!(addr), pair_id).into_tuple();
+                assert_eq!(volume_a, managed_biguint!(0u64));
+                assert_eq!(swap_count, 0);
+                assert_eq!(sc.rebate_unclaimed(&managed_address!(addr), &managed_token_id!(TOKEN_A)).get(), managed_biguint!(0u64));
+            }
+            let (_, _, trader_swaps, _) = sc.get_user_trade_stats(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(trader_swaps, 1);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn router_swaps_count_against_the_beneficiary() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let router_wrapper = setup.blockchain_wrapper.create_sc_account(
+        &rust_biguint!(0u64),
+        Some(&owner),
+        digiko_swap::contract_obj,
+        WASM_PATH,
+    );
+    let router = router_wrapper.address_ref().clone();
+    setup.blockchain_wrapper.set_kda_balance(&router, TOKEN_A, &rust_biguint!(USER_BALANCE));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // The trader may swap 10_000 TKA a day on this pair
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_router_whitelisted(managed_address!(&router), true);
+            sc.set_pair_compliance_flag(pair_id, true);
+            sc.set_address_daily_limit(pair_id, managed_address!(&trader), managed_biguint!(10_000u64));
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&router, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(50_000u64), |sc| {
+            sc.swap_for(pair_id, managed_biguint!(0u64), 0, managed_address!(&trader), OptionalValue::None);
+        })
+        .assert_user_error("Daily swap limit exceeded");
+
+    let (amount_out, _) = setup.quote(pair_id, 5_000, true);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&router, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(5_000u64), |sc| {
+            sc.swap_for(pair_id, managed_biguint!(0u64), 0, managed_address!(&trader), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_B, &rust_biguint!(USER_BALANCE + amount_out));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (_, _, swap_count, _) = sc.get_user_trade_stats(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(swap_count, 1);
+            let (_, _, router_count, _) = sc.get_user_trade_stats(managed_address!(&router), pair_id).into_tuple();
+            assert_eq!(router_count, 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn meta_swap_checks_expiry_and_nonce_and_refunds_allowances() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let relayer = setup.lp2.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+            sc.deposit_relayer_allowance();
+        })
+        .assert_ok();
+
+    let order = |nonce: u64, expiry: u64| MetaSwapOrder::<DebugApi> {
+        user: managed_address!(&trader),
+        pair_id,
+        token_in: managed_token_id!(TOKEN_A),
+        amount_in: managed_biguint!(100_000u64),
+        min_out: managed_biguint!(0u64),
+        nonce,
+        expiry,
+    };
+
+    // Both are checked before the signature
+    setup.blockchain_wrapper.set_block_timestamp(1_000);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&relayer, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.swap_on_behalf(ManagedBuffer::new(), order(0, 999));
+        })
+        .assert_user_error("Meta-swap order expired");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&relayer, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.swap_on_behalf(ManagedBuffer::new(), order(1, 2_000));
+        })
+        .assert_user_error("Invalid meta-swap nonce");
+
+    // Cancelling moves the nonce on, so orders signed for 0 are dead
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.cancel_meta_swap_nonce();
+            assert_eq!(sc.get_meta_swap_nonce(managed_address!(&trader)), 1);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&relayer, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.swap_on_behalf(ManagedBuffer::new(), order(0, 2_000));
+        })
+        .assert_user_error("Invalid meta-swap nonce");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_relayer_allowance(managed_token_id!(TOKEN_A), managed_biguint!(100_000u64));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_A, &rust_biguint!(USER_BALANCE));
+}
+
+#[test]
+fn signed_meta_swap_is_relayed_and_credited_to_the_user() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let relayer = setup.lp2.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // The user's address is its ed25519 public key
+    let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+    let keypair = Keypair { secret, public };
+    let user = Address::from(public.to_bytes());
+    setup
+        .blockchain_wrapper
+        .create_user_account_fixed_address(&user, &rust_biguint!(0u64));
+    setup
+        .blockchain_wrapper
+        .set_kda_balance(&user, TOKEN_A, &rust_biguint!(100_000u64));
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&user, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+            sc.deposit_relayer_allowance();
+        })
+        .assert_ok();
+
+    let (amount_out, _) = setup.quote(pair_id, 100_000, true);
+    let order = || MetaSwapOrder::<DebugApi> {
+        user: managed_address!(&user),
+        pair_id,
+        token_in: managed_token_id!(TOKEN_A),
+        amount_in: managed_biguint!(100_000u64),
+        min_out: managed_biguint!(amount_out),
+        nonce: 0,
+        expiry: 2_000,
+    };
+    let mut message = Vec::new();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            message = sc.get_meta_swap_message(order()).to_boxed_bytes().into_vec();
+        })
+        .assert_ok();
+    let signature = keypair.sign(&message).to_bytes();
+
+    setup.blockchain_wrapper.set_block_timestamp(1_000);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&relayer, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            let user_gets = sc.swap_on_behalf(ManagedBuffer::from(&signature[..]), order());
+            assert_eq!(user_gets, managed_biguint!(amount_out));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&user, TOKEN_B, &rust_biguint!(amount_out));
+
+    // The swap is the user's, not the relayer's
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (volume_a, _, swap_count, _) =
+                sc.get_user_trade_stats(managed_address!(&user), pair_id).into_tuple();
+            assert_eq!(volume_a, managed_biguint!(100_000u64));
+            assert_eq!(swap_count, 1);
+            let (_, _, relayer_count, _) = sc.get_user_trade_stats(managed_address!(&relayer), pair_id).into_tuple();
+            assert_eq!(relayer_count, 0);
+            assert_eq!(sc.get_meta_swap_nonce(managed_address!(&user)), 1);
+        })
+        .assert_ok();
+
+    // The signed order can't be replayed
+    setup
+        .blockchain_wrapper
+        .execute_tx(&relayer, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.swap_on_behalf(ManagedBuffer::from(&signature[..]), order());
+        })
+        .assert_user_error("Invalid meta-swap nonce");
+}
+
+#[test]
+fn limit_order_crank_moves_past_unfillable_orders_and_fills_for_owners() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let stuck_owner = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // Prices scaled by PRECISION (1e12): the first order asks twice the spot price
+    for (owner, min_price) in [(&stuck_owner, 2_000_000_000_000u64), (&trader, 1u64)] {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(owner, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+                sc.place_limit_order(pair_id, managed_biguint!(min_price), 0);
+            })
+            .assert_ok();
+    }
+    let (amount_out, _) = setup.quote(pair_id, 100_000, true);
+
+    // One order per call: the unfillable first order doesn't block the second
+    for expected in [0u64, 1u64] {
+        setup
+            .blockchain_wrapper
+            .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+                assert_eq!(sc.fill_orders(pair_id, 1), expected);
+            })
+            .assert_ok();
+    }
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_B, &rust_biguint!(USER_BALANCE + amount_out));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (_, _, swap_count, _) = sc.get_user_trade_stats(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(swap_count, 1);
+            let (_, _, keeper_count, _) = sc.get_user_trade_stats(managed_address!(&keeper), pair_id).into_tuple();
+            assert_eq!(keeper_count, 0);
+        })
+        .assert_ok();
+
+    // A restricted owner's order is refunded instead of reverting the batch
+    let owner = setup.owner_address.clone();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_address_restricted(managed_address!(&stuck_owner), true);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.fill_orders(pair_id, 10), 0);
+            assert_eq!(sc.get_user_limit_orders(managed_address!(&stuck_owner)).len(), 0);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&stuck_owner, TOKEN_A, &rust_biguint!(USER_BALANCE));
+}
+
+#[test]
+fn limit_order_crank_skips_orders_the_pair_guards_reject() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let whale = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // Swaps may take at most 1% of the output reserve
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_trade_limits(pair_id, 100, managed_biguint!(0u64), managed_biguint!(0u64));
+        })
+        .assert_ok();
+    for (owner, amount) in [(&whale, 1_000_000u64), (&trader, 50_000u64)] {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(owner, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount), |sc| {
+                sc.place_limit_order(pair_id, managed_biguint!(1u64), 0);
+            })
+            .assert_ok();
+    }
+    let (amount_out, _) = setup.quote(pair_id, 50_000, true);
+
+    // The oversized order is skipped instead of reverting the batch
+    for expected in [0u64, 1u64] {
+        setup
+            .blockchain_wrapper
+            .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+                assert_eq!(sc.fill_orders(pair_id, 1), expected);
+            })
+            .assert_ok();
+    }
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_B, &rust_biguint!(USER_BALANCE + amount_out));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.get_user_limit_orders(managed_address!(&whale)).len(), 1);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn dca_crank_moves_past_skipped_orders_and_swaps_for_owners() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let stuck_owner = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // Two chunks of 50_000 an hour apart; the first order's min_price is out of reach
+    for (owner, min_price) in [(&stuck_owner, 2_000_000_000_000u64), (&trader, 0u64)] {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(owner, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+                sc.create_dca_order(pair_id, managed_biguint!(50_000u64), 3_600, managed_biguint!(min_price));
+            })
+            .assert_ok();
+    }
+
+    for expected in [0u64, 1u64] {
+        setup
+            .blockchain_wrapper
+            .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+                assert_eq!(sc.execute_dca_orders(1), expected);
+            })
+            .assert_ok();
+    }
+    // Keeper fee: 10 bps of the chunk (DEFAULT_DCA_KEEPER_FEE_BPS)
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&keeper, TOKEN_A, &rust_biguint!(50));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (volume_a, _, swap_count, _) =
+                sc.get_user_trade_stats(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(volume_a, managed_biguint!(49_950u64));
+            assert_eq!(swap_count, 1);
+            let (_, _, keeper_count, _) = sc.get_user_trade_stats(managed_address!(&keeper), pair_id).into_tuple();
+            assert_eq!(keeper_count, 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn dca_crank_skips_chunks_the_pair_guards_reject() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let whale = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // Price impact capped at 1%: a 500_000 chunk is over it, a 50_000 chunk isn't
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_swap_guards(pair_id, false, 100);
+        })
+        .assert_ok();
+    for (owner, chunk) in [(&whale, 500_000u64), (&trader, 50_000u64)] {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(owner, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(chunk * 2), |sc| {
+                sc.create_dca_order(pair_id, managed_biguint!(chunk), 3_600, managed_biguint!(0u64));
+            })
+            .assert_ok();
+    }
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.execute_dca_orders(10), 1);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (_, _, swap_count, _) = sc.get_user_trade_stats(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(swap_count, 1);
+            let (_, _, whale_count, _) = sc.get_user_trade_stats(managed_address!(&whale), pair_id).into_tuple();
+            assert_eq!(whale_count, 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn stream_crank_refunds_restricted_owners_and_swaps_for_the_rest() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let restricted = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let owner = setup.owner_address.clone();
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    for user in [&restricted, &trader] {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(user, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+                sc.create_stream_order(pair_id, 2, 60, managed_biguint!(0u64));
+            })
+            .assert_ok();
+    }
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_address_restricted(managed_address!(&restricted), true);
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.execute_stream_orders(10), 1);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&restricted, TOKEN_A, &rust_biguint!(USER_BALANCE));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (_, _, swap_count, _) = sc.get_user_trade_stats(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(swap_count, 1);
+            let (_, _, keeper_count, _) = sc.get_user_trade_stats(managed_address!(&keeper), pair_id).into_tuple();
+            assert_eq!(keeper_count, 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn stream_crank_skips_chunks_the_pair_guards_reject() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let whale = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // Swaps may take at most 1% of the output reserve
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_trade_limits(pair_id, 100, managed_biguint!(0u64), managed_biguint!(0u64));
+        })
+        .assert_ok();
+    for (owner, total) in [(&whale, 2_000_000u64), (&trader, 100_000u64)] {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(owner, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(total), |sc| {
+                sc.create_stream_order(pair_id, 2, 60, managed_biguint!(0u64));
+            })
+            .assert_ok();
+    }
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.execute_stream_orders(10), 1);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (_, _, swap_count, _) = sc.get_user_trade_stats(managed_address!(&trader), pair_id).into_tuple();
+            assert_eq!(swap_count, 1);
+            let (_, _, whale_count, _) = sc.get_user_trade_stats(managed_address!(&whale), pair_id).into_tuple();
+            assert_eq!(whale_count, 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn legacy_fee_on_output_pair_charges_the_output_token() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    let amount_in = 100_000u64;
+    let (input_out, _) = setup.quote(pair_id, amount_in, true);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_fee_on_input(pair_id, false);
+        })
+        .assert_ok();
+
+    // Same trade, fee now cut from the curve output instead of the input
+    let (out_b, fee) = setup.quote(pair_id, amount_in, true);
+    assert!(out_b < input_out);
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let (gross, quoted_fee, net, _, _) = sc
+                .get_detailed_quote(pair_id, managed_biguint!(amount_in), true)
+                .into_tuple();
+            assert_eq!(quoted_fee, managed_biguint!(fee));
+            assert_eq!(net, managed_biguint!(out_b));
+            assert_eq!(gross, managed_biguint!(out_b + fee));
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(out_b), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, TOKEN_B, &rust_biguint!(USER_BALANCE + out_b));
+
+    // The full input entered the reserve and the full curve output left it
+    assert_eq!(setup.reserves(pair_id), (10_000_000 + amount_in, 10_000_000 - out_b - fee));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.owner_unclaimed_fees_a(pair_id).get(), managed_biguint!(0u64));
+            assert_eq!(
+                sc.owner_unclaimed_fees_b(pair_id).get(),
+                managed_biguint!(fee * PROTOCOL_FEE_BPS / 10_000)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn fee_splitter_forwards_the_protocol_cut_by_weight() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let owner = setup.owner_address.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // 70% to the owner, 30% to lp2 (standing in for a team wallet), behind the timelock
+    let mut action_id = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            let mut recipients = ManagedVec::new();
+            recipients.push(FeeSplitRecipient { address: managed_address!(&owner), weight_bps: 7_000 });
+            recipients.push(FeeSplitRecipient { address: managed_address!(&lp2), weight_bps: 3_000 });
+            action_id = sc.propose_admin_action(AdminAction::SetFeeSplit(recipients));
+        })
+        .assert_ok();
+    setup.blockchain_wrapper.set_block_timestamp(24 * 60 * 60);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.execute_admin_action(action_id);
+            // Hold token A until it reaches 1000, forward token B on every swap
+            sc.set_fee_split_threshold(pair_id, managed_biguint!(1_000u64), managed_biguint!(0u64));
+        })
+        .assert_ok();
+
+    // Below the threshold the cut waits in the treasury bucket
+    let amount_in = 100_000u64;
+    let (_, fee) = setup.quote(pair_id, amount_in, true);
+    let protocol_portion = fee * PROTOCOL_FEE_BPS / 10_000;
+    assert!(protocol_portion > 0 && protocol_portion < 1_000);
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.treasury_unclaimed_fees_a(pair_id).get(), managed_biguint!(protocol_portion));
+            assert_eq!(sc.owner_unclaimed_fees_a(pair_id).get(), managed_biguint!(0u64));
+        })
+        .assert_ok();
+
+    // Token B has no threshold, so its cut is split right away
+    let (_, fee_b) = setup.quote(pair_id, amount_in, false);
+    let protocol_b = fee_b * PROTOCOL_FEE_BPS / 10_000;
+    let lp2_b = protocol_b * 3_000 / 10_000;
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_B, 0, &rust_biguint!(amount_in), |sc| {
+            sc.swap_b_to_a(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&owner, TOKEN_B, &rust_biguint!(protocol_b - lp2_b));
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&lp2, TOKEN_B, &rust_biguint!(USER_BALANCE + lp2_b));
+
+    // Anyone can flush a bucket that hasn't reached its threshold yet
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.claim_treasury_fees(pair_id);
+        })
+        .assert_ok();
+    let lp2_a = protocol_portion * 3_000 / 10_000;
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&owner, TOKEN_A, &rust_biguint!(protocol_portion - lp2_a));
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.treasury_unclaimed_fees_a(pair_id).get(), managed_biguint!(0u64));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn top_n_competition_pays_only_ranked_traders() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let owner = setup.owner_address.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 10_000_000, 10_000_000);
+
+    // Top-1 competition over [100, 200) with a 50_000 TKB pot
+    let pot = 50_000u64;
+    setup.blockchain_wrapper.set_kda_balance(&owner, TOKEN_B, &rust_biguint!(pot));
+    let mut competition_id = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&owner, &setup.dex_wrapper, TOKEN_B, 0, &rust_biguint!(pot), |sc| {
+            competition_id = sc.open_competition(pair_id, 100, 200, 1);
+        })
+        .assert_ok();
+
+    // Swaps before the start don't count
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&lp2, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(500_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+
+    // The trader's volume comes from a limit order a keeper fills
+    setup.blockchain_wrapper.set_block_timestamp(150);
+    let keeper = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(200_000u64), |sc| {
+            sc.place_limit_order(pair_id, managed_biguint!(1u64), 0);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_tx(&keeper, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.fill_orders(pair_id, 1), 1);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&lp2, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(100_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let competition = sc.get_competition(competition_id);
+            assert_eq!(competition.total_volume, managed_biguint!(300_000u64));
+            let leaderboard: Vec<_> = sc.get_competition_leaderboard(competition_id, 0, 10).into_iter().collect();
+            assert_eq!(leaderboard.len(), 1);
+            assert_eq!(leaderboard[0].address, managed_address!(&trader));
+        })
+        .assert_ok();
+
+    // Claims open once the competition is closed after its end
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.close_competition(competition_id);
+        })
+        .assert_user_error("Competition not over");
+    setup.blockchain_wrapper.set_block_timestamp(200);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.close_competition(competition_id);
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.claim_competition_reward(competition_id);
+        })
+        .assert_user_error("Nothing to claim");
+    let mut reward = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            reward = sc.claim_competition_reward(competition_id).to_u64().unwrap();
+        })
+        .assert_ok();
+    assert_eq!(reward, pot);
+}
+
+#[test]
+fn launch_graduates_into_a_locked_klv_pair() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let creator = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let launch_token: &[u8] = b"NEW-000003";
+    setup.blockchain_wrapper.set_kda_balance(&creator, launch_token, &rust_biguint!(1_000_000u64));
+
+    // Half sold along the curve, half kept for the pair; 100_000 KLV target
+    let mut launch_id = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&creator, &setup.dex_wrapper, launch_token, 0, &rust_biguint!(1_000_000u64), |sc| {
+            launch_id = sc.create_launch(
+                managed_biguint!(500_000u64),
+                managed_biguint!(100_000u64),
+                managed_biguint!(100_000u64),
+                FEE_BPS,
+                30,
+            );
+        })
+        .assert_ok();
+
+    // The launch token can't get a pair of its own while the sale runs
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.create_pair(
+                managed_token_id!(launch_token),
+                managed_token_id!(TOKEN_B),
+                false,
+                false,
+                FEE_BPS,
+                OptionalValue::None,
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Token has a launch in progress");
+
+    // Overpaying the target refunds the excess and graduates the launch
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(150_000u64), |sc| {
+            let bought = sc.buy_launch(launch_id, managed_biguint!(0u64));
+            // 500_000 * 100_000 / (100_000 virtual + 100_000)
+            assert_eq!(bought, managed_biguint!(250_000u64));
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&trader, launch_token, &rust_biguint!(250_000u64));
+
+    let mut pair_id = 0u64;
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            pair_id = sc.get_launch(launch_id).pair_id;
+            assert!(pair_id != 0);
+            assert!(sc.launch_by_token(&managed_token_id!(launch_token)).is_empty());
+        })
+        .assert_ok();
+
+    // Unsold curve tokens plus the liquidity supply seed the pair with the raise
+    assert_eq!(setup.reserves(pair_id), (750_000, 100_000));
+    let shares = setup.lp_shares(pair_id, &creator);
+    assert!(shares > 0);
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let creator_addr = managed_address!(&creator);
+            assert_eq!(sc.lp_locked_shares(pair_id, &creator_addr).get(), managed_biguint!(shares));
+            assert_eq!(sc.pair_creator(pair_id).get(), creator_addr);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn first_mint_respects_minimum_value_and_reference_band() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let pair_id = setup.create_pair(&owner, TOKEN_A, TOKEN_B);
+
+    // TKA is worth 1 KLV, TKB 2 KLV; first deposits need 10_000 KLV of value within 5%
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_min_initial_liquidity(managed_biguint!(10_000u64));
+            sc.set_token_reference_price(managed_token_id!(TOKEN_A), managed_biguint!(1_000_000_000_000u64));
+            sc.set_token_reference_price(managed_token_id!(TOKEN_B), managed_biguint!(2_000_000_000_000u64));
+            sc.set_reference_band(500);
+        })
+        .assert_ok();
+
+    let first_mint = |amount_a: u64, amount_b: u64| {
+        [
+            TxTokenTransfer {
+                token_identifier: TOKEN_A.to_vec(),
+                nonce: 0,
+                value: rust_biguint!(amount_a),
+            },
+            TxTokenTransfer {
+                token_identifier: TOKEN_B.to_vec(),
+                nonce: 0,
+                value: rust_biguint!(amount_b),
+            },
+        ]
+    };
+
+    // 4_000 + 2 * 2_000 = 8_000 KLV of value
+    setup
+        .blockchain_wrapper
+        .execute_kda_multi_transfer(&lp1, &setup.dex_wrapper, &first_mint(4_000, 2_000), |sc| {
+            sc.mint(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_user_error("Initial liquidity below minimum value");
+
+    // 1:1 prices TKB at 1 KLV, half its reference
+    setup
+        .blockchain_wrapper
+        .execute_kda_multi_transfer(&lp1, &setup.dex_wrapper, &first_mint(100_000, 100_000), |sc| {
+            sc.mint(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_user_error("Initial price outside reference band");
+
+    // 2:1 matches the references
+    setup.mint(&lp1, pair_id, 100_000, 50_000);
+    assert_eq!(setup.reserves(pair_id), (100_000, 50_000));
+}
+
+#[test]
+fn first_finalize_respects_minimum_value_and_reference_band() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let pair_id = setup.create_pair(&owner, TOKEN_A, TOKEN_B);
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_min_initial_liquidity(managed_biguint!(10_000u64));
+            sc.set_token_reference_price(managed_token_id!(TOKEN_A), managed_biguint!(1_000_000_000_000u64));
+            sc.set_token_reference_price(managed_token_id!(TOKEN_B), managed_biguint!(2_000_000_000_000u64));
+            sc.set_reference_band(500);
+        })
+        .assert_ok();
+
+    // Pending deposits accumulate between attempts; each finalize checks the total
+    for (add_a, add_b, error) in [
+        (4_000u64, 2_000u64, Some("Initial liquidity below minimum value")),
+        (96_000, 98_000, Some("Initial price outside reference band")),
+        (100_000, 0, None),
+    ] {
+        for (token, amount) in [(TOKEN_A, add_a), (TOKEN_B, add_b)] {
+            if amount == 0 {
+                continue;
+            }
+            setup
+                .blockchain_wrapper
+                .execute_kda_transfer(&lp1, &setup.dex_wrapper, token, 0, &rust_biguint!(amount), |sc| {
+                    if token == TOKEN_A {
+                        sc.deposit_pending_a(pair_id);
+                    } else {
+                        sc.deposit_pending_b(pair_id);
+                    }
+                })
+                .assert_ok();
+        }
+        let result = setup
+            .blockchain_wrapper
+            .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+                sc.finalize_liquidity(pair_id, managed_biguint!(0u64));
+            });
+        match error {
+            Some(message) => result.assert_user_error(message),
+            None => result.assert_ok(),
+        }
+    }
+    assert_eq!(setup.reserves(pair_id), (200_000, 100_000));
+}
+
+#[test]
+fn legacy_owner_shares_migrate_into_an_lp_position() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    let lp1_shares = setup.mint(&lp1, pair_id, 1_000_000, 1_000_000);
+
+    // A V4 pair: part of the pool is still held as owner_shares
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.owner_shares(pair_id).set(managed_biguint!(250_000u64));
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.migrate_owner_shares_to_lp(pair_id);
+        })
+        .assert_user_error("Endpoint can only be called by owner");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            assert_eq!(sc.migrate_owner_shares_to_lp(pair_id), managed_biguint!(250_000u64));
+        })
+        .assert_ok();
+
+    assert_eq!(setup.lp_shares(pair_id, &owner), 250_000);
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert_eq!(sc.owner_shares(pair_id).get(), managed_biguint!(0u64));
+            assert_eq!(sc.total_lp_shares(pair_id).get(), managed_biguint!(lp1_shares + 250_000));
+            assert_eq!(
+                sc.lp_entry_index_a(pair_id, &managed_address!(&owner)).get(),
+                sc.fee_per_share_a(pair_id).get()
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn emergency_withdraw_forfeits_pending_fees() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 2_000_000, 2_000_000);
+    let shares2 = setup.mint(&lp2, pair_id, 500_000, 500_000);
+
+    // Accrue some LP fees on both sides
+    for token in [TOKEN_A, TOKEN_B] {
+        setup
+            .blockchain_wrapper
+            .execute_kda_transfer(&trader, &setup.dex_wrapper, token, 0, &rust_biguint!(100_000u64), |sc| {
+                sc.swap(pair_id, managed_biguint!(0u64), 0, OptionalValue::None);
+            })
+            .assert_ok();
+    }
+
+    let (reserve_a, reserve_b) = setup.reserves(pair_id);
+    let total_shares = 2_500_000u64;
+    let expected_a = shares2 * reserve_a / total_shares;
+    let expected_b = shares2 * reserve_b / total_shares;
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            let (amount_a, amount_b) = sc.emergency_withdraw(pair_id).into_tuple();
+            assert_eq!(amount_a, managed_biguint!(expected_a));
+            assert_eq!(amount_b, managed_biguint!(expected_b));
+        })
+        .assert_ok();
+
+    // Only the reserves' share came back - no fee claim on the way out
+    assert_eq!(setup.lp_shares(pair_id, &lp2), 0);
+    assert_eq!(setup.reserves(pair_id), (reserve_a - expected_a, reserve_b - expected_b));
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&lp2, TOKEN_A, &rust_biguint!(USER_BALANCE - 500_000 + expected_a));
+    setup
+        .blockchain_wrapper
+        .check_kda_balance(&lp2, TOKEN_B, &rust_biguint!(USER_BALANCE - 500_000 + expected_b));
+}
+
+#[test]
+fn frozen_swaps_still_let_lps_exit() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    let shares = setup.mint(&lp1, pair_id, 1_000_000, 1_000_000);
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_pair_swaps_enabled(pair_id, false);
+            let (can_swap, can_add_liquidity, _, can_remove_liquidity) =
+                sc.get_pair_permissions(pair_id).into_tuple();
+            assert!(!can_swap && can_add_liquidity && can_remove_liquidity);
+        })
+        .assert_ok();
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(1_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_user_error("Swaps are disabled for this pair");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.remove_liquidity(pair_id, managed_biguint!(shares), managed_biguint!(0u64), managed_biguint!(0u64));
+        })
+        .assert_ok();
+    assert_eq!(setup.lp_shares(pair_id, &lp1), 0);
+}
+
+#[test]
+fn lp_cap_bounds_the_lp_list_and_pages_cover_it() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let owner = setup.owner_address.clone();
+    let lp1 = setup.lp1.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_max_lps_per_pair(5);
+        })
+        .assert_user_error("LP cap out of bounds");
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.set_max_lps_per_pair(10);
+        })
+        .assert_ok();
+
+    let mut lps = Vec::new();
+    for _ in 0..11 {
+        let lp = setup.blockchain_wrapper.create_user_account(&rust_biguint!(0u64));
+        setup.blockchain_wrapper.set_kda_balance(&lp, TOKEN_A, &rust_biguint!(USER_BALANCE));
+        setup.blockchain_wrapper.set_kda_balance(&lp, TOKEN_B, &rust_biguint!(USER_BALANCE));
+        lps.push(lp);
+    }
+    for lp in &lps[..10] {
+        setup.mint(lp, pair_id, 100_000, 100_000);
+    }
+
+    // The 11th account is turned away, existing LPs can still add
+    let transfers = [
+        TxTokenTransfer {
+            token_identifier: TOKEN_A.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(100_000u64),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_B.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(100_000u64),
+        },
+    ];
+    setup
+        .blockchain_wrapper
+        .execute_kda_multi_transfer(&lps[10], &setup.dex_wrapper, &transfers, |sc| {
+            sc.mint(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_user_error("Pair has reached its LP limit");
+    setup.mint(&lps[0], pair_id, 100_000, 100_000);
+
+    let page = |setup: &mut DexSetup<_>, start: u64, limit: u64| {
+        let mut addresses = Vec::new();
+        setup
+            .blockchain_wrapper
+            .execute_query(&setup.dex_wrapper, |sc| {
+                for addr in sc.get_lp_list_paginated(pair_id, start, limit).into_iter() {
+                    addresses.push(addr.to_address());
+                }
+            })
+            .assert_ok();
+        addresses
+    };
+    let mut listed = page(&mut setup, 0, 4);
+    assert_eq!(listed.len(), 4);
+    listed.extend(page(&mut setup, 4, 4));
+    let last_page = page(&mut setup, 8, 4);
+    assert_eq!(last_page.len(), 2);
+    listed.extend(last_page);
+    for lp in &lps[..10] {
+        assert!(listed.contains(lp));
+    }
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            let _ = sc.get_all_lp_info_paginated(pair_id, 0, 0);
+        })
+        .assert_user_error("Invalid limit");
+}
+
+#[test]
+fn remove_liquidity_returns_pro_rata_reserves() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let lp2 = setup.lp2.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    setup.mint(&lp1, pair_id, 2_000_000, 2_000_000);
+    let shares2 = setup.mint(&lp2, pair_id, 500_000, 500_000);
+
+    let (reserve_a, reserve_b) = setup.reserves(pair_id);
+    let total_shares = 2_500_000u64; // sqrt(2M * 2M) + 500_000, locked minimum included
+    let expected_a = shares2 * reserve_a / total_shares;
+    let expected_b = shares2 * reserve_b / total_shares;
+
+    // Slippage protection on the way out
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.remove_liquidity(
+                pair_id,
+                managed_biguint!(shares2),
+                managed_biguint!(expected_a + 1),
+                managed_biguint!(0u64),
+            );
+        })
+        .assert_user_error("Slippage: token A below minimum");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.remove_liquidity(
+                pair_id,
+                managed_biguint!(shares2),
+                managed_biguint!(expected_a),
+                managed_biguint!(expected_b),
+            );
+        })
+        .assert_ok();
+
+    assert_eq!(setup.lp_shares(pair_id, &lp2), 0);
+    assert_eq!(setup.reserves(pair_id), (reserve_a - expected_a, reserve_b - expected_b));
+    setup.blockchain_wrapper.check_kda_balance(
+        &lp2,
+        TOKEN_A,
+        &rust_biguint!(USER_BALANCE - 500_000 + expected_a),
+    );
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert!(!sc.lp_list(pair_id).contains(&managed_address!(&lp2)));
+        })
+        .assert_ok();
+
+    // Can't remove more than held
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp2, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.remove_liquidity(pair_id, managed_biguint!(1u64), managed_biguint!(0u64), managed_biguint!(0u64));
+        })
+        .assert_user_error("Insufficient LP shares");
+}
+
+#[test]
+fn delete_pair_only_once_emptied() {
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+    let lp1 = setup.lp1.clone();
+    let trader = setup.trader.clone();
+    let owner = setup.owner_address.clone();
+    let pair_id = setup.create_pair(&lp1, TOKEN_A, TOKEN_B);
+    let shares = setup.mint(&lp1, pair_id, 1_000_000, 1_000_000);
+
+    setup
+        .blockchain_wrapper
+        .execute_kda_transfer(&trader, &setup.dex_wrapper, TOKEN_A, 0, &rust_biguint!(10_000u64), |sc| {
+            sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+        })
+        .assert_ok();
+
+    // Only the creator or the owner may delete
+    setup
+        .blockchain_wrapper
+        .execute_tx(&trader, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.delete_pair(pair_id);
+        })
+        .assert_user_error("Only pair creator or contract owner can delete");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.delete_pair(pair_id);
+        })
+        .assert_user_error("Cannot delete pair with liquidity");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.remove_liquidity(pair_id, managed_biguint!(shares), managed_biguint!(0u64), managed_biguint!(0u64));
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.delete_pair(pair_id);
+        })
+        .assert_user_error("Claim owner fees before deleting");
+
+    setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.owner_claim_fees(pair_id);
+        })
+        .assert_ok();
+
+    // The reserves behind the locked minimum are swept to the owner on delete
+    setup
+        .blockchain_wrapper
+        .execute_tx(&lp1, &setup.dex_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.delete_pair(pair_id);
+        })
+        .assert_ok();
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.dex_wrapper, |sc| {
+            assert!(!sc.pair_exists(pair_id));
+            assert!(sc.pair_reserve_a(pair_id).is_empty());
+        })
+        .assert_ok();
+}
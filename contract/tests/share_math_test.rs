@@ -0,0 +1,140 @@
+// ============================================================================
+// SHARE MATH - Rounding edge cases
+// ============================================================================
+// Unit tests for src/math.rs. The pool must never lose a wei to rounding:
+// deposits round up, shares and withdrawals round down.
+//
+//   cargo test --test share_math_test
+// ============================================================================
+
+use digiko_swap::math::*;
+use klever_sc::types::BigUint;
+use klever_sc_scenario::DebugApi;
+
+fn big(value: u64) -> BigUint<DebugApi> {
+    BigUint::from(value)
+}
+
+#[test]
+fn mul_div_floor_truncates() {
+    let _ = DebugApi::dummy();
+    assert_eq!(mul_div_floor(&big(1), &big(1), &big(2)), big(0));
+    assert_eq!(mul_div_floor(&big(7), &big(3), &big(2)), big(10));
+    assert_eq!(mul_div_floor(&big(0), &big(5), &big(3)), big(0));
+}
+
+#[test]
+fn mul_div_ceil_rounds_up_only_on_remainder() {
+    let _ = DebugApi::dummy();
+    assert_eq!(mul_div_ceil(&big(1), &big(1), &big(2)), big(1));
+    assert_eq!(mul_div_ceil(&big(7), &big(3), &big(2)), big(11));
+    // Exact divisions are not bumped
+    assert_eq!(mul_div_ceil(&big(6), &big(4), &big(3)), big(8));
+    assert_eq!(mul_div_ceil(&big(0), &big(5), &big(3)), big(0));
+}
+
+#[test]
+fn floor_and_ceil_differ_by_at_most_one() {
+    let _ = DebugApi::dummy();
+    for numerator in 0u64..50 {
+        for denominator in 1u64..20 {
+            let floor = mul_div_floor(&big(numerator), &big(1), &big(denominator));
+            let ceil = mul_div_ceil(&big(numerator), &big(1), &big(denominator));
+            assert!(ceil >= floor);
+            assert!(ceil <= floor + 1u64);
+        }
+    }
+}
+
+#[test]
+fn match_deposit_rounds_dependent_side_up() {
+    let _ = DebugApi::dummy();
+    // 1 wei of A against a 3:2 pool needs 2/3 wei of B -> 1 wei, never 0
+    let (used_a, used_b) = match_deposit(&big(1), &big(1), &big(3), &big(2));
+    assert_eq!(used_a, big(1));
+    assert_eq!(used_b, big(1));
+
+    // Exact ratio uses everything
+    let (used_a, used_b) = match_deposit(&big(100), &big(200), &big(1_000), &big(2_000));
+    assert_eq!(used_a, big(100));
+    assert_eq!(used_b, big(200));
+}
+
+#[test]
+fn match_deposit_limited_by_b_never_exceeds_a() {
+    let _ = DebugApi::dummy();
+    // B is the scarce side: A needed = ceil(1 * 3 / 2) = 2
+    let (used_a, used_b) = match_deposit(&big(2), &big(1), &big(3), &big(2));
+    assert_eq!(used_a, big(2));
+    assert_eq!(used_b, big(1));
+
+    for amount_a in 1u64..30 {
+        for amount_b in 1u64..30 {
+            let (used_a, used_b) = match_deposit(&big(amount_a), &big(amount_b), &big(7), &big(5));
+            assert!(used_a <= big(amount_a));
+            assert!(used_b <= big(amount_b));
+            // One side is used in full and the other pays at least its 7:5 share
+            if used_a == big(amount_a) {
+                assert!(&used_b * 7u64 >= &used_a * 5u64);
+            } else {
+                assert_eq!(used_b, big(amount_b));
+                assert!(&used_a * 5u64 >= &used_b * 7u64);
+            }
+        }
+    }
+}
+
+#[test]
+fn shares_for_deposit_takes_smaller_side_rounded_down() {
+    let _ = DebugApi::dummy();
+    // 1 wei into a 3:3 pool with 2 shares outstanding -> floor(2/3) = 0
+    assert_eq!(shares_for_deposit(&big(1), &big(1), &big(3), &big(3), &big(2)), big(0));
+    // Sides disagree: A gives 10 shares, B gives 5
+    assert_eq!(
+        shares_for_deposit(&big(100), &big(50), &big(1_000), &big(1_000), &big(100)),
+        big(5)
+    );
+}
+
+#[test]
+fn amounts_for_shares_rounds_down() {
+    let _ = DebugApi::dummy();
+    // 1 share of 3 over reserves (2, 5) -> (0, 1)
+    let (amount_a, amount_b) = amounts_for_shares(&big(1), &big(2), &big(5), &big(3));
+    assert_eq!(amount_a, big(0));
+    assert_eq!(amount_b, big(1));
+
+    // All shares return the full reserves
+    let (amount_a, amount_b) = amounts_for_shares(&big(3), &big(2), &big(5), &big(3));
+    assert_eq!(amount_a, big(2));
+    assert_eq!(amount_b, big(5));
+}
+
+#[test]
+fn deposit_then_withdraw_never_profits() {
+    let _ = DebugApi::dummy();
+    let reserves = [(1_000u64, 3u64), (7, 11), (999_999, 1_000_001), (2, 1)];
+    for (reserve_a, reserve_b) in reserves {
+        let total_shares = big(reserve_a * reserve_b).sqrt();
+        for deposit in 1u64..40 {
+            let (used_a, used_b) = match_deposit(&big(deposit), &big(deposit), &big(reserve_a), &big(reserve_b));
+            let shares = shares_for_deposit(&used_a, &used_b, &big(reserve_a), &big(reserve_b), &total_shares);
+            if shares == 0u64 {
+                continue;
+            }
+            let new_reserve_a = big(reserve_a) + &used_a;
+            let new_reserve_b = big(reserve_b) + &used_b;
+            let new_total = &total_shares + &shares;
+            let (out_a, out_b) = amounts_for_shares(&shares, &new_reserve_a, &new_reserve_b, &new_total);
+            assert!(out_a <= used_a, "withdrew more A than deposited");
+            assert!(out_b <= used_b, "withdrew more B than deposited");
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn zero_denominator_is_rejected() {
+    let _ = DebugApi::dummy();
+    let _ = mul_div_ceil(&big(1), &big(1), &big(0));
+}
@@ -0,0 +1,408 @@
+// ============================================================================
+// STRESS / BENCHMARK SCENARIO
+// ============================================================================
+// V5 allows unlimited pairs, so per-call cost must not grow with the number of
+// pairs or LPs. This generator builds hundreds of pairs, thousands of LP
+// positions and a long pseudo-random swap sequence with the whitebox framework,
+// then records one call to every public endpoint against the fully-grown state
+// into a generated scenario.
+//
+// The whitebox framework does not meter gas. Every recorded call therefore
+// carries KLEVER_MAX_TX_GAS as its gas limit: replaying the generated scenario
+// on the VM (Klever VSCode extension / scenario runner) fails on any endpoint
+// that would no longer fit in a single transaction.
+//
+// Slow by design - run with:
+//   cargo test --test stress_scenario_rs_test -- --ignored
+// ============================================================================
+
+use digiko_swap::*;
+use digiko_swap::{
+    liquidity::LiquidityModule,
+    pair_admin::PairAdminModule,
+    storage::StorageModule,
+    swap::SwapModule,
+};
+use klever_sc::codec::multi_types::OptionalValue;
+use klever_sc::types::Address;
+use klever_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, whitebox::*, DebugApi,
+};
+
+const WASM_PATH: &str = "output/digiko-swap.wasm";
+const SCENARIO_OUTPUT: &str = "scenarios/_generated_stress.scen.json";
+
+// Gas limit used by our deploy/call scripts for a single transaction
+const KLEVER_MAX_TX_GAS: u64 = 100_000_000;
+
+const NUM_PAIRS: u64 = 300;
+const NUM_USERS: usize = 200;
+const LPS_PER_PAIR: usize = 10; // 3000 LP positions
+const NUM_SWAPS: usize = 3000;
+
+const FEE_BPS: u64 = 30;
+const LIQUIDITY_AMOUNT: u64 = 1_000_000_000;
+const USER_BALANCE: u64 = 1_000_000_000_000;
+
+/// Deterministic xorshift generator so failures are reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: u64) -> u64 {
+        self.next() % max
+    }
+}
+
+fn token_a(pair_id: u64) -> Vec<u8> {
+    format!("TA{:04}-A1B2", pair_id).into_bytes()
+}
+
+fn token_b(pair_id: u64) -> Vec<u8> {
+    format!("TB{:04}-C3D4", pair_id).into_bytes()
+}
+
+struct StressSetup<DexObjBuilder>
+where
+    DexObjBuilder: 'static + Copy + Fn() -> digiko_swap::ContractObj<DebugApi>,
+{
+    pub blockchain_wrapper: BlockchainStateWrapper,
+    pub owner_address: Address,
+    pub users: Vec<Address>,
+    pub dex_wrapper: ContractObjWrapper<digiko_swap::ContractObj<DebugApi>, DexObjBuilder>,
+}
+
+fn setup_dex<DexObjBuilder>(dex_builder: DexObjBuilder) -> StressSetup<DexObjBuilder>
+where
+    DexObjBuilder: 'static + Copy + Fn() -> digiko_swap::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0u64);
+    let mut blockchain_wrapper = BlockchainStateWrapper::new();
+    let owner_address = blockchain_wrapper.create_user_account(&rust_zero);
+    let users = (0..NUM_USERS)
+        .map(|_| blockchain_wrapper.create_user_account(&rust_zero))
+        .collect();
+    let dex_wrapper = blockchain_wrapper.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        dex_builder,
+        WASM_PATH,
+    );
+
+    blockchain_wrapper
+        .execute_tx(&owner_address, &dex_wrapper, &rust_zero, |sc| {
+            sc.init();
+        })
+        .assert_ok();
+
+    StressSetup {
+        blockchain_wrapper,
+        owner_address,
+        users,
+        dex_wrapper,
+    }
+}
+
+impl<DexObjBuilder> StressSetup<DexObjBuilder>
+where
+    DexObjBuilder: 'static + Copy + Fn() -> digiko_swap::ContractObj<DebugApi>,
+{
+    fn create_pairs(&mut self) {
+        let rust_zero = rust_biguint!(0u64);
+        for pair_id in 1..=NUM_PAIRS {
+            let creator = &self.users[(pair_id as usize) % NUM_USERS];
+            self.blockchain_wrapper
+                .execute_tx(creator, &self.dex_wrapper, &rust_zero, |sc| {
+                    let created = sc.create_pair(
+                        managed_token_id!(&token_a(pair_id)[..]),
+                        managed_token_id!(&token_b(pair_id)[..]),
+                        false,
+                        false,
+                        FEE_BPS,
+                        OptionalValue::None,
+                        OptionalValue::None,
+                    );
+                    assert_eq!(created, pair_id);
+                })
+                .assert_ok();
+        }
+    }
+
+    /// Two-step deposit + finalize, the only add-liquidity path that works for
+    /// both empty and seeded pools with single-token transfers
+    fn add_liquidity(&mut self, user_index: usize, pair_id: u64, amount: u64) {
+        let user = self.users[user_index].clone();
+        let amount_big = rust_biguint!(amount);
+        self.blockchain_wrapper
+            .set_kda_balance(&user, &token_a(pair_id), &rust_biguint!(USER_BALANCE));
+        self.blockchain_wrapper
+            .set_kda_balance(&user, &token_b(pair_id), &rust_biguint!(USER_BALANCE));
+
+        self.blockchain_wrapper
+            .execute_kda_transfer(&user, &self.dex_wrapper, &token_a(pair_id), 0, &amount_big, |sc| {
+                sc.deposit_pending_a(pair_id);
+            })
+            .assert_ok();
+        self.blockchain_wrapper
+            .execute_kda_transfer(&user, &self.dex_wrapper, &token_b(pair_id), 0, &amount_big, |sc| {
+                sc.deposit_pending_b(pair_id);
+            })
+            .assert_ok();
+        self.blockchain_wrapper
+            .execute_tx(&user, &self.dex_wrapper, &rust_biguint!(0u64), |sc| {
+                sc.finalize_liquidity(pair_id, managed_biguint!(0u64));
+            })
+            .assert_ok();
+    }
+
+    fn populate_lps(&mut self, rng: &mut Rng) {
+        for pair_id in 1..=NUM_PAIRS {
+            for slot in 0..LPS_PER_PAIR {
+                // Spread positions so every user holds LP in many pairs
+                let user_index = ((pair_id as usize) * 7 + slot * 13) % NUM_USERS;
+                let amount = LIQUIDITY_AMOUNT + rng.below(LIQUIDITY_AMOUNT);
+                self.add_liquidity(user_index, pair_id, amount);
+            }
+        }
+    }
+
+    fn random_swaps(&mut self, rng: &mut Rng) {
+        for _ in 0..NUM_SWAPS {
+            let pair_id = 1 + rng.below(NUM_PAIRS);
+            let user = self.users[rng.below(NUM_USERS as u64) as usize].clone();
+            // Keep trades well below reserves so none revert on price impact
+            let amount = rust_biguint!(1_000 + rng.below(LIQUIDITY_AMOUNT / 100));
+
+            if rng.below(2) == 0 {
+                self.blockchain_wrapper
+                    .set_kda_balance(&user, &token_a(pair_id), &rust_biguint!(USER_BALANCE));
+                self.blockchain_wrapper
+                    .execute_kda_transfer(&user, &self.dex_wrapper, &token_a(pair_id), 0, &amount, |sc| {
+                        sc.swap_a_to_b(pair_id, managed_biguint!(0u64), OptionalValue::None);
+                    })
+                    .assert_ok();
+            } else {
+                self.blockchain_wrapper
+                    .set_kda_balance(&user, &token_b(pair_id), &rust_biguint!(USER_BALANCE));
+                self.blockchain_wrapper
+                    .execute_kda_transfer(&user, &self.dex_wrapper, &token_b(pair_id), 0, &amount, |sc| {
+                        sc.swap_b_to_a(pair_id, managed_biguint!(0u64), OptionalValue::None);
+                    })
+                    .assert_ok();
+            }
+        }
+    }
+
+    fn check_invariants(&mut self) {
+        self.blockchain_wrapper
+            .execute_query(&self.dex_wrapper, |sc| {
+                assert_eq!(sc.registered_pair_ids().len() as u64, NUM_PAIRS);
+                for pair_id in 1..=NUM_PAIRS {
+                    assert!(sc.pair_reserve_a(pair_id).get() > 0u64);
+                    assert!(sc.pair_reserve_b(pair_id).get() > 0u64);
+                    assert!(sc.pair_pending_users(pair_id).is_empty());
+                }
+            })
+            .assert_ok();
+    }
+
+    /// Record one gas-capped call per public endpoint against the current state
+    fn record_endpoint_calls(&mut self, rng: &mut Rng) {
+        let owner = self.owner_address.clone();
+        let sc_address = self.dex_wrapper.address_ref().clone();
+        let pair_id = 1 + rng.below(NUM_PAIRS);
+        let lp = self.users[((pair_id as usize) * 7) % NUM_USERS].clone();
+        let trader = self.users[rng.below(NUM_USERS as u64) as usize].clone();
+
+        self.blockchain_wrapper
+            .set_kda_balance(&trader, &token_a(pair_id), &rust_biguint!(USER_BALANCE));
+        self.blockchain_wrapper
+            .set_kda_balance(&trader, &token_b(pair_id), &rust_biguint!(USER_BALANCE));
+        self.blockchain_wrapper
+            .set_kda_balance(&lp, &token_a(pair_id), &rust_biguint!(USER_BALANCE));
+        self.blockchain_wrapper
+            .set_kda_balance(&lp, &token_b(pair_id), &rust_biguint!(USER_BALANCE));
+
+        self.blockchain_wrapper.add_mandos_set_account(&sc_address);
+        self.blockchain_wrapper.add_mandos_set_account(&owner);
+        self.blockchain_wrapper.add_mandos_set_account(&lp);
+        self.blockchain_wrapper.add_mandos_set_account(&trader);
+
+        let lp_shares = {
+            let mut shares = 0u64;
+            let lp_managed = lp.clone();
+            self.blockchain_wrapper
+                .execute_query(&self.dex_wrapper, |sc| {
+                    shares = sc
+                        .lp_shares(pair_id, &managed_address!(&lp_managed))
+                        .get()
+                        .to_u64()
+                        .unwrap();
+                })
+                .assert_ok();
+            shares
+        };
+
+        let new_token_a = b"NEWA-0001".to_vec();
+        let new_token_b = b"NEWB-0001".to_vec();
+        let swap_amount = 10_000u64;
+
+        // --- Pair creation and owner management ---
+        let mut call = ScCallMandos::new(&trader, &sc_address, "createPair");
+        call.add_argument(&&new_token_a[..]);
+        call.add_argument(&&new_token_b[..]);
+        call.add_argument(&false);
+        call.add_argument(&false);
+        call.add_argument(&FEE_BPS);
+        self.record(call);
+
+        for (function, args) in [
+            ("setPairActive", vec![pair_id, 1]),
+            ("setRequireUniquePairs", vec![1]),
+            ("indexExistingPairs", vec![]),
+            ("setMinimumLiquidity", vec![1_000]),
+            ("setFeeBounds", vec![1, 1_000]),
+            ("setKlvPairMaxFee", vec![300]),
+            ("setProtocolFeeBps", vec![1_000]),
+            ("setPairComplianceFlag", vec![pair_id, 1]),
+            ("setCheckpointConfig", vec![0, 0]),
+            ("checkpoint", vec![]),
+            ("checkpointPairFees", vec![pair_id]),
+            ("adminHeartbeat", vec![]),
+            ("ownerClaimFees", vec![pair_id]),
+            ("claimTreasuryFees", vec![pair_id]),
+        ] {
+            let mut call = ScCallMandos::new(&owner, &sc_address, function);
+            for arg in args.iter() {
+                call.add_argument(arg);
+            }
+            self.record(call);
+        }
+
+        // --- Liquidity ---
+        for (function, token) in [
+            ("depositPendingA", token_a(pair_id)),
+            ("depositPendingB", token_b(pair_id)),
+        ] {
+            let mut call = ScCallMandos::new(&lp, &sc_address, function);
+            call.add_argument(&pair_id);
+            call.add_kda_transfer(&token, 0, &rust_biguint!(LIQUIDITY_AMOUNT));
+            self.record(call);
+        }
+
+        let mut call = ScCallMandos::new(&lp, &sc_address, "finalizeLiquidity");
+        call.add_argument(&pair_id);
+        call.add_argument(&0u64);
+        self.record(call);
+
+        let mut call = ScCallMandos::new(&lp, &sc_address, "mint");
+        call.add_argument(&pair_id);
+        call.add_argument(&0u64);
+        call.add_kda_transfer(&token_a(pair_id), 0, &rust_biguint!(LIQUIDITY_AMOUNT));
+        call.add_kda_transfer(&token_b(pair_id), 0, &rust_biguint!(LIQUIDITY_AMOUNT));
+        self.record(call);
+
+        let mut call = ScCallMandos::new(&lp, &sc_address, "claimLpFees");
+        call.add_argument(&pair_id);
+        self.record(call);
+
+        let mut call = ScCallMandos::new(&lp, &sc_address, "removeLiquidity");
+        call.add_argument(&pair_id);
+        call.add_argument(&(lp_shares / 2));
+        call.add_argument(&0u64);
+        call.add_argument(&0u64);
+        self.record(call);
+
+        let mut call = ScCallMandos::new(&lp, &sc_address, "depositPendingA");
+        call.add_argument(&pair_id);
+        call.add_kda_transfer(&token_a(pair_id), 0, &rust_biguint!(LIQUIDITY_AMOUNT));
+        self.record(call);
+
+        let mut call = ScCallMandos::new(&lp, &sc_address, "withdrawPendingAll");
+        call.add_argument(&pair_id);
+        self.record(call);
+
+        // --- Swaps ---
+        for (function, token) in [
+            ("swapAtoB", token_a(pair_id)),
+            ("swapBtoA", token_b(pair_id)),
+        ] {
+            let mut call = ScCallMandos::new(&trader, &sc_address, function);
+            call.add_argument(&pair_id);
+            call.add_argument(&0u64);
+            call.add_kda_transfer(&token, 0, &rust_biguint!(swap_amount));
+            self.record(call);
+        }
+
+        let mut call = ScCallMandos::new(&trader, &sc_address, "swapTokensForExactOut");
+        call.add_argument(&pair_id);
+        call.add_argument(&(swap_amount / 2));
+        call.add_kda_transfer(&token_a(pair_id), 0, &rust_biguint!(swap_amount));
+        self.record(call);
+
+        let mut call = ScCallMandos::new(&trader, &sc_address, "swapExactInMultiHop");
+        call.add_argument(&0u64);
+        call.add_argument(&pair_id);
+        call.add_kda_transfer(&token_a(pair_id), 0, &rust_biguint!(swap_amount));
+        self.record(call);
+
+        // --- Views that walk pairs or LP lists ---
+        for (function, args) in [
+            ("getRegisteredPairs", vec![]),
+            ("getPairsInfoPaginated", vec![1, 100]),
+            ("getAllTokenTvl", vec![]),
+            ("getAllLpInfo", vec![pair_id]),
+            ("getLpList", vec![pair_id]),
+            ("getLpFeeMerkleRoot", vec![pair_id]),
+            ("getPairStats", vec![pair_id]),
+            ("getSwapQuote", vec![pair_id, swap_amount, 1]),
+            ("canDeletePair", vec![pair_id]),
+        ] {
+            let mut call = ScCallMandos::new(&trader, &sc_address, function);
+            for arg in args.iter() {
+                call.add_argument(arg);
+            }
+            self.record(call);
+        }
+
+        let mut call = ScCallMandos::new(&trader, &sc_address, "findPairByTokens");
+        call.add_argument(&&token_a(pair_id)[..]);
+        call.add_argument(&&token_b(pair_id)[..]);
+        self.record(call);
+
+        // Archive last: it freezes the pair for everything above
+        let mut call = ScCallMandos::new(&owner, &sc_address, "archivePair");
+        call.add_argument(&pair_id);
+        self.record(call);
+    }
+
+    fn record(&mut self, mut call: ScCallMandos) {
+        call.set_gas_limit(KLEVER_MAX_TX_GAS);
+        self.blockchain_wrapper
+            .add_mandos_sc_call(call, Some(TxExpectMandos::new(0)));
+    }
+}
+
+#[test]
+#[ignore = "stress run: cargo test --test stress_scenario_rs_test -- --ignored"]
+fn stress_hundreds_of_pairs_and_thousands_of_lps() {
+    let mut rng = Rng(0x5EED_D161_C0DE_0005);
+    let mut setup = setup_dex(digiko_swap::contract_obj);
+
+    setup.create_pairs();
+    setup.populate_lps(&mut rng);
+    setup.random_swaps(&mut rng);
+    setup.check_invariants();
+
+    setup.record_endpoint_calls(&mut rng);
+    std::fs::create_dir_all("scenarios").unwrap();
+    setup
+        .blockchain_wrapper
+        .write_mandos_output(SCENARIO_OUTPUT);
+}
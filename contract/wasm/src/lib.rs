@@ -5,8 +5,8 @@
 
 // Init:                                 1
 // Upgrade:                              1
-// Endpoints:                           48
-// Total number of exported functions:  50
+// Endpoints:                          325
+// Total number of exported functions: 327
 
 #![no_std]
 
@@ -18,53 +18,330 @@ klever_sc_wasm_adapter::endpoints! {
     (
         init => init
         upgrade => upgrade
+        createLaunch => create_launch
+        buyLaunch => buy_launch
+        sellLaunch => sell_launch
+        cancelLaunch => cancel_launch
         createPair => create_pair
+        createSftPair => create_sft_pair
+        createLbpPair => create_lbp_pair
         deletePair => delete_pair
+        archivePair => archive_pair
         setPairActive => set_pair_active
-        setPairFee => set_pair_fee
-        updatePairTokenA => update_pair_token_a
-        updatePairTokenB => update_pair_token_b
-        ownerRemoveLiquidity => owner_remove_liquidity
-        ownerClaimFees => owner_claim_fees
-        mint => mint
-        depositPendingA => deposit_pending_a
-        depositPendingAKlv => deposit_pending_a_klv
-        depositPendingB => deposit_pending_b
-        depositPendingBKlv => deposit_pending_b_klv
-        finalizeLiquidity => finalize_liquidity
-        withdrawPendingA => withdraw_pending_a
-        withdrawPendingB => withdraw_pending_b
-        withdrawPendingAll => withdraw_pending_all
-        removeLiquidity => remove_liquidity
-        claimLpFees => claim_lp_fees
-        swapAtoB => swap_a_to_b
-        swapBtoA => swap_b_to_a
-        swapKlvToB => swap_klv_to_b
-        swapKlvToA => swap_klv_to_a
+        setPairSwapsEnabled => set_pair_swaps_enabled
+        setPairLiquidityEnabled => set_pair_liquidity_enabled
+        setPairAmplification => set_pair_amplification
+        setPairMetadata => set_pair_metadata
+        setPairVerified => set_pair_verified
+        setPairSwapGuards => set_pair_swap_guards
+        setPairTwapGuard => set_pair_twap_guard
+        setPairTradeLimits => set_pair_trade_limits
+        setPairFeeOnInput => set_pair_fee_on_input
+        setPairFeeByCreator => set_pair_fee_by_creator
+        setPairLpToken => set_pair_lp_token
+        setLpPositionNftToken => set_lp_position_nft_token
+        setRequireUniquePairs => set_require_unique_pairs
+        setTokenAllowlistEnabled => set_token_allowlist_enabled
+        setTokensAllowed => set_tokens_allowed
+        setFeeTiersAllowed => set_fee_tiers_allowed
+        setTokensDenied => set_tokens_denied
+        setPairCreationFee => set_pair_creation_fee
+        setPairCreationCooldown => set_pair_creation_cooldown
+        setMaxPairsPerCreator => set_max_pairs_per_creator
+        indexExistingPairs => index_existing_pairs
+        indexExistingPositions => index_existing_positions
+        pauseAll => pause_all
+        unpauseAll => unpause_all
+        seedPairObligations => seed_pair_obligations
+        skim => skim
+        sync => sync
+        checkpoint => checkpoint
+        checkpointPairFees => checkpoint_pair_fees
+        setCheckpointConfig => set_checkpoint_config
+        fundCheckpointRewardPool => fund_checkpoint_reward_pool
+        withdrawCheckpointRewardPool => withdraw_checkpoint_reward_pool
+        setMinimumLiquidity => set_minimum_liquidity
+        setMaxLpsPerPair => set_max_lps_per_pair
+        setMinInitialLiquidity => set_min_initial_liquidity
+        setTokenDecimals => set_token_decimals
+        setTokenReferencePrice => set_token_reference_price
+        setCreatorFeeBand => set_creator_fee_band
+        setReferenceBand => set_reference_band
+        setPendingExpiryDays => set_pending_expiry_days
+        setDcaKeeperFeeBps => set_dca_keeper_fee_bps
+        setFeeBounds => set_fee_bounds
+        setKlvPairMaxFee => set_klv_pair_max_fee
+        setProtocolFeeBps => set_protocol_fee_bps
+        setReferralFeeBps => set_referral_fee_bps
+        setFeeSplitThreshold => set_fee_split_threshold
+        proposeAdminAction => propose_admin_action
+        executeAdminAction => execute_admin_action
+        cancelAdminAction => cancel_admin_action
+        transferAdmin => transfer_admin
+        acceptAdmin => accept_admin
+        cancelAdminTransfer => cancel_admin_transfer
+        setBackupAdmin => set_backup_admin
+        clearBackupAdmin => clear_backup_admin
+        adminHeartbeat => admin_heartbeat
+        initiateAdminRecovery => initiate_admin_recovery
+        claimAdminRecovery => claim_admin_recovery
+        grantRole => grant_role
+        revokeRole => revoke_role
+        renounceRoles => renounce_roles
+        setAddressRestricted => set_address_restricted
+        setPairComplianceFlag => set_pair_compliance_flag
+        setAddressDailyLimit => set_address_daily_limit
+        importPairState => import_pair_state
+        importPairPositions => import_pair_positions
+        finishPairImport => finish_pair_import
         getReserves => get_reserves
         getPairInfo => get_pair_info
+        getVerifiedPairs => get_verified_pairs
         getPairInfoExtended => get_pair_info_extended
+        getPairTokenNonces => get_pair_token_nonces
+        getPairFees => get_pair_fees
+        isPairFeeOnInput => is_pair_fee_on_input
+        getFeeHistory => get_fee_history
+        getPairsInfoPaginated => get_pairs_info_paginated
+        getManyPairInfo => get_many_pair_info
+        getAllActivePairs => get_all_active_pairs
+        getPairPermissions => get_pair_permissions
+        getPairStats => get_pair_stats
+        getUserTradeStats => get_user_trade_stats
+        getContractStats => get_contract_stats
+        getPairDailyVolume => get_pair_daily_volume
+        getPairApr => get_pair_apr
         getTotalShares => get_total_shares
         getOwnerFees => get_owner_fees
         getLpPosition => get_lp_position
+        getUserPositions => get_user_positions
+        getLpLock => get_lp_lock
+        getLockTiers => get_lock_tiers
         getPendingDeposits => get_pending_deposits
+        getPendingExpiry => get_pending_expiry
+        getPairPendingTotals => get_pair_pending_totals
         getLpCount => get_lp_count
         isLp => is_lp
         getLpList => get_lp_list
+        getLpListPaginated => get_lp_list_paginated
+        getFeeDistributionInfo => get_fee_distribution_info
+        getMaxLpsPerPair => get_max_lps_per_pair_view
         getLpInfo => get_lp_info
+        getLpAccounting => get_lp_accounting
+        getPairLpToken => get_pair_lp_token
         getAllLpInfo => get_all_lp_info
+        getAllLpInfoPaginated => get_all_lp_info_paginated
         getRegisteredPairs => get_registered_pairs
         getAllPairIds => get_all_pair_ids
         getNextPairId => get_next_pair_id
         getPairCreator => get_pair_creator_view
         isPoolEmpty => is_pool_empty
+        getLockedLiquidity => get_locked_liquidity
         getPairsByCreator => get_pairs_by_creator
         findPairsByTokens => find_pairs_by_tokens
+        findPairByTokens => find_pair_by_tokens
+        getPairCurve => get_pair_curve
+        getLbpInfo => get_lbp_info
+        exportPairState => export_pair_state
+        exportPairPositions => export_pair_positions
+        getPairMetadata => get_pair_metadata
+        getPairSwapGuards => get_pair_swap_guards
+        getPairTwap => get_pair_twap
+        getPriceNormalized => get_price_normalized
+        getTokenDecimals => get_token_decimals_view
+        getPairPriceCumulative => get_pair_price_cumulative
+        getPairTradeLimits => get_pair_trade_limits
+        getPairsForToken => get_pairs_for_token
+        getBestRoute => get_best_route
+        getLegacyAddLiquidityEnabled => get_legacy_add_liquidity_enabled
+        getRequireUniquePairs => get_require_unique_pairs
+        getPairArchiveInfo => get_pair_archive_info
+        getArchivedPairs => get_archived_pairs
         getPendingUserCount => get_pending_user_count
+        getPendingUsers => get_pending_users
         canDeletePair => can_delete_pair
         previewFirstLiquidity => preview_first_liquidity
         previewFirstPrice => preview_first_price
         quoteSwap => quote_swap
+        getSwapQuote => get_swap_quote
+        getDetailedQuote => get_detailed_quote
         quoteSwapReverse => quote_swap_reverse
+        getSwapQuoteExactOut => get_swap_quote_exact_out
+        getTokenTvl => get_token_tvl
+        getAllTokenTvl => get_all_token_tvl
+        getLpFeeMerkleRoot => get_lp_fee_merkle_root
+        verifyLpFeeProof => verify_lp_fee_proof
+        getCheckpointInfo => get_checkpoint_info
+        getParameters => get_parameters
+        getInitialLiquidityRules => get_initial_liquidity_rules
+        getCreatorFeeRules => get_creator_fee_rules
+        getTokenReferencePrice => get_token_reference_price
+        getFeeTiers => get_fee_tiers
+        getPairCreationRules => get_pair_creation_rules
+        getTokenListing => get_token_listing
+        getTokenAllowlist => get_token_allowlist
+        getTokenDenylist => get_token_denylist
+        getCreatorStats => get_creator_stats
+        getTreasuryAddress => get_treasury_address
+        getFeeSplit => get_fee_split
+        getFeeSplitThreshold => get_fee_split_threshold
+        getTreasuryFees => get_treasury_fees
+        getRoles => get_roles
+        getRoleHolders => get_role_holders
+        getPendingAdminActions => get_pending_admin_actions
+        getTimelockDelay => get_timelock_delay_view
+        getPendingAdmin => get_pending_admin
+        getReferralEarnings => get_referral_earnings
+        getPairRebateTiers => get_pair_rebate_tiers
+        getTraderRebateInfo => get_trader_rebate_info
+        getFeeRebates => get_fee_rebates
+        getWhitelistedRouters => get_whitelisted_routers
+        getRouteAliases => get_route_aliases
+        getRoute => get_route
+        getPreferredRoute => get_preferred_route
+        getBuybackInfo => get_buyback_info
+        getReferralFeeBps => get_referral_fee_bps
+        getStrayBalances => get_stray_balances
+        auditPair => audit_pair
+        getIlProtectionParams => get_il_protection_params
+        getIlFund => get_il_fund
+        getLpAirdrop => get_lp_airdrop
+        getLpPositionNft => get_lp_position_nft
+        getLpAirdropClaimable => get_lp_airdrop_claimable
+        getLpIlSnapshot => get_lp_il_snapshot
+        getLaunch => get_launch
+        getLaunchQuote => get_launch_quote
+        getVestingSchedule => get_vesting_schedule
+        getVestingClaimable => get_vesting_claimable
+        getBeneficiaryVestingIds => get_beneficiary_vesting_ids
+        getCompetition => get_competition
+        getPairActiveCompetition => get_pair_active_competition
+        getCompetitionLeaderboard => get_competition_leaderboard
+        getCompetitionTrader => get_competition_trader
+        getLimitOrder => get_limit_order
+        getPairLimitOrders => get_pair_limit_orders
+        getUserLimitOrders => get_user_limit_orders
+        getDcaOrder => get_dca_order
+        getUserDcaOrders => get_user_dca_orders
+        getStreamOrder => get_stream_order
+        getUserStreamOrders => get_user_stream_orders
+        getDcaKeeperFeeBps => get_dca_keeper_fee_bps_view
+        getFarmInfo => get_farm_info
+        getFarmPool => get_farm_pool
+        getFarmPosition => get_farm_position
+        isGloballyPaused => is_globally_paused
+        getAdmin => get_admin
+        getDeadManSwitchInfo => get_dead_man_switch_info
+        getRestrictedAddresses => get_restricted_addresses
+        isAddressRestricted => is_address_restricted
+        getComplianceFlaggedPairs => get_compliance_flagged_pairs
+        getStakingConfig => get_staking_config
+        getStakingTiers => get_staking_tiers
+        getStakerInfo => get_staker_info
+        getTraderFeeBps => get_trader_fee_bps_view
+        getMetaSwapNonce => get_meta_swap_nonce
+        getRelayerAllowance => get_relayer_allowance
+        getMetaSwapMessage => get_meta_swap_message
+        getPairComplianceLimits => get_pair_compliance_limits
+        getAddressCompliance => get_address_compliance
+        getSpotPrice => get_spot_price
+        getTwapPrice => get_twap_price_view
+        getReservesNormalized => get_reserves_normalized
+        mint => mint
+        releaseExpiredLock => release_expired_lock
+        zapInSingleToken => zap_in_single_token
+        depositPendingA => deposit_pending_a
+        depositPendingAKlv => deposit_pending_a_klv
+        depositPendingB => deposit_pending_b
+        depositPendingBKlv => deposit_pending_b_klv
+        finalizeLiquidity => finalize_liquidity
+        withdrawPendingA => withdraw_pending_a
+        withdrawPendingB => withdraw_pending_b
+        withdrawPendingAll => withdraw_pending_all
+        expirePending => expire_pending
+        ownerAddLiquidityA => owner_add_liquidity_a
+        ownerAddLiquidityB => owner_add_liquidity_b
+        ownerAddLiquidityAKlv => owner_add_liquidity_a_klv
+        ownerAddLiquidityBKlv => owner_add_liquidity_b_klv
+        ownerInitializeLiquidity => owner_initialize_liquidity
+        setLegacyAddLiquidityEnabled => set_legacy_add_liquidity_enabled
+        addLiquidityA => add_liquidity_a
+        addLiquidityB => add_liquidity_b
+        addLiquidityAKlv => add_liquidity_a_klv
+        addLiquidityBKlv => add_liquidity_b_klv
+        ownerRecalculateShares => owner_recalculate_shares
+        removeLiquidity => remove_liquidity
+        removeLiquidityBatch => remove_liquidity_batch
+        emergencyWithdraw => emergency_withdraw
+        removeLiquidityLpToken => remove_liquidity_lp_token
+        migrateLiquidity => migrate_liquidity
+        claimLpFees => claim_lp_fees
+        claimAllLpFees => claim_all_lp_fees
+        distributePendingFees => distribute_pending_fees
+        setDistributionIncentiveBps => set_distribution_incentive_bps
+        setFarmRewardToken => set_farm_reward_token
+        setFarmRewardRate => set_farm_reward_rate
+        setFarmPoolWeight => set_farm_pool_weight
+        fundFarmRewards => fund_farm_rewards
+        stake => stake
+        unstake => unstake
+        harvest => harvest
+        createLpAirdrop => create_lp_airdrop
+        claimLpAirdrop => claim_lp_airdrop
+        closeLpAirdrop => close_lp_airdrop
+        wrapLockedPosition => wrap_locked_position
+        claimLpPositionFees => claim_lp_position_fees
+        unwrapLpPosition => unwrap_lp_position
+        swap => swap
+        swapAtoB => swap_a_to_b
+        swapBtoA => swap_b_to_a
+        swapKlvToB => swap_klv_to_b
+        swapKlvToA => swap_klv_to_a
+        swapTokensForExactOut => swap_tokens_for_exact_out
+        swapExactInMultiHop => swap_exact_in_multi_hop
+        swapExactInRoute => swap_exact_in_route
+        setRoute => set_route
+        removeRoute => remove_route
+        arb => arb
+        setRouterWhitelisted => set_router_whitelisted
+        swapFor => swap_for
+        depositRelayerAllowance => deposit_relayer_allowance
+        withdrawRelayerAllowance => withdraw_relayer_allowance
+        cancelMetaSwapNonce => cancel_meta_swap_nonce
+        swapOnBehalf => swap_on_behalf
+        flashSwap => flash_swap
+        repayFlashSwap => repay_flash_swap
+        placeLimitOrder => place_limit_order
+        cancelLimitOrder => cancel_limit_order
+        fillOrders => fill_orders
+        createDcaOrder => create_dca_order
+        cancelDcaOrder => cancel_dca_order
+        executeDcaOrders => execute_dca_orders
+        createStreamOrder => create_stream_order
+        cancelStreamOrder => cancel_stream_order
+        executeStreamOrders => execute_stream_orders
+        openCompetition => open_competition
+        closeCompetition => close_competition
+        claimCompetitionReward => claim_competition_reward
+        ownerRemoveLiquidity => owner_remove_liquidity
+        migrateOwnerSharesToLp => migrate_owner_shares_to_lp
+        ownerClaimFees => owner_claim_fees
+        claimTreasuryFees => claim_treasury_fees
+        setBuybackConfig => set_buyback_config
+        buybackAndBurn => buyback_and_burn
+        claimReferralFees => claim_referral_fees
+        setPairRebateTiers => set_pair_rebate_tiers
+        claimFeeRebates => claim_fee_rebates
+        setIlProtection => set_il_protection
+        fundIlProtection => fund_il_protection
+        createVestingSchedule => create_vesting_schedule
+        claimVested => claim_vested
+        revokeVesting => revoke_vesting
+        setStakingToken => set_staking_token
+        setStakingTiers => set_staking_tiers
+        setUnstakeCooldown => set_unstake_cooldown
+        stakeDgko => stake_dgko
+        unstakeDgko => unstake_dgko
+        withdrawUnstakedDgko => withdraw_unstaked_dgko
     )
 }